@@ -1,4 +1,5 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { GoogleCalendarSettings } from "./GoogleCalendarSettings";
+import type { OutlookCalendarSettings } from "./OutlookCalendarSettings";
 
-export type AdminSettingsClientFacing = { created_at: string, updated_at: string, id: number, allow_new_registrations: boolean, google_calendar_settings: GoogleCalendarSettings | null, };
+export type AdminSettingsClientFacing = { created_at: string, updated_at: string, id: number, allow_new_registrations: boolean, google_calendar_settings: GoogleCalendarSettings | null, outlook_calendar_settings: OutlookCalendarSettings | null, };
@@ -1,4 +1,5 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { GoogleCalendarSettings } from "./GoogleCalendarSettings";
+import type { OutlookCalendarSettings } from "./OutlookCalendarSettings";
 
-export type AdminSettingsParams = { allow_new_registrations: boolean | null, google_calendar_settings: GoogleCalendarSettings | null, };
+export type AdminSettingsParams = { allow_new_registrations: boolean | null, google_calendar_settings: GoogleCalendarSettings | null, outlook_calendar_settings: OutlookCalendarSettings | null, };
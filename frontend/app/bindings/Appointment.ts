@@ -1,5 +1,11 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
-import type { GoogleCalendarEvent } from "./GoogleCalendarEvent";
+import type { CalendarEvent } from "./CalendarEvent";
+import type { CalendarReview } from "./CalendarReview";
+import type { IntakeAnswers } from "./IntakeAnswers";
 import type { Status } from "./Status";
 
-export type Appointment = { created_at: string, updated_at: string, id: number, booker_name: string, booker_phone: string, booker_timezone: string, booker_email: string, start_time: string, endtime: string, status: Status, user_id: number, appointment_type_id: number, google_calendar_events: Array<GoogleCalendarEvent>, };
+export type Appointment = { created_at: string, updated_at: string, id: number, booker_name: string, booker_phone: string, booker_timezone: string, booker_email: string, start_time: string, endtime: string, status: Status, user_id: number, appointment_type_id: number, calendar_events: Array<CalendarEvent>, manage_token: string | null, intake_answers: IntakeAnswers, pending_expires_at: string | null, 
+/**
+ * Set when the event was changed outside the app in a way the owner has to look at.
+ */
+calendar_review: CalendarReview | null, };
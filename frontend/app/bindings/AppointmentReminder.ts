@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ReminderRecipient } from "./ReminderRecipient";
+
+export type AppointmentReminder = { created_at: string, updated_at: string, id: number, appointment_id: number, offset_in_minutes: number, recipient: ReminderRecipient, };
@@ -1,3 +1,4 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { IntakeQuestions } from "./IntakeQuestions";
 
-export type AppointmentType = { created_at: string, updated_at: string, id: number, duration_in_minutes: number, name: string, display_name: string, user_id: number, };
+export type AppointmentType = { created_at: string, updated_at: string, id: number, duration_in_minutes: number, name: string, display_name: string, user_id: number, buffer_before_minutes: number, buffer_after_minutes: number, slot_interval_minutes: number | null, schedule_id: number | null, max_attendees: number, intake_questions: IntakeQuestions, requires_approval: boolean, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { OverrideWindows } from "./OverrideWindows";
+
+export type AvailabilityOverride = { created_at: string, updated_at: string, id: number, user_id: number, date: string, unavailable: boolean, windows: OverrideWindows, };
@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { OverrideWindow } from "./OverrideWindow";
+
+export type AvailabilityOverrideParams = { 
+/**
+ * Date in the owner's timezone.
+ */
+date: string, 
+/**
+ * Blocks the whole date when true.
+ */
+unavailable?: boolean, 
+/**
+ * Windows in minutes from 00:00 replacing the weekly pattern for the date.
+ */
+windows?: Array<OverrideWindow>, };
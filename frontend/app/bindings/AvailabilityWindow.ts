@@ -1,3 +1,7 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type AvailabilityWindow = { start: string, end: string, };
+export type AvailabilityWindow = { start: string, end: string, 
+/**
+ * Seats still open on a bookable slot, `None` for busy windows.
+ */
+remaining_seats: number | null, };
@@ -1,3 +1,8 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { IntakeAnswerParam } from "./IntakeAnswerParam";
 
-export type BookingParams = { booker_name: string, booker_phone: string, booker_email: string, from: string, to: string, };
+export type BookingParams = { booker_name: string, booker_phone: string, booker_email: string, from: string, to: string, 
+/**
+ * Answers to the intake questions, keyed by question key.
+ */
+answers?: { [key in string]?: IntakeAnswerParam }, };
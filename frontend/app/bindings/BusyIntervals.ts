@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Intervals } from "./Intervals";
+
+export type BusyIntervals = { created_at: string, updated_at: string, id: number, user_id: number, intervals: Intervals, covers_from: string, covers_until: string, fetched_at: string, };
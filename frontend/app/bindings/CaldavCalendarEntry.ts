@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type CaldavCalendarEntry = { 
+/**
+ * URL of the calendar, used as `calendar_id` when picking calendars.
+ */
+id: string, summary: string, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { StringHash } from "./StringHash";
+
+export type CaldavConnection = { created_at: string, updated_at: string, id: number, user_id: number, server_url: string, username: string, password: string, calendars_for_collision_check: StringHash, calendars_for_event_handling: StringHash, };
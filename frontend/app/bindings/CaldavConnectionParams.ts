@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type CaldavConnectionParams = { 
+/**
+ * Address of the CalDAV server, e.g. `https://caldav.fastmail.com/dav/`.
+ */
+server_url: string, username: string, 
+/**
+ * Most providers want an app password here rather than the account password.
+ */
+password: string, };
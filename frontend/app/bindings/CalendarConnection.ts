@@ -0,0 +1,13 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CalendarProviderKind } from "./CalendarProviderKind";
+
+export type CalendarConnection = { provider: CalendarProviderKind, 
+/**
+ * Tells apart several connections of the same provider, set for Google.
+ */
+connection_id: number | null, 
+/**
+ * False once the provider stopped accepting the stored credentials, the owner has to
+ * connect it again.
+ */
+connected: boolean, };
@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CalendarProviderKind } from "./CalendarProviderKind";
+
+/**
+ * Event created for the appointment in one of the owner's calendars.
+ */
+export type CalendarEvent = { 
+/**
+ * Missing on events stored before there was more than one provider, those are Google's.
+ */
+provider: CalendarProviderKind, calendar_id: string, event_id: string, 
+/**
+ * Connection the event was created through, for providers a user can connect more than
+ * once. Missing on events stored before that.
+ */
+connection_id: number | null, };
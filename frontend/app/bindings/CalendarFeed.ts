@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type CalendarFeed = { 
+/**
+ * Secret address to subscribe to from calendar apps, `null` while the feed is off.
+ */
+url: string | null, };
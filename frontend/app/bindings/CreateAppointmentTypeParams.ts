@@ -1,3 +1,32 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { IntakeQuestion } from "./IntakeQuestion";
 
-export type CreateAppointmentTypeParams = { duration_in_minutes: number, display_name: string, };
+export type CreateAppointmentTypeParams = { duration_in_minutes: number, display_name: string, 
+/**
+ * Free time kept before each appointment, defaults to 0.
+ */
+buffer_before_minutes?: number, 
+/**
+ * Free time kept after each appointment, defaults to 0.
+ */
+buffer_after_minutes?: number, 
+/**
+ * How often a slot may start, defaults to the duration.
+ */
+slot_interval_minutes?: number, 
+/**
+ * Schedule to take weekly windows from, defaults to the default schedule.
+ */
+schedule_id?: number, 
+/**
+ * Seats per slot, defaults to 1.
+ */
+max_attendees?: number, 
+/**
+ * Questions asked when booking, defaults to none.
+ */
+intake_questions?: Array<IntakeQuestion>, 
+/**
+ * Bookings wait for the owner's approval, defaults to false.
+ */
+requires_approval?: boolean, };
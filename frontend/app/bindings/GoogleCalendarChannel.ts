@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type GoogleCalendarChannel = { created_at: string, updated_at: string, id: number, google_calendar_id: number, calendar_id: string, channel_id: string, resource_id: string, token: string, expires_at: string, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * One connected Google account.
+ */
+export type GoogleCalendarConnection = { id: number, 
+/**
+ * `None` for accounts connected before several accounts were supported.
+ */
+account_email: string | null, 
+/**
+ * False once Google revoked access, the account has to be connected again.
+ */
+connected: boolean, calendars_for_collision_check: Array<string>, calendars_for_event_handling: Array<string>, };
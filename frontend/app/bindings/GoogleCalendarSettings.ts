@@ -1,3 +1,11 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type GoogleCalendarSettings = { google_calendar_api_key: string, google_oauth_client_id: string, google_oauth_secret: string, google_oauth_redirect_uri_base: string, };
+export type GoogleCalendarSettings = { google_calendar_api_key: string, google_oauth_client_id: string, google_oauth_secret: string, google_oauth_redirect_uri_base: string, 
+/**
+ * OAuth token endpoint, Google's own when empty.
+ */
+google_oauth_token_url: string | null, 
+/**
+ * Calendar API root, Google's own when empty.
+ */
+google_calendar_api_url: string | null, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Answer to an intake question, keeps the question as it was asked.
+ */
+export type IntakeAnswer = { key: string, question: string, answer: string, };
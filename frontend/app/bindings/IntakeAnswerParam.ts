@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Checkbox questions take a boolean, every other kind a string.
+ */
+export type IntakeAnswerParam = boolean | string;
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { IntakeQuestionKind } from "./IntakeQuestionKind";
+
+/**
+ * Question a booker answers when booking, `key` identifies the answer.
+ */
+export type IntakeQuestion = { key: string, label: string, kind: IntakeQuestionKind, required: boolean, 
+/**
+ * Choices of a `select` question.
+ */
+options: Array<string>, };
@@ -1,3 +1,3 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type GoogleCalendarEvent = { calendar_id: string, event_id: string, };
+export type Interval = { start: string, end: string, };
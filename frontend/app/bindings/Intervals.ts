@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Interval } from "./Interval";
+
+/**
+ * Busy times read from the connected calendars, merged and sorted by start.
+ */
+export type Intervals = Array<Interval>;
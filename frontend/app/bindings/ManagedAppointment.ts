@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Status } from "./Status";
+
+/**
+ * What a booker gets to see about their own appointment through the manage link.
+ */
+export type ManagedAppointment = { user_name: string, appointment_type_id: number, appointment_type_display_name: string, booker_name: string, start_time: string, endtime: string, status: Status, };
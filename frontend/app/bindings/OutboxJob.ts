@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { OutboxJobKind } from "./OutboxJobKind";
+
+export type OutboxJob = { created_at: string, updated_at: string, id: number, appointment_id: number, kind: OutboxJobKind, attempts: number, run_at: string, last_error: string | null, completed_at: string | null, };
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export enum OutboxJobKind { "AddToCalendars" = "AddToCalendars", "NotifyBooker" = "NotifyBooker", "NotifyOwner" = "NotifyOwner", "NotifyWebhooks" = "NotifyWebhooks" }
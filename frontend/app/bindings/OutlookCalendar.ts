@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { StringHash } from "./StringHash";
+
+export type OutlookCalendar = { created_at: string, updated_at: string, id: number, user_id: number, access_token: string, refresh_token: string, expires_at: string, scope: string, calendars_for_collision_check: StringHash, calendars_for_event_handling: StringHash, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type OutlookCalendarEntry = { id: string, summary: string, primary: boolean, 
+/**
+ * Events can only be written to calendars the user can edit.
+ */
+can_edit: boolean, 
+/**
+ * Address of the mailbox the calendar belongs to.
+ */
+owner: string | null, };
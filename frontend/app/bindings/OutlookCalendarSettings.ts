@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type OutlookCalendarSettings = { outlook_oauth_client_id: string, outlook_oauth_secret: string, outlook_oauth_redirect_uri_base: string, 
+/**
+ * Microsoft Graph root, the public `v1.0` endpoint when empty.
+ */
+graph_base_url: string | null, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Window in minutes from 00:00 of the override date.
+ */
+export type OverrideWindow = { from: number, to: number, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How long before `start_time` reminders go out, in minutes.
+ */
+export type ReminderOffsets = Array<number>;
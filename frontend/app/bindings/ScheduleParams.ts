@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type ScheduleParams = { name: string, 
+/**
+ * Makes this the default schedule, unsetting the previous one.
+ */
+is_default?: boolean, };
@@ -1,3 +1,3 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export enum Status { "Booked" = "Booked", "Cancelled" = "Cancelled" }
+export enum Status { "Booked" = "Booked", "Cancelled" = "Cancelled", "Pending" = "Pending", "Declined" = "Declined" }
@@ -1,3 +1,32 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { IntakeQuestion } from "./IntakeQuestion";
 
-export type UpdateAppointmentTypeParams = { duration_in_minutes: number, display_name: string, };
+export type UpdateAppointmentTypeParams = { duration_in_minutes: number, display_name: string, 
+/**
+ * Leaves the current value untouched when missing.
+ */
+buffer_before_minutes?: number, 
+/**
+ * Leaves the current value untouched when missing.
+ */
+buffer_after_minutes?: number, 
+/**
+ * Leaves the current value untouched when missing, `null` goes back to the duration.
+ */
+slot_interval_minutes?: number | null, 
+/**
+ * Leaves the current value untouched when missing.
+ */
+schedule_id?: number, 
+/**
+ * Leaves the current value untouched when missing.
+ */
+max_attendees?: number, 
+/**
+ * Leaves the current value untouched when missing.
+ */
+intake_questions?: Array<IntakeQuestion>, 
+/**
+ * Leaves the current value untouched when missing.
+ */
+requires_approval?: boolean, };
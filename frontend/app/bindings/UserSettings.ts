@@ -1,3 +1,4 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ReminderOffsets } from "./ReminderOffsets";
 
-export type UserSettings = { created_at: string, updated_at: string, id: number, user_id: number, start_how_far_from_now_in_minutes: number, end_how_far_from_now_in_minutes: number, };
+export type UserSettings = { created_at: string, updated_at: string, id: number, user_id: number, start_how_far_from_now_in_minutes: number, end_how_far_from_now_in_minutes: number, pending_expires_after_in_minutes: number, reminder_offsets_in_minutes: ReminderOffsets, calendar_feed_token: string | null, };
@@ -1,4 +1,13 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { DaysHoursMinutes } from "./DaysHoursMinutes";
 
-export type UserSettingsProps = { start_how_far_from_now: DaysHoursMinutes, end_how_far_from_now: DaysHoursMinutes, };
+export type UserSettingsProps = { start_how_far_from_now: DaysHoursMinutes, end_how_far_from_now: DaysHoursMinutes, 
+/**
+ * Leaves the current value untouched when missing.
+ */
+pending_expires_after?: DaysHoursMinutes, 
+/**
+ * How long before an appointment reminders go out. Leaves the current value untouched
+ * when missing.
+ */
+reminder_offsets?: Array<DaysHoursMinutes>, };
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { WebhookEvent } from "./WebhookEvent";
+import type { WebhookPayload } from "./WebhookPayload";
+
+export type WebhookDelivery = { created_at: string, updated_at: string, id: number, webhook_endpoint_id: number, event: WebhookEvent, payload: WebhookPayload, attempt: number, response_status: number | null, error: string | null, retry_at: string | null, };
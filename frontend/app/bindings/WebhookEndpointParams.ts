@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { WebhookEvent } from "./WebhookEvent";
+
+export type WebhookEndpointParams = { 
+/**
+ * Where the signed `POST` requests are sent.
+ */
+url: string, events: Array<WebhookEvent>, 
+/**
+ * Disabled endpoints keep their secret and history but receive nothing. Defaults to true.
+ */
+active?: boolean, };
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export enum WebhookEvent { "appointment.booked" = "appointment.booked", "appointment.approved" = "appointment.approved", "appointment.declined" = "appointment.declined", "appointment.cancelled" = "appointment.cancelled", "appointment.rescheduled" = "appointment.rescheduled" }
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Appointment } from "./Appointment";
+import type { WebhookEvent } from "./WebhookEvent";
+
+/**
+ * Body posted to the endpoint.
+ */
+export type WebhookPayload = { event: WebhookEvent, occurred_at: string, appointment: Appointment, };
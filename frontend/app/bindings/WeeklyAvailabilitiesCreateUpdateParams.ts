@@ -5,4 +5,8 @@ export type WeeklyAvailabilitiesCreateUpdateParams = { normalized: NormalizedAva
 /**
  * Zero indexed weekday (0 = Monday, 1 = Tuesday, etc.)
  */
-weekday: number, };
+weekday: number, 
+/**
+ * Schedule the window belongs to, defaults to the default schedule.
+ */
+schedule_id?: number, };
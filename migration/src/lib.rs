@@ -16,6 +16,27 @@ mod m20251106_105710_add_collision_and_events_to_google_calendars;
 mod m20251111_073449_user_settings;
 mod m20251119_034526_add_google_calendar_references_to_appointments;
 mod m20251207_195134_remove_google_calendars_refresh_token_expiry;
+mod m20261018_090000_add_manage_token_to_appointments;
+mod m20261018_090200_add_buffers_to_appointment_types;
+mod m20261018_090300_add_slot_interval_to_appointment_types;
+mod m20261018_090400_availability_overrides;
+mod m20261018_090500_schedules;
+mod m20261018_090600_add_max_attendees_to_appointment_types;
+mod m20261018_090700_add_intake_questions;
+mod m20261018_090800_approval_workflow;
+mod m20261018_090900_appointment_reminders;
+mod m20261018_091000_outbox_jobs;
+mod m20261018_091100_webhooks;
+mod m20261018_091200_calendar_feed_token;
+mod m20261018_091300_calendar_providers;
+mod m20261018_091400_caldav_connections;
+mod m20261018_091500_outlook_calendars;
+mod m20261018_091600_google_calendar_token_expiry;
+mod m20261018_091700_google_calendar_accounts;
+mod m20261018_091800_appointment_calendar_review;
+mod m20261018_091900_google_calendar_channels;
+mod m20261018_092000_busy_intervals;
+mod m20261018_092100_unique_google_calendar_accounts;
 pub struct Migrator;
 
 #[async_trait::async_trait]
@@ -35,6 +56,27 @@ impl MigratorTrait for Migrator {
             Box::new(m20251111_073449_user_settings::Migration),
             Box::new(m20251119_034526_add_google_calendar_references_to_appointments::Migration),
             Box::new(m20251207_195134_remove_google_calendars_refresh_token_expiry::Migration),
+            Box::new(m20261018_090000_add_manage_token_to_appointments::Migration),
+            Box::new(m20261018_090200_add_buffers_to_appointment_types::Migration),
+            Box::new(m20261018_090300_add_slot_interval_to_appointment_types::Migration),
+            Box::new(m20261018_090400_availability_overrides::Migration),
+            Box::new(m20261018_090500_schedules::Migration),
+            Box::new(m20261018_090600_add_max_attendees_to_appointment_types::Migration),
+            Box::new(m20261018_090700_add_intake_questions::Migration),
+            Box::new(m20261018_090800_approval_workflow::Migration),
+            Box::new(m20261018_090900_appointment_reminders::Migration),
+            Box::new(m20261018_091000_outbox_jobs::Migration),
+            Box::new(m20261018_091100_webhooks::Migration),
+            Box::new(m20261018_091200_calendar_feed_token::Migration),
+            Box::new(m20261018_091300_calendar_providers::Migration),
+            Box::new(m20261018_091400_caldav_connections::Migration),
+            Box::new(m20261018_091500_outlook_calendars::Migration),
+            Box::new(m20261018_091600_google_calendar_token_expiry::Migration),
+            Box::new(m20261018_091700_google_calendar_accounts::Migration),
+            Box::new(m20261018_091800_appointment_calendar_review::Migration),
+            Box::new(m20261018_091900_google_calendar_channels::Migration),
+            Box::new(m20261018_092000_busy_intervals::Migration),
+            Box::new(m20261018_092100_unique_google_calendar_accounts::Migration),
             // inject-above (do not remove this comment)
         ]
     }
@@ -0,0 +1,83 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(Iden)]
+enum Appointments {
+    Table,
+    Id,
+    ManageToken,
+}
+
+/// Same as `appointments::MANAGE_TOKEN_LENGTH` of the app.
+const MANAGE_TOKEN_LENGTH: usize = 48;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        m.alter_table(
+            Table::alter()
+                .table(Appointments::Table)
+                .add_column(string_null(Appointments::ManageToken))
+                .to_owned(),
+        )
+        .await?;
+
+        m.create_index(
+            Index::create()
+                .name("idx-appointments-manage_token")
+                .table(Appointments::Table)
+                .col(Appointments::ManageToken)
+                .unique()
+                .to_owned(),
+        )
+        .await?;
+
+        // Appointments booked before get a manage link too.
+        let rows = m
+            .get_connection()
+            .query_all(
+                m.get_database_backend().build(
+                    Query::select()
+                        .column(Appointments::Id)
+                        .from(Appointments::Table)
+                        .and_where(Expr::col(Appointments::ManageToken).is_null()),
+                ),
+            )
+            .await?;
+        for row in rows {
+            let id: i32 = row.try_get("", &Appointments::Id.to_string())?;
+            m.exec_stmt(
+                Query::update()
+                    .table(Appointments::Table)
+                    .value(
+                        Appointments::ManageToken,
+                        loco_rs::hash::random_string(MANAGE_TOKEN_LENGTH),
+                    )
+                    .and_where(Expr::col(Appointments::Id).eq(id))
+                    .to_owned(),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        m.drop_index(
+            Index::drop()
+                .name("idx-appointments-manage_token")
+                .table(Appointments::Table)
+                .to_owned(),
+        )
+        .await?;
+        m.alter_table(
+            Table::alter()
+                .table(Appointments::Table)
+                .drop_column(Appointments::ManageToken)
+                .to_owned(),
+        )
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,50 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(Iden)]
+enum AppointmentTypes {
+    Table,
+    BufferBeforeMinutes,
+    BufferAfterMinutes,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        m.alter_table(
+            Table::alter()
+                .table(AppointmentTypes::Table)
+                .add_column(integer(AppointmentTypes::BufferBeforeMinutes).default(0))
+                .to_owned(),
+        )
+        .await?;
+        m.alter_table(
+            Table::alter()
+                .table(AppointmentTypes::Table)
+                .add_column(integer(AppointmentTypes::BufferAfterMinutes).default(0))
+                .to_owned(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        m.alter_table(
+            Table::alter()
+                .table(AppointmentTypes::Table)
+                .drop_column(AppointmentTypes::BufferBeforeMinutes)
+                .to_owned(),
+        )
+        .await?;
+        m.alter_table(
+            Table::alter()
+                .table(AppointmentTypes::Table)
+                .drop_column(AppointmentTypes::BufferAfterMinutes)
+                .to_owned(),
+        )
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,33 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(Iden)]
+enum AppointmentTypes {
+    Table,
+    SlotIntervalMinutes,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        m.alter_table(
+            Table::alter()
+                .table(AppointmentTypes::Table)
+                .add_column(integer_null(AppointmentTypes::SlotIntervalMinutes))
+                .to_owned(),
+        )
+        .await
+    }
+
+    async fn down(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        m.alter_table(
+            Table::alter()
+                .table(AppointmentTypes::Table)
+                .drop_column(AppointmentTypes::SlotIntervalMinutes)
+                .to_owned(),
+        )
+        .await
+    }
+}
@@ -0,0 +1,89 @@
+use loco_rs::schema::table_auto_tz;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(Iden)]
+enum AvailabilityOverrides {
+    Table,
+    Id,
+    UserId,
+    Date,
+    Unavailable,
+    Windows,
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        m.create_table(
+            table_auto_tz(AvailabilityOverrides::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(AvailabilityOverrides::Id)
+                        .integer()
+                        .not_null()
+                        .auto_increment()
+                        .primary_key(),
+                )
+                .col(
+                    ColumnDef::new(AvailabilityOverrides::UserId)
+                        .integer()
+                        .not_null(),
+                )
+                .col(
+                    ColumnDef::new(AvailabilityOverrides::Date)
+                        .date()
+                        .not_null(),
+                )
+                .col(
+                    ColumnDef::new(AvailabilityOverrides::Unavailable)
+                        .boolean()
+                        .not_null()
+                        .default(false),
+                )
+                .col(
+                    ColumnDef::new(AvailabilityOverrides::Windows)
+                        .json_binary()
+                        .not_null()
+                        .default(Expr::value("[]")),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-availability-overrides-user_id")
+                        .from(AvailabilityOverrides::Table, AvailabilityOverrides::UserId)
+                        .to(Users::Table, Users::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        )
+        .await?;
+
+        m.create_index(
+            Index::create()
+                .name("idx-availability-overrides-user_id-date")
+                .table(AvailabilityOverrides::Table)
+                .col(AvailabilityOverrides::UserId)
+                .col(AvailabilityOverrides::Date)
+                .unique()
+                .to_owned(),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        m.drop_table(Table::drop().table(AvailabilityOverrides::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
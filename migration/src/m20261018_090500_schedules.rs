@@ -0,0 +1,118 @@
+use loco_rs::schema::table_auto_tz;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(Iden)]
+enum Schedules {
+    Table,
+    Id,
+    UserId,
+    Name,
+    IsDefault,
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum WeeklyAvailabilities {
+    Table,
+    ScheduleId,
+}
+
+#[derive(Iden)]
+enum AppointmentTypes {
+    Table,
+    ScheduleId,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        m.create_table(
+            table_auto_tz(Schedules::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(Schedules::Id)
+                        .integer()
+                        .not_null()
+                        .auto_increment()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(Schedules::UserId).integer().not_null())
+                .col(ColumnDef::new(Schedules::Name).string().not_null())
+                .col(
+                    ColumnDef::new(Schedules::IsDefault)
+                        .boolean()
+                        .not_null()
+                        .default(false),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-schedules-user_id")
+                        .from(Schedules::Table, Schedules::UserId)
+                        .to(Users::Table, Users::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        )
+        .await?;
+
+        m.alter_table(
+            Table::alter()
+                .table(WeeklyAvailabilities::Table)
+                .add_column(integer_null(WeeklyAvailabilities::ScheduleId))
+                .to_owned(),
+        )
+        .await?;
+
+        // `NULL` keeps using the owner's default schedule.
+        m.alter_table(
+            Table::alter()
+                .table(AppointmentTypes::Table)
+                .add_column(integer_null(AppointmentTypes::ScheduleId))
+                .to_owned(),
+        )
+        .await?;
+
+        // Every existing owner gets a default schedule holding their current windows.
+        let db = m.get_connection();
+        db.execute_unprepared(
+            "INSERT INTO schedules (user_id, name, is_default) SELECT id, 'Default', TRUE FROM users",
+        )
+        .await?;
+        db.execute_unprepared(
+            "UPDATE weekly_availabilities SET schedule_id = (SELECT schedules.id FROM schedules \
+             WHERE schedules.user_id = weekly_availabilities.user_id AND schedules.is_default = TRUE)",
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        m.alter_table(
+            Table::alter()
+                .table(AppointmentTypes::Table)
+                .drop_column(AppointmentTypes::ScheduleId)
+                .to_owned(),
+        )
+        .await?;
+        m.alter_table(
+            Table::alter()
+                .table(WeeklyAvailabilities::Table)
+                .drop_column(WeeklyAvailabilities::ScheduleId)
+                .to_owned(),
+        )
+        .await?;
+        m.drop_table(Table::drop().table(Schedules::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
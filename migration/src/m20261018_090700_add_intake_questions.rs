@@ -0,0 +1,65 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(Iden)]
+enum AppointmentTypes {
+    Table,
+    IntakeQuestions,
+}
+
+#[derive(Iden)]
+enum Appointments {
+    Table,
+    IntakeAnswers,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        m.alter_table(
+            Table::alter()
+                .table(AppointmentTypes::Table)
+                .add_column_if_not_exists(
+                    ColumnDef::new(AppointmentTypes::IntakeQuestions)
+                        .json_binary()
+                        .not_null()
+                        .default(Expr::value("[]")),
+                )
+                .to_owned(),
+        )
+        .await?;
+        m.alter_table(
+            Table::alter()
+                .table(Appointments::Table)
+                .add_column_if_not_exists(
+                    ColumnDef::new(Appointments::IntakeAnswers)
+                        .json_binary()
+                        .not_null()
+                        .default(Expr::value("[]")),
+                )
+                .to_owned(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        m.alter_table(
+            Table::alter()
+                .table(Appointments::Table)
+                .drop_column(Appointments::IntakeAnswers)
+                .to_owned(),
+        )
+        .await?;
+        m.alter_table(
+            Table::alter()
+                .table(AppointmentTypes::Table)
+                .drop_column(AppointmentTypes::IntakeQuestions)
+                .to_owned(),
+        )
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,77 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(Iden)]
+enum AppointmentTypes {
+    Table,
+    RequiresApproval,
+}
+
+#[derive(Iden)]
+enum Appointments {
+    Table,
+    PendingExpiresAt,
+}
+
+#[derive(Iden)]
+enum UserSettings {
+    Table,
+    PendingExpiresAfterInMinutes,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        m.alter_table(
+            Table::alter()
+                .table(AppointmentTypes::Table)
+                .add_column(boolean(AppointmentTypes::RequiresApproval).default(false))
+                .to_owned(),
+        )
+        .await?;
+        m.alter_table(
+            Table::alter()
+                .table(Appointments::Table)
+                .add_column(timestamp_with_time_zone_null(
+                    Appointments::PendingExpiresAt,
+                ))
+                .to_owned(),
+        )
+        .await?;
+        m.alter_table(
+            Table::alter()
+                .table(UserSettings::Table)
+                .add_column(integer(UserSettings::PendingExpiresAfterInMinutes).default(60 * 24))
+                .to_owned(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        m.alter_table(
+            Table::alter()
+                .table(UserSettings::Table)
+                .drop_column(UserSettings::PendingExpiresAfterInMinutes)
+                .to_owned(),
+        )
+        .await?;
+        m.alter_table(
+            Table::alter()
+                .table(Appointments::Table)
+                .drop_column(Appointments::PendingExpiresAt)
+                .to_owned(),
+        )
+        .await?;
+        m.alter_table(
+            Table::alter()
+                .table(AppointmentTypes::Table)
+                .drop_column(AppointmentTypes::RequiresApproval)
+                .to_owned(),
+        )
+        .await?;
+        Ok(())
+    }
+}
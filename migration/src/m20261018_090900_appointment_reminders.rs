@@ -0,0 +1,111 @@
+use loco_rs::schema::table_auto_tz;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(Iden)]
+enum AppointmentReminders {
+    Table,
+    Id,
+    AppointmentId,
+    OffsetInMinutes,
+    Recipient,
+}
+
+#[derive(Iden)]
+enum Appointments {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum UserSettings {
+    Table,
+    ReminderOffsetsInMinutes,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        m.create_table(
+            table_auto_tz(AppointmentReminders::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(AppointmentReminders::Id)
+                        .integer()
+                        .not_null()
+                        .auto_increment()
+                        .primary_key(),
+                )
+                .col(
+                    ColumnDef::new(AppointmentReminders::AppointmentId)
+                        .integer()
+                        .not_null(),
+                )
+                .col(
+                    ColumnDef::new(AppointmentReminders::OffsetInMinutes)
+                        .integer()
+                        .not_null(),
+                )
+                .col(
+                    ColumnDef::new(AppointmentReminders::Recipient)
+                        .string()
+                        .not_null(),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-appointment-reminders-appointment_id")
+                        .from(
+                            AppointmentReminders::Table,
+                            AppointmentReminders::AppointmentId,
+                        )
+                        .to(Appointments::Table, Appointments::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        )
+        .await?;
+
+        // Claiming a reminder is an insert, this index is what keeps it from being sent twice.
+        m.create_index(
+            Index::create()
+                .name("idx-appointment-reminders-appointment_id-offset-recipient")
+                .table(AppointmentReminders::Table)
+                .col(AppointmentReminders::AppointmentId)
+                .col(AppointmentReminders::OffsetInMinutes)
+                .col(AppointmentReminders::Recipient)
+                .unique()
+                .to_owned(),
+        )
+        .await?;
+
+        m.alter_table(
+            Table::alter()
+                .table(UserSettings::Table)
+                .add_column_if_not_exists(
+                    ColumnDef::new(UserSettings::ReminderOffsetsInMinutes)
+                        .json_binary()
+                        .not_null()
+                        .default(Expr::value("[1440,60]")),
+                )
+                .to_owned(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        m.alter_table(
+            Table::alter()
+                .table(UserSettings::Table)
+                .drop_column(UserSettings::ReminderOffsetsInMinutes)
+                .to_owned(),
+        )
+        .await?;
+        m.drop_table(Table::drop().table(AppointmentReminders::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,90 @@
+use loco_rs::schema::table_auto_tz;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(Iden)]
+enum OutboxJobs {
+    Table,
+    Id,
+    AppointmentId,
+    Kind,
+    Attempts,
+    RunAt,
+    LastError,
+    CompletedAt,
+}
+
+#[derive(Iden)]
+enum Appointments {
+    Table,
+    Id,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        m.create_table(
+            table_auto_tz(OutboxJobs::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(OutboxJobs::Id)
+                        .integer()
+                        .not_null()
+                        .auto_increment()
+                        .primary_key(),
+                )
+                .col(
+                    ColumnDef::new(OutboxJobs::AppointmentId)
+                        .integer()
+                        .not_null(),
+                )
+                .col(ColumnDef::new(OutboxJobs::Kind).string().not_null())
+                .col(
+                    ColumnDef::new(OutboxJobs::Attempts)
+                        .integer()
+                        .not_null()
+                        .default(0),
+                )
+                .col(
+                    ColumnDef::new(OutboxJobs::RunAt)
+                        .timestamp_with_time_zone()
+                        .not_null(),
+                )
+                .col(ColumnDef::new(OutboxJobs::LastError).text().null())
+                .col(
+                    ColumnDef::new(OutboxJobs::CompletedAt)
+                        .timestamp_with_time_zone()
+                        .null(),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-outbox-jobs-appointment_id")
+                        .from(OutboxJobs::Table, OutboxJobs::AppointmentId)
+                        .to(Appointments::Table, Appointments::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        )
+        .await?;
+
+        m.create_index(
+            Index::create()
+                .name("idx-outbox-jobs-completed_at-run_at")
+                .table(OutboxJobs::Table)
+                .col(OutboxJobs::CompletedAt)
+                .col(OutboxJobs::RunAt)
+                .to_owned(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        m.drop_table(Table::drop().table(OutboxJobs::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
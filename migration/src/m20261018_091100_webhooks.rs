@@ -0,0 +1,152 @@
+use loco_rs::schema::table_auto_tz;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(Iden)]
+enum WebhookEndpoints {
+    Table,
+    Id,
+    UserId,
+    Url,
+    Secret,
+    Events,
+    Active,
+}
+
+#[derive(Iden)]
+enum WebhookDeliveries {
+    Table,
+    Id,
+    WebhookEndpointId,
+    Event,
+    Payload,
+    Attempt,
+    ResponseStatus,
+    Error,
+    RetryAt,
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        m.create_table(
+            table_auto_tz(WebhookEndpoints::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(WebhookEndpoints::Id)
+                        .integer()
+                        .not_null()
+                        .auto_increment()
+                        .primary_key(),
+                )
+                .col(
+                    ColumnDef::new(WebhookEndpoints::UserId)
+                        .integer()
+                        .not_null(),
+                )
+                .col(ColumnDef::new(WebhookEndpoints::Url).string().not_null())
+                .col(ColumnDef::new(WebhookEndpoints::Secret).string().not_null())
+                .col(
+                    ColumnDef::new(WebhookEndpoints::Events)
+                        .json_binary()
+                        .not_null()
+                        .default(Expr::value("[]")),
+                )
+                .col(
+                    ColumnDef::new(WebhookEndpoints::Active)
+                        .boolean()
+                        .not_null()
+                        .default(true),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-webhook-endpoints-user_id")
+                        .from(WebhookEndpoints::Table, WebhookEndpoints::UserId)
+                        .to(Users::Table, Users::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        )
+        .await?;
+
+        m.create_table(
+            table_auto_tz(WebhookDeliveries::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(WebhookDeliveries::Id)
+                        .integer()
+                        .not_null()
+                        .auto_increment()
+                        .primary_key(),
+                )
+                .col(
+                    ColumnDef::new(WebhookDeliveries::WebhookEndpointId)
+                        .integer()
+                        .not_null(),
+                )
+                .col(ColumnDef::new(WebhookDeliveries::Event).string().not_null())
+                .col(
+                    ColumnDef::new(WebhookDeliveries::Payload)
+                        .json_binary()
+                        .not_null(),
+                )
+                .col(
+                    ColumnDef::new(WebhookDeliveries::Attempt)
+                        .integer()
+                        .not_null()
+                        .default(1),
+                )
+                .col(
+                    ColumnDef::new(WebhookDeliveries::ResponseStatus)
+                        .integer()
+                        .null(),
+                )
+                .col(ColumnDef::new(WebhookDeliveries::Error).text().null())
+                .col(
+                    ColumnDef::new(WebhookDeliveries::RetryAt)
+                        .timestamp_with_time_zone()
+                        .null(),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-webhook-deliveries-webhook_endpoint_id")
+                        .from(
+                            WebhookDeliveries::Table,
+                            WebhookDeliveries::WebhookEndpointId,
+                        )
+                        .to(WebhookEndpoints::Table, WebhookEndpoints::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        )
+        .await?;
+
+        m.create_index(
+            Index::create()
+                .name("idx-webhook-deliveries-retry_at")
+                .table(WebhookDeliveries::Table)
+                .col(WebhookDeliveries::RetryAt)
+                .to_owned(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        m.drop_table(Table::drop().table(WebhookDeliveries::Table).to_owned())
+            .await?;
+        m.drop_table(Table::drop().table(WebhookEndpoints::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
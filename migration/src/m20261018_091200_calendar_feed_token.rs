@@ -0,0 +1,53 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(Iden)]
+enum UserSettings {
+    Table,
+    CalendarFeedToken,
+}
+
+const INDEX_NAME: &str = "idx-user-settings-calendar_feed_token";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        m.alter_table(
+            Table::alter()
+                .table(UserSettings::Table)
+                .add_column(string_null(UserSettings::CalendarFeedToken))
+                .to_owned(),
+        )
+        .await?;
+        m.create_index(
+            Index::create()
+                .name(INDEX_NAME)
+                .table(UserSettings::Table)
+                .col(UserSettings::CalendarFeedToken)
+                .unique()
+                .to_owned(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        m.drop_index(
+            Index::drop()
+                .name(INDEX_NAME)
+                .table(UserSettings::Table)
+                .to_owned(),
+        )
+        .await?;
+        m.alter_table(
+            Table::alter()
+                .table(UserSettings::Table)
+                .drop_column(UserSettings::CalendarFeedToken)
+                .to_owned(),
+        )
+        .await?;
+        Ok(())
+    }
+}
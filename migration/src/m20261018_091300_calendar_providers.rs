@@ -0,0 +1,61 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(Iden)]
+enum Appointments {
+    Table,
+    GoogleCalendarEvents,
+    CalendarEvents,
+}
+
+#[derive(Iden)]
+enum OutboxJobs {
+    Table,
+    Kind,
+}
+
+async fn rename_outbox_job_kind(m: &SchemaManager<'_>, from: &str, to: &str) -> Result<(), DbErr> {
+    m.exec_stmt(
+        Query::update()
+            .table(OutboxJobs::Table)
+            .value(OutboxJobs::Kind, to)
+            .and_where(Expr::col(OutboxJobs::Kind).eq(from))
+            .to_owned(),
+    )
+    .await
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        m.alter_table(
+            Table::alter()
+                .table(Appointments::Table)
+                .rename_column(
+                    Appointments::GoogleCalendarEvents,
+                    Appointments::CalendarEvents,
+                )
+                .to_owned(),
+        )
+        .await?;
+        rename_outbox_job_kind(m, "AddToGoogleCalendar", "AddToCalendars").await?;
+        Ok(())
+    }
+
+    async fn down(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        rename_outbox_job_kind(m, "AddToCalendars", "AddToGoogleCalendar").await?;
+        m.alter_table(
+            Table::alter()
+                .table(Appointments::Table)
+                .rename_column(
+                    Appointments::CalendarEvents,
+                    Appointments::GoogleCalendarEvents,
+                )
+                .to_owned(),
+        )
+        .await?;
+        Ok(())
+    }
+}
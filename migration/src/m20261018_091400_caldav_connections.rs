@@ -0,0 +1,88 @@
+use loco_rs::schema::table_auto_tz;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(Iden)]
+enum CaldavConnections {
+    Table,
+    Id,
+    UserId,
+    ServerUrl,
+    Username,
+    Password,
+    CalendarsForCollisionCheck,
+    CalendarsForEventHandling,
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        m.create_table(
+            table_auto_tz(CaldavConnections::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(CaldavConnections::Id)
+                        .integer()
+                        .not_null()
+                        .auto_increment()
+                        .primary_key(),
+                )
+                .col(
+                    ColumnDef::new(CaldavConnections::UserId)
+                        .integer()
+                        .not_null()
+                        .unique_key(),
+                )
+                .col(
+                    ColumnDef::new(CaldavConnections::ServerUrl)
+                        .string()
+                        .not_null(),
+                )
+                .col(
+                    ColumnDef::new(CaldavConnections::Username)
+                        .string()
+                        .not_null(),
+                )
+                .col(
+                    ColumnDef::new(CaldavConnections::Password)
+                        .string()
+                        .not_null(),
+                )
+                .col(
+                    ColumnDef::new(CaldavConnections::CalendarsForCollisionCheck)
+                        .json_binary()
+                        .not_null()
+                        .default(Expr::value("[]")),
+                )
+                .col(
+                    ColumnDef::new(CaldavConnections::CalendarsForEventHandling)
+                        .json_binary()
+                        .not_null()
+                        .default(Expr::value("[]")),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-caldav-connections-user_id")
+                        .from(CaldavConnections::Table, CaldavConnections::UserId)
+                        .to(Users::Table, Users::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        )
+        .await
+    }
+
+    async fn down(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        m.drop_table(Table::drop().table(CaldavConnections::Table).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,111 @@
+use loco_rs::schema::table_auto_tz;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(Iden)]
+enum OutlookCalendars {
+    Table,
+    Id,
+    UserId,
+    AccessToken,
+    RefreshToken,
+    ExpiresAt,
+    Scope,
+    CalendarsForCollisionCheck,
+    CalendarsForEventHandling,
+}
+
+#[derive(Iden)]
+enum AdminSettings {
+    Table,
+    OutlookCalendarSettings,
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        m.alter_table(
+            Table::alter()
+                .table(AdminSettings::Table)
+                .add_column(ColumnDef::new(AdminSettings::OutlookCalendarSettings).json_binary())
+                .to_owned(),
+        )
+        .await?;
+
+        m.create_table(
+            table_auto_tz(OutlookCalendars::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(OutlookCalendars::Id)
+                        .integer()
+                        .not_null()
+                        .auto_increment()
+                        .primary_key(),
+                )
+                .col(
+                    ColumnDef::new(OutlookCalendars::UserId)
+                        .integer()
+                        .not_null(),
+                )
+                .col(
+                    ColumnDef::new(OutlookCalendars::AccessToken)
+                        .text()
+                        .not_null(),
+                )
+                .col(
+                    ColumnDef::new(OutlookCalendars::RefreshToken)
+                        .text()
+                        .not_null(),
+                )
+                .col(
+                    ColumnDef::new(OutlookCalendars::ExpiresAt)
+                        .timestamp_with_time_zone()
+                        .not_null(),
+                )
+                .col(ColumnDef::new(OutlookCalendars::Scope).string().not_null())
+                .col(
+                    ColumnDef::new(OutlookCalendars::CalendarsForCollisionCheck)
+                        .json_binary()
+                        .not_null()
+                        .default(Expr::value("[]")),
+                )
+                .col(
+                    ColumnDef::new(OutlookCalendars::CalendarsForEventHandling)
+                        .json_binary()
+                        .not_null()
+                        .default(Expr::value("[]")),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-outlook-calendars-user_id")
+                        .from(OutlookCalendars::Table, OutlookCalendars::UserId)
+                        .to(Users::Table, Users::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        )
+        .await
+    }
+
+    async fn down(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        m.drop_table(Table::drop().table(OutlookCalendars::Table).to_owned())
+            .await?;
+
+        m.alter_table(
+            Table::alter()
+                .table(AdminSettings::Table)
+                .drop_column(AdminSettings::OutlookCalendarSettings)
+                .to_owned(),
+        )
+        .await
+    }
+}
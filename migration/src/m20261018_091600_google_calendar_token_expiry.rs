@@ -0,0 +1,47 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(Iden, Clone, Copy)]
+enum GoogleCalendars {
+    Table,
+    ExpiresAt,
+    BrokenAt,
+    BrokenNotifiedAt,
+}
+
+const COLUMNS: [GoogleCalendars; 3] = [
+    GoogleCalendars::ExpiresAt,
+    GoogleCalendars::BrokenAt,
+    GoogleCalendars::BrokenNotifiedAt,
+];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        for column in COLUMNS {
+            m.alter_table(
+                Table::alter()
+                    .table(GoogleCalendars::Table)
+                    .add_column(timestamp_with_time_zone_null(column))
+                    .to_owned(),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        for column in COLUMNS {
+            m.alter_table(
+                Table::alter()
+                    .table(GoogleCalendars::Table)
+                    .drop_column(column)
+                    .to_owned(),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+}
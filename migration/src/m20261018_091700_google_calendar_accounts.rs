@@ -0,0 +1,35 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(Iden)]
+enum GoogleCalendars {
+    Table,
+    AccountEmail,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        m.alter_table(
+            Table::alter()
+                .table(GoogleCalendars::Table)
+                .add_column(string_null(GoogleCalendars::AccountEmail))
+                .to_owned(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        m.alter_table(
+            Table::alter()
+                .table(GoogleCalendars::Table)
+                .drop_column(GoogleCalendars::AccountEmail)
+                .to_owned(),
+        )
+        .await?;
+        Ok(())
+    }
+}
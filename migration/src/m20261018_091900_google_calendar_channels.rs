@@ -0,0 +1,93 @@
+use loco_rs::schema::table_auto_tz;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(Iden)]
+enum GoogleCalendarChannels {
+    Table,
+    Id,
+    GoogleCalendarId,
+    CalendarId,
+    ChannelId,
+    ResourceId,
+    Token,
+    ExpiresAt,
+}
+
+#[derive(Iden)]
+enum GoogleCalendars {
+    Table,
+    Id,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        m.create_table(
+            table_auto_tz(GoogleCalendarChannels::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(GoogleCalendarChannels::Id)
+                        .integer()
+                        .not_null()
+                        .auto_increment()
+                        .primary_key(),
+                )
+                .col(
+                    ColumnDef::new(GoogleCalendarChannels::GoogleCalendarId)
+                        .integer()
+                        .not_null(),
+                )
+                .col(
+                    ColumnDef::new(GoogleCalendarChannels::CalendarId)
+                        .string()
+                        .not_null(),
+                )
+                .col(
+                    ColumnDef::new(GoogleCalendarChannels::ChannelId)
+                        .string()
+                        .not_null()
+                        .unique_key(),
+                )
+                .col(
+                    ColumnDef::new(GoogleCalendarChannels::ResourceId)
+                        .string()
+                        .not_null(),
+                )
+                .col(
+                    ColumnDef::new(GoogleCalendarChannels::Token)
+                        .string()
+                        .not_null(),
+                )
+                .col(
+                    ColumnDef::new(GoogleCalendarChannels::ExpiresAt)
+                        .timestamp_with_time_zone()
+                        .not_null(),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-google-calendar-channels-google_calendar_id")
+                        .from(
+                            GoogleCalendarChannels::Table,
+                            GoogleCalendarChannels::GoogleCalendarId,
+                        )
+                        .to(GoogleCalendars::Table, GoogleCalendars::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        )
+        .await
+    }
+
+    async fn down(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        m.drop_table(
+            Table::drop()
+                .table(GoogleCalendarChannels::Table)
+                .to_owned(),
+        )
+        .await
+    }
+}
@@ -0,0 +1,63 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(Iden)]
+enum GoogleCalendars {
+    Table,
+    Id,
+    UserId,
+    AccountEmail,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        // Overlapping connects could each add the same account, the oldest connection stays.
+        m.exec_stmt(
+            Query::delete()
+                .from_table(GoogleCalendars::Table)
+                .and_where(Expr::col(GoogleCalendars::AccountEmail).is_not_null())
+                .and_where(
+                    Expr::col(GoogleCalendars::Id).not_in_subquery(
+                        Query::select()
+                            .expr(Expr::col(GoogleCalendars::Id).min())
+                            .from(GoogleCalendars::Table)
+                            .and_where(Expr::col(GoogleCalendars::AccountEmail).is_not_null())
+                            .group_by_columns([
+                                GoogleCalendars::UserId,
+                                GoogleCalendars::AccountEmail,
+                            ])
+                            .to_owned(),
+                    ),
+                )
+                .to_owned(),
+        )
+        .await?;
+
+        // Connections from before accounts were told apart have no email, those stay allowed.
+        m.create_index(
+            Index::create()
+                .name("idx-google-calendars-user_id-account_email")
+                .table(GoogleCalendars::Table)
+                .col(GoogleCalendars::UserId)
+                .col(GoogleCalendars::AccountEmail)
+                .unique()
+                .to_owned(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, m: &SchemaManager) -> Result<(), DbErr> {
+        m.drop_index(
+            Index::drop()
+                .name("idx-google-calendars-user_id-account_email")
+                .table(GoogleCalendars::Table)
+                .to_owned(),
+        )
+        .await?;
+        Ok(())
+    }
+}
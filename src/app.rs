@@ -13,10 +13,26 @@ use loco_rs::{
 use migration::Migrator;
 use std::path::Path;
 
-use crate::models::{appointment_types, appointments, weekly_availabilities};
+use crate::models::{
+    appointment_reminders, appointment_types, appointments, availability_overrides, busy_intervals,
+    caldav_connections, outbox_jobs, outlook_calendars, schedules, webhook_deliveries,
+    webhook_endpoints, weekly_availabilities,
+};
 #[allow(unused_imports)]
 use crate::{
-    controllers, initializers, models::_entities::users, tasks, workers::downloader::DownloadWorker,
+    controllers, initializers,
+    models::_entities::users,
+    tasks,
+    workers::{
+        attachment_mailer::AttachmentMailerWorker, deliver_webhook::DeliverWebhookWorker,
+        downloader::DownloadWorker, expire_pending_appointments::ExpirePendingAppointmentsWorker,
+        notify_broken_calendars::NotifyBrokenCalendarsWorker, process_outbox::ProcessOutboxWorker,
+        reconcile_google_events::ReconcileGoogleEventsWorker,
+        refresh_busy_intervals::RefreshBusyIntervalsWorker,
+        renew_google_channels::RenewGoogleChannelsWorker,
+        retry_webhook_deliveries::RetryWebhookDeliveriesWorker,
+        send_reminders::SendRemindersWorker, sync_google_calendar::SyncGoogleCalendarWorker,
+    },
 };
 
 pub struct App;
@@ -47,6 +63,7 @@ impl Hooks for App {
     async fn initializers(_ctx: &AppContext) -> Result<Vec<Box<dyn Initializer>>> {
         Ok(vec![
             Box::new(initializers::env_vars::EnvVarsInitializer),
+            Box::new(initializers::encryption::EncryptionInitializer),
             Box::new(initializers::admin_settings::AdminSettingsInitializer),
         ])
     }
@@ -57,31 +74,84 @@ impl Hooks for App {
             .add_route(controllers::api::admin_settings::routes(ctx))
             .add_route(controllers::api::appointment_types::routes())
             .add_route(controllers::api::appointments::routes())
+            .add_route(controllers::api::availability_overrides::routes())
+            .add_route(controllers::api::calendar_feed::routes())
+            .add_route(controllers::api::integrations::caldav::routes())
+            .add_route(controllers::api::integrations::calendar_providers::routes())
             .add_route(controllers::api::auth::routes())
             .add_route(controllers::api::client_facing::routes())
+            .add_route(controllers::api::schedules::routes())
             .add_route(controllers::api::integrations::google_calendar::routes())
+            .add_route(controllers::api::integrations::outlook_calendar::routes())
+            .add_route(controllers::api::webhooks::routes())
             .add_route(controllers::api::weekly_availabilities::routes())
     }
 
     async fn connect_workers(ctx: &AppContext, queue: &Queue) -> Result<()> {
         queue.register(DownloadWorker::build(ctx)).await?;
+        queue.register(AttachmentMailerWorker::build(ctx)).await?;
+        queue
+            .register(ExpirePendingAppointmentsWorker::build(ctx))
+            .await?;
+        queue.register(SendRemindersWorker::build(ctx)).await?;
+        queue.register(ProcessOutboxWorker::build(ctx)).await?;
+        queue.register(DeliverWebhookWorker::build(ctx)).await?;
+        queue
+            .register(RetryWebhookDeliveriesWorker::build(ctx))
+            .await?;
+        queue
+            .register(NotifyBrokenCalendarsWorker::build(ctx))
+            .await?;
+        queue
+            .register(ReconcileGoogleEventsWorker::build(ctx))
+            .await?;
+        queue
+            .register(RenewGoogleChannelsWorker::build(ctx))
+            .await?;
+        queue
+            .register(RefreshBusyIntervalsWorker::build(ctx))
+            .await?;
+        queue.register(SyncGoogleCalendarWorker::build(ctx)).await?;
         Ok(())
     }
 
     #[allow(unused_variables)]
     fn register_tasks(tasks: &mut Tasks) {
+        tasks.register(tasks::expire_pending_appointments::ExpirePendingAppointments);
+        tasks.register(tasks::send_reminders::SendReminders);
+        tasks.register(tasks::process_outbox::ProcessOutbox);
+        tasks.register(tasks::retry_webhook_deliveries::RetryWebhookDeliveries);
+        tasks.register(tasks::notify_broken_calendars::NotifyBrokenCalendars);
+        tasks.register(tasks::reencrypt_secrets::ReencryptSecrets);
+        tasks.register(tasks::reconcile_google_events::ReconcileGoogleEvents);
+        tasks.register(tasks::renew_google_channels::RenewGoogleChannels);
+        tasks.register(tasks::refresh_busy_intervals::RefreshBusyIntervals);
         // tasks-inject (do not remove)
     }
     async fn truncate(ctx: &AppContext) -> Result<()> {
         truncate_table(&ctx.db, users::Entity).await?;
+        truncate_table(&ctx.db, schedules::Entity).await?;
         truncate_table(&ctx.db, weekly_availabilities::Entity).await?;
         truncate_table(&ctx.db, appointment_types::Entity).await?;
+        truncate_table(&ctx.db, appointment_reminders::Entity).await?;
+        truncate_table(&ctx.db, outbox_jobs::Entity).await?;
+        truncate_table(&ctx.db, webhook_deliveries::Entity).await?;
+        truncate_table(&ctx.db, webhook_endpoints::Entity).await?;
+        truncate_table(&ctx.db, caldav_connections::Entity).await?;
+        truncate_table(&ctx.db, outlook_calendars::Entity).await?;
         truncate_table(&ctx.db, appointments::Entity).await?;
+        truncate_table(&ctx.db, availability_overrides::Entity).await?;
+        truncate_table(&ctx.db, busy_intervals::Entity).await?;
         Ok(())
     }
     async fn seed(ctx: &AppContext, base: &Path) -> Result<()> {
         db::seed::<users::ActiveModel>(&ctx.db, &base.join("users.yaml").display().to_string())
             .await?;
+        db::seed::<schedules::ActiveModel>(
+            &ctx.db,
+            &base.join("schedules.yaml").display().to_string(),
+        )
+        .await?;
         db::seed::<weekly_availabilities::ActiveModel>(
             &ctx.db,
             &base
@@ -0,0 +1,178 @@
+use chrono::{DateTime, Utc};
+use futures::future::{join_all, try_join_all};
+use loco_rs::prelude::*;
+
+use super::{CalendarEvent, CalendarProvider, CalendarProviderKind, NewEvent};
+use crate::{
+    common::{caldav, ics},
+    models::caldav_connections,
+    views::client_facing::AvailabilityWindow,
+};
+
+fn event_url(calendar_url: &str, uid: &str) -> Result<String> {
+    let calendar_url = format!("{}/", calendar_url.trim_end_matches('/'));
+    let event_url = url::Url::parse(&calendar_url)
+        .and_then(|calendar| calendar.join(&format!("{uid}.ics")))
+        .map_err(Error::wrap)?;
+    Ok(event_url.to_string())
+}
+
+/// Calendars on a CalDAV server such as Fastmail or Nextcloud. Events are stored as
+/// `{uid}.ics` in the calendars picked for event handling.
+pub struct CaldavCalendarProvider {
+    client: caldav::Client,
+    connection: caldav_connections::Model,
+}
+
+impl CaldavCalendarProvider {
+    pub fn new(connection: caldav_connections::Model) -> Result<Self> {
+        Ok(Self {
+            client: connection.client()?,
+            connection,
+        })
+    }
+
+    /// Reads every event, changes it and writes it back as long as nobody else changed it in
+    /// the meantime.
+    async fn edit_events(
+        &self,
+        events: &[CalendarEvent],
+        edit: impl Fn(&str) -> String + Send + Sync,
+    ) -> Result<()> {
+        let futures = events.iter().map(|event| {
+            let edit = &edit;
+            async move {
+                let (data, etag) = self.client.get(&event.event_id).await?;
+                self.client
+                    .put(&event.event_id, edit(&data), etag.as_deref())
+                    .await
+            }
+        });
+
+        try_join_all(futures).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CalendarProvider for CaldavCalendarProvider {
+    fn kind(&self) -> CalendarProviderKind {
+        CalendarProviderKind::Caldav
+    }
+
+    async fn is_connected(&self) -> bool {
+        self.client.discover_calendars().await.is_ok()
+    }
+
+    async fn busy_times(
+        &self,
+        time_min: DateTime<Utc>,
+        time_max: DateTime<Utc>,
+    ) -> Result<Vec<AvailabilityWindow>> {
+        let futures = self
+            .connection
+            .calendars_for_collision_check
+            .0
+            .iter()
+            .map(|calendar| self.client.events_between(calendar, time_min, time_max));
+
+        let availability_windows = try_join_all(futures)
+            .await?
+            .into_iter()
+            .flatten()
+            .flat_map(|data| ics::busy_times(&data))
+            .map(|(start, end)| AvailabilityWindow {
+                start,
+                end,
+                remaining_seats: None,
+            })
+            .collect();
+        Ok(availability_windows)
+    }
+
+    async fn create_event(&self, event: &NewEvent) -> Result<Vec<CalendarEvent>> {
+        let uid = Uuid::new_v4().to_string();
+        let data = ics::Event {
+            uid: uid.clone(),
+            sequence: 0,
+            stamp: Utc::now(),
+            start: event.start,
+            end: event.end,
+            summary: event.summary.clone(),
+            description: Some(event.description.clone()).filter(|text| !text.is_empty()),
+            url: None,
+            status: ics::EventStatus::Confirmed,
+            organizer: ics::Person {
+                name: event.organizer_name.clone(),
+                email: event.organizer_email.clone(),
+            },
+            attendees: vec![ics::Person {
+                name: event.attendee_name.clone(),
+                email: event.attendee_email.clone(),
+            }],
+        }
+        .to_calendar_object();
+
+        let futures = self
+            .connection
+            .calendars_for_event_handling
+            .0
+            .iter()
+            .map(|calendar_url| {
+                let data = data.clone();
+                let uid = &uid;
+                async move {
+                    let event_url = event_url(calendar_url, uid)?;
+                    self.client.put(&event_url, data, None).await?;
+                    Ok::<_, Error>(CalendarEvent {
+                        provider: CalendarProviderKind::Caldav,
+                        calendar_id: calendar_url.clone(),
+                        event_id: event_url,
+                        connection_id: None,
+                    })
+                }
+            });
+
+        try_join_all(futures).await
+    }
+
+    async fn update_events(
+        &self,
+        events: &[CalendarEvent],
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<()> {
+        self.edit_events(events, |data| ics::reschedule(data, start, end))
+            .await
+    }
+
+    async fn delete_events(&self, events: &[CalendarEvent]) -> Result<()> {
+        join_all(
+            events
+                .iter()
+                .map(|event| self.client.delete(&event.event_id)),
+        )
+        .await
+        .into_iter()
+        .filter_map(std::result::Result::err)
+        .for_each(|e| {
+            tracing::error!("Error deleting event: {}", e);
+        });
+
+        Ok(())
+    }
+
+    async fn add_attendee(&self, events: &[CalendarEvent], email: &str) -> Result<()> {
+        let attendee = ics::Person {
+            name: email.to_string(),
+            email: email.to_string(),
+        };
+        self.edit_events(events, |data| ics::add_attendee(data, &attendee))
+            .await
+    }
+
+    async fn remove_attendee(&self, events: &[CalendarEvent], email: &str) -> Result<()> {
+        self.edit_events(events, |data| ics::remove_attendee(data, email))
+            .await
+    }
+}
@@ -0,0 +1,416 @@
+use chrono::{DateTime, Utc};
+use futures::future::{join_all, try_join_all};
+use google_calendar::{
+    types::{
+        ConferenceData, ConferenceSolutionKey, CreateConferenceRequest, Event, EventAttendee,
+        EventDateTime, FreeBusyRequestItem, SendUpdates,
+    },
+    ClientError, ErrorKind,
+};
+use loco_rs::prelude::*;
+
+use super::{CalendarEvent, CalendarProvider, CalendarProviderKind, NewEvent};
+use crate::{
+    models::{
+        google_calendars::{self, GoogleCalendars},
+        users,
+    },
+    views::client_facing::AvailabilityWindow,
+};
+
+fn event_attendee(email: &str, organizer: bool) -> EventAttendee {
+    EventAttendee {
+        email: email.to_string(),
+        response_status: "accepted".to_string(),
+        additional_guests: 0,
+        comment: String::new(),
+        display_name: String::new(),
+        id: String::new(),
+        optional: false,
+        organizer,
+        resource: false,
+        self_: false,
+    }
+}
+
+fn event_date_time(date_time: DateTime<Utc>) -> Option<EventDateTime> {
+    Some(EventDateTime {
+        date: None,
+        time_zone: String::new(),
+        date_time: Some(date_time),
+    })
+}
+
+/// Change made in Google to the event of an appointment, outside the app.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventDrift {
+    /// The owner deleted the event.
+    Deleted,
+    /// The owner moved the event, `start` is `None` when it became an all day event.
+    Moved { start: Option<DateTime<Utc>> },
+    /// The booker declined the invitation.
+    Declined,
+}
+
+/// Google Calendar of the owner. Busy times come from the calendars picked for collision
+/// checks, events go to the ones picked for event handling.
+pub struct GoogleCalendarProvider {
+    /// `None` when no valid access token could be had, the provider then shows as disconnected.
+    client: Option<google_calendar::Client>,
+    settings: google_calendars::Model,
+}
+
+impl GoogleCalendarProvider {
+    pub async fn new<C: ConnectionTrait>(db: &C, settings: google_calendars::Model) -> Self {
+        let client = match settings.client(db).await {
+            Ok(client) => Some(client),
+            Err(err) => {
+                tracing::warn!(
+                    "Google calendar of user {} unusable: {}",
+                    settings.user_id,
+                    err
+                );
+                None
+            }
+        };
+
+        Self { client, settings }
+    }
+
+    /// Every Google connection of the user that can reach Google right now.
+    pub async fn connected_for_user<C: ConnectionTrait>(
+        db: &C,
+        user: &users::Model,
+    ) -> Result<Vec<Self>> {
+        let mut providers = Vec::new();
+        for google_calendar in GoogleCalendars::find_by_user(db, user).await? {
+            let provider = Self::new(db, google_calendar).await;
+            if provider.client.is_some() {
+                providers.push(provider);
+            }
+        }
+        Ok(providers)
+    }
+
+    fn client(&self) -> Result<google_calendar::Client> {
+        self.client
+            .clone()
+            .ok_or_else(|| Error::Message("Google calendar is not connected".to_string()))
+    }
+
+    /// Compares the event in Google with the window and booker it was created for, `None`
+    /// when nothing that matters to the appointment changed.
+    pub async fn drift(
+        &self,
+        event: &CalendarEvent,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        booker_email: &str,
+    ) -> Result<Option<EventDrift>> {
+        let client = self.client()?;
+        let remote = match client
+            .events()
+            .get(&event.calendar_id, &event.event_id, 0, "")
+            .await
+        {
+            Ok(response) => response.body,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Some(EventDrift::Deleted)),
+            Err(err) => return Err(google_calendars::api_error(err)),
+        };
+
+        // Deleted events stay readable for a while, cancelled.
+        if remote.status == "cancelled" {
+            return Ok(Some(EventDrift::Deleted));
+        }
+        if remote.attendees.iter().any(|attendee| {
+            attendee.email.eq_ignore_ascii_case(booker_email)
+                && attendee.response_status == "declined"
+        }) {
+            return Ok(Some(EventDrift::Declined));
+        }
+        let remote_start = remote.start.and_then(|date_time| date_time.date_time);
+        let remote_end = remote.end.and_then(|date_time| date_time.date_time);
+        // Google keeps whole seconds.
+        let moved = |remote: Option<DateTime<Utc>>, stored: DateTime<Utc>| {
+            remote.is_none_or(|remote| remote.timestamp() != stored.timestamp())
+        };
+        if moved(remote_start, start) || moved(remote_end, end) {
+            return Ok(Some(EventDrift::Moved {
+                start: remote_start,
+            }));
+        }
+
+        Ok(None)
+    }
+
+    async fn update_attendees(
+        &self,
+        events: &[CalendarEvent],
+        update: impl Fn(&mut Vec<EventAttendee>) + Clone,
+    ) -> Result<()> {
+        let client = self.client()?;
+
+        let futures = events
+            .iter()
+            .map(|stored_event| {
+                let client = client.clone();
+                let update = update.clone();
+                async move {
+                    let mut attendees = client
+                        .events()
+                        .get(&stored_event.calendar_id, &stored_event.event_id, 0, "")
+                        .await?
+                        .body
+                        .attendees;
+                    update(&mut attendees);
+                    // Only the guests, a whole `Event` would reset everything else.
+                    let body = serde_json::json!({ "attendees": attendees });
+                    client
+                        .events()
+                        .patch(
+                            &stored_event.calendar_id,
+                            &stored_event.event_id,
+                            1,
+                            0,
+                            false,
+                            SendUpdates::All,
+                            false,
+                            &body,
+                        )
+                        .await?;
+                    Ok::<_, ClientError>(())
+                }
+            })
+            .collect::<Vec<_>>();
+
+        try_join_all(futures)
+            .await
+            .map_err(google_calendars::api_error)?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CalendarProvider for GoogleCalendarProvider {
+    fn kind(&self) -> CalendarProviderKind {
+        CalendarProviderKind::Google
+    }
+
+    fn connection_id(&self) -> Option<i32> {
+        Some(self.settings.id)
+    }
+
+    fn owns(&self, event: &CalendarEvent) -> bool {
+        event.provider == CalendarProviderKind::Google
+            && match event.connection_id {
+                Some(connection_id) => connection_id == self.settings.id,
+                // Stored when a user had one connection, its calendar tells which one it was.
+                None => self
+                    .settings
+                    .calendars_for_event_handling
+                    .0
+                    .contains(&event.calendar_id),
+            }
+    }
+
+    async fn is_connected(&self) -> bool {
+        self.client.is_some()
+    }
+
+    async fn busy_times(
+        &self,
+        time_min: DateTime<Utc>,
+        time_max: DateTime<Utc>,
+    ) -> Result<Vec<AvailabilityWindow>> {
+        let client = self.client()?;
+
+        let items = self
+            .settings
+            .calendars_for_collision_check
+            .0
+            .iter()
+            .map(|id| FreeBusyRequestItem { id: id.clone() })
+            .collect();
+
+        let query = google_calendar::types::FreeBusyRequest {
+            items,
+            time_min: Some(time_min),
+            time_max: Some(time_max),
+            calendar_expansion_max: 0,
+            group_expansion_max: 0,
+            time_zone: String::new(),
+        };
+
+        let free_busy_response = client
+            .freebusy()
+            .query(&query)
+            .await
+            .map_err(google_calendars::api_error)?;
+        let availability_windows = free_busy_response
+            .body
+            .calendars
+            .into_iter()
+            .flat_map(|cal| {
+                cal.1.busy.into_iter().map(|x| AvailabilityWindow {
+                    start: x.start,
+                    end: x.end,
+                    remaining_seats: None,
+                })
+            })
+            .filter(|window| window.start != window.end)
+            .collect();
+        Ok(availability_windows)
+    }
+
+    async fn create_event(&self, event: &NewEvent) -> Result<Vec<CalendarEvent>> {
+        let client = self.client()?;
+
+        let event = Event {
+            summary: event.summary.clone(),
+            description: event.description.clone(),
+            attendees: vec![
+                event_attendee(&event.organizer_email, true),
+                event_attendee(&event.attendee_email, false),
+            ],
+            start: event_date_time(event.start),
+            end: event_date_time(event.end),
+            conference_data: Some(ConferenceData {
+                conference_solution: None,
+                conference_id: Uuid::new_v4().into(),
+                create_request: Some(CreateConferenceRequest {
+                    conference_solution_key: Some(ConferenceSolutionKey {
+                        type_: "hangoutsMeet".to_string(),
+                    }),
+                    request_id: Uuid::new_v4().into(),
+                    status: None,
+                }),
+                entry_points: vec![],
+                notes: String::new(),
+                parameters: None,
+                signature: String::new(),
+            }),
+            ..Default::default()
+        };
+
+        let connection_id = self.settings.id;
+        let futures = self
+            .settings
+            .calendars_for_event_handling
+            .0
+            .iter()
+            .cloned()
+            .map(|calendar_id| {
+                let client = client.clone();
+                let event = event.clone();
+                async move {
+                    let event = client
+                        .events()
+                        .insert(&calendar_id, 1, 0, false, SendUpdates::All, false, &event)
+                        .await?;
+                    Ok::<_, ClientError>(CalendarEvent {
+                        provider: CalendarProviderKind::Google,
+                        calendar_id,
+                        event_id: event.body.id,
+                        connection_id: Some(connection_id),
+                    })
+                }
+            })
+            .collect::<Vec<_>>();
+
+        try_join_all(futures)
+            .await
+            .map_err(google_calendars::api_error)
+    }
+
+    async fn update_events(
+        &self,
+        events: &[CalendarEvent],
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<()> {
+        let client = self.client()?;
+
+        // Only the window, a whole `Event` would reset the guest permissions of the event.
+        let event = serde_json::json!({
+            "start": event_date_time(start),
+            "end": event_date_time(end),
+        });
+
+        let futures = events
+            .iter()
+            .map(|stored_event| {
+                let client = client.clone();
+                let event = event.clone();
+                async move {
+                    client
+                        .events()
+                        .patch(
+                            &stored_event.calendar_id,
+                            &stored_event.event_id,
+                            1,
+                            0,
+                            false,
+                            SendUpdates::All,
+                            false,
+                            &event,
+                        )
+                        .await?;
+                    Ok::<_, ClientError>(())
+                }
+            })
+            .collect::<Vec<_>>();
+
+        try_join_all(futures)
+            .await
+            .map_err(google_calendars::api_error)?;
+
+        Ok(())
+    }
+
+    async fn delete_events(&self, events: &[CalendarEvent]) -> Result<()> {
+        let client = self.client()?;
+
+        let futures = events
+            .iter()
+            .map(|event| {
+                let client = client.clone();
+                async move {
+                    client
+                        .events()
+                        .delete(&event.calendar_id, &event.event_id, true, SendUpdates::All)
+                        .await?;
+                    Ok::<_, ClientError>(())
+                }
+            })
+            .collect::<Vec<_>>();
+
+        join_all(futures)
+            .await
+            .into_iter()
+            .filter_map(std::result::Result::err)
+            // Events already deleted in Google are gone either way.
+            .filter(|e| e.kind() != ErrorKind::NotFound)
+            .for_each(|e| {
+                tracing::error!("Error deleting event: {}", e);
+            });
+
+        Ok(())
+    }
+
+    async fn add_attendee(&self, events: &[CalendarEvent], email: &str) -> Result<()> {
+        self.update_attendees(events, |attendees| {
+            if !attendees.iter().any(|attendee| attendee.email == email) {
+                attendees.push(event_attendee(email, false));
+            }
+        })
+        .await
+    }
+
+    async fn remove_attendee(&self, events: &[CalendarEvent], email: &str) -> Result<()> {
+        self.update_attendees(events, |attendees| {
+            attendees.retain(|attendee| attendee.email != email);
+        })
+        .await
+    }
+}
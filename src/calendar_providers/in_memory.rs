@@ -0,0 +1,197 @@
+//! Calendar that lives in the memory of the process, a stand-in for real providers in tests.
+//! Users only have one after [`InMemoryCalendarProvider::connect`] was called for them.
+
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex, MutexGuard},
+};
+
+use chrono::{DateTime, Utc};
+use loco_rs::prelude::*;
+
+use super::{CalendarEvent, CalendarProvider, CalendarProviderKind, NewEvent};
+use crate::{models::users, views::client_facing::AvailabilityWindow};
+
+const CALENDAR_ID: &str = "in-memory";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InMemoryEvent {
+    pub id: String,
+    pub summary: String,
+    pub description: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub attendees: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+struct InMemoryCalendar {
+    disconnected: bool,
+    busy: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+    events: Vec<InMemoryEvent>,
+}
+
+/// Calendars by user id.
+static CALENDARS: LazyLock<Mutex<HashMap<i32, InMemoryCalendar>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn calendars() -> MutexGuard<'static, HashMap<i32, InMemoryCalendar>> {
+    // A test that panicked while holding the lock leaves nothing half written behind.
+    CALENDARS
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+pub struct InMemoryCalendarProvider {
+    user_id: i32,
+}
+
+impl InMemoryCalendarProvider {
+    /// Gives the user an empty calendar, replacing the one they had.
+    #[must_use]
+    pub fn connect(user: &users::Model) -> Self {
+        calendars().insert(user.id, InMemoryCalendar::default());
+        Self { user_id: user.id }
+    }
+
+    pub fn disconnect(user: &users::Model) {
+        calendars().remove(&user.id);
+    }
+
+    #[must_use]
+    pub fn find_by_user(user: &users::Model) -> Option<Self> {
+        calendars()
+            .contains_key(&user.id)
+            .then_some(Self { user_id: user.id })
+    }
+
+    /// Makes every call fail, like a provider that revoked access.
+    pub fn set_connected(&self, connected: bool) {
+        self.with_calendar(|calendar| calendar.disconnected = !connected);
+    }
+
+    /// Blocks time that has nothing to do with appointments.
+    pub fn add_busy(&self, start: DateTime<Utc>, end: DateTime<Utc>) {
+        self.with_calendar(|calendar| calendar.busy.push((start, end)));
+    }
+
+    #[must_use]
+    pub fn events(&self) -> Vec<InMemoryEvent> {
+        self.with_calendar(|calendar| calendar.events.clone())
+    }
+
+    fn with_calendar<T>(&self, f: impl FnOnce(&mut InMemoryCalendar) -> T) -> T {
+        f(calendars().entry(self.user_id).or_default())
+    }
+
+    fn with_connected_calendar<T>(&self, f: impl FnOnce(&mut InMemoryCalendar) -> T) -> Result<T> {
+        self.with_calendar(|calendar| {
+            if calendar.disconnected {
+                return Err(Error::Message(
+                    "In-memory calendar is disconnected.".to_string(),
+                ));
+            }
+            Ok(f(calendar))
+        })
+    }
+
+    fn update_matching(
+        &self,
+        events: &[CalendarEvent],
+        update: impl Fn(&mut InMemoryEvent),
+    ) -> Result<()> {
+        self.with_connected_calendar(|calendar| {
+            calendar
+                .events
+                .iter_mut()
+                .filter(|event| events.iter().any(|stored| stored.event_id == event.id))
+                .for_each(update);
+        })
+    }
+}
+
+#[async_trait]
+impl CalendarProvider for InMemoryCalendarProvider {
+    fn kind(&self) -> CalendarProviderKind {
+        CalendarProviderKind::InMemory
+    }
+
+    async fn is_connected(&self) -> bool {
+        self.with_calendar(|calendar| !calendar.disconnected)
+    }
+
+    async fn busy_times(
+        &self,
+        time_min: DateTime<Utc>,
+        time_max: DateTime<Utc>,
+    ) -> Result<Vec<AvailabilityWindow>> {
+        self.with_connected_calendar(|calendar| {
+            calendar
+                .busy
+                .iter()
+                .copied()
+                .chain(calendar.events.iter().map(|event| (event.start, event.end)))
+                .filter(|(start, end)| *end > time_min && *start < time_max)
+                .map(|(start, end)| AvailabilityWindow {
+                    start,
+                    end,
+                    remaining_seats: None,
+                })
+                .collect()
+        })
+    }
+
+    async fn create_event(&self, event: &NewEvent) -> Result<Vec<CalendarEvent>> {
+        self.with_connected_calendar(|calendar| {
+            let id = Uuid::new_v4().to_string();
+            calendar.events.push(InMemoryEvent {
+                id: id.clone(),
+                summary: event.summary.clone(),
+                description: event.description.clone(),
+                start: event.start,
+                end: event.end,
+                attendees: vec![event.organizer_email.clone(), event.attendee_email.clone()],
+            });
+            vec![CalendarEvent {
+                provider: CalendarProviderKind::InMemory,
+                calendar_id: CALENDAR_ID.to_string(),
+                event_id: id,
+                connection_id: None,
+            }]
+        })
+    }
+
+    async fn update_events(
+        &self,
+        events: &[CalendarEvent],
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<()> {
+        self.update_matching(events, |event| {
+            event.start = start;
+            event.end = end;
+        })
+    }
+
+    async fn delete_events(&self, events: &[CalendarEvent]) -> Result<()> {
+        self.with_connected_calendar(|calendar| {
+            calendar
+                .events
+                .retain(|event| !events.iter().any(|stored| stored.event_id == event.id));
+        })
+    }
+
+    async fn add_attendee(&self, events: &[CalendarEvent], email: &str) -> Result<()> {
+        self.update_matching(events, |event| {
+            if !event.attendees.iter().any(|attendee| attendee == email) {
+                event.attendees.push(email.to_string());
+            }
+        })
+    }
+
+    async fn remove_attendee(&self, events: &[CalendarEvent], email: &str) -> Result<()> {
+        self.update_matching(events, |event| {
+            event.attendees.retain(|attendee| attendee != email);
+        })
+    }
+}
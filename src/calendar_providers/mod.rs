@@ -0,0 +1,229 @@
+//! Calendars an owner connects. Busy times are read from every one of them, and booked
+//! appointments get an event in each.
+
+pub mod caldav;
+pub mod google;
+#[cfg(any(test, feature = "testing"))]
+pub mod in_memory;
+pub mod outlook;
+
+use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
+use futures::future::join_all;
+use loco_rs::{controller::ErrorDetail, prelude::*};
+
+pub use crate::models::_entities::appointments::{CalendarEvent, CalendarProviderKind};
+use crate::{
+    models::{
+        caldav_connections::CaldavConnections, google_calendars::GoogleCalendars,
+        outlook_calendars::OutlookCalendars, users,
+    },
+    views::client_facing::AvailabilityWindow,
+};
+use caldav::CaldavCalendarProvider;
+use google::GoogleCalendarProvider;
+#[cfg(any(test, feature = "testing"))]
+use in_memory::InMemoryCalendarProvider;
+use outlook::OutlookCalendarProvider;
+
+/// Error returned when a check that needs every calendar could not reach one of them.
+#[must_use]
+pub fn calendars_unavailable() -> Error {
+    Error::CustomError(
+        StatusCode::SERVICE_UNAVAILABLE,
+        ErrorDetail::new(
+            "calendar_unavailable",
+            "A connected calendar is not answering, try again in a moment.",
+        ),
+    )
+}
+
+/// Event to create for an appointment, the same in every provider.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewEvent {
+    pub summary: String,
+    pub description: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub organizer_name: String,
+    pub organizer_email: String,
+    pub attendee_name: String,
+    pub attendee_email: String,
+}
+
+#[async_trait]
+pub trait CalendarProvider: Send + Sync {
+    fn kind(&self) -> CalendarProviderKind;
+
+    /// Tells connections apart for kinds a user can connect more than once.
+    fn connection_id(&self) -> Option<i32> {
+        None
+    }
+
+    /// Whether the event was created through this provider.
+    fn owns(&self, event: &CalendarEvent) -> bool {
+        event.provider == self.kind()
+    }
+
+    /// Whether the provider still accepts the stored credentials.
+    async fn is_connected(&self) -> bool;
+
+    async fn busy_times(
+        &self,
+        time_min: DateTime<Utc>,
+        time_max: DateTime<Utc>,
+    ) -> Result<Vec<AvailabilityWindow>>;
+
+    /// Creates the event, possibly in more than one calendar of the provider.
+    async fn create_event(&self, event: &NewEvent) -> Result<Vec<CalendarEvent>>;
+
+    /// Moves the events to a new window.
+    async fn update_events(
+        &self,
+        events: &[CalendarEvent],
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<()>;
+
+    async fn delete_events(&self, events: &[CalendarEvent]) -> Result<()>;
+
+    /// Adds a booker to events shared by every seat of a group slot.
+    async fn add_attendee(&self, events: &[CalendarEvent], email: &str) -> Result<()>;
+
+    /// Removes a booker from events shared by every seat of a group slot.
+    async fn remove_attendee(&self, events: &[CalendarEvent], email: &str) -> Result<()>;
+}
+
+/// Every provider a user connected.
+pub struct CalendarProviders(Vec<Box<dyn CalendarProvider>>);
+
+impl CalendarProviders {
+    pub async fn for_user<C: ConnectionTrait>(db: &C, user: &users::Model) -> Result<Self> {
+        let mut providers: Vec<Box<dyn CalendarProvider>> = Vec::new();
+        for google_calendar in GoogleCalendars::find_by_user(db, user).await? {
+            providers.push(Box::new(
+                GoogleCalendarProvider::new(db, google_calendar).await,
+            ));
+        }
+        if let Some(connection) = CaldavConnections::find_optional_by_user(db, user).await? {
+            providers.push(Box::new(CaldavCalendarProvider::new(connection)?));
+        }
+        if let Some(outlook_calendar) = OutlookCalendars::find_optional_by_user(db, user).await? {
+            // Getting the client may refresh the access token, a revoked one must not take the
+            // other providers down with it.
+            match OutlookCalendarProvider::new(db, outlook_calendar).await {
+                Ok(provider) => providers.push(Box::new(provider)),
+                Err(err) => tracing::warn!("Skipping Outlook calendar: {}", err),
+            }
+        }
+        #[cfg(any(test, feature = "testing"))]
+        if let Some(in_memory) = InMemoryCalendarProvider::find_by_user(user) {
+            providers.push(Box::new(in_memory));
+        }
+
+        Ok(Self(providers))
+    }
+
+    pub fn all(&self) -> impl Iterator<Item = &dyn CalendarProvider> {
+        self.0.iter().map(AsRef::as_ref)
+    }
+
+    /// Busy times of all providers, overlapping windows merged into one. A provider that fails
+    /// is only logged, so one broken connection does not make everything look free or busy.
+    pub async fn busy_times(
+        &self,
+        time_min: DateTime<Utc>,
+        time_max: DateTime<Utc>,
+    ) -> Vec<AvailabilityWindow> {
+        self.busy_times_checked(time_min, time_max).await.0
+    }
+
+    /// Like [`Self::busy_times`], also telling whether every provider answered.
+    pub async fn busy_times_checked(
+        &self,
+        time_min: DateTime<Utc>,
+        time_max: DateTime<Utc>,
+    ) -> (Vec<AvailabilityWindow>, bool) {
+        let results = join_all(
+            self.all()
+                .map(|provider| provider.busy_times(time_min, time_max)),
+        )
+        .await;
+
+        let mut complete = true;
+        let windows = self
+            .all()
+            .zip(results)
+            .flat_map(|(provider, result)| match result {
+                Ok(windows) => windows,
+                Err(err) => {
+                    tracing::warn!(
+                        "Error getting busy times from {:?}: {}",
+                        provider.kind(),
+                        err
+                    );
+                    complete = false;
+                    Vec::new()
+                }
+            })
+            .collect();
+
+        (merge_windows(windows), complete)
+    }
+
+    /// Moves the events to a new window, each in the provider it was created in.
+    pub async fn update_events(
+        &self,
+        events: &[CalendarEvent],
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<()> {
+        for (provider, events) in self.group_by_provider(events) {
+            provider.update_events(&events, start, end).await?;
+        }
+        Ok(())
+    }
+
+    /// Deletes the events, each from the provider it was created in. Events of providers that
+    /// were disconnected since are left alone.
+    pub async fn delete_events(&self, events: &[CalendarEvent]) -> Result<()> {
+        for (provider, events) in self.group_by_provider(events) {
+            provider.delete_events(&events).await?;
+        }
+        Ok(())
+    }
+
+    fn group_by_provider(
+        &self,
+        events: &[CalendarEvent],
+    ) -> Vec<(&dyn CalendarProvider, Vec<CalendarEvent>)> {
+        self.all()
+            .map(|provider| (provider, events_of(events, provider)))
+            .filter(|(_, events)| !events.is_empty())
+            .collect()
+    }
+}
+
+/// The events that were created in the calendars of one provider.
+#[must_use]
+pub fn events_of(events: &[CalendarEvent], provider: &dyn CalendarProvider) -> Vec<CalendarEvent> {
+    events
+        .iter()
+        .filter(|event| provider.owns(event))
+        .cloned()
+        .collect()
+}
+
+/// Sorts the windows and merges the ones that overlap or touch.
+fn merge_windows(mut windows: Vec<AvailabilityWindow>) -> Vec<AvailabilityWindow> {
+    windows.sort_by_key(|window| window.start);
+
+    let mut merged: Vec<AvailabilityWindow> = Vec::with_capacity(windows.len());
+    for window in windows {
+        match merged.last_mut() {
+            Some(last) if window.start <= last.end => last.end = last.end.max(window.end),
+            _ => merged.push(window),
+        }
+    }
+    merged
+}
@@ -0,0 +1,202 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use futures::future::{join_all, try_join_all};
+use loco_rs::prelude::*;
+
+use super::{CalendarEvent, CalendarProvider, CalendarProviderKind, NewEvent};
+use crate::{
+    common::microsoft_graph::{self, Attendee, GraphDateTime, ItemBody},
+    models::outlook_calendars,
+    views::client_facing::AvailabilityWindow,
+};
+
+/// Outlook calendars of the owner, through Microsoft Graph. Events get a Teams meeting.
+pub struct OutlookCalendarProvider {
+    client: microsoft_graph::Client,
+    settings: outlook_calendars::Model,
+}
+
+impl OutlookCalendarProvider {
+    pub async fn new<C: ConnectionTrait>(
+        db: &C,
+        settings: outlook_calendars::Model,
+    ) -> Result<Self> {
+        Ok(Self {
+            client: settings.client(db).await?,
+            settings,
+        })
+    }
+
+    /// Graph reports free/busy per mailbox, so the calendars picked for collision checks are
+    /// resolved to the mailboxes that own them.
+    async fn collision_check_mailboxes(&self) -> Result<Vec<String>> {
+        let selected = &self.settings.calendars_for_collision_check.0;
+        if selected.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mailboxes: HashSet<String> = self
+            .client
+            .calendars()
+            .await?
+            .into_iter()
+            .filter(|calendar| selected.contains(&calendar.id))
+            .filter_map(|calendar| calendar.owner.map(|owner| owner.address))
+            .collect();
+        Ok(mailboxes.into_iter().collect())
+    }
+
+    async fn update_attendees(
+        &self,
+        events: &[CalendarEvent],
+        update: impl Fn(&mut Vec<Attendee>) + Send + Sync,
+    ) -> Result<()> {
+        let futures = events.iter().map(|stored_event| {
+            let update = &update;
+            async move {
+                let mut attendees = self
+                    .client
+                    .get_event(&stored_event.event_id)
+                    .await?
+                    .attendees;
+                update(&mut attendees);
+                self.client
+                    .update_event(
+                        &stored_event.event_id,
+                        &serde_json::json!({ "attendees": attendees }),
+                    )
+                    .await
+            }
+        });
+
+        try_join_all(futures).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CalendarProvider for OutlookCalendarProvider {
+    fn kind(&self) -> CalendarProviderKind {
+        CalendarProviderKind::Outlook
+    }
+
+    async fn is_connected(&self) -> bool {
+        self.client.calendars().await.is_ok()
+    }
+
+    async fn busy_times(
+        &self,
+        time_min: DateTime<Utc>,
+        time_max: DateTime<Utc>,
+    ) -> Result<Vec<AvailabilityWindow>> {
+        let mailboxes = self.collision_check_mailboxes().await?;
+
+        let availability_windows = self
+            .client
+            .busy_times(&mailboxes, time_min, time_max)
+            .await?
+            .into_iter()
+            .map(|(start, end)| AvailabilityWindow {
+                start,
+                end,
+                remaining_seats: None,
+            })
+            .collect();
+        Ok(availability_windows)
+    }
+
+    async fn create_event(&self, event: &NewEvent) -> Result<Vec<CalendarEvent>> {
+        let event = microsoft_graph::NewEvent {
+            subject: event.summary.clone(),
+            body: ItemBody {
+                content_type: "text".to_string(),
+                content: event.description.clone(),
+            },
+            start: GraphDateTime::from(event.start),
+            end: GraphDateTime::from(event.end),
+            attendees: vec![Attendee::required(
+                &event.attendee_email,
+                &event.attendee_name,
+            )],
+            is_online_meeting: true,
+            online_meeting_provider: "teamsForBusiness".to_string(),
+        };
+
+        let futures = self
+            .settings
+            .calendars_for_event_handling
+            .0
+            .iter()
+            .map(|calendar_id| {
+                let event = &event;
+                async move {
+                    let created = self.client.create_event(calendar_id, event).await?;
+                    Ok::<_, Error>(CalendarEvent {
+                        provider: CalendarProviderKind::Outlook,
+                        calendar_id: calendar_id.clone(),
+                        event_id: created.id,
+                        connection_id: None,
+                    })
+                }
+            });
+
+        try_join_all(futures).await
+    }
+
+    async fn update_events(
+        &self,
+        events: &[CalendarEvent],
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<()> {
+        let changes = serde_json::json!({
+            "start": GraphDateTime::from(start),
+            "end": GraphDateTime::from(end),
+        });
+
+        try_join_all(
+            events
+                .iter()
+                .map(|event| self.client.update_event(&event.event_id, &changes)),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn delete_events(&self, events: &[CalendarEvent]) -> Result<()> {
+        join_all(
+            events
+                .iter()
+                .map(|event| self.client.delete_event(&event.event_id)),
+        )
+        .await
+        .into_iter()
+        .filter_map(std::result::Result::err)
+        .for_each(|e| {
+            tracing::error!("Error deleting event: {}", e);
+        });
+
+        Ok(())
+    }
+
+    async fn add_attendee(&self, events: &[CalendarEvent], email: &str) -> Result<()> {
+        self.update_attendees(events, |attendees| {
+            if !attendees
+                .iter()
+                .any(|attendee| attendee.email_address.address.eq_ignore_ascii_case(email))
+            {
+                attendees.push(Attendee::required(email, email));
+            }
+        })
+        .await
+    }
+
+    async fn remove_attendee(&self, events: &[CalendarEvent], email: &str) -> Result<()> {
+        self.update_attendees(events, |attendees| {
+            attendees
+                .retain(|attendee| !attendee.email_address.address.eq_ignore_ascii_case(email));
+        })
+        .await
+    }
+}
@@ -0,0 +1,354 @@
+//! Small CalDAV (RFC 4791) client, just enough to find a user's calendars, read busy times
+//! from them and write the events of booked appointments.
+
+use chrono::{DateTime, Utc};
+use loco_rs::prelude::*;
+use quick_xml::events::Event as XmlEvent;
+use reqwest::{header, Method, StatusCode};
+
+use crate::common::public_http;
+
+const REQUEST_TIMEOUT_IN_SECONDS: u64 = 20;
+
+const PROPFIND_PRINCIPAL: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop>
+    <d:current-user-principal/>
+    <c:calendar-home-set/>
+  </d:prop>
+</d:propfind>"#;
+
+const PROPFIND_CALENDARS: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop>
+    <d:resourcetype/>
+    <d:displayname/>
+    <c:supported-calendar-component-set/>
+  </d:prop>
+</d:propfind>"#;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Calendar {
+    /// Absolute URL of the calendar collection.
+    pub url: String,
+    pub display_name: String,
+}
+
+/// The parts of a `DAV:response` the client looks at.
+#[derive(Debug, Default)]
+struct DavResponse {
+    href: String,
+    current_user_principal: Option<String>,
+    calendar_home_set: Option<String>,
+    is_calendar: bool,
+    display_name: Option<String>,
+    components: Vec<String>,
+    calendar_data: Option<String>,
+}
+
+/// Reads a `DAV:multistatus` body. Namespaces are ignored, the element names the client cares
+/// about are unique across `DAV:` and CalDAV.
+fn parse_multistatus(body: &str) -> Result<Vec<DavResponse>> {
+    let mut reader = quick_xml::Reader::from_str(body);
+    reader.config_mut().trim_text(true);
+
+    let mut responses: Vec<DavResponse> = Vec::new();
+    let mut path: Vec<String> = Vec::new();
+    loop {
+        let event = reader.read_event().map_err(Error::wrap)?;
+        let (name, empty) = match &event {
+            XmlEvent::Start(start) => (local_name(start.local_name().as_ref()), false),
+            XmlEvent::Empty(start) => (local_name(start.local_name().as_ref()), true),
+            _ => (String::new(), false),
+        };
+
+        match event {
+            XmlEvent::Start(_) | XmlEvent::Empty(_) => {
+                let parent = path.last().map(String::as_str);
+                match (name.as_str(), parent) {
+                    ("response", _) => responses.push(DavResponse::default()),
+                    ("calendar", Some("resourcetype")) => {
+                        if let Some(response) = responses.last_mut() {
+                            response.is_calendar = true;
+                        }
+                    }
+                    ("comp", Some("supported-calendar-component-set")) => {
+                        if let (XmlEvent::Start(comp) | XmlEvent::Empty(comp), Some(response)) =
+                            (&event, responses.last_mut())
+                        {
+                            if let Some(attribute) =
+                                comp.try_get_attribute("name").map_err(Error::wrap)?
+                            {
+                                response
+                                    .components
+                                    .push(String::from_utf8_lossy(&attribute.value).to_string());
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+                if !empty {
+                    path.push(name);
+                }
+            }
+            XmlEvent::End(_) => {
+                path.pop();
+            }
+            XmlEvent::Text(text) => {
+                let text = text.unescape().map_err(Error::wrap)?.to_string();
+                set_text(&mut responses, &path, text);
+            }
+            XmlEvent::CData(data) => {
+                let text = String::from_utf8_lossy(&data.into_inner()).to_string();
+                set_text(&mut responses, &path, text);
+            }
+            XmlEvent::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(responses)
+}
+
+fn local_name(name: &[u8]) -> String {
+    String::from_utf8_lossy(name).to_ascii_lowercase()
+}
+
+fn set_text(responses: &mut [DavResponse], path: &[String], text: String) {
+    let Some(response) = responses.last_mut() else {
+        return;
+    };
+    let parent = path.len().checked_sub(2).map(|index| path[index].as_str());
+    match (path.last().map(String::as_str), parent) {
+        (Some("href"), Some("response")) => response.href = text,
+        (Some("href"), Some("current-user-principal")) => {
+            response.current_user_principal = Some(text);
+        }
+        (Some("href"), Some("calendar-home-set")) => response.calendar_home_set = Some(text),
+        (Some("displayname"), _) => response.display_name = Some(text),
+        (Some("calendar-data"), _) => {
+            response
+                .calendar_data
+                .get_or_insert_with(String::new)
+                .push_str(&text);
+        }
+        _ => {}
+    }
+}
+
+pub struct Client {
+    http: reqwest::Client,
+    server_url: url::Url,
+    username: String,
+    password: String,
+}
+
+impl Client {
+    pub fn new(server_url: &str, username: &str, password: &str) -> Result<Self> {
+        public_http::ensure_public(server_url)?;
+        Ok(Self {
+            http: public_http::client(std::time::Duration::from_secs(REQUEST_TIMEOUT_IN_SECONDS))?,
+            server_url: url::Url::parse(server_url).map_err(Error::wrap)?,
+            username: username.to_string(),
+            password: password.to_string(),
+        })
+    }
+
+    /// Resolves hrefs from the server, which are usually absolute paths. Hrefs to another
+    /// scheme, host or port are refused, the credentials only ever go to the configured server.
+    fn url(&self, href: &str) -> Result<url::Url> {
+        let url = self.server_url.join(href).map_err(Error::wrap)?;
+        if url.origin() != self.server_url.origin() {
+            return Err(Error::Message(format!(
+                "CalDAV server referred to another host: {url}"
+            )));
+        }
+        Ok(url)
+    }
+
+    fn request(&self, method: &str, url: url::Url) -> Result<reqwest::RequestBuilder> {
+        let method = Method::from_bytes(method.as_bytes()).map_err(Error::wrap)?;
+        Ok(self
+            .http
+            .request(method, url)
+            .basic_auth(&self.username, Some(&self.password)))
+    }
+
+    async fn multistatus(
+        &self,
+        method: &str,
+        url: url::Url,
+        depth: &str,
+        body: String,
+    ) -> Result<Vec<DavResponse>> {
+        let response = self
+            .request(method, url)?
+            .header("Depth", depth)
+            .header(header::CONTENT_TYPE, "application/xml; charset=utf-8")
+            .body(body)
+            .send()
+            .await
+            .map_err(Error::wrap)?
+            .error_for_status()
+            .map_err(Error::wrap)?;
+
+        parse_multistatus(&response.text().await.map_err(Error::wrap)?)
+    }
+
+    /// Follows the principal of the user to their calendar home and lists the calendars in it
+    /// that can hold events.
+    pub async fn discover_calendars(&self) -> Result<Vec<Calendar>> {
+        let home = self.calendar_home().await?;
+        let responses = self
+            .multistatus("PROPFIND", home, "1", PROPFIND_CALENDARS.to_string())
+            .await?;
+
+        responses
+            .into_iter()
+            .filter(|response| {
+                response.is_calendar
+                    && (response.components.is_empty()
+                        || response
+                            .components
+                            .iter()
+                            .any(|component| component.eq_ignore_ascii_case("VEVENT")))
+            })
+            .map(|response| {
+                let url = self.url(&response.href)?;
+                Ok(Calendar {
+                    display_name: response
+                        .display_name
+                        .filter(|name| !name.is_empty())
+                        .unwrap_or_else(|| response.href.clone()),
+                    url: url.to_string(),
+                })
+            })
+            .collect()
+    }
+
+    async fn calendar_home(&self) -> Result<url::Url> {
+        let server_url = self.server_url.clone();
+        let response = self
+            .multistatus(
+                "PROPFIND",
+                server_url.clone(),
+                "0",
+                PROPFIND_PRINCIPAL.to_string(),
+            )
+            .await?
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+        if let Some(home) = response.calendar_home_set {
+            return self.url(&home);
+        }
+        let Some(principal) = response.current_user_principal else {
+            // Servers without principals are pointed at the calendar home directly.
+            return Ok(server_url);
+        };
+
+        let home = self
+            .multistatus(
+                "PROPFIND",
+                self.url(&principal)?,
+                "0",
+                PROPFIND_PRINCIPAL.to_string(),
+            )
+            .await?
+            .into_iter()
+            .find_map(|response| response.calendar_home_set)
+            .ok_or_else(|| Error::Message("CalDAV server has no calendar home.".to_string()))?;
+        self.url(&home)
+    }
+
+    /// Calendar data of the events overlapping the window, recurring events expanded into
+    /// their occurrences by the server.
+    pub async fn events_between(
+        &self,
+        calendar_url: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<String>> {
+        let start = start.format("%Y%m%dT%H%M%SZ");
+        let end = end.format("%Y%m%dT%H%M%SZ");
+        let body = format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop>
+    <c:calendar-data>
+      <c:expand start="{start}" end="{end}"/>
+    </c:calendar-data>
+  </d:prop>
+  <c:filter>
+    <c:comp-filter name="VCALENDAR">
+      <c:comp-filter name="VEVENT">
+        <c:time-range start="{start}" end="{end}"/>
+      </c:comp-filter>
+    </c:comp-filter>
+  </c:filter>
+</c:calendar-query>"#
+        );
+
+        Ok(self
+            .multistatus("REPORT", self.url(calendar_url)?, "1", body)
+            .await?
+            .into_iter()
+            .filter_map(|response| response.calendar_data)
+            .collect())
+    }
+
+    /// The calendar object along with its `ETag`, to update it without losing changes made in
+    /// the meantime.
+    pub async fn get(&self, href: &str) -> Result<(String, Option<String>)> {
+        let response = self
+            .request("GET", self.url(href)?)?
+            .send()
+            .await
+            .map_err(Error::wrap)?
+            .error_for_status()
+            .map_err(Error::wrap)?;
+        let etag = response
+            .headers()
+            .get(header::ETAG)
+            .and_then(|etag| etag.to_str().ok())
+            .map(ToString::to_string);
+
+        Ok((response.text().await.map_err(Error::wrap)?, etag))
+    }
+
+    /// Writes the calendar object. Without an `ETag` it must not exist yet, with one it must
+    /// not have changed since it was read.
+    pub async fn put(&self, href: &str, data: String, etag: Option<&str>) -> Result<()> {
+        let request = self
+            .request("PUT", self.url(href)?)?
+            .header(header::CONTENT_TYPE, "text/calendar; charset=utf-8")
+            .body(data);
+        let request = match etag {
+            Some(etag) => request.header(header::IF_MATCH, etag),
+            None => request.header(header::IF_NONE_MATCH, "*"),
+        };
+
+        request
+            .send()
+            .await
+            .map_err(Error::wrap)?
+            .error_for_status()
+            .map_err(Error::wrap)?;
+        Ok(())
+    }
+
+    /// Deletes the calendar object, one that is already gone counts as deleted.
+    pub async fn delete(&self, href: &str) -> Result<()> {
+        let response = self
+            .request("DELETE", self.url(href)?)?
+            .send()
+            .await
+            .map_err(Error::wrap)?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(());
+        }
+
+        response.error_for_status().map_err(Error::wrap)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,198 @@
+//! Encryption of the secrets kept in the database: OAuth tokens and the admin's API secrets.
+//!
+//! Values are sealed with AES-256-GCM and stored as `enc:v<version>:<base64 nonce and
+//! ciphertext>`. The key with the highest version encrypts, older keys are kept to decrypt
+//! until the `reencrypt_secrets` task moved every row to the newest one. Values without the
+//! prefix were stored before encryption and are read as they are.
+
+use std::{collections::BTreeMap, sync::OnceLock};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use loco_rs::{config::Config, prelude::*};
+use ring::{
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN},
+    rand::{SecureRandom, SystemRandom},
+};
+use sea_orm::{ActiveValue, DbErr};
+
+use crate::common::settings::Settings;
+
+const PREFIX: &str = "enc:v";
+/// Read when the config has no `settings.encryption_keys`.
+const KEYS_ENV_VAR: &str = "ENCRYPTION_KEYS";
+
+static KEYRING: OnceLock<Keyring> = OnceLock::new();
+
+fn invalid_keys(reason: &str) -> Error {
+    Error::Message(format!("Invalid encryption keys: {reason}."))
+}
+
+fn unreadable() -> Error {
+    Error::Message("Encrypted value can not be decrypted.".to_string())
+}
+
+pub struct Keyring {
+    current: u32,
+    keys: BTreeMap<u32, LessSafeKey>,
+}
+
+impl Keyring {
+    /// Parses `version:key` pairs separated by commas, each key 32 base64 encoded bytes.
+    pub fn parse(value: &str) -> Result<Self> {
+        let mut keys = BTreeMap::new();
+        for entry in value
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+        {
+            let (version, key) = entry
+                .split_once(':')
+                .ok_or_else(|| invalid_keys("expected `version:key` pairs"))?;
+            let version = version
+                .trim()
+                .parse::<u32>()
+                .map_err(|_| invalid_keys("versions must be numbers"))?;
+            let key = STANDARD
+                .decode(key.trim())
+                .map_err(|_| invalid_keys("keys must be base64"))?;
+            let key = UnboundKey::new(&AES_256_GCM, &key)
+                .map_err(|_| invalid_keys("keys must be 32 bytes"))?;
+            keys.insert(version, LessSafeKey::new(key));
+        }
+
+        let current = *keys
+            .keys()
+            .next_back()
+            .ok_or_else(|| invalid_keys("no key configured"))?;
+        Ok(Self { current, keys })
+    }
+}
+
+/// Uses the keys of `settings.encryption_keys`, or of `ENCRYPTION_KEYS` when the config has
+/// none. The first keys installed stay for the life of the process.
+pub fn install(config: &Config) -> Result<()> {
+    if KEYRING.get().is_some() {
+        return Ok(());
+    }
+
+    let configured = match &config.settings {
+        Some(settings) => Settings::from_json(settings)?.encryption_keys,
+        None => None,
+    };
+    let keys = match configured {
+        Some(keys) => keys,
+        None => std::env::var(KEYS_ENV_VAR).map_err(|_| invalid_keys("no key configured"))?,
+    };
+    let _ = KEYRING.set(Keyring::parse(&keys)?);
+
+    Ok(())
+}
+
+fn keyring() -> Result<&'static Keyring> {
+    if let Some(keyring) = KEYRING.get() {
+        return Ok(keyring);
+    }
+
+    let keys = std::env::var(KEYS_ENV_VAR).map_err(|_| invalid_keys("no key configured"))?;
+    let keyring = Keyring::parse(&keys)?;
+    Ok(KEYRING.get_or_init(|| keyring))
+}
+
+/// Key version and payload of an encrypted value, `None` for plaintext.
+fn split(value: &str) -> Option<(u32, &str)> {
+    let (version, payload) = value.strip_prefix(PREFIX)?.split_once(':')?;
+    Some((version.parse().ok()?, payload))
+}
+
+impl Keyring {
+    pub fn encrypt(&self, plaintext: &str) -> Result<String> {
+        let key = &self.keys[&self.current];
+
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .map_err(|_| Error::Message("Could not generate a nonce.".to_string()))?;
+        let mut sealed = plaintext.as_bytes().to_vec();
+        key.seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::empty(),
+            &mut sealed,
+        )
+        .map_err(|_| Error::Message("Could not encrypt the value.".to_string()))?;
+
+        Ok(format!(
+            "{PREFIX}{}:{}",
+            self.current,
+            STANDARD.encode([nonce.as_slice(), &sealed].concat())
+        ))
+    }
+
+    /// Plaintext of `value`, which is returned unchanged when it was never encrypted.
+    pub fn decrypt(&self, value: &str) -> Result<String> {
+        let Some((version, payload)) = split(value) else {
+            return Ok(value.to_string());
+        };
+        let key = self.keys.get(&version).ok_or_else(|| {
+            Error::Message(format!(
+                "Encryption key version {version} is not configured."
+            ))
+        })?;
+
+        let mut sealed = STANDARD.decode(payload).map_err(|_| unreadable())?;
+        if sealed.len() < NONCE_LEN {
+            return Err(unreadable());
+        }
+        let (nonce, ciphertext) = sealed.split_at_mut(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| unreadable())?;
+        let plaintext = key
+            .open_in_place(nonce, Aad::empty(), ciphertext)
+            .map_err(|_| unreadable())?;
+
+        String::from_utf8(plaintext.to_vec()).map_err(|_| unreadable())
+    }
+
+    /// Whether `value` is encrypted with the newest key.
+    #[must_use]
+    pub fn is_current(&self, value: &str) -> bool {
+        split(value).is_some_and(|(version, _)| version == self.current)
+    }
+
+    /// `value` encrypted with the newest key, whether it was plaintext or used an older key.
+    pub fn reencrypt(&self, value: &str) -> Result<String> {
+        if self.is_current(value) {
+            return Ok(value.to_string());
+        }
+        self.encrypt(&self.decrypt(value)?)
+    }
+}
+
+/// Encrypts with the installed keys.
+pub fn encrypt(plaintext: &str) -> Result<String> {
+    keyring()?.encrypt(plaintext)
+}
+
+/// Decrypts with the installed keys, plaintext is read without needing any.
+pub fn decrypt(value: &str) -> Result<String> {
+    if split(value).is_none() {
+        return Ok(value.to_string());
+    }
+    keyring()?.decrypt(value)
+}
+
+#[must_use]
+pub fn is_current(value: &str) -> bool {
+    keyring().is_ok_and(|keyring| keyring.is_current(value))
+}
+
+pub fn reencrypt(value: &str) -> Result<String> {
+    keyring()?.reencrypt(value)
+}
+
+/// Encrypts a column about to be written. `before_save` runs it for every secret column, values
+/// that are already encrypted with the newest key stay as they are.
+pub fn encrypt_active_value(value: &mut ActiveValue<String>) -> std::result::Result<(), DbErr> {
+    if let ActiveValue::Set(plaintext) = value {
+        *plaintext = reencrypt(plaintext).map_err(|err| DbErr::Custom(err.to_string()))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,459 @@
+//! Minimal iCalendar (RFC 5545) writer for appointment invites and feeds, plus the bit of
+//! reading and editing CalDAV servers need.
+//!
+//! Times are written in UTC (`DTSTART:20250113T180000Z`), which calendar clients convert to
+//! the zone of whoever looks at the event, so no `VTIMEZONE` definitions are needed.
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+const PRODUCT_ID: &str = "-//Appointments//Appointments//EN";
+/// Content lines longer than this many octets are folded.
+const MAX_LINE_OCTETS: usize = 75;
+
+/// iTIP method of the calendar, tells clients what to do with the events in it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// Read only copy, used for subscribed feeds.
+    Publish,
+    /// Adds or updates the event.
+    Request,
+    /// Removes the event.
+    Cancel,
+}
+
+impl Method {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Publish => "PUBLISH",
+            Self::Request => "REQUEST",
+            Self::Cancel => "CANCEL",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventStatus {
+    Tentative,
+    Confirmed,
+    Cancelled,
+}
+
+impl EventStatus {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Tentative => "TENTATIVE",
+            Self::Confirmed => "CONFIRMED",
+            Self::Cancelled => "CANCELLED",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Person {
+    pub name: String,
+    pub email: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    /// Stays the same for the whole life of the appointment, clients match updates on it.
+    pub uid: String,
+    /// Must grow with every change, clients ignore updates with a lower sequence.
+    pub sequence: i64,
+    pub stamp: DateTime<Utc>,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub summary: String,
+    pub description: Option<String>,
+    pub url: Option<String>,
+    pub status: EventStatus,
+    pub organizer: Person,
+    pub attendees: Vec<Person>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Calendar {
+    pub method: Method,
+    /// Shown by clients as the name of subscribed calendars.
+    pub name: Option<String>,
+    pub events: Vec<Event>,
+}
+
+impl Calendar {
+    /// MIME type of the `.ics` file, including the method as iTIP asks for.
+    #[must_use]
+    pub fn content_type(&self) -> String {
+        format!(
+            "text/calendar; charset=utf-8; method={}",
+            self.method.as_str()
+        )
+    }
+
+    #[must_use]
+    pub fn to_ics(&self) -> String {
+        let mut lines = calendar_header();
+        lines.push(format!("METHOD:{}", self.method.as_str()));
+        if let Some(name) = &self.name {
+            lines.push(format!("X-WR-CALNAME:{}", escape_text(name)));
+        }
+        for event in &self.events {
+            event.write_lines(&mut lines);
+        }
+        lines.push("END:VCALENDAR".to_string());
+
+        lines.iter().map(|line| fold(line)).collect()
+    }
+}
+
+fn calendar_header() -> Vec<String> {
+    vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{PRODUCT_ID}"),
+        "CALSCALE:GREGORIAN".to_string(),
+    ]
+}
+
+impl Event {
+    /// The event alone in a calendar without `METHOD`, the shape CalDAV servers store.
+    #[must_use]
+    pub fn to_calendar_object(&self) -> String {
+        let mut lines = calendar_header();
+        self.write_lines(&mut lines);
+        lines.push("END:VCALENDAR".to_string());
+
+        lines.iter().map(|line| fold(line)).collect()
+    }
+
+    fn write_lines(&self, lines: &mut Vec<String>) {
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}", escape_text(&self.uid)));
+        lines.push(format!("SEQUENCE:{}", self.sequence));
+        lines.push(format!("DTSTAMP:{}", format_time(&self.stamp)));
+        lines.push(format!("DTSTART:{}", format_time(&self.start)));
+        lines.push(format!("DTEND:{}", format_time(&self.end)));
+        lines.push(format!("SUMMARY:{}", escape_text(&self.summary)));
+        if let Some(description) = &self.description {
+            lines.push(format!("DESCRIPTION:{}", escape_text(description)));
+        }
+        if let Some(url) = &self.url {
+            lines.push(format!("URL:{url}"));
+        }
+        lines.push(format!("STATUS:{}", self.status.as_str()));
+        lines.push(format!(
+            "ORGANIZER;CN={}:mailto:{}",
+            quote_param(&self.organizer.name),
+            self.organizer.email
+        ));
+        for attendee in &self.attendees {
+            lines.push(format!(
+                "ATTENDEE;CN={};ROLE=REQ-PARTICIPANT;PARTSTAT=ACCEPTED;RSVP=FALSE:mailto:{}",
+                quote_param(&attendee.name),
+                attendee.email
+            ));
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+}
+
+fn format_time(time: &DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Escapes a TEXT value, see RFC 5545 section 3.3.11.
+fn escape_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for char in value.chars() {
+        match char {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(char),
+        }
+    }
+    escaped
+}
+
+/// Parameter values can not be escaped, quotes are dropped and the value is quoted as a whole.
+fn quote_param(value: &str) -> String {
+    let value: String = value
+        .chars()
+        .filter(|char| *char != '"' && !char.is_control())
+        .collect();
+    format!("\"{value}\"")
+}
+
+/// Splits the line into chunks of at most 75 octets, continuation lines start with a space.
+/// Every line ends with CRLF.
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 2);
+    let mut octets = 0;
+    for char in line.chars() {
+        if octets + char.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            // The leading space counts towards the limit of the continuation line.
+            octets = 1;
+        }
+        folded.push(char);
+        octets += char.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+/// Joins folded lines back together and drops empty ones.
+fn unfold(data: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in data.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if let Some(continuation) = line.strip_prefix([' ', '\t']) {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(continuation);
+            }
+        } else if !line.is_empty() {
+            lines.push(line.to_string());
+        }
+    }
+    lines
+}
+
+/// Splits an unfolded content line into its name with parameters and its value, at the first
+/// colon outside of quoted parameter values.
+fn split_line(line: &str) -> (&str, &str) {
+    let mut quoted = false;
+    for (index, char) in line.char_indices() {
+        match char {
+            '"' => quoted = !quoted,
+            ':' if !quoted => return (&line[..index], &line[index + 1..]),
+            _ => {}
+        }
+    }
+    (line, "")
+}
+
+/// Property name of a content line, `DTSTART` for `DTSTART;TZID=Europe/Berlin:20250113T090000`.
+fn property_name(line: &str) -> String {
+    let (name_and_params, _) = split_line(line);
+    name_and_params
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .to_ascii_uppercase()
+}
+
+fn property_param<'a>(line: &'a str, param: &str) -> Option<&'a str> {
+    let (name_and_params, _) = split_line(line);
+    name_and_params.split(';').skip(1).find_map(|pair| {
+        let (key, value) = pair.split_once('=')?;
+        key.eq_ignore_ascii_case(param)
+            .then(|| value.trim_matches('"'))
+    })
+}
+
+/// Reads a `DATE-TIME` or `DATE` value. Floating times and unknown zones are taken as UTC.
+fn parse_time(line: &str) -> Option<(DateTime<Utc>, bool)> {
+    let (_, value) = split_line(line);
+    if value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        return Some((date.and_hms_opt(0, 0, 0)?.and_utc(), true));
+    }
+
+    let naive = NaiveDateTime::parse_from_str(value.trim_end_matches('Z'), "%Y%m%dT%H%M%S").ok()?;
+    let zone = property_param(line, "TZID").and_then(|tzid| tzid.parse::<Tz>().ok());
+    let time = match zone {
+        Some(zone) if !value.ends_with('Z') => zone
+            .from_local_datetime(&naive)
+            .earliest()?
+            .with_timezone(&Utc),
+        _ => naive.and_utc(),
+    };
+    Some((time, false))
+}
+
+/// Reads a `DURATION` value such as `PT1H30M` or `P1D`.
+fn parse_duration(value: &str) -> Option<Duration> {
+    let (sign, value) = match value.strip_prefix('-') {
+        Some(value) => (-1, value),
+        None => (1, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let value = value.strip_prefix('P')?;
+
+    let mut duration = Duration::zero();
+    let mut number = String::new();
+    for char in value.chars() {
+        match char {
+            'T' => {}
+            '0'..='9' => number.push(char),
+            unit => {
+                let amount: i64 = number.parse().ok()?;
+                number.clear();
+                duration += match unit {
+                    'W' => Duration::weeks(amount),
+                    'D' => Duration::days(amount),
+                    'H' => Duration::hours(amount),
+                    'M' => Duration::minutes(amount),
+                    'S' => Duration::seconds(amount),
+                    _ => return None,
+                };
+            }
+        }
+    }
+    Some(duration * sign)
+}
+
+/// Start and end of every event in the data that blocks time. Transparent and cancelled events
+/// are left out. Recurring events are expected to be expanded by the server already.
+#[must_use]
+pub fn busy_times(data: &str) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut busy = Vec::new();
+    let mut event: Option<Vec<String>> = None;
+    let mut nested = 0;
+
+    for line in unfold(data) {
+        let name = property_name(&line);
+        let (_, value) = split_line(&line);
+        match (name.as_str(), value.to_ascii_uppercase().as_str()) {
+            ("BEGIN", "VEVENT") => event = Some(Vec::new()),
+            ("END", "VEVENT") => {
+                if let Some(window) = event.take().and_then(|lines| event_window(&lines)) {
+                    busy.push(window);
+                }
+            }
+            // Alarms and other components inside the event have properties of their own.
+            ("BEGIN", _) if event.is_some() => nested += 1,
+            ("END", _) if event.is_some() => nested -= 1,
+            _ if nested == 0 => {
+                if let Some(lines) = event.as_mut() {
+                    lines.push(line);
+                }
+            }
+            _ => {}
+        }
+    }
+    busy
+}
+
+fn event_window(lines: &[String]) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let property = |name: &str| lines.iter().find(|line| property_name(line) == name);
+    let value = |name: &str| property(name).map(|line| split_line(line).1.to_ascii_uppercase());
+
+    if value("TRANSP").as_deref() == Some("TRANSPARENT")
+        || value("STATUS").as_deref() == Some("CANCELLED")
+    {
+        return None;
+    }
+
+    let (start, all_day) = parse_time(property("DTSTART")?)?;
+    let end = match (property("DTEND"), property("DURATION")) {
+        (Some(end), _) => parse_time(end)?.0,
+        (None, Some(duration)) => start + parse_duration(split_line(duration).1)?,
+        (None, None) if all_day => start + Duration::days(1),
+        (None, None) => start,
+    };
+
+    (end > start).then_some((start, end))
+}
+
+/// Applies `edit` to the property lines of the first event in the data and writes it back.
+/// Lines of components nested in the event, like alarms, are kept as they are.
+fn edit_event(data: &str, edit: impl FnOnce(&mut Vec<String>)) -> String {
+    let lines = unfold(data);
+    let Some(begin) = lines
+        .iter()
+        .position(|line| line.eq_ignore_ascii_case("BEGIN:VEVENT"))
+    else {
+        return lines.iter().map(|line| fold(line)).collect();
+    };
+
+    let mut properties = Vec::new();
+    let mut components = Vec::new();
+    let mut depth = 0;
+    let mut end = lines.len();
+    for (index, line) in lines.iter().enumerate().skip(begin + 1) {
+        match property_name(line).as_str() {
+            "END" if depth == 0 => {
+                end = index;
+                break;
+            }
+            "BEGIN" => {
+                depth += 1;
+                components.push(line.clone());
+            }
+            "END" => {
+                depth -= 1;
+                components.push(line.clone());
+            }
+            _ if depth == 0 => properties.push(line.clone()),
+            _ => components.push(line.clone()),
+        }
+    }
+    edit(&mut properties);
+
+    lines[..=begin]
+        .iter()
+        .chain(&properties)
+        .chain(&components)
+        .chain(&lines[end..])
+        .map(|line| fold(line))
+        .collect()
+}
+
+/// Moves the event to a new window and bumps its sequence, so attendees' clients take the
+/// update.
+#[must_use]
+pub fn reschedule(data: &str, start: DateTime<Utc>, end: DateTime<Utc>) -> String {
+    edit_event(data, |properties| {
+        let sequence = properties
+            .iter()
+            .find(|line| property_name(line) == "SEQUENCE")
+            .and_then(|line| split_line(line).1.parse::<i64>().ok())
+            .unwrap_or_default();
+        properties.retain(|line| {
+            !matches!(
+                property_name(line).as_str(),
+                "DTSTART" | "DTEND" | "DURATION" | "SEQUENCE" | "DTSTAMP"
+            )
+        });
+        properties.push(format!("DTSTAMP:{}", format_time(&Utc::now())));
+        properties.push(format!("DTSTART:{}", format_time(&start)));
+        properties.push(format!("DTEND:{}", format_time(&end)));
+        properties.push(format!("SEQUENCE:{}", sequence + 1));
+    })
+}
+
+fn is_attendee(line: &str, email: &str) -> bool {
+    property_name(line) == "ATTENDEE"
+        && split_line(line)
+            .1
+            .trim_start_matches("mailto:")
+            .trim_start_matches("MAILTO:")
+            .eq_ignore_ascii_case(email)
+}
+
+#[must_use]
+pub fn add_attendee(data: &str, attendee: &Person) -> String {
+    edit_event(data, |properties| {
+        if properties
+            .iter()
+            .any(|line| is_attendee(line, &attendee.email))
+        {
+            return;
+        }
+        properties.push(format!(
+            "ATTENDEE;CN={};ROLE=REQ-PARTICIPANT;PARTSTAT=ACCEPTED;RSVP=FALSE:mailto:{}",
+            quote_param(&attendee.name),
+            attendee.email
+        ));
+    })
+}
+
+#[must_use]
+pub fn remove_attendee(data: &str, email: &str) -> String {
+    edit_event(data, |properties| {
+        properties.retain(|line| !is_attendee(line, email));
+    })
+}
@@ -0,0 +1,252 @@
+//! Small Microsoft Graph client covering the calendar endpoints used for Outlook connections.
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use loco_rs::prelude::*;
+use reqwest::{Method, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+pub const DEFAULT_BASE_URL: &str = "https://graph.microsoft.com/v1.0/";
+
+const REQUEST_TIMEOUT_IN_SECONDS: u64 = 20;
+/// Every time sent and read is in UTC, Graph is told so with the `Prefer` header.
+const UTC_PREFERENCE: &str = "outlook.timezone=\"UTC\"";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmailAddress {
+    #[serde(default)]
+    pub name: String,
+    pub address: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Calendar {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub can_edit: bool,
+    #[serde(default)]
+    pub is_default_calendar: bool,
+    pub owner: Option<EmailAddress>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Attendee {
+    pub email_address: EmailAddress,
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+impl Attendee {
+    #[must_use]
+    pub fn required(address: &str, name: &str) -> Self {
+        Self {
+            email_address: EmailAddress {
+                name: name.to_string(),
+                address: address.to_string(),
+            },
+            kind: "required".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphDateTime {
+    pub date_time: String,
+    pub time_zone: String,
+}
+
+impl From<DateTime<Utc>> for GraphDateTime {
+    fn from(date_time: DateTime<Utc>) -> Self {
+        Self {
+            date_time: date_time.format("%Y-%m-%dT%H:%M:%S").to_string(),
+            time_zone: "UTC".to_string(),
+        }
+    }
+}
+
+impl GraphDateTime {
+    fn to_utc(&self) -> Option<DateTime<Utc>> {
+        NaiveDateTime::parse_from_str(&self.date_time, "%Y-%m-%dT%H:%M:%S%.f")
+            .ok()
+            .map(|naive| naive.and_utc())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemBody {
+    pub content_type: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewEvent {
+    pub subject: String,
+    pub body: ItemBody,
+    pub start: GraphDateTime,
+    pub end: GraphDateTime,
+    pub attendees: Vec<Attendee>,
+    pub is_online_meeting: bool,
+    pub online_meeting_provider: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Event {
+    pub id: String,
+    #[serde(default)]
+    pub attendees: Vec<Attendee>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Collection<T> {
+    value: Vec<T>,
+    #[serde(rename = "@odata.nextLink")]
+    next_link: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ScheduleInformation {
+    #[serde(default)]
+    schedule_items: Vec<ScheduleItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ScheduleItem {
+    status: String,
+    start: GraphDateTime,
+    end: GraphDateTime,
+}
+
+#[derive(Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    base_url: url::Url,
+    access_token: String,
+}
+
+impl Client {
+    /// `base_url` is the versioned Graph root, [`DEFAULT_BASE_URL`] outside of tests.
+    pub fn new(base_url: &url::Url, access_token: &str) -> Result<Self> {
+        let mut base_url = base_url.clone();
+        if !base_url.path().ends_with('/') {
+            base_url.set_path(&format!("{}/", base_url.path()));
+        }
+
+        Ok(Self {
+            http: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(REQUEST_TIMEOUT_IN_SECONDS))
+                .build()
+                .map_err(Error::wrap)?,
+            base_url,
+            access_token: access_token.to_string(),
+        })
+    }
+
+    fn request(&self, method: Method, url: url::Url) -> reqwest::RequestBuilder {
+        self.http
+            .request(method, url)
+            .bearer_auth(&self.access_token)
+            .header("Prefer", UTC_PREFERENCE)
+    }
+
+    fn url(&self, path: &str) -> Result<url::Url> {
+        self.base_url.join(path).map_err(Error::wrap)
+    }
+
+    async fn send<T: DeserializeOwned>(request: reqwest::RequestBuilder) -> Result<T> {
+        request
+            .send()
+            .await
+            .map_err(Error::wrap)?
+            .error_for_status()
+            .map_err(Error::wrap)?
+            .json::<T>()
+            .await
+            .map_err(Error::wrap)
+    }
+
+    /// Every calendar of the signed-in user, following Graph's paging.
+    pub async fn calendars(&self) -> Result<Vec<Calendar>> {
+        let mut url = self.url("me/calendars?$select=id,name,canEdit,isDefaultCalendar,owner")?;
+        let mut calendars = Vec::new();
+        loop {
+            let page: Collection<Calendar> = Self::send(self.request(Method::GET, url)).await?;
+            calendars.extend(page.value);
+            match page.next_link {
+                Some(next_link) => url = url::Url::parse(&next_link).map_err(Error::wrap)?,
+                None => return Ok(calendars),
+            }
+        }
+    }
+
+    /// Busy windows of the mailboxes, read with `getSchedule`. Free and working-elsewhere
+    /// items do not block time.
+    pub async fn busy_times(
+        &self,
+        schedules: &[String],
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<(DateTime<Utc>, DateTime<Utc>)>> {
+        if schedules.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let body = serde_json::json!({
+            "schedules": schedules,
+            "startTime": GraphDateTime::from(start),
+            "endTime": GraphDateTime::from(end),
+        });
+        let response: Collection<ScheduleInformation> = Self::send(
+            self.request(Method::POST, self.url("me/calendar/getSchedule")?)
+                .json(&body),
+        )
+        .await?;
+
+        Ok(response
+            .value
+            .into_iter()
+            .flat_map(|schedule| schedule.schedule_items)
+            .filter(|item| !matches!(item.status.as_str(), "free" | "workingElsewhere"))
+            .filter_map(|item| Some((item.start.to_utc()?, item.end.to_utc()?)))
+            .filter(|(start, end)| start < end)
+            .collect())
+    }
+
+    pub async fn create_event(&self, calendar_id: &str, event: &NewEvent) -> Result<Event> {
+        let url = self.url(&format!("me/calendars/{calendar_id}/events"))?;
+        Self::send(self.request(Method::POST, url).json(event)).await
+    }
+
+    pub async fn get_event(&self, event_id: &str) -> Result<Event> {
+        let url = self.url(&format!("me/events/{event_id}?$select=id,attendees"))?;
+        Self::send(self.request(Method::GET, url)).await
+    }
+
+    /// Changes only the fields present in `changes`.
+    pub async fn update_event(&self, event_id: &str, changes: &serde_json::Value) -> Result<()> {
+        let url = self.url(&format!("me/events/{event_id}"))?;
+        let _event: Event = Self::send(self.request(Method::PATCH, url).json(changes)).await?;
+        Ok(())
+    }
+
+    /// Deletes the event, one that is already gone counts as deleted.
+    pub async fn delete_event(&self, event_id: &str) -> Result<()> {
+        let response = self
+            .request(Method::DELETE, self.url(&format!("me/events/{event_id}"))?)
+            .send()
+            .await
+            .map_err(Error::wrap)?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(());
+        }
+
+        response.error_for_status().map_err(Error::wrap)?;
+        Ok(())
+    }
+}
@@ -1 +1,6 @@
+pub mod caldav;
+pub mod encryption;
+pub mod ics;
+pub mod microsoft_graph;
+pub mod public_http;
 pub mod settings;
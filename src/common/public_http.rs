@@ -0,0 +1,151 @@
+//! HTTP to URLs users configure themselves, webhook endpoints and CalDAV servers.
+//!
+//! Outside development such requests may only reach public hosts: the URL has to be https to a
+//! public host and every address its host resolves to is checked again when connecting, so a
+//! public name pointing to the network the server runs in is refused as well. Redirects are
+//! never followed, they would lead somewhere that was not checked.
+
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
+
+use loco_rs::{
+    environment::{self, Environment},
+    prelude::*,
+};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use validator::ValidationError;
+
+/// Whether requests are limited to public hosts, everywhere but development and test.
+fn public_only() -> bool {
+    !matches!(
+        Environment::from(environment::resolve_from_env()),
+        Environment::Development | Environment::Test
+    )
+}
+
+fn validate_url(url: &str) -> Result<(), ValidationError> {
+    match url::Url::parse(url) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") && url.host().is_some() => Ok(()),
+        _ => Err(ValidationError::new("invalid_url")
+            .with_message("Use an absolute http or https URL.".into())),
+    }
+}
+
+/// An absolute URL, outside development also https to a public host.
+pub(crate) fn validate_user_url(url: &str) -> Result<(), ValidationError> {
+    validate_url(url)?;
+    if public_only() {
+        validate_public_url(url)?;
+    }
+    Ok(())
+}
+
+/// Requires https and a host outside loopback, private and link-local ranges.
+///
+/// # Errors
+///
+/// When the URL is not https or its host is not public.
+pub fn validate_public_url(url: &str) -> Result<(), ValidationError> {
+    let url = url::Url::parse(url).map_err(|_| {
+        ValidationError::new("invalid_url").with_message("Use an absolute https URL.".into())
+    })?;
+    if url.scheme() != "https" {
+        return Err(ValidationError::new("insecure_url").with_message("Use an https URL.".into()));
+    }
+    let public = match url.host() {
+        Some(url::Host::Domain(domain)) => {
+            let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+            domain != "localhost" && !domain.ends_with(".localhost")
+        }
+        Some(url::Host::Ipv4(ip)) => is_public_ip(IpAddr::V4(ip)),
+        Some(url::Host::Ipv6(ip)) => is_public_ip(IpAddr::V6(ip)),
+        None => false,
+    };
+    if !public {
+        return Err(
+            ValidationError::new("private_url").with_message("Use a URL on a public host.".into())
+        );
+    }
+    Ok(())
+}
+
+/// Whether the address is outside the loopback, private, link-local and other reserved ranges.
+#[must_use]
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => ip
+            .to_ipv4_mapped()
+            .map_or_else(|| is_public_ipv6(ip), is_public_ipv4),
+    }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [first, second, ..] = ip.octets();
+    let shared = first == 100 && (64..128).contains(&second);
+    !(ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || first == 0
+        || shared)
+}
+
+const fn is_public_ipv6(ip: Ipv6Addr) -> bool {
+    !(ip.is_loopback() || ip.is_unspecified() || ip.is_unique_local() || ip.is_unicast_link_local())
+}
+
+/// Resolves with the system resolver and drops every address that is not public.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addresses: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|address| is_public_ip(address.ip()))
+                .collect();
+            if addresses.is_empty() {
+                return Err(format!("{} has no public address", name.as_str()).into());
+            }
+            let addresses: Addrs = Box::new(addresses.into_iter());
+            Ok(addresses)
+        })
+    }
+}
+
+/// A client that does not follow redirects and, outside development, only connects to public
+/// addresses.
+///
+/// # Errors
+///
+/// When the client can not be built.
+pub fn client(timeout: Duration) -> Result<reqwest::Client> {
+    let builder = reqwest::Client::builder()
+        .timeout(timeout)
+        .redirect(reqwest::redirect::Policy::none());
+    let builder = if public_only() {
+        builder.dns_resolver(Arc::new(PublicResolver))
+    } else {
+        builder
+    };
+    builder.build().map_err(Error::wrap)
+}
+
+/// Checks a URL right before it is requested. Hosts given as an IP address are not resolved, so
+/// the resolver of [`client`] does not see them.
+///
+/// # Errors
+///
+/// When requests are limited to public hosts and the URL is not one.
+pub fn ensure_public(url: &str) -> Result<()> {
+    if public_only() {
+        validate_public_url(url).map_err(|err| Error::Message(err.to_string()))?;
+    }
+    Ok(())
+}
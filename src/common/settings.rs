@@ -4,6 +4,8 @@ use serde::Deserialize;
 #[derive(Debug, Deserialize)]
 pub struct Settings {
     pub default_email_sender: String,
+    /// Versioned keys for the secrets stored in the database, see [`super::encryption`].
+    pub encryption_keys: Option<String>,
 }
 
 impl Settings {
@@ -13,7 +13,12 @@ use loco_rs::prelude::*;
 
 #[debug_handler]
 pub async fn read(State(ctx): State<AppContext>) -> Result<Json<AdminSettingsClientFacing>> {
-    Ok(Json(AdminSettings::load(&ctx.db).await?.into()))
+    Ok(Json(
+        AdminSettings::load(&ctx.db)
+            .await?
+            .decrypt_secrets()?
+            .into(),
+    ))
 }
 
 #[debug_handler]
@@ -25,7 +30,7 @@ pub async fn update(
     let mut item = item.into_active_model();
     params.update(&mut item);
     let item = item.update(&ctx.db).await?;
-    Ok(Json(item.into()))
+    Ok(Json(item.decrypt_secrets()?.into()))
 }
 
 async fn must_be_admin(user: users::Model, req: Request, next: Next) -> Result<Response> {
@@ -5,7 +5,10 @@ use loco_rs::prelude::*;
 
 use crate::{
     models::{
-        appointment_types::{self, AppointmentTypes, CreateOrUpdateAppointmentType},
+        appointment_types::{
+            self, AppointmentTypes, CreateOrUpdateAppointmentType, IntakeQuestions,
+        },
+        schedules::Schedules,
         users,
     },
     views::appointment_types::{CreateAppointmentTypeParams, UpdateAppointmentTypeParams},
@@ -17,11 +20,22 @@ pub async fn create(
     user: users::Model,
     Json(params): Json<CreateAppointmentTypeParams>,
 ) -> Result<Json<appointment_types::Model>> {
+    if let Some(schedule_id) = params.schedule_id {
+        Schedules::find_for_user(&ctx.db, &user, Some(schedule_id)).await?;
+    }
+
     let appointment_type = appointment_types::ActiveModel::create(
         &ctx.db,
         CreateOrUpdateAppointmentType {
             duration_in_minutes: params.duration_in_minutes,
             display_name: params.display_name,
+            buffer_before_minutes: params.buffer_before_minutes.unwrap_or(0),
+            buffer_after_minutes: params.buffer_after_minutes.unwrap_or(0),
+            slot_interval_minutes: params.slot_interval_minutes,
+            schedule_id: params.schedule_id,
+            max_attendees: params.max_attendees.unwrap_or(1),
+            intake_questions: IntakeQuestions(params.intake_questions.unwrap_or_default()),
+            requires_approval: params.requires_approval.unwrap_or(false),
             user: &user,
         },
     )
@@ -66,6 +80,33 @@ pub async fn update(
         return Err(Error::Unauthorized("Does not belong to user.".to_string()));
     }
 
+    let buffer_before_minutes = params
+        .buffer_before_minutes
+        .unwrap_or(appointment_type.buffer_before_minutes);
+    let buffer_after_minutes = params
+        .buffer_after_minutes
+        .unwrap_or(appointment_type.buffer_after_minutes);
+    let slot_interval_minutes = params
+        .slot_interval_minutes
+        .unwrap_or(appointment_type.slot_interval_minutes);
+    let schedule_id = match params.schedule_id {
+        Some(schedule_id) => Some(
+            Schedules::find_for_user(&ctx.db, &user, Some(schedule_id))
+                .await?
+                .id,
+        ),
+        None => appointment_type.schedule_id,
+    };
+    let max_attendees = params
+        .max_attendees
+        .unwrap_or(appointment_type.max_attendees);
+    let intake_questions = params.intake_questions.map_or_else(
+        || appointment_type.intake_questions.clone(),
+        IntakeQuestions,
+    );
+    let requires_approval = params
+        .requires_approval
+        .unwrap_or(appointment_type.requires_approval);
     let updated_appointment_type = appointment_type
         .into_active_model()
         .update_with_params(
@@ -73,6 +114,13 @@ pub async fn update(
             CreateOrUpdateAppointmentType {
                 duration_in_minutes: params.duration_in_minutes,
                 display_name: params.display_name,
+                buffer_before_minutes,
+                buffer_after_minutes,
+                slot_interval_minutes,
+                schedule_id,
+                max_attendees,
+                intake_questions,
+                requires_approval,
                 user: &user,
             },
         )
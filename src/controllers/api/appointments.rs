@@ -3,11 +3,15 @@
 #![allow(clippy::unused_async)]
 
 use crate::{
+    mailers::appointments::AppointmentsMailer,
     models::{
         appointments::{self, Appointments},
         users::users,
     },
-    views::appointments::{AppointmentsQueryParams, AppointmentsResponse},
+    views::{
+        appointments::{AppointmentsQueryParams, AppointmentsResponse},
+        client_facing::RescheduleParams,
+    },
 };
 use loco_rs::prelude::*;
 
@@ -36,9 +40,63 @@ pub async fn cancel_appointment(
     Ok(Json(appointment.cancel_appointment(&ctx, &user).await?))
 }
 
+#[debug_handler]
+pub async fn approve_appointment(
+    Path(id): Path<i32>,
+    user: users::Model,
+    State(ctx): State<AppContext>,
+) -> Result<Json<appointments::Model>> {
+    let appointment = Appointments::find_by_id_and_user(&ctx.db, id, &user).await?;
+
+    Ok(Json(appointment.approve(&ctx, &user).await?))
+}
+
+#[debug_handler]
+pub async fn decline_appointment(
+    Path(id): Path<i32>,
+    user: users::Model,
+    State(ctx): State<AppContext>,
+) -> Result<Json<appointments::Model>> {
+    let appointment = Appointments::find_by_id_and_user(&ctx.db, id, &user).await?;
+
+    Ok(Json(appointment.decline(&ctx).await?))
+}
+
+#[debug_handler]
+pub async fn keep_appointment(
+    Path(id): Path<i32>,
+    user: users::Model,
+    State(ctx): State<AppContext>,
+) -> Result<Json<appointments::Model>> {
+    let appointment = Appointments::find_by_id_and_user(&ctx.db, id, &user).await?;
+
+    Ok(Json(appointment.keep_after_review(&ctx, &user).await?))
+}
+
+#[debug_handler]
+pub async fn reschedule_appointment(
+    Path(id): Path<i32>,
+    user: users::Model,
+    State(ctx): State<AppContext>,
+    Json(params): Json<RescheduleParams>,
+) -> Result<Json<appointments::Model>> {
+    let appointment = Appointments::find_by_id_and_user(&ctx.db, id, &user)
+        .await?
+        .reschedule_appointment(&ctx, &user, &params.from, &params.to)
+        .await?;
+
+    AppointmentsMailer::send_reschedule_to_booker(&ctx, &appointment).await?;
+
+    Ok(Json(appointment))
+}
+
 pub fn routes() -> Routes {
     Routes::new()
         .prefix("api/appointments/")
         .add("/", get(read))
         .add("/cancel/{id}", patch(cancel_appointment))
+        .add("/approve/{id}", patch(approve_appointment))
+        .add("/decline/{id}", patch(decline_appointment))
+        .add("/keep/{id}", patch(keep_appointment))
+        .add("/reschedule/{id}", patch(reschedule_appointment))
 }
@@ -0,0 +1,112 @@
+#![allow(clippy::missing_errors_doc)]
+#![allow(clippy::unnecessary_struct_initialization)]
+#![allow(clippy::unused_async)]
+use loco_rs::prelude::*;
+
+use crate::{
+    models::{
+        availability_overrides::{self, AvailabilityOverrides, CreateOrUpdateAvailabilityOverride},
+        users,
+    },
+    views::availability_overrides::AvailabilityOverrideParams,
+};
+
+#[debug_handler]
+pub async fn create(
+    State(ctx): State<AppContext>,
+    user: users::Model,
+    Json(params): Json<AvailabilityOverrideParams>,
+) -> Result<Json<availability_overrides::Model>> {
+    let availability_override = availability_overrides::ActiveModel::create(
+        &ctx.db,
+        CreateOrUpdateAvailabilityOverride {
+            date: params.date,
+            unavailable: params.unavailable.unwrap_or_default(),
+            windows: params.windows.unwrap_or_default(),
+            user: &user,
+        },
+    )
+    .await?;
+    Ok(Json(availability_override))
+}
+
+#[debug_handler]
+pub async fn read_all(
+    State(ctx): State<AppContext>,
+    user: users::Model,
+) -> Result<Json<Vec<availability_overrides::Model>>> {
+    let availability_overrides = AvailabilityOverrides::find_by_user(&ctx.db, &user).await?;
+    Ok(Json(availability_overrides))
+}
+
+#[debug_handler]
+pub async fn read_single(
+    State(ctx): State<AppContext>,
+    Path(id): Path<i32>,
+    user: users::Model,
+) -> Result<Json<availability_overrides::Model>> {
+    let availability_override = AvailabilityOverrides::find_by_id(&ctx.db, id).await?;
+
+    if availability_override.user_id != user.id {
+        return Err(Error::Unauthorized("Does not belong to user.".to_string()));
+    }
+
+    Ok(Json(availability_override))
+}
+
+#[debug_handler]
+pub async fn update(
+    State(ctx): State<AppContext>,
+    Path(id): Path<i32>,
+    user: users::Model,
+    Json(params): Json<AvailabilityOverrideParams>,
+) -> Result<Json<availability_overrides::Model>> {
+    let availability_override = AvailabilityOverrides::find_by_id(&ctx.db, id).await?;
+
+    if availability_override.user_id != user.id {
+        return Err(Error::Unauthorized("Does not belong to user.".to_string()));
+    }
+
+    let updated_availability_override = availability_override
+        .into_active_model()
+        .update_with_params(
+            &ctx.db,
+            CreateOrUpdateAvailabilityOverride {
+                date: params.date,
+                unavailable: params.unavailable.unwrap_or_default(),
+                windows: params.windows.unwrap_or_default(),
+                user: &user,
+            },
+        )
+        .await?;
+    Ok(Json(updated_availability_override))
+}
+
+#[debug_handler]
+pub async fn destroy(
+    State(ctx): State<AppContext>,
+    user: users::Model,
+    Path(id): Path<i32>,
+) -> Result<Response> {
+    let availability_override = AvailabilityOverrides::find_by_id(&ctx.db, id).await?;
+
+    if availability_override.user_id != user.id {
+        return Err(Error::Unauthorized("Does not belong to user.".to_string()));
+    }
+
+    availability_override
+        .into_active_model()
+        .delete(&ctx.db)
+        .await?;
+    format::empty_json()
+}
+
+pub fn routes() -> Routes {
+    Routes::new()
+        .prefix("api/availability_overrides/")
+        .add("/", post(create))
+        .add("/", get(read_all))
+        .add("/{id}", get(read_single))
+        .add("/{id}", put(update))
+        .add("/{id}", delete(destroy))
+}
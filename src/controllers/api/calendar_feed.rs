@@ -0,0 +1,33 @@
+#![allow(clippy::missing_errors_doc)]
+#![allow(clippy::unnecessary_struct_initialization)]
+#![allow(clippy::unused_async)]
+use axum::{http::header, response::IntoResponse};
+use loco_rs::prelude::*;
+
+use crate::models::{appointments::Appointments, user_settings::UserSettings, users::Users};
+
+/// The owner's appointments as `text/calendar`. The token in the address is the only
+/// credential, calendar apps can not log in.
+#[debug_handler]
+async fn read(State(ctx): State<AppContext>, Path(token): Path<String>) -> Result<Response> {
+    let user_settings = UserSettings::find_by_calendar_feed_token(&ctx.db, &token)
+        .await
+        .map_err(|_| Error::NotFound)?;
+    let owner = Users::find_by_id(&ctx.db, user_settings.user_id).await?;
+    let calendar = Appointments::calendar_feed(&ctx, &owner).await?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, calendar.content_type()),
+            (header::CACHE_CONTROL, "no-cache".to_string()),
+        ],
+        calendar.to_ics(),
+    )
+        .into_response())
+}
+
+pub fn routes() -> Routes {
+    Routes::new()
+        .prefix("/api/calendar-feed")
+        .add("/{token}", get(read))
+}
@@ -7,10 +7,15 @@ use crate::{
     mailers::appointments::AppointmentsMailer,
     models::{
         appointment_types::{self, AppointmentTypes},
-        appointments, google_calendars, user_settings,
+        appointments::{self, Appointments},
+        user_settings,
         users::{self, Users},
     },
-    views::client_facing::{AvailabilityWindow, BookDay, BookingParams},
+    our_chrono,
+    views::client_facing::{
+        AvailabilityWindow, BookDay, BookingParams, ManagedAppointment, RescheduleParams,
+    },
+    workers::process_outbox::ProcessOutboxWorker,
 };
 use axum::debug_handler;
 use chrono::{DateTime, TimeDelta};
@@ -31,55 +36,63 @@ async fn appointment_types(
     Ok(Json(appointments))
 }
 
-#[debug_handler]
-async fn availabilities_by_day(
-    State(ctx): State<AppContext>,
-    Path(appointment_type_id): Path<i32>,
-    Timezone(user_timezone): Timezone,
-) -> Result<Json<Vec<BookDay>>> {
-    let appointment_type = AppointmentTypes::find_by_id(&ctx.db, appointment_type_id).await?;
-
+async fn current_availabilities(
+    ctx: &AppContext,
+    appointment_type: &appointment_types::Model,
+    exclude_appointment: Option<&appointments::Model>,
+) -> Result<Vec<AvailabilityWindow>> {
     let user = Users::find_by_id(&ctx.db, appointment_type.user_id).await?;
     let user_settings = user_settings::Model::get_or_create(&ctx.db, &user).await?;
     let availabilities = user
         .get_current_availabilities_by_appointment_type(
             &ctx.db,
             users::CurrentAvailabilityProps {
-                appointment_type: &appointment_type,
+                appointment_type,
                 start_how_far_from_now: TimeDelta::minutes(
                     user_settings.start_how_far_from_now_in_minutes.into(),
                 ),
                 end_how_far_from_now: TimeDelta::minutes(
                     user_settings.end_how_far_from_now_in_minutes.into(),
                 ),
+                exclude_appointment,
+                fresh_busy_times: false,
+                ignore_bookings: false,
             },
         )
         .await?;
 
+    Ok(availabilities)
+}
+
+fn group_by_day(availabilities: Vec<AvailabilityWindow>, timezone: &Tz) -> Vec<BookDay> {
     let mut days: BTreeMap<DateTime<Tz>, Vec<AvailabilityWindow>> = BTreeMap::new();
     availabilities
         .into_iter()
-        .chunk_by(|element| {
-            element
-                .start
-                .with_timezone(&user_timezone)
-                .beginning_of_day()
-        })
+        .chunk_by(|element| element.start.with_timezone(timezone).beginning_of_day())
         .into_iter()
         .for_each(|(key, chunk)| {
             let windows: Vec<AvailabilityWindow> = chunk.collect();
             days.entry(key).or_insert(windows);
         });
 
-    let days = days
-        .into_iter()
+    days.into_iter()
         .map(|(key, chunk)| BookDay {
             day: key.to_utc(),
             availabilities: chunk,
         })
-        .collect();
+        .collect()
+}
 
-    Ok(Json(days))
+#[debug_handler]
+async fn availabilities_by_day(
+    State(ctx): State<AppContext>,
+    Path(appointment_type_id): Path<i32>,
+    Timezone(user_timezone): Timezone,
+) -> Result<Json<Vec<BookDay>>> {
+    let appointment_type = AppointmentTypes::find_by_id(&ctx.db, appointment_type_id).await?;
+    let availabilities = current_availabilities(&ctx, &appointment_type, None).await?;
+
+    Ok(Json(group_by_day(availabilities, &user_timezone)))
 }
 
 pub async fn booking(
@@ -97,10 +110,19 @@ pub async fn booking(
         &user,
         &booking.from,
         &booking.to,
+        None,
     )
     .await?;
+    let intake_answers =
+        appointment_type.answer_intake_questions(&booking.answers.unwrap_or_default())?;
+    let pending_expires_at = if appointment_type.requires_approval {
+        let user_settings = user_settings::Model::get_or_create(&ctx.db, &user).await?;
+        Some((our_chrono::utc_now() + user_settings.pending_expires_after()).min(booking.from))
+    } else {
+        None
+    };
 
-    let appointment = appointments::ActiveModel::create(
+    appointments::ActiveModel::create_if_available(
         &ctx.db,
         appointments::CreateAppointmentProps {
             booker_phone: booking.booker_phone,
@@ -109,33 +131,92 @@ pub async fn booking(
             booker_email: booking.booker_email,
             start_time: booking.from,
             endtime: booking.to,
+            intake_answers,
+            pending_expires_at,
             user: &user,
             appointment_type: &appointment_type,
         },
     )
     .await?;
 
-    // TODO: This could be handled by a separate thread. Or even as a worker thread.
-    match google_calendars::Model::create_calendars_event(&ctx.db, &user, &appointment).await {
-        Ok(events) => {
-            tracing::info!("Google Calendar event created successfully");
-            appointment
-                .clone()
-                .into_active_model()
-                .attach_google_calendar_events(&ctx.db, events)
-                .await?;
-        }
-        Err(err) => {
-            tracing::warn!("Failed to create google calendar event: {}", err);
-        }
-    }
-
-    AppointmentsMailer::send_notification_to_booker(&ctx, &appointment).await?;
-    AppointmentsMailer::send_notification_to_user(&ctx, &appointment).await?;
+    // The calendar event and notifications were queued with the appointment.
+    ProcessOutboxWorker::kick(&ctx).await;
 
     Ok(Json(()))
 }
 
+async fn managed_appointment(
+    ctx: &AppContext,
+    appointment: appointments::Model,
+) -> Result<ManagedAppointment> {
+    let appointment_type =
+        AppointmentTypes::find_by_id(&ctx.db, appointment.appointment_type_id).await?;
+    let user = Users::find_by_id(&ctx.db, appointment.user_id).await?;
+
+    Ok(ManagedAppointment::new(
+        appointment,
+        &appointment_type,
+        &user,
+    ))
+}
+
+#[debug_handler]
+async fn manage(
+    State(ctx): State<AppContext>,
+    Path(token): Path<String>,
+) -> Result<Json<ManagedAppointment>> {
+    let appointment = Appointments::find_by_manage_token(&ctx.db, &token).await?;
+
+    Ok(Json(managed_appointment(&ctx, appointment).await?))
+}
+
+#[debug_handler]
+async fn manage_availabilities(
+    State(ctx): State<AppContext>,
+    Path(token): Path<String>,
+    Timezone(booker_timezone): Timezone,
+) -> Result<Json<Vec<BookDay>>> {
+    let appointment = Appointments::find_by_manage_token(&ctx.db, &token).await?;
+    let appointment_type =
+        AppointmentTypes::find_by_id(&ctx.db, appointment.appointment_type_id).await?;
+    let availabilities =
+        current_availabilities(&ctx, &appointment_type, Some(&appointment)).await?;
+
+    Ok(Json(group_by_day(availabilities, &booker_timezone)))
+}
+
+#[debug_handler]
+async fn manage_cancel(
+    State(ctx): State<AppContext>,
+    Path(token): Path<String>,
+) -> Result<Json<ManagedAppointment>> {
+    let appointment = Appointments::find_by_manage_token(&ctx.db, &token)
+        .await?
+        .cancel_by_booker(&ctx)
+        .await?;
+
+    Ok(Json(managed_appointment(&ctx, appointment).await?))
+}
+
+#[debug_handler]
+async fn manage_reschedule(
+    State(ctx): State<AppContext>,
+    Path(token): Path<String>,
+    Json(params): Json<RescheduleParams>,
+) -> Result<Json<ManagedAppointment>> {
+    let appointment = Appointments::find_by_manage_token(&ctx.db, &token).await?;
+    let user = Users::find_by_id(&ctx.db, appointment.user_id).await?;
+
+    let appointment = appointment
+        .reschedule_appointment(&ctx, &user, &params.from, &params.to)
+        .await?;
+
+    AppointmentsMailer::send_reschedule_to_booker(&ctx, &appointment).await?;
+    AppointmentsMailer::send_notification_to_user(&ctx, &appointment).await?;
+
+    Ok(Json(managed_appointment(&ctx, appointment).await?))
+}
+
 pub fn routes() -> Routes {
     Routes::new()
         .prefix("/api/client-facing")
@@ -145,4 +226,8 @@ pub fn routes() -> Routes {
             get(availabilities_by_day),
         )
         .add("/book/{appointment_type_id}", post(booking))
+        .add("/manage/{token}", get(manage))
+        .add("/manage/{token}/availabilities", get(manage_availabilities))
+        .add("/manage/{token}/cancel", post(manage_cancel))
+        .add("/manage/{token}/reschedule", post(manage_reschedule))
 }
@@ -0,0 +1,101 @@
+#![allow(clippy::missing_errors_doc)]
+#![allow(clippy::unnecessary_struct_initialization)]
+#![allow(clippy::unused_async)]
+
+use crate::{
+    models::{
+        caldav_connections::{self, CaldavConnections, ConnectCaldav},
+        users::users,
+    },
+    views::{
+        caldav::{CaldavCalendarEntry, CaldavConnectionParams},
+        google_calendars::{CalendarSettingParams, CalendarSettingsResponse},
+    },
+};
+use loco_rs::prelude::*;
+
+#[debug_handler]
+pub async fn connect(
+    State(ctx): State<AppContext>,
+    user: users::Model,
+    Json(params): Json<CaldavConnectionParams>,
+) -> Result<Json<Vec<CaldavCalendarEntry>>> {
+    let (_, calendars) = caldav_connections::ActiveModel::connect(
+        &ctx.db,
+        &user,
+        ConnectCaldav {
+            server_url: params.server_url,
+            username: params.username,
+            password: params.password,
+        },
+    )
+    .await?;
+
+    Ok(Json(calendars.into_iter().map(Into::into).collect()))
+}
+
+#[debug_handler]
+pub async fn disconnect(State(ctx): State<AppContext>, user: users::Model) -> Result<Json<()>> {
+    let connection = CaldavConnections::find_by_user(&ctx.db, &user).await?;
+    connection.into_active_model().delete(&ctx.db).await?;
+
+    Ok(Json(()))
+}
+
+#[debug_handler]
+pub async fn get_calendars(
+    State(ctx): State<AppContext>,
+    user: users::Model,
+) -> Result<Json<Vec<CaldavCalendarEntry>>> {
+    let connection = CaldavConnections::find_by_user(&ctx.db, &user).await?;
+    let calendars = connection.client()?.discover_calendars().await?;
+
+    Ok(Json(calendars.into_iter().map(Into::into).collect()))
+}
+
+#[debug_handler]
+pub async fn add_calendar(
+    State(ctx): State<AppContext>,
+    user: users::Model,
+    Json(params): Json<CalendarSettingParams>,
+) -> Result<Json<CalendarSettingsResponse>> {
+    let connection = CaldavConnections::find_by_user(&ctx.db, &user).await?;
+    let updated_connection = connection.add_calendar_to_settings(&ctx.db, params).await?;
+
+    Ok(Json(updated_connection.into()))
+}
+
+#[debug_handler]
+pub async fn remove_calendar(
+    State(ctx): State<AppContext>,
+    user: users::Model,
+    Json(params): Json<CalendarSettingParams>,
+) -> Result<Json<CalendarSettingsResponse>> {
+    let connection = CaldavConnections::find_by_user(&ctx.db, &user).await?;
+    let updated_connection = connection
+        .remove_calendar_from_settings(&ctx.db, params)
+        .await?;
+
+    Ok(Json(updated_connection.into()))
+}
+
+#[debug_handler]
+pub async fn get_settings(
+    State(ctx): State<AppContext>,
+    user: users::Model,
+) -> Result<Json<CalendarSettingsResponse>> {
+    let connection = CaldavConnections::find_by_user(&ctx.db, &user).await?;
+
+    Ok(Json(connection.into()))
+}
+
+pub fn routes() -> Routes {
+    Routes::new()
+        .prefix("api/caldav/")
+        .add("/", post(connect))
+        .add("/", delete(disconnect))
+        .add("/get_calendars", get(get_calendars))
+        .add("/add_calendar", post(add_calendar))
+        .add("/remove_calendar", post(remove_calendar))
+        .add("/get_settings", get(get_settings))
+}
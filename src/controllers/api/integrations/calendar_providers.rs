@@ -0,0 +1,34 @@
+#![allow(clippy::missing_errors_doc)]
+#![allow(clippy::unnecessary_struct_initialization)]
+#![allow(clippy::unused_async)]
+
+use crate::{
+    calendar_providers::CalendarProviders, models::users::users,
+    views::calendar_providers::CalendarConnection,
+};
+use futures::future::join_all;
+use loco_rs::prelude::*;
+
+#[debug_handler]
+pub async fn list(
+    State(ctx): State<AppContext>,
+    user: users::Model,
+) -> Result<Json<Vec<CalendarConnection>>> {
+    let providers = CalendarProviders::for_user(&ctx.db, &user).await?;
+    let connections = join_all(providers.all().map(|provider| async move {
+        CalendarConnection {
+            provider: provider.kind(),
+            connection_id: provider.connection_id(),
+            connected: provider.is_connected().await,
+        }
+    }))
+    .await;
+
+    Ok(Json(connections))
+}
+
+pub fn routes() -> Routes {
+    Routes::new()
+        .prefix("api/calendar_providers/")
+        .add("/", get(list))
+}
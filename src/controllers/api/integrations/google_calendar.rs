@@ -4,13 +4,16 @@
 
 use crate::{
     models::{
-        admin_settings::AdminSettings,
+        google_calendar_channels::GoogleCalendarChannels,
         google_calendars::{self, GoogleCalendars},
         users::users,
     },
-    views::google_calendars::{CalendarEntry, CalendarSettingParams, CalendarSettingsResponse},
+    views::google_calendars::{
+        CalendarEntry, CalendarSettingParams, CalendarSettingsResponse, GoogleCalendarConnection,
+    },
+    workers::sync_google_calendar::{SyncGoogleCalendarWorker, SyncGoogleCalendarWorkerArgs},
 };
-use axum::response::Redirect;
+use axum::{http::HeaderMap, response::Redirect};
 use google_calendar::types::MinAccessRole;
 use loco_rs::prelude::*;
 use serde::Deserialize;
@@ -40,13 +43,29 @@ pub async fn oauth_callback(
     Ok(Redirect::to(&redirect_url))
 }
 
+#[debug_handler]
+pub async fn list(
+    State(ctx): State<AppContext>,
+    user: users::Model,
+) -> Result<Json<Vec<GoogleCalendarConnection>>> {
+    let connections = GoogleCalendars::find_by_user(&ctx.db, &user).await?;
+
+    Ok(Json(
+        connections
+            .into_iter()
+            .map(GoogleCalendarConnection::from)
+            .collect(),
+    ))
+}
+
 #[debug_handler]
 pub async fn revoke_and_delete_token(
     State(ctx): State<AppContext>,
     user: users::Model,
+    Path(id): Path<i32>,
 ) -> Result<Json<()>> {
     // Revoke the access token and delete the token from the database
-    let google_calendar_config = GoogleCalendars::find_by_user(&ctx.db, &user).await?;
+    let google_calendar_config = GoogleCalendars::find_by_user_and_id(&ctx.db, &user, id).await?;
     google_calendar_config
         .revoke_and_delete_token(&ctx.db)
         .await?;
@@ -58,26 +77,16 @@ pub async fn revoke_and_delete_token(
 pub async fn get_calendars(
     State(ctx): State<AppContext>,
     user: users::Model,
+    Path(id): Path<i32>,
 ) -> Result<Json<Vec<CalendarEntry>>> {
-    let google_calendar_settings = AdminSettings::get_google_calendar_settings(&ctx.db).await?;
-    let google_calendar_config = GoogleCalendars::find_by_user(&ctx.db, &user).await?;
-    let redirect_url = google_calendars::OAuthUrl::redirect_uri(&google_calendar_settings)?;
-
-    let client = google_calendar::Client::new(
-        google_calendar_settings.google_oauth_client_id,
-        google_calendar_settings.google_oauth_secret,
-        redirect_url,
-        google_calendar_config.access_token,
-        google_calendar_config.refresh_token,
-    );
-
-    let _access_token = client.refresh_access_token().await.map_err(Error::wrap)?;
+    let google_calendar_config = GoogleCalendars::find_by_user_and_id(&ctx.db, &user, id).await?;
+    let client = google_calendar_config.client(&ctx.db).await?;
 
     let calendar_list = client
         .calendar_list()
         .list_all(MinAccessRole::Reader, false, false)
         .await
-        .map_err(Error::wrap)?;
+        .map_err(google_calendars::api_error)?;
 
     let calendar_entries: Vec<CalendarEntry> = calendar_list
         .body
@@ -88,18 +97,35 @@ pub async fn get_calendars(
     Ok(Json(calendar_entries))
 }
 
+/// Follows the calendar picks right away, the scheduled renewal catches up when Google is
+/// unreachable now.
+async fn watch_collision_check_calendars(
+    ctx: &AppContext,
+    google_calendar_config: &google_calendars::Model,
+) {
+    if let Err(err) = GoogleCalendarChannels::sync(&ctx.db, google_calendar_config).await {
+        tracing::warn!(
+            "Could not update the Google channels of {}: {}",
+            google_calendar_config.id,
+            err
+        );
+    }
+}
+
 #[debug_handler]
 pub async fn add_calendar(
     State(ctx): State<AppContext>,
     user: users::Model,
+    Path(id): Path<i32>,
     Json(params): Json<CalendarSettingParams>,
 ) -> Result<Json<CalendarSettingsResponse>> {
     // Implementation goes here
-    let google_calendar_config = GoogleCalendars::find_by_user(&ctx.db, &user).await?;
+    let google_calendar_config = GoogleCalendars::find_by_user_and_id(&ctx.db, &user, id).await?;
 
     let updated_google_calendar_config = google_calendar_config
         .add_calendar_to_settings(&ctx.db, params)
         .await?;
+    watch_collision_check_calendars(&ctx, &updated_google_calendar_config).await;
 
     Ok(Json(updated_google_calendar_config.into()))
 }
@@ -108,14 +134,16 @@ pub async fn add_calendar(
 pub async fn remove_calendar(
     State(ctx): State<AppContext>,
     user: users::Model,
+    Path(id): Path<i32>,
     Json(params): Json<CalendarSettingParams>,
 ) -> Result<Json<CalendarSettingsResponse>> {
     // Implementation goes here
-    let google_calendar_config = GoogleCalendars::find_by_user(&ctx.db, &user).await?;
+    let google_calendar_config = GoogleCalendars::find_by_user_and_id(&ctx.db, &user, id).await?;
 
     let updated_google_calendar_config = google_calendar_config
         .remove_calendar_from_settings(&ctx.db, params)
         .await?;
+    watch_collision_check_calendars(&ctx, &updated_google_calendar_config).await;
 
     Ok(Json(updated_google_calendar_config.into()))
 }
@@ -124,21 +152,56 @@ pub async fn remove_calendar(
 pub async fn get_settings(
     State(ctx): State<AppContext>,
     user: users::Model,
+    Path(id): Path<i32>,
 ) -> Result<Json<CalendarSettingsResponse>> {
     // Implementation goes here
-    let google_calendar_config = GoogleCalendars::find_by_user(&ctx.db, &user).await?;
+    let google_calendar_config = GoogleCalendars::find_by_user_and_id(&ctx.db, &user, id).await?;
 
     Ok(Json(google_calendar_config.into()))
 }
 
+/// Receiver of Google's push notifications. Google sends no credentials but the channel id,
+/// token and resource id it was given when the channel was registered.
+#[debug_handler]
+pub async fn notification(State(ctx): State<AppContext>, headers: HeaderMap) -> Result<Json<()>> {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+    };
+    let channel = GoogleCalendarChannels::find_verified(
+        &ctx.db,
+        header("x-goog-channel-id"),
+        header("x-goog-channel-token"),
+        header("x-goog-resource-id"),
+    )
+    .await?;
+
+    // The first message of a channel only confirms it was set up.
+    if header("x-goog-resource-state") != "sync" {
+        SyncGoogleCalendarWorker::perform_later(
+            &ctx,
+            SyncGoogleCalendarWorkerArgs {
+                google_calendar_id: channel.google_calendar_id,
+            },
+        )
+        .await?;
+    }
+
+    Ok(Json(()))
+}
+
 pub fn routes() -> Routes {
     Routes::new()
         .prefix("api/google_calendar/")
-        .add("/", delete(revoke_and_delete_token))
+        .add("/", get(list))
         .add("/oauth_url", get(oauth_url))
         .add("/oauth_callback", get(oauth_callback))
-        .add("/get_calendars", get(get_calendars))
-        .add("/add_calendar", post(add_calendar))
-        .add("/remove_calendar", post(remove_calendar))
-        .add("/get_settings", get(get_settings))
+        .add("/notifications", post(notification))
+        .add("/{id}", delete(revoke_and_delete_token))
+        .add("/{id}/get_calendars", get(get_calendars))
+        .add("/{id}/add_calendar", post(add_calendar))
+        .add("/{id}/remove_calendar", post(remove_calendar))
+        .add("/{id}/get_settings", get(get_settings))
 }
@@ -1 +1,4 @@
+pub mod caldav;
+pub mod calendar_providers;
 pub mod google_calendar;
+pub mod outlook_calendar;
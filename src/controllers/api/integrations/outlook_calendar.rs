@@ -0,0 +1,111 @@
+#![allow(clippy::missing_errors_doc)]
+#![allow(clippy::unnecessary_struct_initialization)]
+#![allow(clippy::unused_async)]
+
+use crate::{
+    models::{
+        outlook_calendars::{self, OutlookCalendars},
+        users::users,
+    },
+    views::{
+        google_calendars::{CalendarSettingParams, CalendarSettingsResponse},
+        outlook_calendars::OutlookCalendarEntry,
+    },
+};
+use axum::response::Redirect;
+use loco_rs::prelude::*;
+use serde::Deserialize;
+
+#[debug_handler]
+pub async fn oauth_url(State(ctx): State<AppContext>, user: users::Model) -> Result<Json<String>> {
+    let oauth_url = outlook_calendars::Model::generate_oauth_url(&ctx, &user).await?;
+    Ok(Json(oauth_url.to_string()))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OAuthCallbackQueryParams {
+    pub code: String,
+    pub state: Uuid,
+}
+
+#[debug_handler]
+pub async fn oauth_callback(
+    State(ctx): State<AppContext>,
+    Query(params): Query<OAuthCallbackQueryParams>,
+) -> Result<Redirect> {
+    let redirect_url =
+        outlook_calendars::Model::exchange_code_for_token(&ctx, &params.code, &params.state)
+            .await?;
+
+    Ok(Redirect::to(&redirect_url))
+}
+
+#[debug_handler]
+pub async fn disconnect(State(ctx): State<AppContext>, user: users::Model) -> Result<Json<()>> {
+    // Microsoft has no endpoint to revoke a single token, forgetting it is all there is to do.
+    let outlook_calendar = OutlookCalendars::find_by_user(&ctx.db, &user).await?;
+    outlook_calendar.into_active_model().delete(&ctx.db).await?;
+
+    Ok(Json(()))
+}
+
+#[debug_handler]
+pub async fn get_calendars(
+    State(ctx): State<AppContext>,
+    user: users::Model,
+) -> Result<Json<Vec<OutlookCalendarEntry>>> {
+    let outlook_calendar = OutlookCalendars::find_by_user(&ctx.db, &user).await?;
+    let calendars = outlook_calendar.client(&ctx.db).await?.calendars().await?;
+
+    Ok(Json(calendars.into_iter().map(Into::into).collect()))
+}
+
+#[debug_handler]
+pub async fn add_calendar(
+    State(ctx): State<AppContext>,
+    user: users::Model,
+    Json(params): Json<CalendarSettingParams>,
+) -> Result<Json<CalendarSettingsResponse>> {
+    let outlook_calendar = OutlookCalendars::find_by_user(&ctx.db, &user).await?;
+    let updated_outlook_calendar = outlook_calendar
+        .add_calendar_to_settings(&ctx.db, params)
+        .await?;
+
+    Ok(Json(updated_outlook_calendar.into()))
+}
+
+#[debug_handler]
+pub async fn remove_calendar(
+    State(ctx): State<AppContext>,
+    user: users::Model,
+    Json(params): Json<CalendarSettingParams>,
+) -> Result<Json<CalendarSettingsResponse>> {
+    let outlook_calendar = OutlookCalendars::find_by_user(&ctx.db, &user).await?;
+    let updated_outlook_calendar = outlook_calendar
+        .remove_calendar_from_settings(&ctx.db, params)
+        .await?;
+
+    Ok(Json(updated_outlook_calendar.into()))
+}
+
+#[debug_handler]
+pub async fn get_settings(
+    State(ctx): State<AppContext>,
+    user: users::Model,
+) -> Result<Json<CalendarSettingsResponse>> {
+    let outlook_calendar = OutlookCalendars::find_by_user(&ctx.db, &user).await?;
+
+    Ok(Json(outlook_calendar.into()))
+}
+
+pub fn routes() -> Routes {
+    Routes::new()
+        .prefix("api/outlook_calendar/")
+        .add("/", delete(disconnect))
+        .add("/oauth_url", get(oauth_url))
+        .add("/oauth_callback", get(oauth_callback))
+        .add("/get_calendars", get(get_calendars))
+        .add("/add_calendar", post(add_calendar))
+        .add("/remove_calendar", post(remove_calendar))
+        .add("/get_settings", get(get_settings))
+}
@@ -2,7 +2,11 @@ pub mod admin_settings;
 pub mod appointment_types;
 pub mod appointments;
 pub mod auth;
+pub mod availability_overrides;
+pub mod calendar_feed;
 pub mod client_facing;
 pub mod integrations;
+pub mod schedules;
 pub mod user_settings;
+pub mod webhooks;
 pub mod weekly_availabilities;
@@ -0,0 +1,94 @@
+#![allow(clippy::missing_errors_doc)]
+#![allow(clippy::unnecessary_struct_initialization)]
+#![allow(clippy::unused_async)]
+use loco_rs::prelude::*;
+
+use crate::{
+    models::{
+        schedules::{self, CreateOrUpdateSchedule, Schedules},
+        users,
+    },
+    views::schedules::ScheduleParams,
+};
+
+#[debug_handler]
+pub async fn create(
+    State(ctx): State<AppContext>,
+    user: users::Model,
+    Json(params): Json<ScheduleParams>,
+) -> Result<Json<schedules::Model>> {
+    let schedule = schedules::ActiveModel::create(
+        &ctx.db,
+        CreateOrUpdateSchedule {
+            name: params.name,
+            is_default: params.is_default.unwrap_or_default(),
+            user: &user,
+        },
+    )
+    .await?;
+    Ok(Json(schedule))
+}
+
+#[debug_handler]
+pub async fn read_all(
+    State(ctx): State<AppContext>,
+    user: users::Model,
+) -> Result<Json<Vec<schedules::Model>>> {
+    // Make sure there is always a default schedule to show.
+    schedules::Model::get_or_create_default(&ctx.db, &user).await?;
+    let schedules = Schedules::find_by_user(&ctx.db, &user).await?;
+    Ok(Json(schedules))
+}
+
+#[debug_handler]
+pub async fn update(
+    State(ctx): State<AppContext>,
+    Path(id): Path<i32>,
+    user: users::Model,
+    Json(params): Json<ScheduleParams>,
+) -> Result<Json<schedules::Model>> {
+    let schedule = Schedules::find_by_id(&ctx.db, id).await?;
+
+    if schedule.user_id != user.id {
+        return Err(Error::Unauthorized("Does not belong to user.".to_string()));
+    }
+
+    let is_default = params.is_default.unwrap_or(schedule.is_default);
+    let updated_schedule = schedule
+        .into_active_model()
+        .update_with_params(
+            &ctx.db,
+            CreateOrUpdateSchedule {
+                name: params.name,
+                is_default,
+                user: &user,
+            },
+        )
+        .await?;
+    Ok(Json(updated_schedule))
+}
+
+#[debug_handler]
+pub async fn destroy(
+    State(ctx): State<AppContext>,
+    user: users::Model,
+    Path(id): Path<i32>,
+) -> Result<Response> {
+    let schedule = Schedules::find_by_id(&ctx.db, id).await?;
+
+    if schedule.user_id != user.id {
+        return Err(Error::Unauthorized("Does not belong to user.".to_string()));
+    }
+
+    schedule.destroy(&ctx.db).await?;
+    format::empty_json()
+}
+
+pub fn routes() -> Routes {
+    Routes::new()
+        .prefix("api/schedules/")
+        .add("/", post(create))
+        .add("/", get(read_all))
+        .add("/{id}", put(update))
+        .add("/{id}", delete(destroy))
+}
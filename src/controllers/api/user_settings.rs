@@ -8,7 +8,7 @@ use crate::{
         user_settings::{self},
         users::users,
     },
-    views::user_settings::UserSettingsProps,
+    views::user_settings::{CalendarFeed, UserSettingsProps},
 };
 
 #[debug_handler]
@@ -36,9 +36,56 @@ pub async fn update(
     ))
 }
 
+#[debug_handler]
+pub async fn read_calendar_feed(
+    State(ctx): State<AppContext>,
+    user: users::Model,
+) -> Result<Json<CalendarFeed>> {
+    let user_settings = user_settings::Model::get_or_create(&ctx.db, &user).await?;
+
+    Ok(Json(CalendarFeed {
+        url: user_settings.calendar_feed_url(&ctx),
+    }))
+}
+
+#[debug_handler]
+pub async fn rotate_calendar_feed(
+    State(ctx): State<AppContext>,
+    user: users::Model,
+) -> Result<Json<CalendarFeed>> {
+    let user_settings = user_settings::Model::get_or_create(&ctx.db, &user)
+        .await?
+        .into_active_model()
+        .rotate_calendar_feed_token(&ctx.db)
+        .await?;
+
+    Ok(Json(CalendarFeed {
+        url: user_settings.calendar_feed_url(&ctx),
+    }))
+}
+
+#[debug_handler]
+pub async fn disable_calendar_feed(
+    State(ctx): State<AppContext>,
+    user: users::Model,
+) -> Result<Json<CalendarFeed>> {
+    let user_settings = user_settings::Model::get_or_create(&ctx.db, &user)
+        .await?
+        .into_active_model()
+        .disable_calendar_feed(&ctx.db)
+        .await?;
+
+    Ok(Json(CalendarFeed {
+        url: user_settings.calendar_feed_url(&ctx),
+    }))
+}
+
 pub fn routes() -> Routes {
     Routes::new()
         .prefix("api/user_settings/")
         .add("/", get(read))
         .add("/", post(update))
+        .add("/calendar-feed", get(read_calendar_feed))
+        .add("/calendar-feed", post(rotate_calendar_feed))
+        .add("/calendar-feed", delete(disable_calendar_feed))
 }
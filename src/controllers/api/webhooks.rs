@@ -0,0 +1,127 @@
+#![allow(clippy::missing_errors_doc)]
+#![allow(clippy::unnecessary_struct_initialization)]
+#![allow(clippy::unused_async)]
+use loco_rs::prelude::*;
+
+use crate::{
+    models::{
+        users,
+        webhook_deliveries::{self, WebhookDeliveries},
+        webhook_endpoints::{self, CreateOrUpdateWebhookEndpoint, WebhookEndpoints},
+    },
+    views::webhooks::WebhookEndpointParams,
+};
+
+async fn find_owned_endpoint(
+    ctx: &AppContext,
+    id: i32,
+    user: &users::Model,
+) -> Result<webhook_endpoints::Model> {
+    let endpoint = WebhookEndpoints::find_by_id(&ctx.db, id).await?;
+
+    if endpoint.user_id != user.id {
+        return Err(Error::Unauthorized("Does not belong to user.".to_string()));
+    }
+
+    Ok(endpoint)
+}
+
+#[debug_handler]
+pub async fn create(
+    State(ctx): State<AppContext>,
+    user: users::Model,
+    Json(params): Json<WebhookEndpointParams>,
+) -> Result<Json<webhook_endpoints::Model>> {
+    let endpoint = webhook_endpoints::ActiveModel::create(
+        &ctx.db,
+        CreateOrUpdateWebhookEndpoint {
+            url: params.url,
+            events: params.events,
+            active: params.active.unwrap_or(true),
+            user: &user,
+        },
+    )
+    .await?;
+    Ok(Json(endpoint))
+}
+
+#[debug_handler]
+pub async fn read_all(
+    State(ctx): State<AppContext>,
+    user: users::Model,
+) -> Result<Json<Vec<webhook_endpoints::Model>>> {
+    let endpoints = WebhookEndpoints::find_by_user(&ctx.db, &user).await?;
+    Ok(Json(endpoints))
+}
+
+#[debug_handler]
+pub async fn update(
+    State(ctx): State<AppContext>,
+    Path(id): Path<i32>,
+    user: users::Model,
+    Json(params): Json<WebhookEndpointParams>,
+) -> Result<Json<webhook_endpoints::Model>> {
+    let endpoint = find_owned_endpoint(&ctx, id, &user).await?;
+
+    let updated_endpoint = endpoint
+        .into_active_model()
+        .update_with_params(
+            &ctx.db,
+            CreateOrUpdateWebhookEndpoint {
+                url: params.url,
+                events: params.events,
+                active: params.active.unwrap_or(true),
+                user: &user,
+            },
+        )
+        .await?;
+    Ok(Json(updated_endpoint))
+}
+
+#[debug_handler]
+pub async fn destroy(
+    State(ctx): State<AppContext>,
+    user: users::Model,
+    Path(id): Path<i32>,
+) -> Result<Response> {
+    let endpoint = find_owned_endpoint(&ctx, id, &user).await?;
+
+    endpoint.into_active_model().delete(&ctx.db).await?;
+    format::empty_json()
+}
+
+#[debug_handler]
+pub async fn deliveries(
+    State(ctx): State<AppContext>,
+    Path(id): Path<i32>,
+    user: users::Model,
+) -> Result<Json<Vec<webhook_deliveries::Model>>> {
+    let endpoint = find_owned_endpoint(&ctx, id, &user).await?;
+
+    Ok(Json(
+        WebhookDeliveries::find_by_endpoint(&ctx.db, &endpoint).await?,
+    ))
+}
+
+#[debug_handler]
+pub async fn replay(
+    State(ctx): State<AppContext>,
+    Path(id): Path<i32>,
+    user: users::Model,
+) -> Result<Json<webhook_deliveries::Model>> {
+    let delivery = WebhookDeliveries::find_by_id(&ctx.db, id).await?;
+    find_owned_endpoint(&ctx, delivery.webhook_endpoint_id, &user).await?;
+
+    Ok(Json(delivery.replay(&ctx).await?))
+}
+
+pub fn routes() -> Routes {
+    Routes::new()
+        .prefix("api/webhooks/")
+        .add("/", post(create))
+        .add("/", get(read_all))
+        .add("/{id}", put(update))
+        .add("/{id}", delete(destroy))
+        .add("/{id}/deliveries", get(deliveries))
+        .add("/deliveries/{id}/replay", post(replay))
+}
@@ -7,25 +7,50 @@ use loco_rs::prelude::*;
 
 use crate::{
     models::{
+        schedules::{self, Schedules},
         users,
         weekly_availabilities::{self, CreateProps},
     },
     traits::GenericWindowComparison,
-    views::admin::{WeeklyAvailabilitiesCreateUpdateParams, WeeklyAvailabilityByWeekday},
+    views::admin::{
+        WeeklyAvailabilitiesCreateUpdateParams, WeeklyAvailabilitiesQueryParams,
+        WeeklyAvailabilityByWeekday,
+    },
 };
 
+async fn by_weekday(
+    ctx: &AppContext,
+    schedule: &schedules::Model,
+) -> Result<Json<WeeklyAvailabilityByWeekday>> {
+    let weekly_availabilities =
+        weekly_availabilities::Entity::find_by_schedule(&ctx.db, schedule, vec![]).await?;
+
+    let normalized_availability_windows: WeeklyAvailabilityByWeekday =
+        weekly_availabilities.into_iter().fold(
+            WeeklyAvailabilityByWeekday::new(),
+            |mut acc, weekly_availability| {
+                acc.add_availability_by_day_index(weekly_availability);
+                acc
+            },
+        );
+
+    Ok(Json(normalized_availability_windows))
+}
+
 #[debug_handler]
 pub async fn create(
     State(ctx): State<AppContext>,
     user: users::Model,
     Json(props): Json<WeeklyAvailabilitiesCreateUpdateParams>,
 ) -> Result<Json<weekly_availabilities::Model>> {
+    let schedule = Schedules::find_for_user(&ctx.db, &user, props.schedule_id).await?;
     let model = weekly_availabilities::ActiveModel::create(
         &ctx.db,
         CreateProps {
             from: props.start_time(),
             to: props.end_time(),
             user: &user,
+            schedule: &schedule,
         },
     )
     .await?;
@@ -37,20 +62,10 @@ pub async fn create(
 pub async fn read(
     State(ctx): State<AppContext>,
     user: users::Model,
+    Query(params): Query<WeeklyAvailabilitiesQueryParams>,
 ) -> Result<Json<WeeklyAvailabilityByWeekday>> {
-    let weekly_availabilities =
-        weekly_availabilities::Entity::find_by_user(&ctx.db, &user, vec![]).await?;
-
-    let normalized_availability_windows: WeeklyAvailabilityByWeekday =
-        weekly_availabilities.into_iter().fold(
-            WeeklyAvailabilityByWeekday::new(),
-            |mut acc, weekly_availability| {
-                acc.add_availability_by_day_index(weekly_availability);
-                acc
-            },
-        );
-
-    Ok(Json(normalized_availability_windows))
+    let schedule = Schedules::find_for_user(&ctx.db, &user, params.schedule_id).await?;
+    by_weekday(&ctx, &schedule).await
 }
 
 #[debug_handler]
@@ -60,12 +75,20 @@ pub async fn update(
     user: users::Model,
     Json(props): Json<WeeklyAvailabilitiesCreateUpdateParams>,
 ) -> Result<Json<weekly_availabilities::Model>> {
-    let model_result = weekly_availabilities::WeeklyAvailabilities::find_by_id(id)
+    let model = weekly_availabilities::WeeklyAvailabilities::find_by_id(id)
         .one(&ctx.db)
         .await?
-        .ok_or(ModelError::EntityNotFound)?
+        .ok_or(ModelError::EntityNotFound)?;
+
+    if model.user_id != user.id {
+        return Err(Error::Unauthorized("Does not belong to user.".to_string()));
+    }
+
+    let schedule = Schedules::find_for_user(&ctx.db, &user, model.schedule_id).await?;
+
+    let model_result = model
         .into_active_model()
-        .put(&ctx.db, props, &user)
+        .put(&ctx.db, props, &schedule)
         .await;
 
     match model_result {
@@ -83,11 +106,22 @@ pub async fn destroy(
     Path(id): Path<i32>,
     user: users::Model,
 ) -> Result<Json<WeeklyAvailabilityByWeekday>> {
+    let model = weekly_availabilities::WeeklyAvailabilities::find_by_id(id)
+        .one(&ctx.db)
+        .await?
+        .ok_or(ModelError::EntityNotFound)?;
+
+    if model.user_id != user.id {
+        return Err(Error::Unauthorized("Does not belong to user.".to_string()));
+    }
+
+    let schedule = Schedules::find_for_user(&ctx.db, &user, model.schedule_id).await?;
+
     let _model = weekly_availabilities::WeeklyAvailabilities::delete_by_id(id)
         .exec(&ctx.db)
         .await?;
 
-    read(State(ctx), user).await
+    by_weekday(&ctx, &schedule).await
 }
 
 pub fn routes() -> Routes {
@@ -0,0 +1,18 @@
+use async_trait::async_trait;
+use loco_rs::prelude::*;
+
+use crate::common::encryption;
+
+/// Loads the keys for the secrets stored in the database, refusing to boot without one.
+pub struct EncryptionInitializer;
+
+#[async_trait]
+impl Initializer for EncryptionInitializer {
+    fn name(&self) -> String {
+        "encryption".to_string()
+    }
+
+    async fn before_run(&self, ctx: &AppContext) -> Result<()> {
+        encryption::install(&ctx.config)
+    }
+}
@@ -1,2 +1,3 @@
 pub mod admin_settings;
+pub mod encryption;
 pub mod env_vars;
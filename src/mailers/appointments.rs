@@ -6,11 +6,27 @@ use chrono_tz::Tz;
 use loco_rs::prelude::*;
 use serde_json::json;
 
-use crate::models::{appointments, users::Users};
+use crate::{
+    common::ics,
+    mailers::attachments::{Attachment, EmailWithAttachments},
+    models::{
+        _entities::appointments::{CalendarReview, Status},
+        appointment_types::AppointmentTypes,
+        appointments,
+        users::{self, Users},
+    },
+    workers::attachment_mailer::AttachmentMailerWorker,
+};
 
 static notify_user: Dir<'_> = include_dir!("src/mailers/appointments/notify_user");
 static notify_client: Dir<'_> = include_dir!("src/mailers/appointments/notify_client");
 static cancel_client: Dir<'_> = include_dir!("src/mailers/appointments/cancel_client");
+static cancel_user: Dir<'_> = include_dir!("src/mailers/appointments/cancel_user");
+static reschedule_client: Dir<'_> = include_dir!("src/mailers/appointments/reschedule_client");
+static decline_client: Dir<'_> = include_dir!("src/mailers/appointments/decline_client");
+static remind_client: Dir<'_> = include_dir!("src/mailers/appointments/remind_client");
+static remind_user: Dir<'_> = include_dir!("src/mailers/appointments/remind_user");
+static review_user: Dir<'_> = include_dir!("src/mailers/appointments/review_user");
 
 #[allow(clippy::module_name_repetitions)]
 pub struct AppointmentsMailer {}
@@ -23,6 +39,52 @@ impl Mailer for AppointmentsMailer {
     }
 }
 impl AppointmentsMailer {
+    /// Calendar entry for the booker, cancelling it once the appointment is off. Clients
+    /// match it to earlier invites by UID, so they update the entry instead of adding one.
+    async fn booker_invite(
+        ctx: &AppContext,
+        appointment: &appointments::Model,
+        user: &users::Model,
+    ) -> Result<ics::Calendar> {
+        let appointment_type =
+            AppointmentTypes::find_by_id(&ctx.db, appointment.appointment_type_id).await?;
+        let summary = format!("{} with {}", appointment_type.display_name, user.name);
+        let event = ics::Event {
+            url: appointment.manage_url(ctx),
+            ..appointment.ics_event(ctx, user, summary, None)
+        };
+        let method = if event.status == ics::EventStatus::Cancelled {
+            ics::Method::Cancel
+        } else {
+            ics::Method::Request
+        };
+
+        Ok(ics::Calendar {
+            method,
+            name: None,
+            events: vec![event],
+        })
+    }
+
+    /// Like `mail_template`, with the invite attached as `invite.ics`.
+    async fn mail_template_with_invite(
+        ctx: &AppContext,
+        dir: &Dir<'_>,
+        args: mailer::Args,
+        invite: &ics::Calendar,
+    ) -> Result<()> {
+        let email = EmailWithAttachments::render(
+            dir,
+            args.from.unwrap_or_else(|| Self::opts().from),
+            args.to,
+            &args.locals,
+            vec![Attachment::calendar("invite.ics", invite)],
+        )?;
+        AttachmentMailerWorker::perform_later(ctx, email).await?;
+
+        Ok(())
+    }
+
     /// Send an email
     ///
     /// # Errors
@@ -44,6 +106,8 @@ impl AppointmentsMailer {
                     "user_name": user.name,
                     "booker_name": appointment.booker_name,
                     "start_time": start_time,
+                    "pending": appointment.status == Status::Pending,
+                    "answers": appointment.intake_answers.0,
                     "domain": ctx.config.server.full_url()
                 }),
                 ..Default::default()
@@ -65,8 +129,9 @@ impl AppointmentsMailer {
         let user = Users::find_by_id(&ctx.db, appointment.user_id).await?;
         let booker_timezone = Tz::from_str(&appointment.booker_timezone).map_err(Error::wrap)?;
         let start_time = appointment.start_time.with_timezone(&booker_timezone);
+        let invite = Self::booker_invite(ctx, appointment, &user).await?;
 
-        Self::mail_template(
+        Self::mail_template_with_invite(
             ctx,
             &notify_client,
             mailer::Args {
@@ -75,10 +140,13 @@ impl AppointmentsMailer {
                     "user_name": user.name,
                     "booker_name": appointment.booker_name,
                     "start_time": start_time,
+                    "pending": appointment.status == Status::Pending,
+                    "manage_url": appointment.manage_url(ctx),
                     "domain": ctx.config.server.full_url()
                 }),
                 ..Default::default()
             },
+            &invite,
         )
         .await?;
 
@@ -89,7 +157,74 @@ impl AppointmentsMailer {
     ///
     /// # Errors
     /// When email sending is failed
-    pub async fn send_cancellation_to_booker(
+    pub async fn send_reschedule_to_booker(
+        ctx: &AppContext,
+        appointment: &appointments::Model,
+    ) -> Result<()> {
+        let user = Users::find_by_id(&ctx.db, appointment.user_id).await?;
+        let booker_timezone = Tz::from_str(&appointment.booker_timezone).map_err(Error::wrap)?;
+        let start_time = appointment.start_time.with_timezone(&booker_timezone);
+        let invite = Self::booker_invite(ctx, appointment, &user).await?;
+
+        Self::mail_template_with_invite(
+            ctx,
+            &reschedule_client,
+            mailer::Args {
+                to: appointment.booker_email.clone(),
+                locals: json!({
+                    "user_name": user.name,
+                    "booker_name": appointment.booker_name,
+                    "start_time": start_time,
+                    "manage_url": appointment.manage_url(ctx),
+                    "domain": ctx.config.server.full_url()
+                }),
+                ..Default::default()
+            },
+            &invite,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Send an email
+    ///
+    /// # Errors
+    /// When email sending is failed
+    pub async fn send_decline_to_booker(
+        ctx: &AppContext,
+        appointment: &appointments::Model,
+    ) -> Result<()> {
+        let user = Users::find_by_id(&ctx.db, appointment.user_id).await?;
+        let booker_timezone = Tz::from_str(&appointment.booker_timezone).map_err(Error::wrap)?;
+        let start_time = appointment.start_time.with_timezone(&booker_timezone);
+        let invite = Self::booker_invite(ctx, appointment, &user).await?;
+
+        Self::mail_template_with_invite(
+            ctx,
+            &decline_client,
+            mailer::Args {
+                to: appointment.booker_email.clone(),
+                locals: json!({
+                    "user_name": user.name,
+                    "booker_name": appointment.booker_name,
+                    "start_time": start_time,
+                    "domain": ctx.config.server.full_url()
+                }),
+                ..Default::default()
+            },
+            &invite,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Send an email
+    ///
+    /// # Errors
+    /// When email sending is failed
+    pub async fn send_reminder_to_booker(
         ctx: &AppContext,
         appointment: &appointments::Model,
     ) -> Result<()> {
@@ -98,6 +233,71 @@ impl AppointmentsMailer {
         let start_time = appointment.start_time.with_timezone(&booker_timezone);
 
         Self::mail_template(
+            ctx,
+            &remind_client,
+            mailer::Args {
+                to: appointment.booker_email.clone(),
+                locals: json!({
+                    "user_name": user.name,
+                    "booker_name": appointment.booker_name,
+                    "start_time": start_time,
+                    "manage_url": appointment.manage_url(ctx),
+                    "domain": ctx.config.server.full_url()
+                }),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Send an email
+    ///
+    /// # Errors
+    /// When email sending is failed
+    pub async fn send_reminder_to_user(
+        ctx: &AppContext,
+        appointment: &appointments::Model,
+    ) -> Result<()> {
+        let user = Users::find_by_id(&ctx.db, appointment.user_id).await?;
+        let user_timezone = Tz::from_str(&user.timezone).map_err(Error::wrap)?;
+        let start_time = appointment.start_time.with_timezone(&user_timezone);
+
+        Self::mail_template(
+            ctx,
+            &remind_user,
+            mailer::Args {
+                to: user.email,
+                locals: json!({
+                    "user_name": user.name,
+                    "booker_name": appointment.booker_name,
+                    "start_time": start_time,
+                    "answers": appointment.intake_answers.0,
+                    "domain": ctx.config.server.full_url()
+                }),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Send an email
+    ///
+    /// # Errors
+    /// When email sending is failed
+    pub async fn send_cancellation_to_booker(
+        ctx: &AppContext,
+        appointment: &appointments::Model,
+    ) -> Result<()> {
+        let user = Users::find_by_id(&ctx.db, appointment.user_id).await?;
+        let booker_timezone = Tz::from_str(&appointment.booker_timezone).map_err(Error::wrap)?;
+        let start_time = appointment.start_time.with_timezone(&booker_timezone);
+        let invite = Self::booker_invite(ctx, appointment, &user).await?;
+
+        Self::mail_template_with_invite(
             ctx,
             &cancel_client,
             mailer::Args {
@@ -110,6 +310,72 @@ impl AppointmentsMailer {
                 }),
                 ..Default::default()
             },
+            &invite,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Send an email
+    ///
+    /// # Errors
+    /// When email sending is failed
+    pub async fn send_cancellation_to_user(
+        ctx: &AppContext,
+        appointment: &appointments::Model,
+    ) -> Result<()> {
+        let user = Users::find_by_id(&ctx.db, appointment.user_id).await?;
+        let user_timezone = Tz::from_str(&user.timezone).map_err(Error::wrap)?;
+        let start_time = appointment.start_time.with_timezone(&user_timezone);
+
+        Self::mail_template(
+            ctx,
+            &cancel_user,
+            mailer::Args {
+                to: user.email,
+                locals: json!({
+                    "user_name": user.name,
+                    "booker_name": appointment.booker_name,
+                    "start_time": start_time,
+                    "domain": ctx.config.server.full_url()
+                }),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Asks the owner to look at an appointment whose event was changed in their calendar.
+    ///
+    /// # Errors
+    /// When email sending is failed
+    pub async fn send_review_to_user(
+        ctx: &AppContext,
+        appointment: &appointments::Model,
+        moved_to: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<()> {
+        let user = Users::find_by_id(&ctx.db, appointment.user_id).await?;
+        let user_timezone = Tz::from_str(&user.timezone).map_err(Error::wrap)?;
+        let start_time = appointment.start_time.with_timezone(&user_timezone);
+
+        Self::mail_template(
+            ctx,
+            &review_user,
+            mailer::Args {
+                to: user.email,
+                locals: json!({
+                    "user_name": user.name,
+                    "booker_name": appointment.booker_name,
+                    "start_time": start_time,
+                    "moved": appointment.calendar_review == Some(CalendarReview::EventMoved),
+                    "moved_to": moved_to.map(|moved_to| moved_to.with_timezone(&user_timezone)),
+                    "domain": ctx.config.server.full_url()
+                }),
+                ..Default::default()
+            },
         )
         .await?;
 
@@ -0,0 +1 @@
+Hi {{user_name}}! {{booker_name}} cancelled their appointment at {{start_time}}.
@@ -0,0 +1 @@
+{{booker_name}} cancelled their appointment at {{start_time}}
@@ -0,0 +1 @@
+Appointment request declined by {{user_name}} for {{start_time}}.
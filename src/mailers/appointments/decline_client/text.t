@@ -0,0 +1 @@
+Hi {{booker_name}}! Your request for an appointment with {{user_name}} at {{start_time}} was declined. Please contact {{user_name}} for further assistance.
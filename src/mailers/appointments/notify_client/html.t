@@ -1 +1,8 @@
+{% if pending %}
+Hi {{booker_name}}! Your request for an appointment with {{user_name}} at {{start_time}} was received and is waiting for approval.
+{% else %}
 Hi {{booker_name}}! Appointment booked with {{user_name}} at {{start_time}}.
+{% endif %}
+{% if manage_url %}
+Need to cancel or reschedule? <a href="{{manage_url}}">Manage your appointment</a>.
+{% endif %}
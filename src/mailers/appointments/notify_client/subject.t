@@ -1 +1 @@
-Appointment booked with {{user_name}} at {{start_time}}.
+{% if pending %}Appointment requested with {{user_name}} at {{start_time}}.{% else %}Appointment booked with {{user_name}} at {{start_time}}.{% endif %}
@@ -1 +1,12 @@
+{% if pending %}
+Hi {{user_name}}! {{booker_name}} requested an appointment at {{start_time}}. It is waiting for your approval.
+{% else %}
 Hi {{user_name}}! {{booker_name}} booked an appointment at {{start_time}}
+{% endif %}
+{% if answers %}
+<ul>
+{% for answer in answers %}
+<li>{{answer.question | escape}}: {{answer.answer | escape}}</li>
+{% endfor %}
+</ul>
+{% endif %}
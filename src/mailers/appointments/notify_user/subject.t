@@ -1 +1 @@
-{{booker_name}} booked an appointment at {{start_time}}
+{% if pending %}{{booker_name}} requested an appointment at {{start_time}}{% else %}{{booker_name}} booked an appointment at {{start_time}}{% endif %}
@@ -1 +1,8 @@
+{% if pending %}
+Hi {{user_name}}! {{booker_name}} requested an appointment at {{start_time}}. It is waiting for your approval.
+{% else %}
 Hi {{user_name}}! {{booker_name}} booked an appointment at {{start_time}}
+{% endif %}
+{% for answer in answers %}
+{{answer.question}}: {{answer.answer}}
+{% endfor %}
@@ -0,0 +1,4 @@
+Hi {{booker_name}}! This is a reminder of your appointment with {{user_name}} at {{start_time}}.
+{% if manage_url %}
+Need to cancel or reschedule? <a href="{{manage_url}}">Manage your appointment</a>.
+{% endif %}
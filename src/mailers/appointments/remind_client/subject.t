@@ -0,0 +1 @@
+Reminder: appointment with {{user_name}} at {{start_time}}.
@@ -0,0 +1,8 @@
+Hi {{user_name}}! This is a reminder of your appointment with {{booker_name}} at {{start_time}}.
+{% if answers %}
+<ul>
+{% for answer in answers %}
+<li>{{answer.question | escape}}: {{answer.answer | escape}}</li>
+{% endfor %}
+</ul>
+{% endif %}
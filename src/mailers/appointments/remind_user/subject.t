@@ -0,0 +1 @@
+Reminder: appointment with {{booker_name}} at {{start_time}}
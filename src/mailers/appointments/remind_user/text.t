@@ -0,0 +1,4 @@
+Hi {{user_name}}! This is a reminder of your appointment with {{booker_name}} at {{start_time}}.
+{% for answer in answers %}
+{{answer.question}}: {{answer.answer}}
+{% endfor %}
@@ -0,0 +1,4 @@
+Hi {{booker_name}}! Your appointment with {{user_name}} has been moved to {{start_time}}.
+{% if manage_url %}
+Need to cancel or reschedule? <a href="{{manage_url}}">Manage your appointment</a>.
+{% endif %}
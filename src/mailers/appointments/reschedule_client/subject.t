@@ -0,0 +1 @@
+Appointment with {{user_name}} moved to {{start_time}}.
@@ -0,0 +1,2 @@
+Hi {{user_name}}! The calendar event of your appointment with {{booker_name}} at {{start_time}} was {% if moved_to %}moved to {{moved_to}}{% elif moved %}moved{% else %}deleted{% endif %} in Google Calendar. The appointment is still booked at {{start_time}}.
+<a href="{{domain}}/dashboard">Keep, reschedule or cancel it</a>.
@@ -0,0 +1 @@
+Review your appointment with {{booker_name}} at {{start_time}}
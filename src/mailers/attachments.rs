@@ -0,0 +1,128 @@
+//! loco's mailer only sends a text and an html part. Emails that need files attached are
+//! rendered from the same template dirs and sent through [`AttachmentMailerWorker`] instead.
+//!
+//! [`AttachmentMailerWorker`]: crate::workers::attachment_mailer::AttachmentMailerWorker
+
+use lettre::{
+    message::{header::ContentType, Attachment as AttachmentPart, Mailbox, MultiPart},
+    transport::smtp::{authentication::Credentials, extension::ClientId},
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use loco_rs::{config, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::common::ics;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attachment {
+    pub filename: String,
+    pub content_type: String,
+    pub content: String,
+}
+
+impl Attachment {
+    #[must_use]
+    pub fn calendar(filename: &str, calendar: &ics::Calendar) -> Self {
+        Self {
+            filename: filename.to_string(),
+            content_type: calendar.content_type(),
+            content: calendar.to_ics(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailWithAttachments {
+    pub from: String,
+    pub to: String,
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+    pub attachments: Vec<Attachment>,
+}
+
+fn render_template(dir: &Dir<'_>, name: &str, locals: &serde_json::Value) -> Result<String> {
+    let template = dir
+        .get_file(name)
+        .and_then(|file| file.contents_utf8())
+        .ok_or_else(|| Error::Message(format!("Mail template {name} is missing.")))?;
+    let context = tera::Context::from_serialize(locals).map_err(Error::wrap)?;
+
+    tera::Tera::one_off(template, &context, false).map_err(Error::wrap)
+}
+
+impl EmailWithAttachments {
+    /// Renders `subject.t`, `text.t` and `html.t` of the dir, the same files loco's
+    /// `mail_template` uses.
+    pub fn render(
+        dir: &Dir<'_>,
+        from: String,
+        to: String,
+        locals: &serde_json::Value,
+        attachments: Vec<Attachment>,
+    ) -> Result<Self> {
+        Ok(Self {
+            from,
+            to,
+            subject: render_template(dir, "subject.t", locals)?
+                .trim()
+                .to_string(),
+            text: render_template(dir, "text.t", locals)?,
+            html: render_template(dir, "html.t", locals)?,
+            attachments,
+        })
+    }
+
+    fn message(&self) -> Result<Message> {
+        let mut body = MultiPart::mixed().multipart(MultiPart::alternative_plain_html(
+            self.text.clone(),
+            self.html.clone(),
+        ));
+        for attachment in &self.attachments {
+            body = body.singlepart(AttachmentPart::new(attachment.filename.clone()).body(
+                attachment.content.clone(),
+                ContentType::parse(&attachment.content_type).map_err(Error::wrap)?,
+            ));
+        }
+
+        Message::builder()
+            .from(self.from.parse::<Mailbox>().map_err(Error::wrap)?)
+            .to(self.to.parse::<Mailbox>().map_err(Error::wrap)?)
+            .subject(self.subject.clone())
+            .multipart(body)
+            .map_err(Error::wrap)
+    }
+
+    /// Sends the email over the SMTP server of the mailer config. Nothing leaves the app with
+    /// the stub mailer, the message is only built.
+    pub async fn send(&self, mailer: Option<&config::Mailer>) -> Result<()> {
+        let message = self.message()?;
+        let Some(mailer) = mailer else {
+            return Err(Error::Message("The mailer is not configured.".to_string()));
+        };
+        if mailer.stub {
+            tracing::debug!("Stub mailer, not sending {:?} to {}", self.subject, self.to);
+            return Ok(());
+        }
+        let Some(smtp) = mailer.smtp.as_ref().filter(|smtp| smtp.enable) else {
+            return Err(Error::Message("SMTP is not enabled.".to_string()));
+        };
+
+        let mut transport = if smtp.secure {
+            AsyncSmtpTransport::<Tokio1Executor>::relay(&smtp.host).map_err(Error::wrap)?
+        } else {
+            AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&smtp.host)
+        }
+        .port(smtp.port);
+        if let Some(auth) = &smtp.auth {
+            transport =
+                transport.credentials(Credentials::new(auth.user.clone(), auth.password.clone()));
+        }
+        if let Some(hello_name) = &smtp.hello_name {
+            transport = transport.hello_name(ClientId::Domain(hello_name.clone()));
+        }
+
+        transport.build().send(message).await.map_err(Error::wrap)?;
+        Ok(())
+    }
+}
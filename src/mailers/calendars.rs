@@ -0,0 +1,47 @@
+#![allow(non_upper_case_globals)]
+
+use loco_rs::prelude::*;
+use serde_json::json;
+
+use crate::models::users;
+
+static disconnected: Dir<'_> = include_dir!("src/mailers/calendars/disconnected");
+
+#[allow(clippy::module_name_repetitions)]
+pub struct CalendarsMailer {}
+impl Mailer for CalendarsMailer {
+    fn opts() -> mailer::MailerOpts {
+        mailer::MailerOpts {
+            from: std::env::var("DEFAULT_EMAIL_SENDER").unwrap(),
+            ..Default::default()
+        }
+    }
+}
+impl CalendarsMailer {
+    /// Tells the owner a calendar connection stopped working and has to be connected again.
+    ///
+    /// # Errors
+    /// When email sending is failed
+    pub async fn send_disconnected_to_user(
+        ctx: &AppContext,
+        user: &users::Model,
+        provider: &str,
+    ) -> Result<()> {
+        Self::mail_template(
+            ctx,
+            &disconnected,
+            mailer::Args {
+                to: user.email.clone(),
+                locals: json!({
+                    "user_name": user.name,
+                    "provider": provider,
+                    "domain": ctx.config.server.full_url()
+                }),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        Ok(())
+    }
+}
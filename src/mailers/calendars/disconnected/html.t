@@ -0,0 +1,2 @@
+Hi {{user_name}}! Your {{provider}} stopped accepting our access, so bookings no longer check it for conflicts or add events to it.
+<a href="{{domain}}/dashboard/integrations">Connect it again</a>.
@@ -0,0 +1 @@
+Your {{provider}} is disconnected
@@ -0,0 +1 @@
+Hi {{user_name}}! Your {{provider}} stopped accepting our access, so bookings no longer check it for conflicts or add events to it. Connect it again at {{domain}}/dashboard/integrations.
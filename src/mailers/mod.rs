@@ -1,2 +1,4 @@
 pub mod appointments;
+pub mod attachments;
 pub mod auth;
+pub mod calendars;
@@ -10,6 +10,26 @@ pub struct GoogleCalendarSettings {
     pub google_oauth_secret: String,
     #[ts(as = "String")]
     pub google_oauth_redirect_uri_base: url::Url,
+    /// OAuth token endpoint, Google's own when empty.
+    #[serde(default)]
+    #[ts(as = "Option<String>")]
+    pub google_oauth_token_url: Option<url::Url>,
+    /// Calendar API root, Google's own when empty.
+    #[serde(default)]
+    #[ts(as = "Option<String>")]
+    pub google_calendar_api_url: Option<url::Url>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult, ts_rs::TS)]
+pub struct OutlookCalendarSettings {
+    pub outlook_oauth_client_id: String,
+    pub outlook_oauth_secret: String,
+    #[ts(as = "String")]
+    pub outlook_oauth_redirect_uri_base: url::Url,
+    /// Microsoft Graph root, the public `v1.0` endpoint when empty.
+    #[serde(default)]
+    #[ts(as = "Option<String>")]
+    pub graph_base_url: Option<url::Url>,
 }
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize, ts_rs :: TS)]
@@ -23,6 +43,8 @@ pub struct Model {
     pub allow_new_registrations: bool,
     #[sea_orm(column_type = "JsonBinary", nullable)]
     pub google_calendar_settings: Option<GoogleCalendarSettings>,
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub outlook_calendar_settings: Option<OutlookCalendarSettings>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
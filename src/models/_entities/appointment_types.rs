@@ -1,8 +1,33 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.16
 
-use sea_orm::entity::prelude::*;
+use sea_orm::{entity::prelude::*, FromJsonQueryResult};
 use serde::{Deserialize, Serialize};
 
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, ts_rs :: TS)]
+#[serde(rename_all = "snake_case")]
+pub enum IntakeQuestionKind {
+    Text,
+    LongText,
+    Select,
+    Checkbox,
+}
+
+/// Question a booker answers when booking, `key` identifies the answer.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, ts_rs :: TS)]
+pub struct IntakeQuestion {
+    pub key: String,
+    pub label: String,
+    pub kind: IntakeQuestionKind,
+    pub required: bool,
+    /// Choices of a `select` question.
+    pub options: Vec<String>,
+}
+
+#[derive(
+    Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult, ts_rs :: TS,
+)]
+pub struct IntakeQuestions(pub Vec<IntakeQuestion>);
+
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize, ts_rs :: TS)]
 #[sea_orm(table_name = "appointment_types")]
 #[ts(export, rename = "AppointmentType")]
@@ -16,12 +41,28 @@ pub struct Model {
     pub name: String,
     pub display_name: String,
     pub user_id: i32,
+    pub buffer_before_minutes: i32,
+    pub buffer_after_minutes: i32,
+    pub slot_interval_minutes: Option<i32>,
+    pub schedule_id: Option<i32>,
+    pub max_attendees: i32,
+    #[sea_orm(column_type = "JsonBinary")]
+    pub intake_questions: IntakeQuestions,
+    pub requires_approval: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
     #[sea_orm(has_many = "super::appointments::Entity")]
     Appointments,
+    #[sea_orm(
+        belongs_to = "super::schedules::Entity",
+        from = "Column::ScheduleId",
+        to = "super::schedules::Column::Id",
+        on_update = "NoAction",
+        on_delete = "NoAction"
+    )]
+    Schedules,
     #[sea_orm(
         belongs_to = "super::users::Entity",
         from = "Column::UserId",
@@ -38,6 +79,12 @@ impl Related<super::appointments::Entity> for Entity {
     }
 }
 
+impl Related<super::schedules::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Schedules.def()
+    }
+}
+
 impl Related<super::users::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Users.def()
@@ -21,7 +21,14 @@ pub struct Model {
     pub user_id: i32,
     pub appointment_type_id: i32,
     #[sea_orm(column_type = "JsonBinary")]
-    pub google_calendar_events: Vec<GoogleCalendarEvent>,
+    pub calendar_events: Vec<CalendarEvent>,
+    #[sea_orm(unique)]
+    pub manage_token: Option<String>,
+    #[sea_orm(column_type = "JsonBinary")]
+    pub intake_answers: IntakeAnswers,
+    pub pending_expires_at: Option<DateTimeWithTimeZone>,
+    /// Set when the event was changed outside the app in a way the owner has to look at.
+    pub calendar_review: Option<CalendarReview>,
 }
 
 #[derive(
@@ -34,16 +41,63 @@ pub enum Status {
     Booked,
     #[sea_orm(string_value = "Cancelled")]
     Cancelled,
+    #[sea_orm(string_value = "Pending")]
+    Pending,
+    #[sea_orm(string_value = "Declined")]
+    Declined,
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ts_rs::TS,
+)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::None)")]
+#[ts(repr(enum = name))]
+pub enum CalendarReview {
+    #[sea_orm(string_value = "EventDeleted")]
+    EventDeleted,
+    #[sea_orm(string_value = "EventMoved")]
+    EventMoved,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, ts_rs::TS)]
+#[ts(repr(enum = name))]
+pub enum CalendarProviderKind {
+    #[default]
+    Google,
+    Caldav,
+    Outlook,
+    InMemory,
 }
 
+/// Event created for the appointment in one of the owner's calendars.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult, ts_rs::TS)]
-pub struct GoogleCalendarEvent {
+pub struct CalendarEvent {
+    /// Missing on events stored before there was more than one provider, those are Google's.
+    #[serde(default)]
+    pub provider: CalendarProviderKind,
     pub calendar_id: String,
     pub event_id: String,
+    /// Connection the event was created through, for providers a user can connect more than
+    /// once. Missing on events stored before that.
+    #[serde(default)]
+    pub connection_id: Option<i32>,
 }
 
+/// Answer to an intake question, keeps the question as it was asked.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, ts_rs::TS)]
+pub struct IntakeAnswer {
+    pub key: String,
+    pub question: String,
+    pub answer: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult, ts_rs::TS)]
+pub struct IntakeAnswers(pub Vec<IntakeAnswer>);
+
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::appointment_reminders::Entity")]
+    AppointmentReminders,
     #[sea_orm(
         belongs_to = "super::appointment_types::Entity",
         from = "Column::AppointmentTypeId",
@@ -52,6 +106,8 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     AppointmentTypes,
+    #[sea_orm(has_many = "super::outbox_jobs::Entity")]
+    OutboxJobs,
     #[sea_orm(
         belongs_to = "super::users::Entity",
         from = "Column::UserId",
@@ -62,12 +118,24 @@ pub enum Relation {
     Users,
 }
 
+impl Related<super::appointment_reminders::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AppointmentReminders.def()
+    }
+}
+
 impl Related<super::appointment_types::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::AppointmentTypes.def()
     }
 }
 
+impl Related<super::outbox_jobs::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::OutboxJobs.def()
+    }
+}
+
 impl Related<super::users::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Users.def()
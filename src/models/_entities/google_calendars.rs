@@ -36,10 +36,16 @@ pub struct Model {
     pub calendars_for_collision_check: StringHash,
     #[sea_orm(column_type = "JsonBinary")]
     pub calendars_for_event_handling: StringHash,
+    pub expires_at: Option<DateTimeWithTimeZone>,
+    pub broken_at: Option<DateTimeWithTimeZone>,
+    pub broken_notified_at: Option<DateTimeWithTimeZone>,
+    pub account_email: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::google_calendar_channels::Entity")]
+    GoogleCalendarChannels,
     #[sea_orm(
         belongs_to = "super::users::Entity",
         from = "Column::UserId",
@@ -50,6 +56,12 @@ pub enum Relation {
     Users,
 }
 
+impl Related<super::google_calendar_channels::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::GoogleCalendarChannels.def()
+    }
+}
+
 impl Related<super::users::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Users.def()
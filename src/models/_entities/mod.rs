@@ -3,10 +3,20 @@
 pub mod prelude;
 
 pub mod admin_settings;
+pub mod appointment_reminders;
 pub mod appointment_types;
 pub mod appointments;
+pub mod availability_overrides;
+pub mod busy_intervals;
+pub mod caldav_connections;
+pub mod google_calendar_channels;
 pub mod google_calendars;
 pub mod oauth_states;
+pub mod outbox_jobs;
+pub mod outlook_calendars;
+pub mod schedules;
 pub mod user_settings;
 pub mod users;
+pub mod webhook_deliveries;
+pub mod webhook_endpoints;
 pub mod weekly_availabilities;
@@ -1,10 +1,20 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.16
 
 pub use super::admin_settings::Entity as AdminSettings;
+pub use super::appointment_reminders::Entity as AppointmentReminders;
 pub use super::appointment_types::Entity as AppointmentTypes;
 pub use super::appointments::Entity as Appointments;
+pub use super::availability_overrides::Entity as AvailabilityOverrides;
+pub use super::busy_intervals::Entity as BusyIntervals;
+pub use super::caldav_connections::Entity as CaldavConnections;
+pub use super::google_calendar_channels::Entity as GoogleCalendarChannels;
 pub use super::google_calendars::Entity as GoogleCalendars;
 pub use super::oauth_states::Entity as OauthStates;
+pub use super::outbox_jobs::Entity as OutboxJobs;
+pub use super::outlook_calendars::Entity as OutlookCalendars;
+pub use super::schedules::Entity as Schedules;
 pub use super::user_settings::Entity as UserSettings;
 pub use super::users::Entity as Users;
+pub use super::webhook_deliveries::Entity as WebhookDeliveries;
+pub use super::webhook_endpoints::Entity as WebhookEndpoints;
 pub use super::weekly_availabilities::Entity as WeeklyAvailabilities;
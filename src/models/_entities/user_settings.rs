@@ -1,6 +1,6 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.16
 
-use sea_orm::entity::prelude::*;
+use sea_orm::{entity::prelude::*, FromJsonQueryResult};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize, ts_rs :: TS)]
@@ -15,8 +15,17 @@ pub struct Model {
     pub user_id: i32,
     pub start_how_far_from_now_in_minutes: i32,
     pub end_how_far_from_now_in_minutes: i32,
+    pub pending_expires_after_in_minutes: i32,
+    #[sea_orm(column_type = "JsonBinary")]
+    pub reminder_offsets_in_minutes: ReminderOffsets,
+    #[sea_orm(unique)]
+    pub calendar_feed_token: Option<String>,
 }
 
+/// How long before `start_time` reminders go out, in minutes.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult, ts_rs :: TS)]
+pub struct ReminderOffsets(pub Vec<i32>);
+
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
     #[sea_orm(
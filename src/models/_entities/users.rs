@@ -45,12 +45,24 @@ pub enum Relation {
     AppointmentTypes,
     #[sea_orm(has_many = "super::appointments::Entity")]
     Appointments,
+    #[sea_orm(has_many = "super::availability_overrides::Entity")]
+    AvailabilityOverrides,
+    #[sea_orm(has_one = "super::busy_intervals::Entity")]
+    BusyIntervals,
+    #[sea_orm(has_one = "super::caldav_connections::Entity")]
+    CaldavConnections,
     #[sea_orm(has_many = "super::google_calendars::Entity")]
     GoogleCalendars,
     #[sea_orm(has_many = "super::oauth_states::Entity")]
     OauthStates,
+    #[sea_orm(has_many = "super::outlook_calendars::Entity")]
+    OutlookCalendars,
+    #[sea_orm(has_many = "super::schedules::Entity")]
+    Schedules,
     #[sea_orm(has_one = "super::user_settings::Entity")]
     UserSettings,
+    #[sea_orm(has_many = "super::webhook_endpoints::Entity")]
+    WebhookEndpoints,
     #[sea_orm(has_many = "super::weekly_availabilities::Entity")]
     WeeklyAvailabilities,
 }
@@ -67,6 +79,24 @@ impl Related<super::appointments::Entity> for Entity {
     }
 }
 
+impl Related<super::availability_overrides::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AvailabilityOverrides.def()
+    }
+}
+
+impl Related<super::busy_intervals::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::BusyIntervals.def()
+    }
+}
+
+impl Related<super::caldav_connections::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::CaldavConnections.def()
+    }
+}
+
 impl Related<super::google_calendars::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::GoogleCalendars.def()
@@ -79,12 +109,30 @@ impl Related<super::oauth_states::Entity> for Entity {
     }
 }
 
+impl Related<super::outlook_calendars::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::OutlookCalendars.def()
+    }
+}
+
+impl Related<super::schedules::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Schedules.def()
+    }
+}
+
 impl Related<super::user_settings::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::UserSettings.def()
     }
 }
 
+impl Related<super::webhook_endpoints::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::WebhookEndpoints.def()
+    }
+}
+
 impl Related<super::weekly_availabilities::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::WeeklyAvailabilities.def()
@@ -14,10 +14,19 @@ pub struct Model {
     pub from: i32,
     pub to: i32,
     pub user_id: i32,
+    pub schedule_id: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::schedules::Entity",
+        from = "Column::ScheduleId",
+        to = "super::schedules::Column::Id",
+        on_update = "NoAction",
+        on_delete = "NoAction"
+    )]
+    Schedules,
     #[sea_orm(
         belongs_to = "super::users::Entity",
         from = "Column::UserId",
@@ -28,6 +37,12 @@ pub enum Relation {
     Users,
 }
 
+impl Related<super::schedules::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Schedules.def()
+    }
+}
+
 impl Related<super::users::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Users.def()
@@ -1,18 +1,63 @@
-use crate::models::_entities::admin_settings::GoogleCalendarSettings;
+use crate::{
+    common::encryption,
+    models::_entities::admin_settings::{GoogleCalendarSettings, OutlookCalendarSettings},
+};
 
 pub use super::_entities::admin_settings::{ActiveModel, Entity, Model};
 use loco_rs::prelude::*;
 use sea_orm::entity::prelude::*;
 pub type AdminSettings = Entity;
 
+impl GoogleCalendarSettings {
+    fn map_secrets(self, map: impl Fn(&str) -> Result<String>) -> Result<Self> {
+        Ok(Self {
+            google_calendar_api_key: map(&self.google_calendar_api_key)?,
+            google_oauth_secret: map(&self.google_oauth_secret)?,
+            ..self
+        })
+    }
+
+    fn secrets_are_current(&self) -> bool {
+        encryption::is_current(&self.google_calendar_api_key)
+            && encryption::is_current(&self.google_oauth_secret)
+    }
+}
+
+impl OutlookCalendarSettings {
+    fn map_secrets(self, map: impl Fn(&str) -> Result<String>) -> Result<Self> {
+        Ok(Self {
+            outlook_oauth_secret: map(&self.outlook_oauth_secret)?,
+            ..self
+        })
+    }
+
+    fn secrets_are_current(&self) -> bool {
+        encryption::is_current(&self.outlook_oauth_secret)
+    }
+}
+
 #[async_trait::async_trait]
 impl ActiveModelBehavior for ActiveModel {
     async fn before_save<C>(self, _db: &C, insert: bool) -> std::result::Result<Self, DbErr>
     where
         C: ConnectionTrait,
     {
-        if !insert && self.updated_at.is_unchanged() {
-            let mut this = self;
+        let mut this = self;
+        let to_db_err = |err: Error| DbErr::Custom(err.to_string());
+        if let ActiveValue::Set(Some(settings)) = &mut this.google_calendar_settings {
+            *settings = settings
+                .clone()
+                .map_secrets(encryption::reencrypt)
+                .map_err(to_db_err)?;
+        }
+        if let ActiveValue::Set(Some(settings)) = &mut this.outlook_calendar_settings {
+            *settings = settings
+                .clone()
+                .map_secrets(encryption::reencrypt)
+                .map_err(to_db_err)?;
+        }
+
+        if !insert && this.updated_at.is_unchanged() {
             this.updated_at = sea_orm::ActiveValue::Set(chrono::Utc::now().into());
             Ok(this)
         } else {
@@ -20,13 +65,28 @@ impl ActiveModelBehavior for ActiveModel {
             if count > 0 {
                 return Err(DbErr::Custom("Only one admin setting allowed".to_string()));
             }
-            Ok(self)
+            Ok(this)
         }
     }
 }
 
 // implement your read-oriented logic here
-impl Model {}
+impl Model {
+    /// The settings with their secrets readable, they are stored encrypted.
+    pub fn decrypt_secrets(self) -> Result<Self> {
+        Ok(Self {
+            google_calendar_settings: self
+                .google_calendar_settings
+                .map(|settings| settings.map_secrets(encryption::decrypt))
+                .transpose()?,
+            outlook_calendar_settings: self
+                .outlook_calendar_settings
+                .map(|settings| settings.map_secrets(encryption::decrypt))
+                .transpose()?,
+            ..self
+        })
+    }
+}
 
 // implement your write-oriented logic here
 impl ActiveModel {
@@ -49,11 +109,49 @@ impl Entity {
     pub async fn get_google_calendar_settings(
         db: &impl ConnectionTrait,
     ) -> Result<GoogleCalendarSettings> {
-        let admin_settings = Self::load(db).await?;
+        let admin_settings = Self::load(db).await?.decrypt_secrets()?;
         admin_settings
             .google_calendar_settings
             .ok_or(Error::Message(
                 "Missing Google Calendar API settings.".to_string(),
             ))
     }
+
+    pub async fn get_outlook_calendar_settings(
+        db: &impl ConnectionTrait,
+    ) -> Result<OutlookCalendarSettings> {
+        let admin_settings = Self::load(db).await?.decrypt_secrets()?;
+        admin_settings
+            .outlook_calendar_settings
+            .ok_or(Error::Message(
+                "Missing Outlook Calendar API settings.".to_string(),
+            ))
+    }
+
+    /// Moves the admin's API secrets to the newest encryption key, plaintext ones included.
+    /// Returns how many rows changed.
+    pub async fn reencrypt_secrets<C: ConnectionTrait>(db: &C) -> Result<usize> {
+        let mut reencrypted = 0;
+        for admin_settings in Self::find().all(db).await? {
+            if admin_settings
+                .google_calendar_settings
+                .as_ref()
+                .is_none_or(GoogleCalendarSettings::secrets_are_current)
+                && admin_settings
+                    .outlook_calendar_settings
+                    .as_ref()
+                    .is_none_or(OutlookCalendarSettings::secrets_are_current)
+            {
+                continue;
+            }
+
+            let mut active_model = admin_settings.into_active_model();
+            active_model.google_calendar_settings.reset();
+            active_model.outlook_calendar_settings.reset();
+            active_model.update(db).await?;
+            reencrypted += 1;
+        }
+
+        Ok(reencrypted)
+    }
 }
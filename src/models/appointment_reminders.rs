@@ -0,0 +1,104 @@
+pub use super::_entities::appointment_reminders::{ActiveModel, Entity, Model, ReminderRecipient};
+use crate::models::{_entities::appointment_reminders::Column, appointments};
+use loco_rs::prelude::*;
+use sea_orm::{entity::prelude::*, sea_query::OnConflict};
+pub type AppointmentReminders = Entity;
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    async fn before_save<C>(self, _db: &C, insert: bool) -> std::result::Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        if !insert && self.updated_at.is_unchanged() {
+            let mut this = self;
+            this.updated_at = sea_orm::ActiveValue::Set(chrono::Utc::now().into());
+            Ok(this)
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+// implement your write-oriented logic here
+impl ActiveModel {
+    /// Records the reminder as sent, returning `false` when somebody already did. Claim before
+    /// sending, so a restart or a second worker can never send the same reminder twice.
+    pub async fn claim<C>(
+        db: &C,
+        appointment: &appointments::Model,
+        offset_in_minutes: i32,
+        recipient: ReminderRecipient,
+    ) -> ModelResult<bool>
+    where
+        C: ConnectionTrait,
+    {
+        let reminder = Self {
+            appointment_id: ActiveValue::set(appointment.id),
+            offset_in_minutes: ActiveValue::set(offset_in_minutes),
+            recipient: ActiveValue::set(recipient),
+            ..Default::default()
+        };
+        let inserted = Entity::insert(reminder)
+            .on_conflict(
+                OnConflict::columns([
+                    Column::AppointmentId,
+                    Column::OffsetInMinutes,
+                    Column::Recipient,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await?;
+
+        Ok(inserted > 0)
+    }
+
+    /// Gives a claim back when sending failed, so the next run tries again.
+    pub async fn release<C>(
+        db: &C,
+        appointment: &appointments::Model,
+        offset_in_minutes: i32,
+        recipient: ReminderRecipient,
+    ) -> ModelResult<()>
+    where
+        C: ConnectionTrait,
+    {
+        Entity::delete_many()
+            .filter(Column::AppointmentId.eq(appointment.id))
+            .filter(Column::OffsetInMinutes.eq(offset_in_minutes))
+            .filter(Column::Recipient.eq(recipient))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+}
+
+// implement your custom finders, selectors oriented logic here
+impl Entity {
+    pub async fn find_by_appointment<C>(
+        db: &C,
+        appointment: &appointments::Model,
+    ) -> ModelResult<Vec<Model>>
+    where
+        C: ConnectionTrait,
+    {
+        Ok(Self::find()
+            .filter(Column::AppointmentId.eq(appointment.id))
+            .all(db)
+            .await?)
+    }
+
+    /// Forgets the reminders sent for the old time of a rescheduled appointment.
+    pub async fn delete_by_appointment_id<C>(db: &C, appointment_id: i32) -> ModelResult<()>
+    where
+        C: ConnectionTrait,
+    {
+        Self::delete_many()
+            .filter(Column::AppointmentId.eq(appointment_id))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+}
@@ -1,18 +1,129 @@
-use std::sync::LazyLock;
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::LazyLock,
+};
 
-pub use super::_entities::appointment_types::{ActiveModel, Entity, Model};
-use crate::models::{_entities::appointment_types::Column, users::users};
-use loco_rs::prelude::*;
+use axum::http::StatusCode;
+use chrono::Duration;
+
+pub use super::_entities::appointment_types::{
+    ActiveModel, Entity, IntakeQuestion, IntakeQuestionKind, IntakeQuestions, Model,
+};
+use crate::{
+    models::{
+        _entities::{
+            appointment_types::Column,
+            appointments::{IntakeAnswer, IntakeAnswers},
+        },
+        schedules::{self, Schedules},
+        users::users,
+    },
+    views::client_facing::IntakeAnswerParam,
+};
+use loco_rs::{controller::ErrorDetail, prelude::*};
 use regex::Regex;
 use sea_orm::entity::prelude::*;
 use serde::Deserialize;
 use stringcase::kebab_case;
-use validator::Validate;
+use validator::{Validate, ValidationError};
 pub type AppointmentTypes = Entity;
 
+const MAX_INTAKE_QUESTIONS: usize = 30;
+const MAX_TEXT_ANSWER_LENGTH: usize = 200;
+const MAX_LONG_TEXT_ANSWER_LENGTH: usize = 5000;
+
 static REGEX_KEBAB_CASE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new("^([a-z0-9]*)(-[a-z0-9]+)*$").unwrap());
 
+fn validate_intake_questions(questions: &[IntakeQuestion]) -> Result<(), ValidationError> {
+    if questions.len() > MAX_INTAKE_QUESTIONS {
+        return Err(ValidationError::new("too_many_questions")
+            .with_message(format!("At most {MAX_INTAKE_QUESTIONS} questions.").into()));
+    }
+
+    let mut keys = HashSet::new();
+    for question in questions {
+        if question.key.trim().is_empty() || question.label.trim().is_empty() {
+            return Err(ValidationError::new("blank_question")
+                .with_message("Questions need a key and a label.".into()));
+        }
+        if !keys.insert(question.key.as_str()) {
+            return Err(ValidationError::new("duplicate_question")
+                .with_message(format!("Question key \"{}\" is repeated.", question.key).into()));
+        }
+        let is_select = question.kind == IntakeQuestionKind::Select;
+        if is_select == question.options.is_empty() {
+            return Err(ValidationError::new("invalid_options").with_message(
+                "Select questions need options, other questions can not have any.".into(),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn invalid_answer(message: &str) -> Error {
+    Error::CustomError(
+        StatusCode::BAD_REQUEST,
+        ErrorDetail::new("invalid_answer", message),
+    )
+}
+
+/// Normalizes a single answer, `None` when an optional question was left blank.
+fn answer_for(
+    question: &IntakeQuestion,
+    answer: Option<&IntakeAnswerParam>,
+) -> Result<Option<String>> {
+    let text = match (&question.kind, answer) {
+        (IntakeQuestionKind::Checkbox, None | Some(IntakeAnswerParam::Checked(false))) => {
+            if question.required {
+                return Err(invalid_answer(&format!(
+                    "\"{}\" is required.",
+                    question.label
+                )));
+            }
+            return Ok(Some("No".to_string()));
+        }
+        (IntakeQuestionKind::Checkbox, Some(IntakeAnswerParam::Checked(true))) => {
+            return Ok(Some("Yes".to_string()));
+        }
+        (IntakeQuestionKind::Checkbox, Some(IntakeAnswerParam::Text(_)))
+        | (_, Some(IntakeAnswerParam::Checked(_))) => {
+            return Err(invalid_answer(&format!(
+                "Invalid answer to \"{}\".",
+                question.label
+            )));
+        }
+        (_, None) => "",
+        (_, Some(IntakeAnswerParam::Text(text))) => text.trim(),
+    };
+
+    if text.is_empty() {
+        if question.required {
+            return Err(invalid_answer(&format!(
+                "\"{}\" is required.",
+                question.label
+            )));
+        }
+        return Ok(None);
+    }
+
+    let valid = match question.kind {
+        IntakeQuestionKind::Text => text.chars().count() <= MAX_TEXT_ANSWER_LENGTH,
+        IntakeQuestionKind::LongText => text.chars().count() <= MAX_LONG_TEXT_ANSWER_LENGTH,
+        IntakeQuestionKind::Select => question.options.iter().any(|option| option == text),
+        IntakeQuestionKind::Checkbox => false,
+    };
+    if !valid {
+        return Err(invalid_answer(&format!(
+            "Invalid answer to \"{}\".",
+            question.label
+        )));
+    }
+
+    Ok(Some(text.to_string()))
+}
+
 #[derive(Debug, Validate, Deserialize)]
 pub struct Validator {
     #[validate(range(min = 1, max = 1440))]
@@ -21,6 +132,16 @@ pub struct Validator {
     pub name: String,
     #[validate(length(min = 1, max = 100))]
     pub display_name: String,
+    #[validate(range(min = 0, max = 1440))]
+    pub buffer_before_minutes: i32,
+    #[validate(range(min = 0, max = 1440))]
+    pub buffer_after_minutes: i32,
+    #[validate(range(min = 1, max = 1440))]
+    pub slot_interval_minutes: Option<i32>,
+    #[validate(range(min = 1, max = 1000))]
+    pub max_attendees: i32,
+    #[validate(custom(function = "validate_intake_questions"))]
+    pub intake_questions: Vec<IntakeQuestion>,
 }
 
 impl Validatable for ActiveModel {
@@ -29,6 +150,11 @@ impl Validatable for ActiveModel {
             duration_in_minutes: self.duration_in_minutes.as_ref().to_owned(),
             name: self.name.as_ref().to_owned(),
             display_name: self.display_name.as_ref().to_owned(),
+            buffer_before_minutes: self.buffer_before_minutes.as_ref().to_owned(),
+            buffer_after_minutes: self.buffer_after_minutes.as_ref().to_owned(),
+            slot_interval_minutes: self.slot_interval_minutes.as_ref().to_owned(),
+            max_attendees: self.max_attendees.as_ref().to_owned(),
+            intake_questions: self.intake_questions.as_ref().0.clone(),
         })
     }
 }
@@ -51,12 +177,82 @@ impl ActiveModelBehavior for ActiveModel {
 }
 
 // implement your read-oriented logic here
-impl Model {}
+impl Model {
+    #[must_use]
+    pub fn buffer_before(&self) -> Duration {
+        Duration::minutes(i64::from(self.buffer_before_minutes))
+    }
+
+    #[must_use]
+    pub fn buffer_after(&self) -> Duration {
+        Duration::minutes(i64::from(self.buffer_after_minutes))
+    }
+
+    /// How often a slot may start, falls back to the appointment duration.
+    #[must_use]
+    pub fn slot_interval(&self) -> Duration {
+        Duration::minutes(i64::from(
+            self.slot_interval_minutes
+                .unwrap_or(self.duration_in_minutes),
+        ))
+    }
+
+    /// Group appointment types let several bookers share one slot.
+    #[must_use]
+    pub const fn is_group(&self) -> bool {
+        self.max_attendees > 1
+    }
+
+    /// Checks the booker's answers against the intake questions, returning them in question
+    /// order ready to be stored. Unanswered optional questions are left out.
+    pub fn answer_intake_questions(
+        &self,
+        answers: &BTreeMap<String, IntakeAnswerParam>,
+    ) -> Result<IntakeAnswers> {
+        let questions = &self.intake_questions.0;
+        if let Some(key) = answers
+            .keys()
+            .find(|key| !questions.iter().any(|question| &question.key == *key))
+        {
+            return Err(invalid_answer(&format!("Unknown question \"{key}\".")));
+        }
+
+        let mut stored = Vec::new();
+        for question in questions {
+            if let Some(answer) = answer_for(question, answers.get(&question.key))? {
+                stored.push(IntakeAnswer {
+                    key: question.key.clone(),
+                    question: question.label.clone(),
+                    answer,
+                });
+            }
+        }
+
+        Ok(IntakeAnswers(stored))
+    }
+
+    /// Schedule whose weekly windows apply to this appointment type.
+    pub async fn schedule<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        user: &users::Model,
+    ) -> ModelResult<schedules::Model> {
+        Schedules::find_for_user(db, user, self.schedule_id).await
+    }
+}
 
 #[derive(Debug)]
 pub struct CreateOrUpdateAppointmentType<'a> {
     pub duration_in_minutes: i32,
     pub display_name: String,
+    pub buffer_before_minutes: i32,
+    pub buffer_after_minutes: i32,
+    pub slot_interval_minutes: Option<i32>,
+    /// `None` uses the owner's default schedule.
+    pub schedule_id: Option<i32>,
+    pub max_attendees: i32,
+    pub intake_questions: IntakeQuestions,
+    pub requires_approval: bool,
     pub user: &'a users::Model,
 }
 
@@ -70,6 +266,13 @@ impl ActiveModel {
             duration_in_minutes: sea_orm::ActiveValue::Set(params.duration_in_minutes),
             name: sea_orm::ActiveValue::Set(kebab_case(&params.display_name)),
             display_name: sea_orm::ActiveValue::Set(params.display_name),
+            buffer_before_minutes: sea_orm::ActiveValue::Set(params.buffer_before_minutes),
+            buffer_after_minutes: sea_orm::ActiveValue::Set(params.buffer_after_minutes),
+            slot_interval_minutes: sea_orm::ActiveValue::Set(params.slot_interval_minutes),
+            schedule_id: sea_orm::ActiveValue::Set(params.schedule_id),
+            max_attendees: sea_orm::ActiveValue::Set(params.max_attendees),
+            intake_questions: sea_orm::ActiveValue::Set(params.intake_questions),
+            requires_approval: sea_orm::ActiveValue::Set(params.requires_approval),
             user_id: sea_orm::ActiveValue::Set(params.user.id),
             ..Default::default()
         };
@@ -87,6 +290,13 @@ impl ActiveModel {
         self.duration_in_minutes = sea_orm::ActiveValue::Set(params.duration_in_minutes);
         self.name = sea_orm::ActiveValue::Set(kebab_case(&params.display_name));
         self.display_name = sea_orm::ActiveValue::Set(params.display_name);
+        self.buffer_before_minutes = sea_orm::ActiveValue::Set(params.buffer_before_minutes);
+        self.buffer_after_minutes = sea_orm::ActiveValue::Set(params.buffer_after_minutes);
+        self.slot_interval_minutes = sea_orm::ActiveValue::Set(params.slot_interval_minutes);
+        self.schedule_id = sea_orm::ActiveValue::Set(params.schedule_id);
+        self.max_attendees = sea_orm::ActiveValue::Set(params.max_attendees);
+        self.intake_questions = sea_orm::ActiveValue::Set(params.intake_questions);
+        self.requires_approval = sea_orm::ActiveValue::Set(params.requires_approval);
 
         Ok(self.update(db).await?)
     }
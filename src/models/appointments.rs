@@ -2,25 +2,61 @@ pub use super::_entities::appointments::{ActiveModel, Entity, Model};
 
 use super::{_entities::appointments::Column, appointment_types, users};
 use crate::{
+    calendar_providers::{
+        self,
+        google::{EventDrift, GoogleCalendarProvider},
+        CalendarProvider, CalendarProviders, NewEvent,
+    },
+    common::ics,
     mailers::appointments::AppointmentsMailer,
     models::{
-        _entities::appointments::{GoogleCalendarEvent, Status},
+        _entities::appointments::{
+            CalendarEvent, CalendarProviderKind, CalendarReview, IntakeAnswers, Status,
+        },
+        appointment_reminders::AppointmentReminders,
         appointment_types::AppointmentTypes,
-        google_calendars,
+        outbox_jobs::{self, OutboxJobKind},
         users::CurrentAvailabilityProps,
+        webhook_endpoints::{WebhookEndpoints, WebhookEvent},
     },
     our_chrono,
     traits::GenericWindowComparison,
     views::appointments::AppointmentsQueryParams,
 };
+use axum::http::StatusCode;
 use chrono::{TimeZone, Utc};
 use chrono_tz::Tz;
-use loco_rs::prelude::*;
+use loco_rs::{controller::ErrorDetail, hash, prelude::*};
 use now::DateTimeNow;
-use sea_orm::{entity::prelude::*, QueryOrder, QuerySelect};
+use sea_orm::{entity::prelude::*, Condition, QueryOrder, QuerySelect, Select};
+use std::collections::HashMap;
 
 pub type Appointments = Entity;
 
+pub const MANAGE_TOKEN_LENGTH: usize = 48;
+/// How far back the calendar feed goes.
+const FEED_HISTORY_IN_DAYS: i64 = 90;
+/// How long called off appointments stay in the calendar feed as cancelled.
+const FEED_CANCELLED_RETENTION_IN_DAYS: i64 = 30;
+
+/// Error returned when the requested window was taken in the meantime.
+#[must_use]
+pub fn slot_no_longer_available() -> Error {
+    Error::CustomError(
+        StatusCode::CONFLICT,
+        ErrorDetail::new("slot_unavailable", "Slot no longer available."),
+    )
+}
+
+/// Error returned when the requested window was never on offer, taken or not.
+#[must_use]
+pub fn slot_not_offered() -> Error {
+    Error::CustomError(
+        StatusCode::BAD_REQUEST,
+        ErrorDetail::new("slot_not_offered", "Slot is not offered."),
+    )
+}
+
 #[async_trait::async_trait]
 impl ActiveModelBehavior for ActiveModel {
     async fn before_save<C>(self, _db: &C, insert: bool) -> std::result::Result<Self, DbErr>
@@ -37,6 +73,11 @@ impl ActiveModelBehavior for ActiveModel {
     }
 }
 
+impl Status {
+    /// Statuses that keep the slot taken, a pending appointment holds it until answered.
+    pub const HOLDING_SLOT: [Self; 2] = [Self::Booked, Self::Pending];
+}
+
 impl GenericWindowComparison<chrono::DateTime<Utc>> for Model {
     fn start_time(&self) -> chrono::DateTime<Utc> {
         self.start_time.to_utc()
@@ -54,6 +95,7 @@ impl Model {
         user: &users::Model,
         from: &chrono::DateTime<Utc>,
         to: &chrono::DateTime<Utc>,
+        exclude_appointment: Option<&Self>,
     ) -> Result<()> {
         let time_delta = *to - *from;
 
@@ -64,19 +106,33 @@ impl Model {
 
         // Validate weekly_availabilities has availability for this window
         let now = our_chrono::utc_now();
-        let window = user
-            .get_current_availabilities_by_appointment_type(
-                db,
-                CurrentAvailabilityProps {
-                    appointment_type,
-                    start_how_far_from_now: *from - now,
-                    end_how_far_from_now: *to - now,
-                },
-            )
+        let props = |ignore_bookings| CurrentAvailabilityProps {
+            appointment_type,
+            start_how_far_from_now: *from - now,
+            end_how_far_from_now: *to - now,
+            exclude_appointment,
+            fresh_busy_times: true,
+            ignore_bookings,
+        };
+        let Some(window) = user
+            .get_current_availabilities_by_appointment_type(db, props(false))
             .await?
             .into_iter()
             .nth(0)
-            .ok_or_else(|| Error::string("No open weekly_availability window."))?;
+        else {
+            // Only a slot that would be offered with nothing booked was lost to somebody else.
+            let offered = user
+                .get_current_availabilities_by_appointment_type(db, props(true))
+                .await?
+                .into_iter()
+                .nth(0)
+                .is_some_and(|window| window.start == *from && window.end == *to);
+            return Err(if offered {
+                slot_no_longer_available()
+            } else {
+                slot_not_offered()
+            });
+        };
         if window.start != *from || window.end != *to {
             return Err(Error::Unauthorized("Invalid window.".to_string()));
         }
@@ -87,16 +143,377 @@ impl Model {
     pub async fn body<C: ConnectionTrait>(&self, db: &C) -> Result<String> {
         let appointment_type = AppointmentTypes::find_by_id(db, self.appointment_type_id).await?;
 
+        // Every seat of a group slot shares the same event.
+        if appointment_type.is_group() {
+            return Ok(appointment_type.display_name);
+        }
+
         Ok(format!(
             "{} with {}",
             appointment_type.display_name, self.booker_name
         ))
     }
 
+    /// Event description listing the booker's intake answers. Group slots share one event
+    /// between every seat, so their answers stay out of it.
+    pub async fn description<C: ConnectionTrait>(&self, db: &C) -> Result<String> {
+        let appointment_type = AppointmentTypes::find_by_id(db, self.appointment_type_id).await?;
+
+        if appointment_type.is_group() {
+            return Ok(String::new());
+        }
+
+        Ok(self.intake_answers_text())
+    }
+
+    fn intake_answers_text(&self) -> String {
+        self.intake_answers
+            .0
+            .iter()
+            .map(|answer| format!("{}: {}", answer.question, answer.answer))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Puts the appointment in every calendar the owner connected. Seats of a group slot join
+    /// the event of the slot as attendees, everything else gets events of its own. Provider
+    /// failures are only logged, see [`Self::try_add_to_calendars`] to handle them.
+    pub async fn add_to_calendars(
+        self,
+        db: &DatabaseConnection,
+        user: &users::Model,
+    ) -> Result<Self> {
+        let (appointment, failure) = self.add_missing_calendar_events(db, user).await?;
+        if let Some(err) = failure {
+            tracing::warn!("Failed to create calendar events: {}", err);
+        }
+        Ok(appointment)
+    }
+
+    /// Like [`Self::add_to_calendars`], but hands provider failures back to the caller. Calling
+    /// it again only goes to the calendars that are still missing the appointment.
+    pub async fn try_add_to_calendars(
+        self,
+        db: &DatabaseConnection,
+        user: &users::Model,
+    ) -> Result<Self> {
+        match self.add_missing_calendar_events(db, user).await? {
+            (_, Some(err)) => Err(err),
+            (appointment, None) => Ok(appointment),
+        }
+    }
+
+    /// Creates events in the connected calendars that do not have one for the appointment yet.
+    /// Events that were created are stored even when another provider failed.
+    async fn add_missing_calendar_events(
+        self,
+        db: &DatabaseConnection,
+        user: &users::Model,
+    ) -> Result<(Self, Option<Error>)> {
+        let providers = CalendarProviders::for_user(db, user).await?;
+        let missing: Vec<&dyn CalendarProvider> = providers
+            .all()
+            .filter(|provider| {
+                calendar_providers::events_of(&self.calendar_events, *provider).is_empty()
+            })
+            .collect();
+        if missing.is_empty() {
+            return Ok((self, None));
+        }
+
+        let seats = Entity::find_seats(db, &self).await?;
+        let new_event = self.new_calendar_event(db, user).await?;
+        let mut events = self.calendar_events.clone();
+        let mut failure = None;
+        for provider in missing {
+            match self.calendar_events_in(provider, &seats, &new_event).await {
+                Ok(created) => events.extend(created),
+                Err(err) => {
+                    failure.get_or_insert(err);
+                }
+            }
+        }
+
+        let appointment = self
+            .into_active_model()
+            .attach_calendar_events(db, events)
+            .await?;
+        Ok((appointment, failure))
+    }
+
+    /// Joins the shared event of the slot for group seats, creates a new event otherwise.
+    async fn calendar_events_in(
+        &self,
+        provider: &dyn CalendarProvider,
+        seats: &[Self],
+        new_event: &NewEvent,
+    ) -> Result<Vec<CalendarEvent>> {
+        let shared_events = seats
+            .iter()
+            .map(|seat| calendar_providers::events_of(&seat.calendar_events, provider))
+            .find(|events| !events.is_empty());
+
+        match shared_events {
+            Some(events) => {
+                provider.add_attendee(&events, &self.booker_email).await?;
+                Ok(events)
+            }
+            None => provider.create_event(new_event).await,
+        }
+    }
+
+    async fn new_calendar_event<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        user: &users::Model,
+    ) -> Result<NewEvent> {
+        Ok(NewEvent {
+            summary: self.body(db).await?,
+            description: self.description(db).await?,
+            start: self.start_time.to_utc(),
+            end: self.endtime.to_utc(),
+            organizer_name: user.name.clone(),
+            organizer_email: user.email.clone(),
+            attendee_name: self.booker_name.clone(),
+            attendee_email: self.booker_email.clone(),
+        })
+    }
+
+    /// Takes the appointment out of the owner's calendars, leaving events other seats still
+    /// share in place.
+    async fn remove_from_calendars(
+        &self,
+        db: &DatabaseConnection,
+        user: &users::Model,
+    ) -> Result<()> {
+        if self.calendar_events.is_empty() {
+            return Ok(());
+        }
+        let providers = CalendarProviders::for_user(db, user).await?;
+        let seats = Entity::find_seats(db, self).await?;
+
+        for provider in providers.all() {
+            let events = calendar_providers::events_of(&self.calendar_events, provider);
+            if events.is_empty() {
+                continue;
+            }
+            let shared = seats.iter().any(|seat| {
+                calendar_providers::events_of(&seat.calendar_events, provider) == events
+            });
+
+            if shared {
+                provider
+                    .remove_attendee(&events, &self.booker_email)
+                    .await?;
+            } else {
+                provider.delete_events(&events).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reminder offset that is due at `now`, the closest to `start_time` among those already
+    /// passed. Offsets that had passed before the appointment was even booked never fire.
+    #[must_use]
+    pub fn due_reminder_offset(
+        &self,
+        offsets_in_minutes: &[i32],
+        now: chrono::DateTime<Utc>,
+    ) -> Option<i32> {
+        offsets_in_minutes
+            .iter()
+            .copied()
+            .filter(|offset| {
+                let remind_at =
+                    self.start_time.to_utc() - chrono::Duration::minutes(i64::from(*offset));
+                remind_at <= now && remind_at >= self.created_at.to_utc()
+            })
+            .min()
+    }
+
+    /// Lets the owner's webhook endpoints know, failing to queue the deliveries is only logged.
+    async fn publish_webhook(&self, ctx: &AppContext, event: WebhookEvent) {
+        if let Err(err) = WebhookEndpoints::publish(ctx, self, event).await {
+            tracing::error!("Failed to publish {:?} webhooks: {}", event, err);
+        }
+    }
+
+    /// Link the booker can use to cancel or reschedule on their own.
+    #[must_use]
+    pub fn manage_url(&self, ctx: &AppContext) -> Option<String> {
+        self.manage_token
+            .as_ref()
+            .map(|token| format!("{}/manage/{token}", ctx.config.server.full_url()))
+    }
+
+    /// Calendar entry of the appointment, organized by the owner with the booker attending.
+    /// The UID stays the same for the life of the appointment and the sequence grows with
+    /// every update, so calendar clients keep updating a single entry.
+    #[must_use]
+    pub fn ics_event(
+        &self,
+        ctx: &AppContext,
+        user: &users::Model,
+        summary: String,
+        description: Option<String>,
+    ) -> ics::Event {
+        let host = url::Url::parse(&ctx.config.server.full_url())
+            .ok()
+            .and_then(|url| url.host_str().map(ToString::to_string))
+            .unwrap_or_else(|| "appointments".to_string());
+        let status = match self.status {
+            Status::Booked => ics::EventStatus::Confirmed,
+            Status::Pending => ics::EventStatus::Tentative,
+            Status::Cancelled | Status::Declined => ics::EventStatus::Cancelled,
+        };
+
+        ics::Event {
+            uid: format!("appointment-{}@{host}", self.id),
+            sequence: (self.updated_at - self.created_at).num_seconds().max(0),
+            stamp: our_chrono::utc_now(),
+            start: self.start_time.to_utc(),
+            end: self.endtime.to_utc(),
+            summary,
+            description,
+            url: None,
+            status,
+            organizer: ics::Person {
+                name: user.name.clone(),
+                email: user.email.clone(),
+            },
+            attendees: vec![ics::Person {
+                name: self.booker_name.clone(),
+                email: self.booker_email.clone(),
+            }],
+        }
+    }
+
+    /// Cancellation requested by the owner, the booker gets notified.
     pub async fn cancel_appointment(self, ctx: &AppContext, user: &users::Model) -> Result<Self> {
+        let updated_appointment = self.cancel_and_release(ctx, user).await?;
+
+        AppointmentsMailer::send_cancellation_to_booker(ctx, &updated_appointment).await?;
+
+        Ok(updated_appointment)
+    }
+
+    /// Cancellation requested by the booker through the manage link, the owner gets notified.
+    pub async fn cancel_by_booker(self, ctx: &AppContext) -> Result<Self> {
+        let user = users::Users::find_by_id(&ctx.db, self.user_id).await?;
+        let updated_appointment = self.cancel_and_release(ctx, &user).await?;
+
+        AppointmentsMailer::send_cancellation_to_user(ctx, &updated_appointment).await?;
+
+        Ok(updated_appointment)
+    }
+
+    async fn cancel_and_release(self, ctx: &AppContext, user: &users::Model) -> Result<Self> {
+        self.ensure_modifiable()?;
+        if self.status == Status::Booked {
+            if let Err(err) = self.remove_from_calendars(&ctx.db, user).await {
+                tracing::error!("Failed to delete calendar events: {}", err);
+            }
+        }
+        let updated_appointment = self.into_active_model().cancel_appointment(&ctx.db).await?;
+        updated_appointment
+            .publish_webhook(ctx, WebhookEvent::AppointmentCancelled)
+            .await;
+
+        Ok(updated_appointment)
+    }
+
+    /// Moves the appointment to a new window, keeping the calendar events in sync.
+    pub async fn reschedule_appointment(
+        self,
+        ctx: &AppContext,
+        user: &users::Model,
+        from: &chrono::DateTime<Utc>,
+        to: &chrono::DateTime<Utc>,
+    ) -> Result<Self> {
+        let rescheduled_appointment = self.move_to(ctx, user, from, to).await?;
+        rescheduled_appointment
+            .publish_webhook(ctx, WebhookEvent::AppointmentRescheduled)
+            .await;
+
+        Ok(rescheduled_appointment)
+    }
+
+    async fn move_to(
+        self,
+        ctx: &AppContext,
+        user: &users::Model,
+        from: &chrono::DateTime<Utc>,
+        to: &chrono::DateTime<Utc>,
+    ) -> Result<Self> {
+        self.ensure_modifiable()?;
+        let appointment_type =
+            AppointmentTypes::find_by_id(&ctx.db, self.appointment_type_id).await?;
+
+        Self::validate_appointment(&ctx.db, &appointment_type, user, from, to, Some(&self)).await?;
+
+        // A group seat can not drag the event it shares along, it leaves it and joins the
+        // new slot instead.
+        let previous = self.clone();
+        let leaves_shared_event = appointment_type.is_group()
+            && !self.calendar_events.is_empty()
+            && !Entity::find_seats(&ctx.db, &self).await?.is_empty();
+
+        let txn = ctx.db.begin().await?;
+        lock_owner_and_ensure_free(&txn, user, &appointment_type, from, to, Some(self.id)).await?;
+        let mut updated_appointment = self
+            .into_active_model()
+            .reschedule_appointment(&txn, from, to)
+            .await?;
+        txn.commit().await?;
+
+        // Nothing is in the calendars until the owner approves.
+        if updated_appointment.status == Status::Pending {
+            return Ok(updated_appointment);
+        }
+
+        let providers = CalendarProviders::for_user(&ctx.db, user).await?;
+        if leaves_shared_event {
+            if let Err(err) = previous.remove_from_calendars(&ctx.db, user).await {
+                tracing::error!("Failed to leave calendar events: {}", err);
+            }
+        }
+        let joins_shared_event = appointment_type.is_group()
+            && !Entity::find_seats(&ctx.db, &updated_appointment)
+                .await?
+                .is_empty();
+        if leaves_shared_event || joins_shared_event {
+            if !leaves_shared_event {
+                if let Err(err) = providers
+                    .delete_events(&updated_appointment.calendar_events)
+                    .await
+                {
+                    tracing::error!("Failed to delete calendar events: {}", err);
+                }
+            }
+            updated_appointment = updated_appointment
+                .into_active_model()
+                .attach_calendar_events(&ctx.db, vec![])
+                .await?;
+            return updated_appointment.add_to_calendars(&ctx.db, user).await;
+        }
+
+        // Patch existing events in place so attendees keep the same invite, only
+        // create new ones in calendars that had nothing to begin with.
+        if let Err(err) = providers
+            .update_events(&updated_appointment.calendar_events, *from, *to)
+            .await
+        {
+            tracing::error!("Failed to update calendar events: {}", err);
+        }
+
+        updated_appointment.add_to_calendars(&ctx.db, user).await
+    }
+
+    fn ensure_modifiable(&self) -> Result<()> {
         if self.start_time <= our_chrono::utc_now() {
             return Err(Error::Message(
-                "Can not cancel an appointment that has already passed.".to_string(),
+                "Can not modify an appointment that has already passed.".to_string(),
             ));
         }
         if self.status == Status::Cancelled {
@@ -104,22 +521,251 @@ impl Model {
                 "Appointment is already cancelled.".to_string(),
             ));
         }
-        let google_calendar_events = self.google_calendar_events.clone();
-        let updated_appointment = self.into_active_model().cancel_appointment(&ctx.db).await?;
+        if self.status == Status::Declined {
+            return Err(Error::Message("Appointment was declined.".to_string()));
+        }
+        Ok(())
+    }
 
-        AppointmentsMailer::send_cancellation_to_booker(ctx, &updated_appointment).await?;
+    fn ensure_pending(&self) -> Result<()> {
+        if self.status != Status::Pending {
+            return Err(Error::Message(
+                "Appointment is not waiting for approval.".to_string(),
+            ));
+        }
+        Ok(())
+    }
 
-        if let Err(err) =
-            google_calendars::Model::delete_calendar_events(&ctx.db, user, google_calendar_events)
-                .await
+    /// Confirms a pending appointment, puts it in the owner's calendars and lets the booker
+    /// know.
+    pub async fn approve(self, ctx: &AppContext, user: &users::Model) -> Result<Self> {
+        self.ensure_pending()?;
+        self.ensure_modifiable()?;
+        // Expired requests wait for the expiry worker to decline them, their slot may be gone.
+        if self
+            .pending_expires_at
+            .is_some_and(|expires_at| expires_at <= our_chrono::utc_now())
         {
-            tracing::error!("Failed to delete calendar events: {}", err);
+            return Err(Error::Message(
+                "Appointment request has expired.".to_string(),
+            ));
         }
+        let approved_appointment = self
+            .into_active_model()
+            .update_status(&ctx.db, Status::Booked)
+            .await?
+            .add_to_calendars(&ctx.db, user)
+            .await?;
 
-        Ok(updated_appointment)
+        approved_appointment
+            .publish_webhook(ctx, WebhookEvent::AppointmentApproved)
+            .await;
+        AppointmentsMailer::send_notification_to_booker(ctx, &approved_appointment).await?;
+
+        Ok(approved_appointment)
+    }
+
+    /// Turns a pending appointment down, freeing its slot, and lets the booker know. Pending
+    /// appointments nobody answered in time end up here too.
+    pub async fn decline(self, ctx: &AppContext) -> Result<Self> {
+        self.ensure_pending()?;
+        let declined_appointment = self
+            .into_active_model()
+            .update_status(&ctx.db, Status::Declined)
+            .await?;
+
+        declined_appointment
+            .publish_webhook(ctx, WebhookEvent::AppointmentDeclined)
+            .await;
+        AppointmentsMailer::send_decline_to_booker(ctx, &declined_appointment).await?;
+
+        Ok(declined_appointment)
+    }
+
+    /// Follows changes made to the Google events of the appointment outside the app. A booker
+    /// who declined the invitation cancels the appointment, an event the owner deleted or moved
+    /// flags it for the owner to review and leaves it booked as it is until then.
+    pub async fn reconcile_with_google(
+        self,
+        ctx: &AppContext,
+        providers: &[GoogleCalendarProvider],
+    ) -> Result<Self> {
+        for provider in providers {
+            for event in calendar_providers::events_of(&self.calendar_events, provider) {
+                let drift = provider
+                    .drift(
+                        &event,
+                        self.start_time.to_utc(),
+                        self.endtime.to_utc(),
+                        &self.booker_email,
+                    )
+                    .await;
+                match drift {
+                    Ok(None) => {}
+                    Ok(Some(EventDrift::Declined)) => return self.cancel_by_booker(ctx).await,
+                    Ok(Some(EventDrift::Deleted)) => {
+                        return self
+                            .flag_for_review(ctx, &event, CalendarReview::EventDeleted, None)
+                            .await;
+                    }
+                    Ok(Some(EventDrift::Moved { start })) => {
+                        return self
+                            .flag_for_review(ctx, &event, CalendarReview::EventMoved, start)
+                            .await;
+                    }
+                    Err(err) => tracing::warn!(
+                        "Could not check Google event {} of appointment {}: {}",
+                        event.event_id,
+                        self.id,
+                        err
+                    ),
+                }
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Flags the appointment and emails the owner, once however often the change is seen.
+    async fn flag_for_review(
+        self,
+        ctx: &AppContext,
+        event: &CalendarEvent,
+        review: CalendarReview,
+        moved_to: Option<chrono::DateTime<Utc>>,
+    ) -> Result<Self> {
+        if !Entity::claim_calendar_review(&ctx.db, &self, review.clone()).await? {
+            return Ok(self);
+        }
+
+        let mut flagged = Self {
+            calendar_review: Some(review),
+            ..self
+        };
+        // A deleted event is gone for good, keeping the appointment creates a new one.
+        if flagged.calendar_review == Some(CalendarReview::EventDeleted) {
+            let events = flagged
+                .calendar_events
+                .iter()
+                .filter(|stored| *stored != event)
+                .cloned()
+                .collect();
+            flagged = flagged
+                .into_active_model()
+                .attach_calendar_events(&ctx.db, events)
+                .await?;
+        }
+        AppointmentsMailer::send_review_to_user(ctx, &flagged, moved_to).await?;
+
+        Ok(flagged)
+    }
+
+    /// Keeps a flagged appointment as it is booked and puts the owner's calendars back in line:
+    /// a deleted event is created again, a moved one goes back to the booked window. Cancelling
+    /// or rescheduling the appointment resolves the review as well.
+    pub async fn keep_after_review(self, ctx: &AppContext, user: &users::Model) -> Result<Self> {
+        self.ensure_modifiable()?;
+        let Some(review) = self.calendar_review.clone() else {
+            return Err(Error::Message(
+                "Appointment is not waiting for review.".to_string(),
+            ));
+        };
+
+        if review == CalendarReview::EventMoved {
+            CalendarProviders::for_user(&ctx.db, user)
+                .await?
+                .update_events(
+                    &self.calendar_events,
+                    self.start_time.to_utc(),
+                    self.endtime.to_utc(),
+                )
+                .await?;
+        }
+        let kept_appointment = self
+            .into_active_model()
+            .clear_calendar_review(&ctx.db)
+            .await?;
+
+        kept_appointment.add_to_calendars(&ctx.db, user).await
     }
 }
 
+/// Write-locks the owner row and checks no other booked appointment of that owner overlaps the
+/// window, apart from seats of the same group slot while it still has room. Buffers count as in
+/// the slot generator: the window keeps its own buffers free, and stays out of the buffers of
+/// the appointments already there. Concurrent bookings
+/// for one owner are serialized this way on both `SQLite` and Postgres, so the loser of a race
+/// gets a conflict instead of a double booking. Must run inside the transaction that writes the
+/// appointment.
+async fn lock_owner_and_ensure_free<C: ConnectionTrait>(
+    txn: &C,
+    owner: &users::Model,
+    appointment_type: &appointment_types::Model,
+    from: &chrono::DateTime<Utc>,
+    to: &chrono::DateTime<Utc>,
+    exclude_appointment_id: Option<i32>,
+) -> Result<()> {
+    users::Users::update_many()
+        .col_expr(
+            users::users::Column::UpdatedAt,
+            Expr::col(users::users::Column::UpdatedAt).into(),
+        )
+        .filter(users::users::Column::Id.eq(owner.id))
+        .exec(txn)
+        .await?;
+
+    let appointment_types = AppointmentTypes::find_by_user(txn, owner).await?;
+    let widest_buffer = appointment_types
+        .iter()
+        .map(|t| t.buffer_before().max(t.buffer_after()))
+        .max()
+        .unwrap_or_default();
+    let padded_from = *from - appointment_type.buffer_before();
+    let padded_to = *to + appointment_type.buffer_after();
+
+    let mut candidates = Entity::find()
+        .filter(Column::UserId.eq(owner.id))
+        .filter(Column::Status.is_in(Status::HOLDING_SLOT))
+        .filter(Column::StartTime.lt(padded_to.max(*to + widest_buffer)))
+        .filter(Column::Endtime.gt(padded_from.min(*from - widest_buffer)));
+    if let Some(id) = exclude_appointment_id {
+        candidates = candidates.filter(Column::Id.ne(id));
+    }
+    let overlapping: Vec<Model> = candidates
+        .all(txn)
+        .await?
+        .into_iter()
+        .filter(|a| {
+            let (buffer_before, buffer_after) = appointment_types
+                .iter()
+                .find(|t| t.id == a.appointment_type_id)
+                .map_or((chrono::Duration::zero(), chrono::Duration::zero()), |t| {
+                    (t.buffer_before(), t.buffer_after())
+                });
+            let start = a.start_time.to_utc();
+            let end = a.endtime.to_utc();
+            (start < padded_to && end > padded_from)
+                || (start - buffer_before < *to && end + buffer_after > *from)
+        })
+        .collect();
+    let seats_taken = overlapping
+        .iter()
+        .filter(|a| {
+            appointment_type.is_group()
+                && a.appointment_type_id == appointment_type.id
+                && a.start_time.to_utc() == *from
+                && a.endtime.to_utc() == *to
+        })
+        .count();
+    if overlapping.len() > seats_taken
+        || seats_taken >= usize::try_from(appointment_type.max_attendees).unwrap_or_default()
+    {
+        return Err(slot_no_longer_available());
+    }
+
+    Ok(())
+}
+
 pub struct CreateAppointmentProps<'a> {
     pub booker_phone: String,
     pub booker_name: String,
@@ -127,6 +773,9 @@ pub struct CreateAppointmentProps<'a> {
     pub booker_email: String,
     pub start_time: chrono::DateTime<Utc>,
     pub endtime: chrono::DateTime<Utc>,
+    pub intake_answers: IntakeAnswers,
+    /// Set when the appointment type requires approval, the appointment stays pending until then.
+    pub pending_expires_at: Option<chrono::DateTime<Utc>>,
     pub user: &'a users::Model,
     pub appointment_type: &'a appointment_types::Model,
 }
@@ -144,33 +793,118 @@ impl ActiveModel {
             booker_email: ActiveValue::set(props.booker_email),
             start_time: ActiveValue::set(props.start_time.into()),
             endtime: ActiveValue::set(props.endtime.into()),
-            status: ActiveValue::set(Status::Booked),
+            status: ActiveValue::set(if props.pending_expires_at.is_some() {
+                Status::Pending
+            } else {
+                Status::Booked
+            }),
             user_id: ActiveValue::set(props.user.id),
             appointment_type_id: ActiveValue::set(props.appointment_type.id),
+            manage_token: ActiveValue::set(Some(hash::random_string(MANAGE_TOKEN_LENGTH))),
+            intake_answers: ActiveValue::set(props.intake_answers),
+            pending_expires_at: ActiveValue::set(props.pending_expires_at.map(Into::into)),
             ..Default::default()
         };
         Ok(active_model.insert(db).await?)
     }
 
+    /// Creates the appointment only if the window is still free, see [`lock_owner_and_ensure_free`].
+    /// Its calendar and mail side effects are queued in the same transaction, the outbox worker
+    /// performs them once it commits.
+    pub async fn create_if_available(
+        db: &DatabaseConnection,
+        props: CreateAppointmentProps<'_>,
+    ) -> Result<Model> {
+        let txn = db.begin().await?;
+        lock_owner_and_ensure_free(
+            &txn,
+            props.user,
+            props.appointment_type,
+            &props.start_time,
+            &props.endtime,
+            None,
+        )
+        .await?;
+        let appointment = Self::create(&txn, props).await?;
+        let side_effects: &[OutboxJobKind] = if appointment.status == Status::Pending {
+            // Nothing goes to the calendars until the owner approves.
+            &[
+                OutboxJobKind::NotifyBooker,
+                OutboxJobKind::NotifyOwner,
+                OutboxJobKind::NotifyWebhooks,
+            ]
+        } else {
+            &[
+                OutboxJobKind::AddToCalendars,
+                OutboxJobKind::NotifyBooker,
+                OutboxJobKind::NotifyOwner,
+                OutboxJobKind::NotifyWebhooks,
+            ]
+        };
+        outbox_jobs::ActiveModel::enqueue(&txn, &appointment, side_effects).await?;
+        txn.commit().await?;
+
+        Ok(appointment)
+    }
+
     pub async fn cancel_appointment<C>(mut self, db: &C) -> ModelResult<Model>
     where
         C: ConnectionTrait,
     {
         self.status = ActiveValue::set(Status::Cancelled);
-        self.google_calendar_events = ActiveValue::set(vec![]);
+        self.calendar_events = ActiveValue::set(vec![]);
+        self.calendar_review = ActiveValue::set(None);
+
+        Ok(self.update(db).await?)
+    }
+
+    pub async fn update_status<C>(mut self, db: &C, status: Status) -> ModelResult<Model>
+    where
+        C: ConnectionTrait,
+    {
+        self.status = ActiveValue::set(status);
+        self.pending_expires_at = ActiveValue::set(None);
 
         Ok(self.update(db).await?)
     }
 
-    pub async fn attach_google_calendar_events<C>(
+    pub async fn reschedule_appointment<C>(
         mut self,
         db: &C,
-        events: Vec<GoogleCalendarEvent>,
+        from: &chrono::DateTime<Utc>,
+        to: &chrono::DateTime<Utc>,
     ) -> ModelResult<Model>
     where
         C: ConnectionTrait,
     {
-        self.google_calendar_events = ActiveValue::set(events);
+        self.start_time = ActiveValue::set((*from).into());
+        self.endtime = ActiveValue::set((*to).into());
+        self.calendar_review = ActiveValue::set(None);
+
+        let rescheduled = self.update(db).await?;
+        // Reminders were for the old time, the new one gets its own.
+        AppointmentReminders::delete_by_appointment_id(db, rescheduled.id).await?;
+        Ok(rescheduled)
+    }
+
+    pub async fn attach_calendar_events<C>(
+        mut self,
+        db: &C,
+        events: Vec<CalendarEvent>,
+    ) -> ModelResult<Model>
+    where
+        C: ConnectionTrait,
+    {
+        self.calendar_events = ActiveValue::set(events);
+
+        Ok(self.update(db).await?)
+    }
+
+    pub async fn clear_calendar_review<C>(mut self, db: &C) -> ModelResult<Model>
+    where
+        C: ConnectionTrait,
+    {
+        self.calendar_review = ActiveValue::set(None);
 
         Ok(self.update(db).await?)
     }
@@ -185,7 +919,7 @@ impl Entity {
         let booked = Self::find()
             .order_by_desc(Column::StartTime)
             .filter(Column::UserId.eq(owner.id))
-            .filter(Column::Status.eq("Booked"))
+            .filter(Column::Status.is_in(Status::HOLDING_SLOT))
             .filter(Column::StartTime.gt(our_chrono::utc_now()))
             .all(db)
             .await?;
@@ -193,6 +927,120 @@ impl Entity {
         Ok(booked)
     }
 
+    /// Booked appointments of every owner starting within the window.
+    pub async fn find_booked_starting_between<C>(
+        db: &C,
+        from: chrono::DateTime<Utc>,
+        to: chrono::DateTime<Utc>,
+    ) -> ModelResult<Vec<Model>>
+    where
+        C: ConnectionTrait,
+    {
+        let booked = Self::find()
+            .order_by_asc(Column::StartTime)
+            .filter(Column::Status.eq(Status::Booked))
+            .filter(Column::StartTime.gt(from))
+            .filter(Column::StartTime.lte(to))
+            .all(db)
+            .await?;
+
+        Ok(booked)
+    }
+
+    /// Booked appointments still ahead that have Google events and are not flagged for review
+    /// yet, the ones to reconcile with Google.
+    pub async fn find_to_reconcile<C>(db: &C) -> ModelResult<Vec<Model>>
+    where
+        C: ConnectionTrait,
+    {
+        Self::to_reconcile(db, Self::find().order_by_asc(Column::UserId)).await
+    }
+
+    /// Like [`Self::find_to_reconcile`], for one owner.
+    pub async fn find_to_reconcile_by_user<C>(
+        db: &C,
+        owner: &users::Model,
+    ) -> ModelResult<Vec<Model>>
+    where
+        C: ConnectionTrait,
+    {
+        Self::to_reconcile(db, Self::find().filter(Column::UserId.eq(owner.id))).await
+    }
+
+    async fn to_reconcile<C>(db: &C, query: Select<Self>) -> ModelResult<Vec<Model>>
+    where
+        C: ConnectionTrait,
+    {
+        let booked = query
+            .filter(Column::Status.eq(Status::Booked))
+            .filter(Column::StartTime.gt(our_chrono::utc_now()))
+            .filter(Column::CalendarReview.is_null())
+            .all(db)
+            .await?;
+
+        Ok(booked
+            .into_iter()
+            .filter(|appointment| {
+                appointment
+                    .calendar_events
+                    .iter()
+                    .any(|event| event.provider == CalendarProviderKind::Google)
+            })
+            .collect())
+    }
+
+    /// Flags the appointment for review. `false` when it already was, so the owner is told
+    /// once.
+    pub async fn claim_calendar_review<C>(
+        db: &C,
+        appointment: &Model,
+        review: CalendarReview,
+    ) -> ModelResult<bool>
+    where
+        C: ConnectionTrait,
+    {
+        let claimed = Self::update_many()
+            .col_expr(Column::CalendarReview, Expr::value(review))
+            .filter(Column::Id.eq(appointment.id))
+            .filter(Column::CalendarReview.is_null())
+            .exec(db)
+            .await?;
+
+        Ok(claimed.rows_affected == 1)
+    }
+
+    /// Pending appointments the owner did not answer in time.
+    pub async fn find_expired_pending<C>(db: &C) -> ModelResult<Vec<Model>>
+    where
+        C: ConnectionTrait,
+    {
+        let expired = Self::find()
+            .filter(Column::Status.eq(Status::Pending))
+            .filter(Column::PendingExpiresAt.lte(our_chrono::utc_now()))
+            .all(db)
+            .await?;
+
+        Ok(expired)
+    }
+
+    /// Other booked seats sharing the exact slot of a group appointment.
+    pub async fn find_seats<C>(db: &C, appointment: &Model) -> ModelResult<Vec<Model>>
+    where
+        C: ConnectionTrait,
+    {
+        let seats = Self::find()
+            .filter(Column::UserId.eq(appointment.user_id))
+            .filter(Column::AppointmentTypeId.eq(appointment.appointment_type_id))
+            .filter(Column::Status.eq(Status::Booked))
+            .filter(Column::StartTime.eq(appointment.start_time))
+            .filter(Column::Endtime.eq(appointment.endtime))
+            .filter(Column::Id.ne(appointment.id))
+            .all(db)
+            .await?;
+
+        Ok(seats)
+    }
+
     pub async fn find_by_user_with_filters<C>(
         db: &C,
         owner: &users::Model,
@@ -201,6 +1049,96 @@ impl Entity {
     where
         C: ConnectionTrait,
     {
+        let appointments_query = Self::filtered(owner, &filters)?;
+
+        let count = appointments_query.clone().count(db).await?;
+        let booked = appointments_query
+            .offset(filters.page * filters.limit)
+            .limit(filters.limit)
+            .all(db)
+            .await?;
+
+        Ok((booked, count))
+    }
+
+    /// Appointments for the owner's calendar feed, selected with the same filters as
+    /// [`Self::find_by_user_with_filters`]. Cancelled and declined ones stay in the feed for
+    /// [`FEED_CANCELLED_RETENTION_IN_DAYS`] after they were called off, so subscribed calendars
+    /// get to remove them.
+    pub async fn find_for_calendar_feed<C>(
+        db: &C,
+        owner: &users::Model,
+        now: chrono::DateTime<Utc>,
+    ) -> ModelResult<Vec<Model>>
+    where
+        C: ConnectionTrait,
+    {
+        let owner_timezone = owner.timezone.parse::<Tz>().unwrap_or(Tz::UTC);
+        let filters = AppointmentsQueryParams {
+            page: 0,
+            limit: 0,
+            from_date: Some(
+                (now - chrono::Duration::days(FEED_HISTORY_IN_DAYS))
+                    .with_timezone(&owner_timezone)
+                    .date_naive(),
+            ),
+            to_date: None,
+            status: None,
+            appointment_type: None,
+        };
+
+        Ok(Self::filtered(owner, &filters)?
+            .filter(
+                Condition::any()
+                    .add(Column::Status.is_in(Status::HOLDING_SLOT))
+                    .add(
+                        Column::UpdatedAt
+                            .gte(now - chrono::Duration::days(FEED_CANCELLED_RETENTION_IN_DAYS)),
+                    ),
+            )
+            .all(db)
+            .await?)
+    }
+
+    /// The owner's appointments as a read only calendar to subscribe to. Every seat of a group
+    /// slot is an event of its own.
+    pub async fn calendar_feed(ctx: &AppContext, owner: &users::Model) -> Result<ics::Calendar> {
+        let appointment_types: HashMap<i32, appointment_types::Model> =
+            AppointmentTypes::find_by_user(&ctx.db, owner)
+                .await?
+                .into_iter()
+                .map(|appointment_type| (appointment_type.id, appointment_type))
+                .collect();
+        let events = Self::find_for_calendar_feed(&ctx.db, owner, our_chrono::utc_now())
+            .await?
+            .iter()
+            .map(|appointment| {
+                let display_name = appointment_types
+                    .get(&appointment.appointment_type_id)
+                    .map_or("Appointment", |appointment_type| {
+                        appointment_type.display_name.as_str()
+                    });
+                let answers = appointment.intake_answers_text();
+                appointment.ics_event(
+                    ctx,
+                    owner,
+                    format!("{display_name} with {}", appointment.booker_name),
+                    (!answers.is_empty()).then_some(answers),
+                )
+            })
+            .collect();
+
+        Ok(ics::Calendar {
+            method: ics::Method::Publish,
+            name: Some(format!("{} appointments", owner.name)),
+            events,
+        })
+    }
+
+    fn filtered(
+        owner: &users::Model,
+        filters: &AppointmentsQueryParams,
+    ) -> ModelResult<Select<Self>> {
         let mut appointments_query = Self::find()
             .order_by_asc(Column::StartTime)
             .filter(Column::UserId.eq(owner.id));
@@ -208,7 +1146,7 @@ impl Entity {
             appointments_query =
                 appointments_query.filter(Column::AppointmentTypeId.eq(appointment_type_id));
         }
-        if let Some(status) = filters.status {
+        if let Some(status) = filters.status.clone() {
             appointments_query = appointments_query.filter(Column::Status.eq(status));
         }
         if let (Some(start_time), Ok(tz)) = (filters.from_date, owner.timezone.parse::<Tz>()) {
@@ -240,14 +1178,15 @@ impl Entity {
             appointments_query = appointments_query.filter(Column::StartTime.lt(end_datetime));
         }
 
-        let count = appointments_query.clone().count(db).await?;
-        let booked = appointments_query
-            .offset(filters.page * filters.limit)
-            .limit(filters.limit)
-            .all(db)
-            .await?;
+        Ok(appointments_query)
+    }
 
-        Ok((booked, count))
+    pub async fn find_by_manage_token<C: ConnectionTrait>(db: &C, token: &str) -> Result<Model> {
+        Self::find()
+            .filter(Column::ManageToken.eq(token))
+            .one(db)
+            .await?
+            .ok_or(Error::NotFound)
     }
 
     pub async fn find_by_id_and_user<C: ConnectionTrait>(
@@ -0,0 +1,220 @@
+use chrono::{Duration, NaiveDate};
+
+pub use super::_entities::availability_overrides::{
+    ActiveModel, Entity, Model, OverrideWindow, OverrideWindows,
+};
+use crate::models::{
+    _entities::availability_overrides::Column, users::users,
+    weekly_availabilities::WeeklyAvailabilityDuration,
+};
+use loco_rs::prelude::*;
+use sea_orm::{entity::prelude::*, QueryOrder};
+use serde::Deserialize;
+use validator::ValidationError;
+pub type AvailabilityOverrides = Entity;
+
+const MINUTES_IN_A_DAY: i32 = 60 * 24;
+
+fn validate_windows(value: &Validator) -> Result<(), ValidationError> {
+    if value.unavailable {
+        if !value.windows.is_empty() {
+            return Err(ValidationError::new("unavailable_with_windows")
+                .with_message("An unavailable day can not have windows.".into()));
+        }
+        return Ok(());
+    }
+
+    if value.windows.is_empty() {
+        return Err(ValidationError::new("missing_windows")
+            .with_message("Add at least one window or mark the day as unavailable.".into()));
+    }
+
+    let mut windows = value.windows.clone();
+    windows.sort_by_key(|window| window.from);
+
+    for window in &windows {
+        if window.from < 0 || window.to > MINUTES_IN_A_DAY || window.to <= window.from {
+            return Err(ValidationError::new("invalid_window").with_message(
+                "\"from\" must be less than \"to\", both within the same day.".into(),
+            ));
+        }
+    }
+
+    if windows.windows(2).any(|pair| pair[1].from < pair[0].to) {
+        return Err(
+            ValidationError::new("clashing_windows").with_message("Clashing periods.".into())
+        );
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Validate, Deserialize)]
+#[validate(schema(function = "validate_windows"))]
+pub struct Validator {
+    pub unavailable: bool,
+    pub windows: Vec<OverrideWindow>,
+}
+
+impl Validatable for ActiveModel {
+    fn validator(&self) -> Box<dyn Validate> {
+        Box::new(Validator {
+            unavailable: self.unavailable.as_ref().to_owned(),
+            windows: self.windows.as_ref().0.clone(),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    async fn before_save<C>(self, _db: &C, insert: bool) -> std::result::Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        self.validate()?;
+        if !insert && self.updated_at.is_unchanged() {
+            let mut this = self;
+            this.updated_at = sea_orm::ActiveValue::Set(chrono::Utc::now().into());
+            Ok(this)
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+// implement your read-oriented logic here
+impl Model {
+    /// Windows open on this date in minutes from 00:00, empty when unavailable.
+    #[must_use]
+    pub fn availability(&self) -> Vec<WeeklyAvailabilityDuration> {
+        if self.unavailable {
+            return Vec::new();
+        }
+
+        let mut windows: Vec<WeeklyAvailabilityDuration> = self
+            .windows
+            .0
+            .iter()
+            .map(|window| WeeklyAvailabilityDuration {
+                from: Duration::minutes(i64::from(window.from)),
+                to: Duration::minutes(i64::from(window.to)),
+            })
+            .collect();
+        windows.sort_by_key(|window| window.from);
+        windows
+    }
+}
+
+#[derive(Debug)]
+pub struct CreateOrUpdateAvailabilityOverride<'a> {
+    pub date: NaiveDate,
+    pub unavailable: bool,
+    pub windows: Vec<OverrideWindow>,
+    pub user: &'a users::Model,
+}
+
+// implement your write-oriented logic here
+impl ActiveModel {
+    pub async fn create<C>(
+        db: &C,
+        params: CreateOrUpdateAvailabilityOverride<'_>,
+    ) -> ModelResult<Model>
+    where
+        C: ConnectionTrait,
+    {
+        if Entity::find_by_user_and_date(db, params.user, params.date)
+            .await?
+            .is_some()
+        {
+            return Err(ModelError::EntityAlreadyExists {});
+        }
+
+        let active_model = Self {
+            date: sea_orm::ActiveValue::Set(params.date),
+            unavailable: sea_orm::ActiveValue::Set(params.unavailable),
+            windows: sea_orm::ActiveValue::Set(OverrideWindows(params.windows)),
+            user_id: sea_orm::ActiveValue::Set(params.user.id),
+            ..Default::default()
+        };
+        Ok(active_model.insert(db).await?)
+    }
+
+    pub async fn update_with_params<C>(
+        mut self,
+        db: &C,
+        params: CreateOrUpdateAvailabilityOverride<'_>,
+    ) -> ModelResult<Model>
+    where
+        C: ConnectionTrait,
+    {
+        if let Some(existing) = Entity::find_by_user_and_date(db, params.user, params.date).await? {
+            if self.id.as_ref() != &existing.id {
+                return Err(ModelError::EntityAlreadyExists {});
+            }
+        }
+
+        self.date = sea_orm::ActiveValue::Set(params.date);
+        self.unavailable = sea_orm::ActiveValue::Set(params.unavailable);
+        self.windows = sea_orm::ActiveValue::Set(OverrideWindows(params.windows));
+
+        Ok(self.update(db).await?)
+    }
+}
+
+// implement your custom finders, selectors oriented logic here
+impl Entity {
+    pub async fn find_by_id<C>(db: &C, id: i32) -> ModelResult<Model>
+    where
+        C: ConnectionTrait,
+    {
+        Self::find()
+            .filter(Column::Id.eq(id))
+            .one(db)
+            .await?
+            .ok_or(ModelError::EntityNotFound)
+    }
+
+    pub async fn find_by_user<C>(db: &C, user: &users::Model) -> ModelResult<Vec<Model>>
+    where
+        C: ConnectionTrait,
+    {
+        Ok(Self::find()
+            .filter(Column::UserId.eq(user.id))
+            .order_by_asc(Column::Date)
+            .all(db)
+            .await?)
+    }
+
+    pub async fn find_by_user_and_date<C>(
+        db: &C,
+        user: &users::Model,
+        date: NaiveDate,
+    ) -> ModelResult<Option<Model>>
+    where
+        C: ConnectionTrait,
+    {
+        Ok(Self::find()
+            .filter(Column::UserId.eq(user.id))
+            .filter(Column::Date.eq(date))
+            .one(db)
+            .await?)
+    }
+
+    /// Overrides between both dates, inclusive.
+    pub async fn find_by_user_between<C>(
+        db: &C,
+        user: &users::Model,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> ModelResult<Vec<Model>>
+    where
+        C: ConnectionTrait,
+    {
+        Ok(Self::find()
+            .filter(Column::UserId.eq(user.id))
+            .filter(Column::Date.between(from, to))
+            .order_by_asc(Column::Date)
+            .all(db)
+            .await?)
+    }
+}
@@ -0,0 +1,234 @@
+pub use super::_entities::busy_intervals::{ActiveModel, Entity, Interval, Intervals, Model};
+use crate::{
+    calendar_providers::CalendarProviders,
+    models::{
+        _entities::{
+            appointments, busy_intervals::Column, caldav_connections, google_calendars,
+            outlook_calendars,
+        },
+        user_settings, users,
+    },
+    views::client_facing::AvailabilityWindow,
+};
+use chrono::{DateTime, Duration, Utc};
+use loco_rs::prelude::*;
+use sea_orm::{entity::prelude::*, sea_query::OnConflict};
+pub type BusyIntervals = Entity;
+
+/// How long fetched busy times are trusted. The refresh worker runs well within it, so owners
+/// with connected calendars rarely wait for their providers.
+const TTL_IN_MINUTES: i64 = 15;
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    async fn before_save<C>(self, _db: &C, insert: bool) -> std::result::Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        if !insert && self.updated_at.is_unchanged() {
+            let mut this = self;
+            this.updated_at = sea_orm::ActiveValue::Set(chrono::Utc::now().into());
+            Ok(this)
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+impl From<&AvailabilityWindow> for Interval {
+    fn from(window: &AvailabilityWindow) -> Self {
+        Self {
+            start: window.start,
+            end: window.end,
+        }
+    }
+}
+
+impl From<&Interval> for AvailabilityWindow {
+    fn from(interval: &Interval) -> Self {
+        Self {
+            start: interval.start,
+            end: interval.end,
+            remaining_seats: None,
+        }
+    }
+}
+
+/// The windows overlapping `time_min` to `time_max`.
+fn overlapping(
+    windows: Vec<AvailabilityWindow>,
+    time_min: DateTime<Utc>,
+    time_max: DateTime<Utc>,
+) -> Vec<AvailabilityWindow> {
+    windows
+        .into_iter()
+        .filter(|window| window.end > time_min && window.start < time_max)
+        .collect()
+}
+
+// implement your read-oriented logic here
+impl Model {
+    #[must_use]
+    pub fn covers(&self, time_min: DateTime<Utc>, time_max: DateTime<Utc>) -> bool {
+        self.covers_from.to_utc() <= time_min && time_max <= self.covers_until.to_utc()
+    }
+
+    #[must_use]
+    pub fn is_expired(&self) -> bool {
+        self.fetched_at.to_utc() + Duration::minutes(TTL_IN_MINUTES) <= Utc::now()
+    }
+
+    /// The stored busy times overlapping `time_min` to `time_max`.
+    #[must_use]
+    pub fn between(
+        &self,
+        time_min: DateTime<Utc>,
+        time_max: DateTime<Utc>,
+    ) -> Vec<AvailabilityWindow> {
+        overlapping(
+            self.intervals.0.iter().map(Into::into).collect(),
+            time_min,
+            time_max,
+        )
+    }
+
+    /// Whether the busy times can still be trusted: not expired, and neither a calendar
+    /// connection nor an appointment of the owner changed since they were fetched. Appointments
+    /// have events in the calendars, booking or cancelling one changes what is busy.
+    pub async fn is_fresh<C: ConnectionTrait>(&self, db: &C) -> Result<bool> {
+        if self.is_expired() {
+            return Ok(false);
+        }
+
+        let fetched_at = self.fetched_at.to_utc();
+        let changed = appointments::Entity::find()
+            .filter(appointments::Column::UserId.eq(self.user_id))
+            .filter(appointments::Column::UpdatedAt.gt(fetched_at))
+            .one(db)
+            .await?
+            .is_some()
+            || google_calendars::Entity::find()
+                .filter(google_calendars::Column::UserId.eq(self.user_id))
+                .filter(google_calendars::Column::UpdatedAt.gt(fetched_at))
+                .one(db)
+                .await?
+                .is_some()
+            || caldav_connections::Entity::find()
+                .filter(caldav_connections::Column::UserId.eq(self.user_id))
+                .filter(caldav_connections::Column::UpdatedAt.gt(fetched_at))
+                .one(db)
+                .await?
+                .is_some()
+            || outlook_calendars::Entity::find()
+                .filter(outlook_calendars::Column::UserId.eq(self.user_id))
+                .filter(outlook_calendars::Column::UpdatedAt.gt(fetched_at))
+                .one(db)
+                .await?
+                .is_some();
+
+        Ok(!changed)
+    }
+}
+
+// implement your custom finders, selectors oriented logic here
+impl Entity {
+    pub async fn find_by_user<C: ConnectionTrait>(
+        db: &C,
+        user: &users::Model,
+    ) -> Result<Option<Model>> {
+        Ok(Self::find()
+            .filter(Column::UserId.eq(user.id))
+            .one(db)
+            .await?)
+    }
+
+    /// Busy times of the user's calendars between `time_min` and `time_max`. They come from the
+    /// table while it is fresh and covers the window, otherwise the calendars are asked for the
+    /// whole booking horizon and the answer is stored for the next request.
+    pub async fn busy_times<C: ConnectionTrait>(
+        db: &C,
+        user: &users::Model,
+        time_min: DateTime<Utc>,
+        time_max: DateTime<Utc>,
+    ) -> Result<Vec<AvailabilityWindow>> {
+        if let Some(cached) = Self::find_by_user(db, user).await? {
+            if cached.covers(time_min, time_max) && cached.is_fresh(db).await? {
+                return Ok(cached.between(time_min, time_max));
+            }
+        }
+
+        let (from, until) = Self::horizon(db, user).await?;
+        let windows = Self::fetch(db, user, from.min(time_min), until.max(time_max)).await?;
+        Ok(overlapping(windows, time_min, time_max))
+    }
+
+    /// Asks the calendars for the busy times of the whole booking horizon again.
+    pub async fn refresh<C: ConnectionTrait>(db: &C, user: &users::Model) -> Result<()> {
+        let (from, until) = Self::horizon(db, user).await?;
+        Self::fetch(db, user, from, until).await?;
+        Ok(())
+    }
+
+    /// From now to the end of the booking horizon, plus the TTL so that the horizon moving on
+    /// does not make a fresh fetch stop covering it.
+    async fn horizon<C: ConnectionTrait>(
+        db: &C,
+        user: &users::Model,
+    ) -> Result<(DateTime<Utc>, DateTime<Utc>)> {
+        let user_settings = user_settings::Model::get_or_create(db, user).await?;
+        let now = Utc::now();
+        let until = now
+            + Duration::minutes(i64::from(user_settings.end_how_far_from_now_in_minutes))
+            + Duration::minutes(TTL_IN_MINUTES);
+        Ok((now, until))
+    }
+
+    /// Fetches the busy times from every provider and stores them. When a provider fails the
+    /// partial answer is still returned, but the stored one is dropped so that a calendar
+    /// that could not be read never looks free for a whole TTL.
+    async fn fetch<C: ConnectionTrait>(
+        db: &C,
+        user: &users::Model,
+        from: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<Vec<AvailabilityWindow>> {
+        let providers = CalendarProviders::for_user(db, user).await?;
+        // Taken after the providers refreshed their tokens, which updates the connections.
+        let fetched_at = Utc::now();
+        let (windows, complete) = providers.busy_times_checked(from, until).await;
+
+        if !complete {
+            Self::delete_many()
+                .filter(Column::UserId.eq(user.id))
+                .exec(db)
+                .await?;
+            return Ok(windows);
+        }
+
+        let busy_intervals = ActiveModel {
+            user_id: ActiveValue::Set(user.id),
+            intervals: ActiveValue::Set(Intervals(windows.iter().map(Into::into).collect())),
+            covers_from: ActiveValue::Set(from.into()),
+            covers_until: ActiveValue::Set(until.into()),
+            fetched_at: ActiveValue::Set(fetched_at.into()),
+            updated_at: ActiveValue::Set(fetched_at.into()),
+            ..Default::default()
+        };
+        Self::insert(busy_intervals)
+            .on_conflict(
+                OnConflict::column(Column::UserId)
+                    .update_columns([
+                        Column::Intervals,
+                        Column::CoversFrom,
+                        Column::CoversUntil,
+                        Column::FetchedAt,
+                        Column::UpdatedAt,
+                    ])
+                    .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await?;
+
+        Ok(windows)
+    }
+}
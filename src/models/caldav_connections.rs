@@ -0,0 +1,195 @@
+pub use super::_entities::caldav_connections::{ActiveModel, Entity, Model};
+use crate::{
+    common::{caldav, encryption, public_http::validate_user_url},
+    models::{_entities::caldav_connections::Column, users},
+    views::google_calendars::{CalendarSettingParams, CalendarSettingType},
+};
+use axum::http::StatusCode;
+use loco_rs::{controller::ErrorDetail, prelude::*};
+use sea_orm::entity::prelude::*;
+use serde::Deserialize;
+pub type CaldavConnections = Entity;
+
+fn invalid_connection(message: &str) -> Error {
+    Error::CustomError(
+        StatusCode::BAD_REQUEST,
+        ErrorDetail::new("invalid_caldav_connection", message),
+    )
+}
+
+#[derive(Debug, Validate, Deserialize)]
+pub struct Validator {
+    #[validate(length(max = 2000), custom(function = "validate_user_url"))]
+    pub server_url: String,
+    #[validate(length(min = 1, message = "Username must not be empty."))]
+    pub username: String,
+}
+
+impl Validatable for ActiveModel {
+    fn validator(&self) -> Box<dyn Validate> {
+        Box::new(Validator {
+            server_url: self.server_url.as_ref().to_owned(),
+            username: self.username.as_ref().to_owned(),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    async fn before_save<C>(self, _db: &C, insert: bool) -> std::result::Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        self.validate()?;
+        let mut this = self;
+        encryption::encrypt_active_value(&mut this.password)?;
+
+        if !insert && this.updated_at.is_unchanged() {
+            this.updated_at = sea_orm::ActiveValue::Set(chrono::Utc::now().into());
+        }
+        Ok(this)
+    }
+}
+
+#[derive(Debug)]
+pub struct ConnectCaldav {
+    pub server_url: String,
+    pub username: String,
+    pub password: String,
+}
+
+// implement your read-oriented logic here
+impl Model {
+    pub fn client(&self) -> Result<caldav::Client> {
+        caldav::Client::new(
+            &self.server_url,
+            &self.username,
+            &encryption::decrypt(&self.password)?,
+        )
+    }
+
+    pub async fn add_calendar_to_settings<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        params: CalendarSettingParams,
+    ) -> Result<Self> {
+        let mut active_model = self.clone().into_active_model();
+        match params.setting_type {
+            CalendarSettingType::CollisionCheck => {
+                let mut cloned = self.calendars_for_collision_check.clone();
+                cloned.add(params.calendar_id);
+                active_model.calendars_for_collision_check = ActiveValue::Set(cloned);
+            }
+            CalendarSettingType::EventHandling => {
+                let mut cloned = self.calendars_for_event_handling.clone();
+                cloned.add(params.calendar_id);
+                active_model.calendars_for_event_handling = ActiveValue::Set(cloned);
+            }
+        }
+
+        Ok(active_model.update(db).await?)
+    }
+
+    pub async fn remove_calendar_from_settings<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        params: CalendarSettingParams,
+    ) -> Result<Self> {
+        let mut active_model = self.clone().into_active_model();
+        match params.setting_type {
+            CalendarSettingType::CollisionCheck => {
+                let mut cloned = self.calendars_for_collision_check.clone();
+                cloned.remove(&params.calendar_id);
+                active_model.calendars_for_collision_check = ActiveValue::Set(cloned);
+            }
+            CalendarSettingType::EventHandling => {
+                let mut cloned = self.calendars_for_event_handling.clone();
+                cloned.remove(&params.calendar_id);
+                active_model.calendars_for_event_handling = ActiveValue::Set(cloned);
+            }
+        }
+
+        Ok(active_model.update(db).await?)
+    }
+}
+
+// implement your write-oriented logic here
+impl ActiveModel {
+    /// Replaces the user's connection once the server accepted the credentials and listed the
+    /// calendars. The calendars are returned so the user can pick from them right away.
+    pub async fn connect<C: ConnectionTrait>(
+        db: &C,
+        user: &users::Model,
+        params: ConnectCaldav,
+    ) -> Result<(Model, Vec<caldav::Calendar>)> {
+        let server_url = params.server_url.trim().to_string();
+        validator::Validate::validate(&Validator {
+            server_url: server_url.clone(),
+            username: params.username.clone(),
+        })
+        .map_err(|err| invalid_connection(&err.to_string()))?;
+
+        let calendars = caldav::Client::new(&server_url, &params.username, &params.password)?
+            .discover_calendars()
+            .await
+            .map_err(|err| {
+                tracing::warn!("CalDAV discovery failed: {}", err);
+                invalid_connection(
+                    "Could not list calendars with these server details and credentials.",
+                )
+            })?;
+
+        if let Some(existing) = CaldavConnections::find_optional_by_user(db, user).await? {
+            existing.into_active_model().delete(db).await?;
+        }
+        let connection = Self {
+            user_id: ActiveValue::Set(user.id),
+            server_url: ActiveValue::Set(server_url),
+            username: ActiveValue::Set(params.username),
+            password: ActiveValue::Set(params.password),
+            ..Default::default()
+        }
+        .insert(db)
+        .await?;
+
+        Ok((connection, calendars))
+    }
+}
+
+// implement your custom finders, selectors oriented logic here
+impl Entity {
+    pub async fn find_by_user<C: ConnectionTrait>(db: &C, user: &users::Model) -> Result<Model> {
+        Self::find_optional_by_user(db, user)
+            .await?
+            .ok_or(Error::NotFound)
+    }
+
+    /// `None` when the user never connected a CalDAV server.
+    pub async fn find_optional_by_user<C: ConnectionTrait>(
+        db: &C,
+        user: &users::Model,
+    ) -> Result<Option<Model>> {
+        Ok(Self::find()
+            .filter(Column::UserId.eq(user.id))
+            .one(db)
+            .await?)
+    }
+
+    /// Moves the password of every CalDAV connection to the newest encryption key, plaintext
+    /// ones included. Returns how many rows changed.
+    pub async fn reencrypt_secrets<C: ConnectionTrait>(db: &C) -> Result<usize> {
+        let mut reencrypted = 0;
+        for caldav_connection in Self::find().all(db).await? {
+            if encryption::is_current(&caldav_connection.password) {
+                continue;
+            }
+
+            let mut active_model = caldav_connection.into_active_model();
+            active_model.password.reset();
+            active_model.update(db).await?;
+            reencrypted += 1;
+        }
+
+        Ok(reencrypted)
+    }
+}
@@ -0,0 +1,246 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Duration, Utc};
+use google_calendar::{
+    types::{Channel, OrderBy},
+    ErrorKind,
+};
+
+pub use super::_entities::google_calendar_channels::{ActiveModel, Entity, Model};
+use crate::models::{
+    _entities::{admin_settings::GoogleCalendarSettings, google_calendar_channels::Column},
+    admin_settings::AdminSettings,
+    google_calendars,
+};
+use loco_rs::{hash, prelude::*};
+use sea_orm::entity::prelude::*;
+pub type GoogleCalendarChannels = Entity;
+
+/// Lifetime asked for new channels, Google may end them sooner.
+const CHANNEL_TTL_IN_DAYS: i64 = 7;
+/// Channels this close to expiring are replaced before Google stops them.
+const RENEWAL_MARGIN_IN_HOURS: i64 = 24;
+const TOKEN_LENGTH: usize = 48;
+const NOTIFICATION_PATH: &str = "/api/google_calendar/notifications";
+
+/// Where Google posts notifications, on the public address the OAuth redirect uses too.
+/// `None` unless that address is HTTPS, the only kind Google delivers to.
+fn notification_address(settings: &GoogleCalendarSettings) -> Option<url::Url> {
+    let mut address = settings.google_oauth_redirect_uri_base.clone();
+    if address.scheme() != "https" {
+        return None;
+    }
+    address.set_path(NOTIFICATION_PATH);
+    Some(address)
+}
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    async fn before_save<C>(self, _db: &C, insert: bool) -> std::result::Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        if !insert && self.updated_at.is_unchanged() {
+            let mut this = self;
+            this.updated_at = sea_orm::ActiveValue::Set(chrono::Utc::now().into());
+            Ok(this)
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+// implement your read-oriented logic here
+impl Model {
+    /// Tells Google to stop sending notifications and forgets the channel. Channels Google
+    /// already dropped can not be stopped, that is only logged.
+    pub async fn stop<C: ConnectionTrait>(
+        self,
+        db: &C,
+        client: &google_calendar::Client,
+    ) -> Result<()> {
+        let channel = Channel {
+            address: String::new(),
+            expiration: 0,
+            id: self.channel_id.clone(),
+            kind: String::new(),
+            params: String::new(),
+            payload: false,
+            resource_id: self.resource_id.clone(),
+            resource_uri: String::new(),
+            token: String::new(),
+            type_: String::new(),
+        };
+        // Not found when the channel already expired on Google's side.
+        if let Err(err) = client.channels().stop(&channel).await {
+            if err.kind() != ErrorKind::NotFound {
+                tracing::warn!("Could not stop Google channel {}: {}", self.channel_id, err);
+            }
+        }
+
+        self.into_active_model().delete(db).await?;
+        Ok(())
+    }
+}
+
+// implement your write-oriented logic here
+impl ActiveModel {
+    /// Asks Google to post a notification whenever an event of `calendar_id` changes.
+    pub async fn register<C: ConnectionTrait>(
+        db: &C,
+        client: &google_calendar::Client,
+        google_calendar: &google_calendars::Model,
+        calendar_id: &str,
+        address: &url::Url,
+    ) -> Result<Model> {
+        let channel_id = Uuid::new_v4().to_string();
+        let token = hash::random_string(TOKEN_LENGTH);
+        let requested_expiration = Utc::now() + Duration::days(CHANNEL_TTL_IN_DAYS);
+        let channel = Channel {
+            address: address.to_string(),
+            expiration: requested_expiration.timestamp_millis(),
+            id: channel_id.clone(),
+            kind: String::new(),
+            params: String::new(),
+            payload: false,
+            resource_id: String::new(),
+            resource_uri: String::new(),
+            token: token.clone(),
+            type_: "web_hook".to_string(),
+        };
+
+        let registered = client
+            .events()
+            .watch(
+                calendar_id,
+                "",
+                0,
+                0,
+                OrderBy::Noop,
+                "",
+                &[],
+                "",
+                &[],
+                false,
+                false,
+                false,
+                "",
+                "",
+                "",
+                "",
+                &channel,
+            )
+            .await
+            .map_err(google_calendars::api_error)?
+            .body;
+        // Google answers with the expiration it settled on, when it can be read.
+        let expires_at = DateTime::from_timestamp_millis(registered.expiration)
+            .filter(|_| registered.expiration > 0)
+            .unwrap_or(requested_expiration);
+
+        let active_model = Self {
+            google_calendar_id: ActiveValue::Set(google_calendar.id),
+            calendar_id: ActiveValue::Set(calendar_id.to_string()),
+            channel_id: ActiveValue::Set(channel_id),
+            resource_id: ActiveValue::Set(registered.resource_id),
+            token: ActiveValue::Set(token),
+            expires_at: ActiveValue::Set(expires_at.into()),
+            ..Default::default()
+        };
+        Ok(active_model.insert(db).await?)
+    }
+}
+
+// implement your custom finders, selectors oriented logic here
+impl Entity {
+    pub async fn find_by_google_calendar<C: ConnectionTrait>(
+        db: &C,
+        google_calendar: &google_calendars::Model,
+    ) -> Result<Vec<Model>> {
+        Ok(Self::find()
+            .filter(Column::GoogleCalendarId.eq(google_calendar.id))
+            .all(db)
+            .await?)
+    }
+
+    /// The channel a notification came through. Unknown channels and wrong tokens are not
+    /// found, so made up notifications go nowhere.
+    pub async fn find_verified<C: ConnectionTrait>(
+        db: &C,
+        channel_id: &str,
+        token: &str,
+        resource_id: &str,
+    ) -> Result<Model> {
+        Self::find()
+            .filter(Column::ChannelId.eq(channel_id))
+            .one(db)
+            .await?
+            .filter(|channel| channel.token == token && channel.resource_id == resource_id)
+            .ok_or(Error::NotFound)
+    }
+
+    /// Keeps one live channel per collision check calendar of the connection: calendars
+    /// without one get one, channels close to expiring are replaced and those of calendars no
+    /// longer checked are stopped. A broken connection can not reach Google, its channels are
+    /// only forgotten.
+    pub async fn sync<C: ConnectionTrait>(
+        db: &C,
+        google_calendar: &google_calendars::Model,
+    ) -> Result<()> {
+        let existing = Self::find_by_google_calendar(db, google_calendar).await?;
+        if google_calendar.broken_at.is_some() {
+            Self::delete_many()
+                .filter(Column::GoogleCalendarId.eq(google_calendar.id))
+                .exec(db)
+                .await?;
+            return Ok(());
+        }
+
+        let settings = AdminSettings::get_google_calendar_settings(db).await?;
+        let Some(address) = notification_address(&settings) else {
+            return Ok(());
+        };
+        let client = google_calendar.client(db).await?;
+
+        let watched = &google_calendar.calendars_for_collision_check.0;
+        let renew_before = Utc::now() + Duration::hours(RENEWAL_MARGIN_IN_HOURS);
+        let mut covered = HashSet::new();
+        let mut stale = Vec::new();
+        for channel in existing {
+            if watched.contains(&channel.calendar_id)
+                && channel.expires_at.to_utc() > renew_before
+                && covered.insert(channel.calendar_id.clone())
+            {
+                continue;
+            }
+            stale.push(channel);
+        }
+
+        // New channels first, so renewing leaves no gap without notifications.
+        for calendar_id in watched.difference(&covered) {
+            ActiveModel::register(db, &client, google_calendar, calendar_id, &address).await?;
+        }
+        for channel in stale {
+            channel.stop(db, &client).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Stops every channel of the connection, before it is disconnected.
+    pub async fn stop_all<C: ConnectionTrait>(
+        db: &C,
+        google_calendar: &google_calendars::Model,
+    ) -> Result<()> {
+        let channels = Self::find_by_google_calendar(db, google_calendar).await?;
+        if channels.is_empty() {
+            return Ok(());
+        }
+
+        let client = google_calendar.client(db).await?;
+        for channel in channels {
+            channel.stop(db, &client).await?;
+        }
+        Ok(())
+    }
+}
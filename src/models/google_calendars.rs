@@ -2,48 +2,127 @@ use std::collections::HashMap;
 
 pub use super::_entities::google_calendars::{ActiveModel, Entity, Model};
 use crate::{
+    common::encryption,
     controllers::api::integrations::google_calendar::OAuthCallbackQueryParams,
     models::{
-        _entities::{
-            admin_settings::GoogleCalendarSettings, appointments::GoogleCalendarEvent,
-            google_calendars::Column,
-        },
+        _entities::{admin_settings::GoogleCalendarSettings, google_calendars::Column},
         admin_settings::AdminSettings,
-        appointments,
+        google_calendar_channels::GoogleCalendarChannels,
         oauth_states::{self, OAuthStates},
         users::{self, Users},
     },
-    views::{
-        client_facing::AvailabilityWindow,
-        google_calendars::{CalendarSettingParams, CalendarSettingType},
-    },
-};
-use futures::future::{join_all, try_join_all};
-use google_calendar::{
-    types::{
-        ConferenceData, ConferenceSolutionKey, CreateConferenceRequest, Event, EventAttendee,
-        EventDateTime, FreeBusyRequestItem, SendUpdates,
-    },
-    ClientError,
+    views::google_calendars::{CalendarSettingParams, CalendarSettingType},
 };
-use loco_rs::prelude::*;
-use sea_orm::entity::prelude::*;
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+use google_calendar::{ClientError, ErrorKind, RetryPolicy};
+use loco_rs::{controller::ErrorDetail, prelude::*};
+use sea_orm::{entity::prelude::*, sea_query::OnConflict, QueryOrder};
 use serde::{Deserialize, Serialize};
 pub type GoogleCalendars = Entity;
 
+const TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+/// Access tokens this close to expiring are refreshed before use.
+const EXPIRY_MARGIN_IN_SECONDS: i64 = 60;
+
+/// Error for a failed Google Calendar API call, telling apart what the caller can do about it.
+/// Rate limits and server errors only get here once the client's retries ran out.
+pub fn api_error(err: ClientError) -> Error {
+    match err.kind() {
+        ErrorKind::Auth => calendar_disconnected(),
+        ErrorKind::NotFound => Error::NotFound,
+        ErrorKind::RateLimited | ErrorKind::Server => Error::CustomError(
+            StatusCode::SERVICE_UNAVAILABLE,
+            ErrorDetail::new(
+                "google_calendar_unavailable",
+                "Google Calendar is not answering, try again in a moment.",
+            ),
+        ),
+        ErrorKind::Other => Error::wrap(err),
+    }
+}
+
+fn calendar_disconnected() -> Error {
+    Error::CustomError(
+        StatusCode::CONFLICT,
+        ErrorDetail::new(
+            "google_calendar_disconnected",
+            "Google revoked access to the calendar, connect it again.",
+        ),
+    )
+}
+
+fn token_url(settings: &GoogleCalendarSettings) -> String {
+    settings
+        .google_oauth_token_url
+        .as_ref()
+        .map_or_else(|| TOKEN_URL.to_string(), ToString::to_string)
+}
+
+fn expires_at(expires_in: i32) -> chrono::DateTime<Utc> {
+    Utc::now() + Duration::seconds(i64::from(expires_in))
+}
+
+fn api_client(
+    settings: &GoogleCalendarSettings,
+    access_token: String,
+    refresh_token: String,
+) -> Result<google_calendar::Client> {
+    let mut client = google_calendar::Client::new(
+        settings.google_oauth_client_id.clone(),
+        settings.google_oauth_secret.clone(),
+        OAuthUrl::redirect_uri(settings)?,
+        access_token,
+        refresh_token,
+    );
+    if let Some(api_url) = &settings.google_calendar_api_url {
+        client.with_host_override(api_url.as_str().trim_end_matches('/'));
+    }
+    // Bookers wait on most of these calls, a Google that stays down is reported quickly.
+    client.with_retry_policy(RetryPolicy {
+        max_retries: 2,
+        base_delay: std::time::Duration::from_millis(250),
+        max_delay: std::time::Duration::from_secs(2),
+        max_elapsed: std::time::Duration::from_secs(3),
+    });
+    Ok(client)
+}
+
+/// Email of the Google account the tokens belong to, which is the id of its primary calendar.
+/// `None` when Google can not be asked.
+async fn account_email(
+    settings: &GoogleCalendarSettings,
+    tokens: &OAuthTokenResponseSuccess,
+) -> Option<String> {
+    let client = api_client(
+        settings,
+        tokens.access_token.clone(),
+        tokens.refresh_token.clone(),
+    )
+    .ok()?;
+    match client.calendars().get("primary").await {
+        Ok(response) => Some(response.body.id),
+        Err(err) => {
+            tracing::warn!("Could not read the Google account of a new connection: {err}");
+            None
+        }
+    }
+}
+
 #[async_trait::async_trait]
 impl ActiveModelBehavior for ActiveModel {
     async fn before_save<C>(self, _db: &C, insert: bool) -> std::result::Result<Self, DbErr>
     where
         C: ConnectionTrait,
     {
-        if !insert && self.updated_at.is_unchanged() {
-            let mut this = self;
+        let mut this = self;
+        encryption::encrypt_active_value(&mut this.access_token)?;
+        encryption::encrypt_active_value(&mut this.refresh_token)?;
+
+        if !insert && this.updated_at.is_unchanged() {
             this.updated_at = sea_orm::ActiveValue::Set(chrono::Utc::now().into());
-            Ok(this)
-        } else {
-            Ok(self)
         }
+        Ok(this)
     }
 }
 
@@ -81,7 +160,7 @@ impl OAuthUrl {
             .append_pair("state", &csrf_token.to_string())
             .append_pair("include_granted_scopes", "true")
             .append_pair("enable_granular_consent", "false")
-            .append_pair("prompt", "consent");
+            .append_pair("prompt", "consent select_account");
 
         Ok(Self(oauth_url))
     }
@@ -139,6 +218,7 @@ pub struct OAuthTokenResponseSuccess {
 #[derive(Debug, Deserialize)]
 pub struct OAuthTokenResponseError {
     pub error: String,
+    #[serde(default)]
     pub error_description: String,
 }
 
@@ -149,6 +229,20 @@ pub enum OAuthTokenResponse {
     Err(OAuthTokenResponseError),
 }
 
+/// A refresh grant returns a new access token, the refresh token stays the same.
+#[derive(Debug, Deserialize)]
+pub struct OAuthRefreshResponseSuccess {
+    pub access_token: String,
+    pub expires_in: i32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum OAuthRefreshResponse {
+    Ok(OAuthRefreshResponseSuccess),
+    Err(OAuthTokenResponseError),
+}
+
 // implement your read-oriented logic here
 impl Model {
     pub async fn generate_oauth_url(ctx: &AppContext, user: &users::Model) -> Result<OAuthUrl> {
@@ -158,13 +252,91 @@ impl Model {
         OAuthUrl::new(oauth_state.id, &google_calendar_settings)
     }
 
+    /// Client with a valid access token. An expiring token is refreshed first and stored, so
+    /// the refresh happens once per token lifetime instead of on every call. A refresh token
+    /// Google revoked marks the connection as broken, the owner is told by
+    /// [`crate::workers::notify_broken_calendars`].
+    pub async fn client<C: ConnectionTrait>(&self, db: &C) -> Result<google_calendar::Client> {
+        if self.broken_at.is_some() {
+            return Err(calendar_disconnected());
+        }
+
+        let google_calendar_settings = AdminSettings::get_google_calendar_settings(db).await?;
+        let refresh_token = encryption::decrypt(&self.refresh_token)?;
+
+        if self.expires_at.is_some_and(|expires_at| {
+            expires_at.to_utc() - Duration::seconds(EXPIRY_MARGIN_IN_SECONDS) > Utc::now()
+        }) {
+            return api_client(
+                &google_calendar_settings,
+                encryption::decrypt(&self.access_token)?,
+                refresh_token,
+            );
+        }
+
+        let response = reqwest::Client::new()
+            .post(token_url(&google_calendar_settings))
+            .form(&[
+                (
+                    "client_id",
+                    google_calendar_settings.google_oauth_client_id.as_str(),
+                ),
+                (
+                    "client_secret",
+                    google_calendar_settings.google_oauth_secret.as_str(),
+                ),
+                ("grant_type", "refresh_token"),
+                ("refresh_token", refresh_token.as_str()),
+            ])
+            .send()
+            .await
+            .map_err(Error::wrap)?
+            .json::<OAuthRefreshResponse>()
+            .await
+            .map_err(Error::wrap)?;
+
+        let tokens = match response {
+            OAuthRefreshResponse::Ok(tokens) => tokens,
+            OAuthRefreshResponse::Err(err) if err.error == "invalid_grant" => {
+                tracing::warn!(
+                    "Google refresh token of user {} was revoked: {}",
+                    self.user_id,
+                    err.error_description
+                );
+                let mut active_model = self.clone().into_active_model();
+                active_model.broken_at = ActiveValue::Set(Some(Utc::now().into()));
+                active_model.update(db).await?;
+                return Err(calendar_disconnected());
+            }
+            OAuthRefreshResponse::Err(err) => {
+                return Err(Error::Message(format!(
+                    "Could not refresh Google access token: {} {}",
+                    err.error, err.error_description
+                )));
+            }
+        };
+
+        let mut active_model = self.clone().into_active_model();
+        active_model.access_token = ActiveValue::Set(tokens.access_token.clone());
+        active_model.expires_in = ActiveValue::Set(tokens.expires_in);
+        active_model.expires_at = ActiveValue::Set(Some(expires_at(tokens.expires_in).into()));
+        active_model.update(db).await?;
+
+        api_client(
+            &google_calendar_settings,
+            tokens.access_token,
+            refresh_token,
+        )
+    }
+
     pub async fn exchange_code_for_token(
         ctx: &AppContext,
         query_params: OAuthCallbackQueryParams,
     ) -> Result<String> {
         let token_request = OAuthTokenRequest::new(ctx, query_params.code).await?;
+        let google_calendar_settings = AdminSettings::get_google_calendar_settings(&ctx.db).await?;
         let response = reqwest::Client::new()
-            .post("https://oauth2.googleapis.com/token")
+            .post(token_url(&google_calendar_settings))
             .json(&token_request)
             .send()
             .await
@@ -184,7 +356,17 @@ impl Model {
                             )
                         })?;
                 let user = Users::find_by_id(&ctx.db, oauth_state.user_id).await?;
-                ActiveModel::create(&ctx.db, auth_success_response, user).await?;
+                // Without the account a reconnect could not find its connection and would add
+                // another one.
+                let Some(account_email) =
+                    account_email(&google_calendar_settings, &auth_success_response).await
+                else {
+                    return Ok(
+                        "/dashboard/integrations?error=account_unknown&description=Could%20not%20read%20the%20Google%20account,%20try%20again."
+                            .to_string(),
+                    );
+                };
+                ActiveModel::create(&ctx.db, auth_success_response, user, account_email).await?;
 
                 Ok("/dashboard/integrations?success".to_string())
             }
@@ -244,8 +426,13 @@ impl Model {
     }
 
     pub async fn revoke_and_delete_token<C: ConnectionTrait>(self, db: &C) -> Result<()> {
+        if let Err(err) = GoogleCalendarChannels::stop_all(db, &self).await {
+            tracing::warn!("Could not stop the Google channels of {}: {}", self.id, err);
+        }
+
+        let access_token = encryption::decrypt(&self.access_token)?;
         let mut query = HashMap::new();
-        query.insert("token", self.access_token.as_str());
+        query.insert("token", access_token.as_str());
         let empty_hash: HashMap<&str, &str> = HashMap::new();
 
         reqwest::Client::new()
@@ -260,237 +447,138 @@ impl Model {
 
         Ok(())
     }
-
-    pub async fn get_free_busy<C: ConnectionTrait>(
-        db: &C,
-        user: &users::Model,
-        time_min: DateTimeUtc,
-        time_max: DateTimeUtc,
-    ) -> Result<Vec<AvailabilityWindow>> {
-        let google_calendar_settings = AdminSettings::get_google_calendar_settings(db).await?;
-        let google_calendar_config = GoogleCalendars::find_by_user(db, user).await?;
-        let redirect_url = OAuthUrl::redirect_uri(&google_calendar_settings)?;
-
-        let client = google_calendar::Client::new(
-            google_calendar_settings.google_oauth_client_id,
-            google_calendar_settings.google_oauth_secret,
-            redirect_url,
-            google_calendar_config.access_token,
-            google_calendar_config.refresh_token,
-        );
-
-        let _access_token = client.refresh_access_token().await.map_err(Error::wrap)?;
-
-        let items = google_calendar_config
-            .calendars_for_collision_check
-            .0
-            .into_iter()
-            .map(|id| FreeBusyRequestItem { id })
-            .collect();
-
-        let query = google_calendar::types::FreeBusyRequest {
-            items,
-            time_min: Some(time_min),
-            time_max: Some(time_max),
-            calendar_expansion_max: 0,
-            group_expansion_max: 0,
-            time_zone: String::new(),
-        };
-
-        let free_busy_response = client.freebusy().query(&query).await.map_err(Error::wrap)?;
-        let availability_windows = free_busy_response
-            .body
-            .calendars
-            .into_iter()
-            .flat_map(|cal| {
-                cal.1.busy.into_iter().map(|x| AvailabilityWindow {
-                    start: x.start,
-                    end: x.end,
-                })
-            })
-            .filter(|window| window.start != window.end)
-            .collect();
-        Ok(availability_windows)
-    }
-
-    pub async fn create_calendars_event<C: ConnectionTrait>(
-        db: &C,
-        user: &users::Model,
-        appointment: &appointments::Model,
-    ) -> Result<Vec<GoogleCalendarEvent>> {
-        let google_calendar_settings = AdminSettings::get_google_calendar_settings(db).await?;
-        let google_calendar_config = GoogleCalendars::find_by_user(db, user).await?;
-        let redirect_url = OAuthUrl::redirect_uri(&google_calendar_settings)?;
-
-        let client = google_calendar::Client::new(
-            google_calendar_settings.google_oauth_client_id,
-            google_calendar_settings.google_oauth_secret,
-            redirect_url,
-            google_calendar_config.access_token,
-            google_calendar_config.refresh_token,
-        );
-
-        let _access_token = client.refresh_access_token().await.map_err(Error::wrap)?;
-        let body = appointment.body(db).await?;
-
-        let event = Event {
-            summary: body,
-            description: String::new(),
-            attendees: vec![
-                EventAttendee {
-                    email: user.email.clone(),
-                    response_status: "accepted".to_string(),
-                    additional_guests: 0,
-                    comment: String::new(),
-                    display_name: String::new(),
-                    id: String::new(),
-                    optional: false,
-                    organizer: true,
-                    resource: false,
-                    self_: false,
-                },
-                EventAttendee {
-                    email: appointment.booker_email.clone(),
-                    response_status: "accepted".to_string(),
-                    additional_guests: 0,
-                    comment: String::new(),
-                    display_name: String::new(),
-                    id: String::new(),
-                    optional: false,
-                    organizer: false,
-                    resource: false,
-                    self_: false,
-                },
-            ],
-            start: Some(EventDateTime {
-                date: None,
-                time_zone: String::new(),
-                date_time: Some(appointment.start_time.to_utc()),
-            }),
-            end: Some(EventDateTime {
-                date: None,
-                time_zone: String::new(),
-                date_time: Some(appointment.endtime.to_utc()),
-            }),
-            conference_data: Some(ConferenceData {
-                conference_solution: None,
-                conference_id: Uuid::new_v4().into(),
-                create_request: Some(CreateConferenceRequest {
-                    conference_solution_key: Some(ConferenceSolutionKey {
-                        type_: "hangoutsMeet".to_string(),
-                    }),
-                    request_id: Uuid::new_v4().into(),
-                    status: None,
-                }),
-                entry_points: vec![],
-                notes: String::new(),
-                parameters: None,
-                signature: String::new(),
-            }),
-            ..Default::default()
-        };
-
-        let futures = google_calendar_config
-            .calendars_for_event_handling
-            .0
-            .into_iter()
-            .map(|calendar_id| {
-                let client = client.clone();
-                let event = event.clone();
-                async move {
-                    let event = client
-                        .events()
-                        .insert(&calendar_id, 1, 0, false, SendUpdates::All, false, &event)
-                        .await?;
-                    Ok::<_, ClientError>(GoogleCalendarEvent {
-                        calendar_id,
-                        event_id: event.body.id,
-                    })
-                }
-            })
-            .collect::<Vec<_>>();
-
-        try_join_all(futures).await.map_err(Error::wrap)
-    }
-
-    pub async fn delete_calendar_events<C: ConnectionTrait>(
-        db: &C,
-        user: &users::Model,
-        events: Vec<GoogleCalendarEvent>,
-    ) -> Result<()> {
-        let google_calendar_settings = AdminSettings::get_google_calendar_settings(db).await?;
-        let google_calendar_config = GoogleCalendars::find_by_user(db, user).await?;
-        let redirect_url = OAuthUrl::redirect_uri(&google_calendar_settings)?;
-
-        let client = google_calendar::Client::new(
-            google_calendar_settings.google_oauth_client_id,
-            google_calendar_settings.google_oauth_secret,
-            redirect_url,
-            google_calendar_config.access_token,
-            google_calendar_config.refresh_token,
-        );
-        let _access_token = client.refresh_access_token().await.map_err(Error::wrap)?;
-
-        let futures = events
-            .into_iter()
-            .map(|event| {
-                let client = client.clone();
-                async move {
-                    client
-                        .events()
-                        .delete(&event.calendar_id, &event.event_id, true, SendUpdates::All)
-                        .await?;
-                    Ok::<_, ClientError>(())
-                }
-            })
-            .collect::<Vec<_>>();
-
-        join_all(futures)
-            .await
-            .into_iter()
-            .filter_map(std::result::Result::err)
-            .for_each(|e| {
-                tracing::error!("Error deleting event: {}", e);
-            });
-
-        Ok(())
-    }
 }
 
 // implement your write-oriented logic here
 impl ActiveModel {
+    /// Adds a connection for `user`. Connecting an account the user already connected replaces
+    /// its tokens and keeps the calendars picked for it.
     pub async fn create<C: ConnectionTrait>(
         db: &C,
         props: OAuthTokenResponseSuccess,
         user: users::Model,
+        account_email: String,
     ) -> Result<Model> {
-        if let Ok(existing_google_calendar) = GoogleCalendars::find_by_user(db, &user).await {
-            existing_google_calendar
-                .into_active_model()
-                .delete(db)
-                .await?;
-        }
-
         let active_model = Self {
+            user_id: ActiveValue::Set(user.id),
+            account_email: ActiveValue::Set(Some(account_email.clone())),
             access_token: ActiveValue::Set(props.access_token),
             expires_in: ActiveValue::Set(props.expires_in),
+            expires_at: ActiveValue::Set(Some(expires_at(props.expires_in).into())),
             refresh_token: ActiveValue::Set(props.refresh_token),
             scope: ActiveValue::Set(props.scope),
             token_type: ActiveValue::Set(props.token_type),
-            user_id: ActiveValue::Set(user.id),
+            broken_at: ActiveValue::Set(None),
+            broken_notified_at: ActiveValue::Set(None),
+            updated_at: ActiveValue::Set(chrono::Utc::now().into()),
             ..Default::default()
-        };
-        Ok(active_model.insert(db).await?)
+        }
+        // An upsert skips `before_save`, its tokens are encrypted here.
+        .before_save(db, true)
+        .await?;
+
+        // One statement, so overlapping callbacks for the same account can not both insert.
+        Entity::insert(active_model)
+            .on_conflict(
+                OnConflict::columns([Column::UserId, Column::AccountEmail])
+                    .update_columns([
+                        Column::AccessToken,
+                        Column::ExpiresIn,
+                        Column::ExpiresAt,
+                        Column::RefreshToken,
+                        Column::Scope,
+                        Column::TokenType,
+                        Column::BrokenAt,
+                        Column::BrokenNotifiedAt,
+                        Column::UpdatedAt,
+                    ])
+                    .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await?;
+
+        // SQLite reports no id for a conflicting insert, the row is looked up by its account.
+        Entity::find()
+            .filter(Column::UserId.eq(user.id))
+            .filter(Column::AccountEmail.eq(account_email))
+            .one(db)
+            .await?
+            .ok_or(Error::Model(ModelError::EntityNotFound))
     }
 }
 
 // implement your custom finders, selectors oriented logic here
 impl Entity {
-    pub async fn find_by_user<C: ConnectionTrait>(db: &C, user: &users::Model) -> Result<Model> {
-        let query = Self::find().filter(Column::UserId.eq(user.id));
-        query
+    /// Every Google account the user connected, oldest first.
+    pub async fn find_by_user<C: ConnectionTrait>(
+        db: &C,
+        user: &users::Model,
+    ) -> Result<Vec<Model>> {
+        Ok(Self::find()
+            .filter(Column::UserId.eq(user.id))
+            .order_by_asc(Column::Id)
+            .all(db)
+            .await?)
+    }
+
+    pub async fn find_by_user_and_id<C: ConnectionTrait>(
+        db: &C,
+        user: &users::Model,
+        id: i32,
+    ) -> Result<Model> {
+        Self::find_by_id(id)
+            .filter(Column::UserId.eq(user.id))
             .one(db)
             .await?
-            .ok_or(Error::Model(ModelError::EntityNotFound))
+            .ok_or(Error::NotFound)
+    }
+
+    /// Connections marked as broken whose owner was not emailed yet.
+    pub async fn find_broken_unnotified<C: ConnectionTrait>(db: &C) -> Result<Vec<Model>> {
+        Ok(Self::find()
+            .filter(Column::BrokenAt.is_not_null())
+            .filter(Column::BrokenNotifiedAt.is_null())
+            .all(db)
+            .await?)
+    }
+
+    /// Marks the owner of a broken connection as notified. `false` when another run got to it
+    /// first, so every owner is emailed once.
+    pub async fn claim_broken_notification<C: ConnectionTrait>(
+        db: &C,
+        google_calendar: &Model,
+    ) -> Result<bool> {
+        let claimed = Self::update_many()
+            .col_expr(
+                Column::BrokenNotifiedAt,
+                Expr::value(DateTimeWithTimeZone::from(Utc::now())),
+            )
+            .filter(Column::Id.eq(google_calendar.id))
+            .filter(Column::BrokenNotifiedAt.is_null())
+            .exec(db)
+            .await?;
+
+        Ok(claimed.rows_affected == 1)
+    }
+
+    /// Moves the tokens of every Google connection to the newest encryption key, plaintext
+    /// ones included. Returns how many rows changed.
+    pub async fn reencrypt_secrets<C: ConnectionTrait>(db: &C) -> Result<usize> {
+        let mut reencrypted = 0;
+        for google_calendar in Self::find().all(db).await? {
+            if encryption::is_current(&google_calendar.access_token)
+                && encryption::is_current(&google_calendar.refresh_token)
+            {
+                continue;
+            }
+
+            let mut active_model = google_calendar.into_active_model();
+            active_model.access_token.reset();
+            active_model.refresh_token.reset();
+            active_model.update(db).await?;
+            reencrypted += 1;
+        }
+
+        Ok(reencrypted)
     }
 }
@@ -0,0 +1,181 @@
+use chrono::{DateTime, Duration, Utc};
+
+pub use super::_entities::outbox_jobs::{ActiveModel, Entity, Model, OutboxJobKind};
+use crate::{
+    mailers::appointments::AppointmentsMailer,
+    models::{
+        _entities::{appointments::Status, outbox_jobs::Column},
+        appointments::{self, Appointments},
+        users::Users,
+        webhook_endpoints::{WebhookEndpoints, WebhookEvent},
+    },
+    our_chrono,
+};
+use loco_rs::prelude::*;
+use sea_orm::{entity::prelude::*, QueryOrder};
+pub type OutboxJobs = Entity;
+
+/// Jobs that keep failing are given up on after this many attempts.
+pub const MAX_ATTEMPTS: i32 = 8;
+/// How long a claimed job is left alone before another worker may pick it up again.
+const LEASE_IN_MINUTES: i64 = 5;
+
+/// Wait before the next attempt, doubling with every failure up to about an hour.
+fn backoff(attempts: i32) -> Duration {
+    Duration::minutes(1 << attempts.clamp(1, 6))
+}
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    async fn before_save<C>(self, _db: &C, insert: bool) -> std::result::Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        if !insert && self.updated_at.is_unchanged() {
+            let mut this = self;
+            this.updated_at = sea_orm::ActiveValue::Set(chrono::Utc::now().into());
+            Ok(this)
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+// implement your read-oriented logic here
+impl Model {
+    /// Runs the side effect. Every kind is safe to run again after a partial failure, and skips
+    /// itself when the appointment moved on in the meantime.
+    pub async fn perform(&self, ctx: &AppContext) -> Result<()> {
+        let db = &ctx.db;
+        let appointment = Appointments::find_by_id(self.appointment_id)
+            .one(db)
+            .await?
+            .ok_or(Error::NotFound)?;
+        if !Status::HOLDING_SLOT.contains(&appointment.status) {
+            return Ok(());
+        }
+
+        match self.kind {
+            OutboxJobKind::AddToCalendars => {
+                if appointment.status != Status::Booked {
+                    return Ok(());
+                }
+                let user = Users::find_by_id(db, appointment.user_id).await?;
+                appointment.try_add_to_calendars(db, &user).await?;
+            }
+            OutboxJobKind::NotifyBooker => {
+                AppointmentsMailer::send_notification_to_booker(ctx, &appointment).await?;
+            }
+            OutboxJobKind::NotifyOwner => {
+                AppointmentsMailer::send_notification_to_user(ctx, &appointment).await?;
+            }
+            OutboxJobKind::NotifyWebhooks => {
+                WebhookEndpoints::publish(ctx, &appointment, WebhookEvent::AppointmentBooked)
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// implement your write-oriented logic here
+impl ActiveModel {
+    /// Queues the side effects of an appointment. Call it with the transaction that writes the
+    /// appointment, so they exist exactly when the appointment does.
+    pub async fn enqueue<C>(
+        db: &C,
+        appointment: &appointments::Model,
+        kinds: &[OutboxJobKind],
+    ) -> ModelResult<()>
+    where
+        C: ConnectionTrait,
+    {
+        let now = our_chrono::utc_now();
+        for kind in kinds {
+            Self {
+                appointment_id: ActiveValue::set(appointment.id),
+                kind: ActiveValue::set(*kind),
+                attempts: ActiveValue::set(0),
+                run_at: ActiveValue::set(now.into()),
+                ..Default::default()
+            }
+            .insert(db)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn complete<C>(mut self, db: &C) -> ModelResult<Model>
+    where
+        C: ConnectionTrait,
+    {
+        self.completed_at = ActiveValue::set(Some(our_chrono::utc_now().into()));
+        self.last_error = ActiveValue::set(None);
+
+        Ok(self.update(db).await?)
+    }
+
+    /// Records the failure and schedules the next attempt.
+    pub async fn fail<C>(mut self, db: &C, error: &str) -> ModelResult<Model>
+    where
+        C: ConnectionTrait,
+    {
+        let attempts = self.attempts.as_ref() + 1;
+        self.attempts = ActiveValue::set(attempts);
+        self.last_error = ActiveValue::set(Some(error.to_string()));
+        self.run_at = ActiveValue::set((our_chrono::utc_now() + backoff(attempts)).into());
+
+        Ok(self.update(db).await?)
+    }
+}
+
+// implement your custom finders, selectors oriented logic here
+impl Entity {
+    /// Unfinished jobs whose time has come, oldest first.
+    pub async fn find_due<C>(db: &C, now: DateTime<Utc>) -> ModelResult<Vec<Model>>
+    where
+        C: ConnectionTrait,
+    {
+        Ok(Self::find()
+            .filter(Column::CompletedAt.is_null())
+            .filter(Column::Attempts.lt(MAX_ATTEMPTS))
+            .filter(Column::RunAt.lte(now))
+            .order_by_asc(Column::RunAt)
+            .all(db)
+            .await?)
+    }
+
+    /// Leases the job to the caller by pushing its `run_at` past `now`, returning `false` when
+    /// another worker got to it first.
+    pub async fn claim<C>(db: &C, job: &Model, now: DateTime<Utc>) -> ModelResult<bool>
+    where
+        C: ConnectionTrait,
+    {
+        let leased_until: DateTimeWithTimeZone = (now + Duration::minutes(LEASE_IN_MINUTES)).into();
+        let claimed = Self::update_many()
+            .col_expr(Column::RunAt, Expr::value(leased_until))
+            .filter(Column::Id.eq(job.id))
+            .filter(Column::RunAt.lte(now))
+            .filter(Column::CompletedAt.is_null())
+            .exec(db)
+            .await?;
+
+        Ok(claimed.rows_affected == 1)
+    }
+
+    pub async fn find_by_appointment<C>(
+        db: &C,
+        appointment: &appointments::Model,
+    ) -> ModelResult<Vec<Model>>
+    where
+        C: ConnectionTrait,
+    {
+        Ok(Self::find()
+            .filter(Column::AppointmentId.eq(appointment.id))
+            .order_by_asc(Column::Id)
+            .all(db)
+            .await?)
+    }
+}
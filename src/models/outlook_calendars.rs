@@ -0,0 +1,285 @@
+pub use super::_entities::outlook_calendars::{ActiveModel, Entity, Model};
+use crate::{
+    common::{encryption, microsoft_graph},
+    models::{
+        _entities::{admin_settings::OutlookCalendarSettings, outlook_calendars::Column},
+        admin_settings::AdminSettings,
+        google_calendars::{OAuthTokenResponse, OAuthTokenResponseSuccess},
+        oauth_states::{self, OAuthStates},
+        users::{self, Users},
+    },
+    views::google_calendars::{CalendarSettingParams, CalendarSettingType},
+};
+use chrono::{Duration, Utc};
+use loco_rs::prelude::*;
+use sea_orm::entity::prelude::*;
+pub type OutlookCalendars = Entity;
+
+const AUTHORIZE_URL: &str = "https://login.microsoftonline.com/common/oauth2/v2.0/authorize";
+const TOKEN_URL: &str = "https://login.microsoftonline.com/common/oauth2/v2.0/token";
+const REDIRECT_URI_PATH: &str = "/api/outlook_calendar/oauth_callback";
+const SCOPE: &str = "offline_access User.Read Calendars.ReadWrite";
+/// Access tokens this close to expiring are refreshed before use.
+const EXPIRY_MARGIN_IN_SECONDS: i64 = 60;
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    async fn before_save<C>(self, _db: &C, insert: bool) -> std::result::Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        let mut this = self;
+        encryption::encrypt_active_value(&mut this.access_token)?;
+        encryption::encrypt_active_value(&mut this.refresh_token)?;
+
+        if !insert && this.updated_at.is_unchanged() {
+            this.updated_at = sea_orm::ActiveValue::Set(chrono::Utc::now().into());
+        }
+        Ok(this)
+    }
+}
+
+fn redirect_uri(settings: &OutlookCalendarSettings) -> url::Url {
+    let mut redirect_uri = settings.outlook_oauth_redirect_uri_base.clone();
+    redirect_uri.set_path(REDIRECT_URI_PATH);
+    redirect_uri
+}
+
+/// Posts a token request to the Microsoft identity platform, which wants a form body.
+async fn request_token(
+    settings: &OutlookCalendarSettings,
+    grant: &[(&str, &str)],
+) -> Result<OAuthTokenResponse> {
+    let redirect_uri = redirect_uri(settings);
+    let mut form = vec![
+        ("client_id", settings.outlook_oauth_client_id.as_str()),
+        ("client_secret", settings.outlook_oauth_secret.as_str()),
+        ("redirect_uri", redirect_uri.as_str()),
+        ("scope", SCOPE),
+    ];
+    form.extend_from_slice(grant);
+
+    reqwest::Client::new()
+        .post(TOKEN_URL)
+        .form(&form)
+        .send()
+        .await
+        .map_err(Error::wrap)?
+        .json::<OAuthTokenResponse>()
+        .await
+        .map_err(Error::wrap)
+}
+
+// implement your read-oriented logic here
+impl Model {
+    pub async fn generate_oauth_url(ctx: &AppContext, user: &users::Model) -> Result<url::Url> {
+        let oauth_state = oauth_states::ActiveModel::create(&ctx.db, user).await?;
+        let settings = AdminSettings::get_outlook_calendar_settings(&ctx.db).await?;
+
+        let mut oauth_url = url::Url::parse(AUTHORIZE_URL).map_err(Error::wrap)?;
+        oauth_url
+            .query_pairs_mut()
+            .append_pair("client_id", &settings.outlook_oauth_client_id)
+            .append_pair("redirect_uri", redirect_uri(&settings).as_str())
+            .append_pair("response_type", "code")
+            .append_pair("response_mode", "query")
+            .append_pair("scope", SCOPE)
+            .append_pair("state", &oauth_state.id.to_string())
+            .append_pair("prompt", "select_account");
+
+        Ok(oauth_url)
+    }
+
+    pub async fn exchange_code_for_token(
+        ctx: &AppContext,
+        code: &str,
+        state: &Uuid,
+    ) -> Result<String> {
+        let settings = AdminSettings::get_outlook_calendar_settings(&ctx.db).await?;
+        let response = request_token(
+            &settings,
+            &[("grant_type", "authorization_code"), ("code", code)],
+        )
+        .await?;
+
+        match response {
+            OAuthTokenResponse::Ok(auth_success_response) => {
+                let oauth_state = OAuthStates::find_by_uuid_and_destroy(&ctx.db, state)
+                    .await
+                    .map_err(|_| {
+                        Error::Unauthorized(
+                            "CSRF token not found. May be a CSRF attack".to_string(),
+                        )
+                    })?;
+                let user = Users::find_by_id(&ctx.db, oauth_state.user_id).await?;
+                ActiveModel::create(&ctx.db, auth_success_response, user).await?;
+
+                Ok("/dashboard/integrations?success".to_string())
+            }
+            OAuthTokenResponse::Err(auth_token_err_response) => {
+                tracing::error!("Outlook OAuth error: {:?}", auth_token_err_response);
+
+                Ok(format!(
+                    "/dashboard/integrations?error={}&description={}",
+                    auth_token_err_response.error, auth_token_err_response.error_description
+                ))
+            }
+        }
+    }
+
+    /// Graph client with a valid access token. An expiring token is refreshed first and the new
+    /// tokens are stored, Microsoft rotates the refresh token on every use.
+    pub async fn client<C: ConnectionTrait>(&self, db: &C) -> Result<microsoft_graph::Client> {
+        let settings = AdminSettings::get_outlook_calendar_settings(db).await?;
+        let base_url = match &settings.graph_base_url {
+            Some(base_url) => base_url.clone(),
+            None => url::Url::parse(microsoft_graph::DEFAULT_BASE_URL).map_err(Error::wrap)?,
+        };
+
+        if self.expires_at.to_utc() - Duration::seconds(EXPIRY_MARGIN_IN_SECONDS) > Utc::now() {
+            return microsoft_graph::Client::new(
+                &base_url,
+                &encryption::decrypt(&self.access_token)?,
+            );
+        }
+        let refresh_token = encryption::decrypt(&self.refresh_token)?;
+
+        let response = request_token(
+            &settings,
+            &[
+                ("grant_type", "refresh_token"),
+                ("refresh_token", &refresh_token),
+            ],
+        )
+        .await?;
+        let tokens = match response {
+            OAuthTokenResponse::Ok(tokens) => tokens,
+            OAuthTokenResponse::Err(err) => {
+                return Err(Error::Message(format!(
+                    "Could not refresh Outlook access token: {} {}",
+                    err.error, err.error_description
+                )));
+            }
+        };
+
+        let mut active_model = self.clone().into_active_model();
+        active_model.access_token = ActiveValue::Set(tokens.access_token.clone());
+        active_model.refresh_token = ActiveValue::Set(tokens.refresh_token);
+        active_model.expires_at = ActiveValue::Set(expires_at(tokens.expires_in).into());
+        active_model.update(db).await?;
+
+        microsoft_graph::Client::new(&base_url, &tokens.access_token)
+    }
+
+    pub async fn add_calendar_to_settings<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        params: CalendarSettingParams,
+    ) -> Result<Self> {
+        let mut active_model = self.clone().into_active_model();
+        match params.setting_type {
+            CalendarSettingType::CollisionCheck => {
+                let mut cloned = self.calendars_for_collision_check.clone();
+                cloned.add(params.calendar_id);
+                active_model.calendars_for_collision_check = ActiveValue::Set(cloned);
+            }
+            CalendarSettingType::EventHandling => {
+                let mut cloned = self.calendars_for_event_handling.clone();
+                cloned.add(params.calendar_id);
+                active_model.calendars_for_event_handling = ActiveValue::Set(cloned);
+            }
+        }
+
+        Ok(active_model.update(db).await?)
+    }
+
+    pub async fn remove_calendar_from_settings<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        params: CalendarSettingParams,
+    ) -> Result<Self> {
+        let mut active_model = self.clone().into_active_model();
+        match params.setting_type {
+            CalendarSettingType::CollisionCheck => {
+                let mut cloned = self.calendars_for_collision_check.clone();
+                cloned.remove(&params.calendar_id);
+                active_model.calendars_for_collision_check = ActiveValue::Set(cloned);
+            }
+            CalendarSettingType::EventHandling => {
+                let mut cloned = self.calendars_for_event_handling.clone();
+                cloned.remove(&params.calendar_id);
+                active_model.calendars_for_event_handling = ActiveValue::Set(cloned);
+            }
+        }
+
+        Ok(active_model.update(db).await?)
+    }
+}
+
+fn expires_at(expires_in: i32) -> chrono::DateTime<Utc> {
+    Utc::now() + Duration::seconds(i64::from(expires_in))
+}
+
+// implement your write-oriented logic here
+impl ActiveModel {
+    pub async fn create<C: ConnectionTrait>(
+        db: &C,
+        props: OAuthTokenResponseSuccess,
+        user: users::Model,
+    ) -> Result<Model> {
+        if let Some(existing) = OutlookCalendars::find_optional_by_user(db, &user).await? {
+            existing.into_active_model().delete(db).await?;
+        }
+
+        let active_model = Self {
+            access_token: ActiveValue::Set(props.access_token),
+            refresh_token: ActiveValue::Set(props.refresh_token),
+            expires_at: ActiveValue::Set(expires_at(props.expires_in).into()),
+            scope: ActiveValue::Set(props.scope),
+            user_id: ActiveValue::Set(user.id),
+            ..Default::default()
+        };
+        Ok(active_model.insert(db).await?)
+    }
+}
+
+// implement your custom finders, selectors oriented logic here
+impl Entity {
+    pub async fn find_by_user<C: ConnectionTrait>(db: &C, user: &users::Model) -> Result<Model> {
+        Self::find_optional_by_user(db, user)
+            .await?
+            .ok_or(Error::NotFound)
+    }
+
+    /// `None` when the user never connected Outlook.
+    pub async fn find_optional_by_user<C: ConnectionTrait>(
+        db: &C,
+        user: &users::Model,
+    ) -> Result<Option<Model>> {
+        Ok(Self::find()
+            .filter(Column::UserId.eq(user.id))
+            .one(db)
+            .await?)
+    }
+
+    /// Moves the tokens of every Outlook connection to the newest encryption key, plaintext
+    /// ones included. Returns how many rows changed.
+    pub async fn reencrypt_secrets<C: ConnectionTrait>(db: &C) -> Result<usize> {
+        let mut reencrypted = 0;
+        for outlook_calendar in Self::find().all(db).await? {
+            if encryption::is_current(&outlook_calendar.access_token)
+                && encryption::is_current(&outlook_calendar.refresh_token)
+            {
+                continue;
+            }
+
+            let mut active_model = outlook_calendar.into_active_model();
+            active_model.access_token.reset();
+            active_model.refresh_token.reset();
+            active_model.update(db).await?;
+            reencrypted += 1;
+        }
+
+        Ok(reencrypted)
+    }
+}
@@ -0,0 +1,211 @@
+pub use super::_entities::schedules::{ActiveModel, Entity, Model};
+use crate::models::{
+    _entities::{appointment_types, schedules::Column, weekly_availabilities},
+    users::users,
+};
+use loco_rs::prelude::*;
+use sea_orm::{entity::prelude::*, sea_query::Expr, QueryOrder, TransactionTrait};
+use serde::Deserialize;
+pub type Schedules = Entity;
+
+pub const DEFAULT_SCHEDULE_NAME: &str = "Default";
+
+#[derive(Debug, Validate, Deserialize)]
+pub struct Validator {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+}
+
+impl Validatable for ActiveModel {
+    fn validator(&self) -> Box<dyn Validate> {
+        Box::new(Validator {
+            name: self.name.as_ref().to_owned(),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    async fn before_save<C>(self, _db: &C, insert: bool) -> std::result::Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        self.validate()?;
+        if !insert && self.updated_at.is_unchanged() {
+            let mut this = self;
+            this.updated_at = sea_orm::ActiveValue::Set(chrono::Utc::now().into());
+            Ok(this)
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+// implement your read-oriented logic here
+impl Model {
+    /// The user's default schedule, created on first use.
+    pub async fn get_or_create_default<C: ConnectionTrait>(
+        db: &C,
+        user: &users::Model,
+    ) -> ModelResult<Self> {
+        if let Some(schedule) = Schedules::find_default_by_user(db, user).await? {
+            Ok(schedule)
+        } else {
+            ActiveModel::create(
+                db,
+                CreateOrUpdateSchedule {
+                    name: DEFAULT_SCHEDULE_NAME.to_string(),
+                    is_default: true,
+                    user,
+                },
+            )
+            .await
+        }
+    }
+
+    /// Deletes the schedule and its windows, appointment types using it fall back
+    /// to the default schedule.
+    pub async fn destroy(self, db: &DatabaseConnection) -> ModelResult<()> {
+        if self.is_default {
+            return Err(ModelError::msg("The default schedule can not be deleted."));
+        }
+
+        let txn = db.begin().await?;
+        weekly_availabilities::Entity::delete_many()
+            .filter(weekly_availabilities::Column::ScheduleId.eq(self.id))
+            .exec(&txn)
+            .await?;
+        appointment_types::Entity::update_many()
+            .col_expr(
+                appointment_types::Column::ScheduleId,
+                Expr::value(Option::<i32>::None),
+            )
+            .filter(appointment_types::Column::ScheduleId.eq(self.id))
+            .exec(&txn)
+            .await?;
+        self.into_active_model().delete(&txn).await?;
+        txn.commit().await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct CreateOrUpdateSchedule<'a> {
+    pub name: String,
+    pub is_default: bool,
+    pub user: &'a users::Model,
+}
+
+/// Only one schedule per user can be the default one.
+async fn unset_default<C: ConnectionTrait>(db: &C, user: &users::Model) -> ModelResult<()> {
+    Entity::update_many()
+        .col_expr(Column::IsDefault, Expr::value(false))
+        .filter(Column::UserId.eq(user.id))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+// implement your write-oriented logic here
+impl ActiveModel {
+    pub async fn create<C>(db: &C, params: CreateOrUpdateSchedule<'_>) -> ModelResult<Model>
+    where
+        C: ConnectionTrait,
+    {
+        // The first schedule of a user is always the default one.
+        let is_default = params.is_default
+            || Schedules::find_default_by_user(db, params.user)
+                .await?
+                .is_none();
+        if is_default {
+            unset_default(db, params.user).await?;
+        }
+
+        let active_model = Self {
+            name: sea_orm::ActiveValue::Set(params.name),
+            is_default: sea_orm::ActiveValue::Set(is_default),
+            user_id: sea_orm::ActiveValue::Set(params.user.id),
+            ..Default::default()
+        };
+        Ok(active_model.insert(db).await?)
+    }
+
+    pub async fn update_with_params<C>(
+        mut self,
+        db: &C,
+        params: CreateOrUpdateSchedule<'_>,
+    ) -> ModelResult<Model>
+    where
+        C: ConnectionTrait,
+    {
+        let was_default = *self.is_default.as_ref();
+        if was_default && !params.is_default {
+            return Err(ModelError::msg("Mark another schedule as default instead."));
+        }
+        if params.is_default && !was_default {
+            unset_default(db, params.user).await?;
+        }
+
+        self.name = sea_orm::ActiveValue::Set(params.name);
+        self.is_default = sea_orm::ActiveValue::Set(params.is_default);
+
+        Ok(self.update(db).await?)
+    }
+}
+
+// implement your custom finders, selectors oriented logic here
+impl Entity {
+    pub async fn find_by_id<C>(db: &C, id: i32) -> ModelResult<Model>
+    where
+        C: ConnectionTrait,
+    {
+        Self::find()
+            .filter(Column::Id.eq(id))
+            .one(db)
+            .await?
+            .ok_or(ModelError::EntityNotFound)
+    }
+
+    pub async fn find_by_user<C>(db: &C, user: &users::Model) -> ModelResult<Vec<Model>>
+    where
+        C: ConnectionTrait,
+    {
+        Ok(Self::find()
+            .filter(Column::UserId.eq(user.id))
+            .order_by_asc(Column::Id)
+            .all(db)
+            .await?)
+    }
+
+    pub async fn find_default_by_user<C>(db: &C, user: &users::Model) -> ModelResult<Option<Model>>
+    where
+        C: ConnectionTrait,
+    {
+        Ok(Self::find()
+            .filter(Column::UserId.eq(user.id))
+            .filter(Column::IsDefault.eq(true))
+            .one(db)
+            .await?)
+    }
+
+    /// The user's schedule with `id`, or the default one when `id` is missing.
+    pub async fn find_for_user<C>(
+        db: &C,
+        user: &users::Model,
+        id: Option<i32>,
+    ) -> ModelResult<Model>
+    where
+        C: ConnectionTrait,
+    {
+        match id {
+            Some(id) => Self::find()
+                .filter(Column::Id.eq(id))
+                .filter(Column::UserId.eq(user.id))
+                .one(db)
+                .await?
+                .ok_or(ModelError::EntityNotFound),
+            None => Model::get_or_create_default(db, user).await,
+        }
+    }
+}
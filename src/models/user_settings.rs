@@ -1,14 +1,19 @@
-pub use super::_entities::user_settings::{ActiveModel, Entity, Model};
+pub use super::_entities::user_settings::{ActiveModel, Entity, Model, ReminderOffsets};
 use crate::{
     models::{_entities::user_settings::Column, users::users},
-    views::user_settings::UserSettingsProps,
+    views::user_settings::{DaysHoursMinutes, UserSettingsProps},
 };
-use loco_rs::prelude::*;
+use loco_rs::{hash, prelude::*};
 use sea_orm::entity::prelude::*;
 use serde::Deserialize;
 use validator::ValidationError;
 pub type UserSettings = Entity;
 
+/// Furthest ahead a reminder can go out, 30 days.
+pub const MAX_REMINDER_OFFSET_IN_MINUTES: i32 = 60 * 24 * 30;
+const MAX_REMINDERS: usize = 5;
+const CALENDAR_FEED_TOKEN_LENGTH: usize = 48;
+
 #[async_trait::async_trait]
 impl ActiveModelBehavior for ActiveModel {
     async fn before_save<C>(self, _db: &C, _insert: bool) -> std::result::Result<Self, DbErr>
@@ -22,6 +27,20 @@ impl ActiveModelBehavior for ActiveModel {
 
 // implement your read-oriented logic here
 impl Model {
+    /// How long a pending appointment waits for the owner before it expires.
+    #[must_use]
+    pub fn pending_expires_after(&self) -> chrono::Duration {
+        chrono::Duration::minutes(i64::from(self.pending_expires_after_in_minutes))
+    }
+
+    /// Secret address of the owner's calendar feed, `None` while the feed is off.
+    #[must_use]
+    pub fn calendar_feed_url(&self, ctx: &AppContext) -> Option<String> {
+        self.calendar_feed_token
+            .as_ref()
+            .map(|token| format!("{}/api/calendar-feed/{token}", ctx.config.server.full_url()))
+    }
+
     pub async fn get_or_create<C: ConnectionTrait>(db: &C, user: &users::Model) -> Result<Self> {
         if let Some(settings) = UserSettings::find_by_user(db, user).await? {
             Ok(settings)
@@ -41,6 +60,21 @@ fn validate_user_settings(settings: &Validator) -> Result<(), ValidationError> {
     }
 }
 
+fn validate_reminder_offsets(offsets: &[i32]) -> Result<(), ValidationError> {
+    if offsets.len() > MAX_REMINDERS {
+        return Err(ValidationError::new("too_many_reminders")
+            .with_message(format!("At most {MAX_REMINDERS} reminders.").into()));
+    }
+    if offsets
+        .iter()
+        .any(|offset| !(1..=MAX_REMINDER_OFFSET_IN_MINUTES).contains(offset))
+    {
+        return Err(ValidationError::new("invalid_reminder")
+            .with_message("Reminders go out between 1 minute and 30 days before.".into()));
+    }
+    Ok(())
+}
+
 #[derive(Debug, Validate, Deserialize)]
 #[validate(schema(function = "validate_user_settings"))]
 pub struct Validator {
@@ -48,6 +82,10 @@ pub struct Validator {
     pub start_how_far_from_now_in_minutes: i32,
     #[validate(range(min = 1, message = "Can't be smaller than 1."))]
     pub end_how_far_from_now_in_minutes: i32,
+    #[validate(range(min = 1, message = "Can't be smaller than 1."))]
+    pub pending_expires_after_in_minutes: i32,
+    #[validate(custom(function = "validate_reminder_offsets"))]
+    pub reminder_offsets_in_minutes: Vec<i32>,
 }
 
 impl Validatable for ActiveModel {
@@ -61,6 +99,11 @@ impl Validatable for ActiveModel {
                 .end_how_far_from_now_in_minutes
                 .as_ref()
                 .to_owned(),
+            pending_expires_after_in_minutes: self
+                .pending_expires_after_in_minutes
+                .as_ref()
+                .to_owned(),
+            reminder_offsets_in_minutes: self.reminder_offsets_in_minutes.as_ref().0.clone(),
         })
     }
 }
@@ -72,6 +115,8 @@ impl ActiveModel {
             user_id: Set(user.id),
             start_how_far_from_now_in_minutes: Set(60),
             end_how_far_from_now_in_minutes: Set(60 * 24 * 14),
+            pending_expires_after_in_minutes: Set(60 * 24),
+            reminder_offsets_in_minutes: Set(ReminderOffsets(vec![60 * 24, 60])),
             ..Default::default()
         };
         model.insert(db).await
@@ -84,6 +129,35 @@ impl ActiveModel {
     ) -> Result<Model, DbErr> {
         self.start_how_far_from_now_in_minutes = Set(props.start_how_far_from_now.as_minutes());
         self.end_how_far_from_now_in_minutes = Set(props.end_how_far_from_now.as_minutes());
+        if let Some(pending_expires_after) = &props.pending_expires_after {
+            self.pending_expires_after_in_minutes = Set(pending_expires_after.as_minutes());
+        }
+        if let Some(reminder_offsets) = &props.reminder_offsets {
+            self.reminder_offsets_in_minutes = Set(ReminderOffsets(
+                reminder_offsets
+                    .iter()
+                    .map(DaysHoursMinutes::as_minutes)
+                    .collect(),
+            ));
+        }
+        self.update(db).await
+    }
+
+    /// Turns the calendar feed on under a new address. Calendars subscribed to the previous
+    /// address stop getting updates.
+    pub async fn rotate_calendar_feed_token<C: ConnectionTrait>(
+        mut self,
+        db: &C,
+    ) -> Result<Model, DbErr> {
+        self.calendar_feed_token = Set(Some(hash::random_string(CALENDAR_FEED_TOKEN_LENGTH)));
+        self.update(db).await
+    }
+
+    pub async fn disable_calendar_feed<C: ConnectionTrait>(
+        mut self,
+        db: &C,
+    ) -> Result<Model, DbErr> {
+        self.calendar_feed_token = Set(None);
         self.update(db).await
     }
 }
@@ -99,4 +173,15 @@ impl Entity {
             .one(db)
             .await
     }
+
+    pub async fn find_by_calendar_feed_token<C: ConnectionTrait>(
+        db: &C,
+        token: &str,
+    ) -> ModelResult<Model> {
+        Self::find()
+            .filter(Column::CalendarFeedToken.eq(token))
+            .one(db)
+            .await?
+            .ok_or(ModelError::EntityNotFound)
+    }
 }
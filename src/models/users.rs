@@ -1,9 +1,14 @@
+use std::{collections::BTreeMap, ops::Bound};
+
 use async_trait::async_trait;
 use axum::{
     extract::{FromRef, FromRequestParts, OptionalFromRequestParts},
     http::request::Parts,
 };
-use chrono::{offset::Local, Duration, DurationRound, TimeDelta};
+use chrono::{
+    offset::Local, DateTime, Duration, DurationRound, NaiveDate, NaiveTime, TimeDelta, TimeZone,
+    Utc,
+};
 use chrono_tz::{ParseError, Tz};
 use loco_rs::{auth::jwt, hash, prelude::*};
 use now::DateTimeNow;
@@ -15,9 +20,11 @@ use validator::ValidationError;
 pub type Users = Entity;
 
 use crate::{
+    calendar_providers::{self, CalendarProviders},
     models::{
         admin_settings::AdminSettings,
-        appointment_types, appointments, google_calendars,
+        appointment_types, appointments, availability_overrides,
+        busy_intervals::BusyIntervals,
         users::users::Role,
         weekly_availabilities::{self, WeeklyAvailabilityDuration},
     },
@@ -119,6 +126,14 @@ pub struct CurrentAvailabilityProps<'a> {
     pub appointment_type: &'a appointment_types::Model,
     pub start_how_far_from_now: Duration,
     pub end_how_far_from_now: Duration,
+    /// Appointment whose own time should not count as busy, e.g. the one being rescheduled.
+    pub exclude_appointment: Option<&'a appointments::Model>,
+    /// Asks the calendars instead of using the stored busy times, for checks that must not go
+    /// by a stale answer.
+    pub fresh_busy_times: bool,
+    /// Leaves appointments and calendar busy times out, to tell a slot that was taken apart
+    /// from one that was never offered.
+    pub ignore_bookings: bool,
 }
 
 fn validate_current_availability_props(
@@ -336,44 +351,122 @@ impl Model {
         &self,
         db: &C,
         props: CurrentAvailabilityProps<'_>,
-    ) -> ModelResult<Vec<AvailabilityWindow>>
+    ) -> Result<Vec<AvailabilityWindow>>
     where
         C: ConnectionTrait,
     {
         validator::Validate::validate(&props).map_err(ModelError::wrap)?;
-        let appointments = appointments::Appointments::find_upcoming(db, self).await?;
-
-        let google_calendar_windows = match google_calendars::Model::get_free_busy(
-            db,
-            self,
-            chrono::Utc::now() + props.start_how_far_from_now,
-            chrono::Utc::now() + props.end_how_far_from_now,
-        )
-        .await
-        {
-            Ok(windows) => windows,
-            Err(err) => {
-                tracing::warn!("Error getting free/busy: {}", err.to_string());
-                Vec::new()
+        let appointments = if props.ignore_bookings {
+            Vec::new()
+        } else {
+            appointments::Appointments::find_upcoming(db, self).await?
+        };
+
+        let time_min = chrono::Utc::now() + props.start_how_far_from_now;
+        let time_max = chrono::Utc::now() + props.end_how_far_from_now;
+        let calendar_windows = if props.ignore_bookings {
+            Vec::new()
+        } else if props.fresh_busy_times {
+            let (windows, complete) = CalendarProviders::for_user(db, self)
+                .await?
+                .busy_times_checked(time_min, time_max)
+                .await;
+            // A calendar that did not answer may hide a clash, booking anyway could double-book.
+            if !complete {
+                return Err(calendar_providers::calendars_unavailable());
             }
+            windows
+        } else {
+            BusyIntervals::busy_times(db, self, time_min, time_max)
+                .await
+                .unwrap_or_else(|err| {
+                    tracing::warn!("Error getting busy times: {}", err.to_string());
+                    Vec::new()
+                })
+        };
+
+        let calendar_windows: Vec<AvailabilityWindow> = match props.exclude_appointment {
+            // The excluded appointment has its own events in the calendars, carve them out.
+            Some(excluded) => calendar_windows
+                .into_iter()
+                .flat_map(|window| window.subtract(excluded))
+                .collect(),
+            None => calendar_windows,
         };
 
+        let appointments: Vec<appointments::Model> = appointments
+            .into_iter()
+            .filter(|a| {
+                props
+                    .exclude_appointment
+                    .is_none_or(|excluded| excluded.id != a.id)
+            })
+            .collect();
+
+        // Seats of a group appointment type fill their slot up instead of blocking it.
+        let (seats, appointments): (Vec<appointments::Model>, Vec<appointments::Model>) =
+            appointments.into_iter().partition(|a| {
+                props.appointment_type.is_group()
+                    && a.appointment_type_id == props.appointment_type.id
+            });
+        let mut seats_taken: BTreeMap<(DateTime<Utc>, DateTime<Utc>), i32> = BTreeMap::new();
+        for seat in &seats {
+            *seats_taken
+                .entry((seat.start_time.to_utc(), seat.endtime.to_utc()))
+                .or_default() += 1;
+        }
+        // All seats of a slot share one calendar event, which must not block the slot itself.
+        let calendar_windows: Vec<AvailabilityWindow> = calendar_windows
+            .into_iter()
+            .flat_map(|window| {
+                seats.iter().fold(vec![window], |windows, seat| {
+                    windows
+                        .into_iter()
+                        .flat_map(|window| window.subtract(seat))
+                        .collect()
+                })
+            })
+            .collect();
+
+        // Existing appointments keep the buffers of their own appointment type.
+        let appointment_types = appointment_types::Entity::find_by_user(db, self).await?;
+        let padded_appointments: Vec<AvailabilityWindow> = appointments
+            .iter()
+            .map(|a| {
+                let appointment_type = appointment_types
+                    .iter()
+                    .find(|t| t.id == a.appointment_type_id);
+                AvailabilityWindow {
+                    start: a.start_time.to_utc()
+                        - appointment_type.map_or(Duration::zero(), |t| t.buffer_before()),
+                    end: a.endtime.to_utc()
+                        + appointment_type.map_or(Duration::zero(), |t| t.buffer_after()),
+                    remaining_seats: None,
+                }
+            })
+            .collect();
+
         let mut my_vec: Vec<AvailabilityWindow> = Vec::new();
-        my_vec.extend(google_calendar_windows);
-        my_vec.extend(appointments.into_iter().map(|a| AvailabilityWindow {
+        my_vec.extend(calendar_windows);
+        my_vec.extend(appointments.iter().map(|a| AvailabilityWindow {
             start: a.start_time.to_utc(),
             end: a.endtime.to_utc(),
+            remaining_seats: None,
         }));
+        let buffer_before = props.appointment_type.buffer_before();
+        let buffer_after = props.appointment_type.buffer_after();
 
         let user_timezone: Tz = self.timezone.parse().map_err(ModelError::wrap)?;
+        let schedule = props.appointment_type.schedule(db, self).await?;
         let weekly_availabilities: Vec<WeeklyAvailabilityDuration> =
-            weekly_availabilities::Entity::find_by_user(db, self, vec![])
+            weekly_availabilities::Entity::find_by_schedule(db, &schedule, vec![])
                 .await?
                 .into_iter()
                 .map(Into::into)
                 .collect();
         let appointment_duration =
             Duration::minutes(i64::from(props.appointment_type.duration_in_minutes));
+        let slot_interval = props.appointment_type.slot_interval();
 
         let mut time_cursor = (our_chrono::utc_now().with_timezone(&user_timezone)
             + props.start_how_far_from_now)
@@ -385,52 +478,119 @@ impl Model {
             .duration_round(TimeDelta::minutes(1))
             .map_err(ModelError::wrap)?;
 
+        let overrides: BTreeMap<NaiveDate, Vec<WeeklyAvailabilityDuration>> =
+            availability_overrides::Entity::find_by_user_between(
+                db,
+                self,
+                time_cursor.date_naive(),
+                up_until.date_naive(),
+            )
+            .await?
+            .into_iter()
+            .map(|availability_override| {
+                (
+                    availability_override.date,
+                    availability_override.availability(),
+                )
+            })
+            .collect();
+
         let mut windows: Vec<AvailabilityWindow> = Vec::new();
 
         while time_cursor < up_until {
-            let monday_at_00 = time_cursor.beginning_of_week();
-
-            let duration_between_time_cursor_and_monday_at_00 = (monday_at_00 - time_cursor).abs();
-
-            let avail = weekly_availabilities
+            // An override replaces the weekly pattern for its whole date, its windows
+            // are measured from 00:00 of that date instead of from Monday 00:00.
+            let day_override = overrides.get(&time_cursor.date_naive());
+            let (anchor, availabilities) = match day_override {
+                Some(availabilities) => (time_cursor.beginning_of_day(), availabilities),
+                None => (time_cursor.beginning_of_week(), &weekly_availabilities),
+            };
+            // Weekly windows stop at the start of the next overridden date.
+            let next_override_at = match day_override {
+                Some(_) => None,
+                None => overrides
+                    .range((Bound::Excluded(time_cursor.date_naive()), Bound::Unbounded))
+                    .next()
+                    .and_then(|(date, _)| {
+                        user_timezone
+                            .from_local_datetime(&date.and_time(NaiveTime::MIN))
+                            .earliest()
+                    }),
+            };
+
+            let duration_between_time_cursor_and_anchor = (anchor - time_cursor).abs();
+
+            let avail = availabilities
                 .iter()
-                .find(|item| item.to > duration_between_time_cursor_and_monday_at_00);
+                .find(|item| item.to > duration_between_time_cursor_and_anchor);
 
             match avail {
                 // Availability found ahead of current time_cursor, but still
                 // outside the window, move time_cursor to found "from".
-                Some(avail) if avail.from > duration_between_time_cursor_and_monday_at_00 => {
+                Some(avail) if avail.from > duration_between_time_cursor_and_anchor => {
                     time_cursor =
-                        time_cursor + avail.from - duration_between_time_cursor_and_monday_at_00;
+                        time_cursor + avail.from - duration_between_time_cursor_and_anchor;
                 }
+                // time_cursor is off the slot grid, move it to the next slot start.
                 Some(avail)
-                    if (duration_between_time_cursor_and_monday_at_00 - avail.from)
-                        .num_minutes()
-                        % appointment_duration.num_minutes()
+                    if (duration_between_time_cursor_and_anchor - avail.from).num_minutes()
+                        % slot_interval.num_minutes()
                         != 0 =>
                 {
-                    let remainder = (duration_between_time_cursor_and_monday_at_00 - avail.from)
+                    let remainder = (duration_between_time_cursor_and_anchor - avail.from)
                         .num_minutes()
-                        % appointment_duration.num_minutes();
+                        % slot_interval.num_minutes();
 
-                    time_cursor = time_cursor + appointment_duration - Duration::minutes(remainder);
+                    time_cursor = time_cursor + slot_interval - Duration::minutes(remainder);
                 }
-                // Availability found this week for current time_cursor, and time_cursor is
+                // Availability found for current time_cursor, and time_cursor is
                 // inside the window, use the duration from "from" to "to".
                 Some(avail) => {
-                    if duration_between_time_cursor_and_monday_at_00 >= avail.from
-                        && duration_between_time_cursor_and_monday_at_00 + appointment_duration
+                    if duration_between_time_cursor_and_anchor >= avail.from
+                        && duration_between_time_cursor_and_anchor + appointment_duration
                             <= avail.to
+                        && next_override_at
+                            .is_none_or(|at| time_cursor + appointment_duration <= at)
                     {
+                        let start = time_cursor.to_utc();
+                        let end = (time_cursor + appointment_duration).to_utc();
+                        let remaining_seats = props.appointment_type.max_attendees
+                            - seats_taken.get(&(start, end)).copied().unwrap_or(0);
                         let window = AvailabilityWindow {
-                            start: time_cursor.to_utc(),
-                            end: (time_cursor + appointment_duration).to_utc(),
+                            start,
+                            end,
+                            remaining_seats: Some(remaining_seats),
+                        };
+                        // The slot needs its own buffers free of anything busy, and must
+                        // stay clear of the buffers of already booked appointments.
+                        let padded_window = AvailabilityWindow {
+                            start: window.start - buffer_before,
+                            end: window.end + buffer_after,
+                            remaining_seats: None,
                         };
-                        if !window.clash_check(&my_vec) {
+                        // Seats booked on another window of this type still clash.
+                        let seats_clash = seats_taken.keys().any(|(seat_start, seat_end)| {
+                            (*seat_start, *seat_end) != (start, end)
+                                && padded_window.start < *seat_end
+                                && padded_window.end > *seat_start
+                        });
+                        if remaining_seats > 0
+                            && !seats_clash
+                            && !padded_window.clash_check(&my_vec)
+                            && !window.clash_check(&padded_appointments)
+                        {
                             windows.push(window);
                         }
                     }
-                    time_cursor += appointment_duration;
+                    time_cursor += slot_interval;
+                }
+                // No availability found for the rest of the overridden day, jump to the
+                // next day time 00:00.
+                None if day_override.is_some() => {
+                    time_cursor = time_cursor
+                        .end_of_day()
+                        .duration_round(TimeDelta::minutes(1))
+                        .map_err(ModelError::wrap)?;
                 }
                 // No availability found this week for current time_cursor,
                 // jump to next monday time 00:00
@@ -441,6 +601,10 @@ impl Model {
                         .map_err(ModelError::wrap)?;
                 }
             }
+
+            if let Some(at) = next_override_at {
+                time_cursor = time_cursor.min(at);
+            }
         }
 
         Ok(windows)
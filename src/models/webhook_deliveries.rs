@@ -0,0 +1,208 @@
+use chrono::{DateTime, Duration, Utc};
+use std::time::Duration as StdDuration;
+
+pub use super::_entities::webhook_deliveries::{ActiveModel, Entity, Model, WebhookPayload};
+use crate::{
+    common::public_http,
+    models::{
+        _entities::webhook_deliveries::Column,
+        webhook_endpoints::{self, WebhookEndpoints},
+    },
+    our_chrono,
+    workers::deliver_webhook::{DeliverWebhookWorker, DeliverWebhookWorkerArgs},
+};
+use loco_rs::prelude::*;
+use sea_orm::{entity::prelude::*, QueryOrder, QuerySelect};
+pub type WebhookDeliveries = Entity;
+
+/// A delivery is retried until it got this many attempts.
+pub const MAX_ATTEMPTS: i32 = 6;
+/// Deliveries listed per endpoint, newest first.
+const LIST_LIMIT: u64 = 100;
+const REQUEST_TIMEOUT_IN_SECONDS: u64 = 10;
+
+/// Wait before the next attempt, doubling with every failure up to about an hour.
+fn backoff(attempt: i32) -> Duration {
+    Duration::minutes(1 << attempt.clamp(1, 6))
+}
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    async fn before_save<C>(self, _db: &C, insert: bool) -> std::result::Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        if !insert && self.updated_at.is_unchanged() {
+            let mut this = self;
+            this.updated_at = sea_orm::ActiveValue::Set(chrono::Utc::now().into());
+            Ok(this)
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+// implement your read-oriented logic here
+impl Model {
+    /// Posts the payload to the endpoint and records how it went. Failed attempts get a
+    /// `retry_at` until they run out of attempts.
+    pub async fn deliver(self, db: &DatabaseConnection) -> Result<Model> {
+        let endpoint = WebhookEndpoints::find_by_id(db, self.webhook_endpoint_id).await?;
+        let outcome = if endpoint.active {
+            post(&endpoint, &self).await
+        } else {
+            Err((None, "The endpoint is disabled.".to_string()))
+        };
+
+        let mut delivery = self.into_active_model();
+        match outcome {
+            Ok(status) => {
+                delivery.response_status = ActiveValue::set(Some(status));
+                delivery.error = ActiveValue::set(None);
+                delivery.retry_at = ActiveValue::set(None);
+            }
+            Err((status, error)) => {
+                let attempt = *delivery.attempt.as_ref();
+                delivery.response_status = ActiveValue::set(status);
+                delivery.error = ActiveValue::set(Some(error));
+                delivery.retry_at = ActiveValue::set(
+                    (endpoint.active && attempt < MAX_ATTEMPTS)
+                        .then(|| (our_chrono::utc_now() + backoff(attempt)).into()),
+                );
+            }
+        }
+
+        Ok(delivery.update(db).await?)
+    }
+
+    /// Sends the same payload again as a new delivery.
+    pub async fn replay(&self, ctx: &AppContext) -> Result<Model> {
+        let endpoint = WebhookEndpoints::find_by_id(&ctx.db, self.webhook_endpoint_id).await?;
+        ActiveModel::enqueue(ctx, &endpoint, self.payload.clone(), 1).await
+    }
+}
+
+/// Returns the response status on success, or the status if any along with what went wrong.
+/// Response bodies are never kept, the owner can read the deliveries back.
+async fn post(
+    endpoint: &webhook_endpoints::Model,
+    delivery: &Model,
+) -> std::result::Result<i32, (Option<i32>, String)> {
+    let body = serde_json::to_vec(&delivery.payload).map_err(|err| (None, err.to_string()))?;
+    let timestamp = our_chrono::utc_now().timestamp();
+
+    public_http::ensure_public(&endpoint.url).map_err(|err| (None, err.to_string()))?;
+    let response = public_http::client(StdDuration::from_secs(REQUEST_TIMEOUT_IN_SECONDS))
+        .map_err(|err| (None, err.to_string()))?
+        .post(&endpoint.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header("Webhook-Id", delivery.id.to_string())
+        .header("Webhook-Event", delivery.event.to_value())
+        .header("Webhook-Timestamp", timestamp.to_string())
+        .header(
+            "Webhook-Signature",
+            format!("sha256={}", endpoint.sign(timestamp, &body)),
+        )
+        .body(body)
+        .send()
+        .await
+        .map_err(|err| (None, err.to_string()))?;
+
+    let status = i32::from(response.status().as_u16());
+    if response.status().is_success() {
+        return Ok(status);
+    }
+
+    Err((Some(status), format!("Responded with {status}.")))
+}
+
+// implement your write-oriented logic here
+impl ActiveModel {
+    /// Records a delivery attempt and hands it to [`DeliverWebhookWorker`].
+    pub async fn enqueue(
+        ctx: &AppContext,
+        endpoint: &webhook_endpoints::Model,
+        payload: WebhookPayload,
+        attempt: i32,
+    ) -> Result<Model> {
+        let delivery = Self {
+            webhook_endpoint_id: ActiveValue::set(endpoint.id),
+            event: ActiveValue::set(payload.event),
+            payload: ActiveValue::set(payload),
+            attempt: ActiveValue::set(attempt),
+            ..Default::default()
+        }
+        .insert(&ctx.db)
+        .await?;
+
+        DeliverWebhookWorker::perform_later(
+            ctx,
+            DeliverWebhookWorkerArgs {
+                delivery_id: delivery.id,
+            },
+        )
+        .await?;
+
+        Ok(delivery)
+    }
+}
+
+// implement your custom finders, selectors oriented logic here
+impl Entity {
+    pub async fn find_by_id<C>(db: &C, id: i32) -> ModelResult<Model>
+    where
+        C: ConnectionTrait,
+    {
+        Self::find()
+            .filter(Column::Id.eq(id))
+            .one(db)
+            .await?
+            .ok_or(ModelError::EntityNotFound)
+    }
+
+    pub async fn find_by_endpoint<C>(
+        db: &C,
+        endpoint: &webhook_endpoints::Model,
+    ) -> ModelResult<Vec<Model>>
+    where
+        C: ConnectionTrait,
+    {
+        Ok(Self::find()
+            .filter(Column::WebhookEndpointId.eq(endpoint.id))
+            .order_by_desc(Column::Id)
+            .limit(LIST_LIMIT)
+            .all(db)
+            .await?)
+    }
+
+    /// Failed deliveries whose next attempt is due.
+    pub async fn find_due_retries<C>(db: &C, now: DateTime<Utc>) -> ModelResult<Vec<Model>>
+    where
+        C: ConnectionTrait,
+    {
+        Ok(Self::find()
+            .filter(Column::RetryAt.lte(now))
+            .order_by_asc(Column::RetryAt)
+            .all(db)
+            .await?)
+    }
+
+    /// Clears the `retry_at` of the delivery, returning `false` when another worker already
+    /// took care of its retry.
+    pub async fn claim_retry<C>(db: &C, delivery: &Model, now: DateTime<Utc>) -> ModelResult<bool>
+    where
+        C: ConnectionTrait,
+    {
+        let claimed = Self::update_many()
+            .col_expr(
+                Column::RetryAt,
+                Expr::value(Option::<DateTimeWithTimeZone>::None),
+            )
+            .filter(Column::Id.eq(delivery.id))
+            .filter(Column::RetryAt.lte(now))
+            .exec(db)
+            .await?;
+
+        Ok(claimed.rows_affected == 1)
+    }
+}
@@ -0,0 +1,189 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+pub use super::_entities::webhook_endpoints::{
+    ActiveModel, Entity, Model, WebhookEvent, WebhookEvents,
+};
+use crate::{
+    common::public_http::validate_user_url,
+    models::{
+        _entities::webhook_endpoints::Column,
+        appointments,
+        users::users,
+        webhook_deliveries::{self, WebhookPayload},
+    },
+    our_chrono,
+};
+use loco_rs::{hash, prelude::*};
+use sea_orm::{entity::prelude::*, QueryOrder};
+use serde::Deserialize;
+use validator::ValidationError;
+pub type WebhookEndpoints = Entity;
+
+const SECRET_LENGTH: usize = 32;
+
+fn validate_events(events: &[WebhookEvent]) -> Result<(), ValidationError> {
+    if events.is_empty() {
+        return Err(ValidationError::new("missing_events")
+            .with_message("Subscribe to at least one event.".into()));
+    }
+    if events
+        .iter()
+        .enumerate()
+        .any(|(index, event)| events[..index].contains(event))
+    {
+        return Err(ValidationError::new("duplicate_events")
+            .with_message("Each event can only be subscribed to once.".into()));
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Validate, Deserialize)]
+pub struct Validator {
+    #[validate(length(max = 2000), custom(function = "validate_user_url"))]
+    pub url: String,
+    #[validate(custom(function = "validate_events"))]
+    pub events: Vec<WebhookEvent>,
+}
+
+impl Validatable for ActiveModel {
+    fn validator(&self) -> Box<dyn Validate> {
+        Box::new(Validator {
+            url: self.url.as_ref().to_owned(),
+            events: self.events.as_ref().0.clone(),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    async fn before_save<C>(self, _db: &C, insert: bool) -> std::result::Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        self.validate()?;
+        if !insert && self.updated_at.is_unchanged() {
+            let mut this = self;
+            this.updated_at = sea_orm::ActiveValue::Set(chrono::Utc::now().into());
+            Ok(this)
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+// implement your read-oriented logic here
+impl Model {
+    /// Hex encoded HMAC-SHA256 of `"{timestamp}.{body}"` keyed with the endpoint secret. Signing
+    /// the timestamp along with the body lets receivers reject replayed requests.
+    #[must_use]
+    pub fn sign(&self, timestamp: i64, body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.secret.as_bytes())
+            .expect("HMAC accepts keys of any length");
+        mac.update(format!("{timestamp}.").as_bytes());
+        mac.update(body);
+
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    #[must_use]
+    pub fn is_subscribed_to(&self, event: WebhookEvent) -> bool {
+        self.active && self.events.0.contains(&event)
+    }
+}
+
+#[derive(Debug)]
+pub struct CreateOrUpdateWebhookEndpoint<'a> {
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    pub active: bool,
+    pub user: &'a users::Model,
+}
+
+// implement your write-oriented logic here
+impl ActiveModel {
+    /// Registers the endpoint with a freshly generated signing secret.
+    pub async fn create<C>(db: &C, params: CreateOrUpdateWebhookEndpoint<'_>) -> ModelResult<Model>
+    where
+        C: ConnectionTrait,
+    {
+        let active_model = Self {
+            url: ActiveValue::set(params.url.trim().to_string()),
+            secret: ActiveValue::set(format!("whsec_{}", hash::random_string(SECRET_LENGTH))),
+            events: ActiveValue::set(WebhookEvents(params.events)),
+            active: ActiveValue::set(params.active),
+            user_id: ActiveValue::set(params.user.id),
+            ..Default::default()
+        };
+        Ok(active_model.insert(db).await?)
+    }
+
+    pub async fn update_with_params<C>(
+        mut self,
+        db: &C,
+        params: CreateOrUpdateWebhookEndpoint<'_>,
+    ) -> ModelResult<Model>
+    where
+        C: ConnectionTrait,
+    {
+        self.url = ActiveValue::set(params.url.trim().to_string());
+        self.events = ActiveValue::set(WebhookEvents(params.events));
+        self.active = ActiveValue::set(params.active);
+
+        Ok(self.update(db).await?)
+    }
+}
+
+// implement your custom finders, selectors oriented logic here
+impl Entity {
+    pub async fn find_by_id<C>(db: &C, id: i32) -> ModelResult<Model>
+    where
+        C: ConnectionTrait,
+    {
+        Self::find()
+            .filter(Column::Id.eq(id))
+            .one(db)
+            .await?
+            .ok_or(ModelError::EntityNotFound)
+    }
+
+    pub async fn find_by_user<C>(db: &C, user: &users::Model) -> ModelResult<Vec<Model>>
+    where
+        C: ConnectionTrait,
+    {
+        Ok(Self::find()
+            .filter(Column::UserId.eq(user.id))
+            .order_by_asc(Column::Id)
+            .all(db)
+            .await?)
+    }
+
+    /// Queues a delivery of the event to every active endpoint of the appointment owner that
+    /// subscribed to it.
+    pub async fn publish(
+        ctx: &AppContext,
+        appointment: &appointments::Model,
+        event: WebhookEvent,
+    ) -> Result<()> {
+        let endpoints = Self::find()
+            .filter(Column::UserId.eq(appointment.user_id))
+            .order_by_asc(Column::Id)
+            .all(&ctx.db)
+            .await?;
+        let payload = WebhookPayload {
+            event,
+            occurred_at: our_chrono::utc_now().into(),
+            appointment: appointment.clone(),
+        };
+
+        for endpoint in endpoints
+            .iter()
+            .filter(|endpoint| endpoint.is_subscribed_to(event))
+        {
+            webhook_deliveries::ActiveModel::enqueue(ctx, endpoint, payload.clone(), 1).await?;
+        }
+
+        Ok(())
+    }
+}
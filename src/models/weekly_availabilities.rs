@@ -1,5 +1,6 @@
 use crate::{
-    models::users::users, traits::GenericWindowComparison,
+    models::{schedules, users::users},
+    traits::GenericWindowComparison,
     views::admin::WeeklyAvailabilitiesCreateUpdateParams,
 };
 
@@ -85,6 +86,7 @@ pub struct CreateProps<'a> {
     pub from: i32,
     pub to: i32,
     pub user: &'a users::Model,
+    pub schedule: &'a schedules::Model,
 }
 
 impl GenericWindowComparison<i32> for CreateProps<'_> {
@@ -110,7 +112,7 @@ impl ActiveModel {
     where
         C: ConnectionTrait,
     {
-        let rest = WeeklyAvailabilities::find_by_user(db, props.user, vec![]).await?;
+        let rest = WeeklyAvailabilities::find_by_schedule(db, props.schedule, vec![]).await?;
 
         if props.clash_check(&rest) {
             return Err(ModelError::Message("Clashing periods.".to_string()));
@@ -120,6 +122,7 @@ impl ActiveModel {
             from: sea_orm::Set(props.from),
             to: sea_orm::Set(props.to),
             user_id: sea_orm::Set(props.user.id),
+            schedule_id: sea_orm::Set(Some(props.schedule.id)),
             ..Default::default()
         };
         Ok(active_model.insert(db).await?)
@@ -129,13 +132,13 @@ impl ActiveModel {
         mut self,
         db: &C,
         props: WeeklyAvailabilitiesCreateUpdateParams,
-        user: &users::Model,
+        schedule: &schedules::Model,
     ) -> ModelResult<Model, MyError>
     where
         C: ConnectionTrait,
     {
         let this = self.clone().try_into_model().map_err(ModelError::from)?;
-        let rest = WeeklyAvailabilities::find_by_user(db, user, vec![&this]).await?;
+        let rest = WeeklyAvailabilities::find_by_schedule(db, schedule, vec![&this]).await?;
 
         if props.clash_check(&rest) {
             return Err(MyError::Clash(this));
@@ -169,4 +172,21 @@ impl Entity {
             .await?;
         Ok(models)
     }
+
+    pub async fn find_by_schedule<C>(
+        db: &C,
+        schedule: &schedules::Model,
+        exclude: Vec<&Model>,
+    ) -> ModelResult<Vec<Model>>
+    where
+        C: ConnectionTrait,
+    {
+        let models = Self::find()
+            .filter(Column::ScheduleId.eq(schedule.id))
+            .filter(Column::Id.is_not_in(exclude.into_iter().map(|item| item.id)))
+            .order_by_asc(Column::From)
+            .all(db)
+            .await?;
+        Ok(models)
+    }
 }
@@ -0,0 +1,24 @@
+use loco_rs::prelude::*;
+
+use crate::workers::expire_pending_appointments::{
+    ExpirePendingAppointmentsWorker, ExpirePendingAppointmentsWorkerArgs,
+};
+
+/// Enqueues [`ExpirePendingAppointmentsWorker`], run it on a schedule.
+pub struct ExpirePendingAppointments;
+
+#[async_trait]
+impl Task for ExpirePendingAppointments {
+    fn task(&self) -> TaskInfo {
+        TaskInfo {
+            name: "expire_pending_appointments".to_string(),
+            detail: "Decline pending appointments the owner did not answer in time".to_string(),
+        }
+    }
+
+    async fn run(&self, ctx: &AppContext, _vars: &task::Vars) -> Result<()> {
+        ExpirePendingAppointmentsWorker::perform_later(ctx, ExpirePendingAppointmentsWorkerArgs {})
+            .await?;
+        Ok(())
+    }
+}
@@ -1 +1,9 @@
-
+pub mod expire_pending_appointments;
+pub mod notify_broken_calendars;
+pub mod process_outbox;
+pub mod reconcile_google_events;
+pub mod reencrypt_secrets;
+pub mod refresh_busy_intervals;
+pub mod renew_google_channels;
+pub mod retry_webhook_deliveries;
+pub mod send_reminders;
@@ -0,0 +1,23 @@
+use loco_rs::prelude::*;
+
+use crate::workers::notify_broken_calendars::{
+    NotifyBrokenCalendarsWorker, NotifyBrokenCalendarsWorkerArgs,
+};
+
+/// Enqueues [`NotifyBrokenCalendarsWorker`], run it on a schedule.
+pub struct NotifyBrokenCalendars;
+
+#[async_trait]
+impl Task for NotifyBrokenCalendars {
+    fn task(&self) -> TaskInfo {
+        TaskInfo {
+            name: "notify_broken_calendars".to_string(),
+            detail: "Email owners whose calendar connection was revoked".to_string(),
+        }
+    }
+
+    async fn run(&self, ctx: &AppContext, _vars: &task::Vars) -> Result<()> {
+        NotifyBrokenCalendarsWorker::perform_later(ctx, NotifyBrokenCalendarsWorkerArgs {}).await?;
+        Ok(())
+    }
+}
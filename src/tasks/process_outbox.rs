@@ -0,0 +1,21 @@
+use loco_rs::prelude::*;
+
+use crate::workers::process_outbox::{ProcessOutboxWorker, ProcessOutboxWorkerArgs};
+
+/// Enqueues [`ProcessOutboxWorker`], run it on a schedule.
+pub struct ProcessOutbox;
+
+#[async_trait]
+impl Task for ProcessOutbox {
+    fn task(&self) -> TaskInfo {
+        TaskInfo {
+            name: "process_outbox".to_string(),
+            detail: "Perform the queued appointment side effects that are due".to_string(),
+        }
+    }
+
+    async fn run(&self, ctx: &AppContext, _vars: &task::Vars) -> Result<()> {
+        ProcessOutboxWorker::perform_later(ctx, ProcessOutboxWorkerArgs {}).await?;
+        Ok(())
+    }
+}
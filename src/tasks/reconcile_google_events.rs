@@ -0,0 +1,24 @@
+use loco_rs::prelude::*;
+
+use crate::workers::reconcile_google_events::{
+    ReconcileGoogleEventsWorker, ReconcileGoogleEventsWorkerArgs,
+};
+
+/// Enqueues [`ReconcileGoogleEventsWorker`], run it on a schedule.
+pub struct ReconcileGoogleEvents;
+
+#[async_trait]
+impl Task for ReconcileGoogleEvents {
+    fn task(&self) -> TaskInfo {
+        TaskInfo {
+            name: "reconcile_google_events".to_string(),
+            detail: "Follow appointment events deleted, moved or declined in Google Calendar"
+                .to_string(),
+        }
+    }
+
+    async fn run(&self, ctx: &AppContext, _vars: &task::Vars) -> Result<()> {
+        ReconcileGoogleEventsWorker::perform_later(ctx, ReconcileGoogleEventsWorkerArgs {}).await?;
+        Ok(())
+    }
+}
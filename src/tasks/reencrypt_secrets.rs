@@ -0,0 +1,44 @@
+use loco_rs::prelude::*;
+
+use crate::{
+    common::encryption,
+    models::{
+        admin_settings::AdminSettings, caldav_connections::CaldavConnections,
+        google_calendars::GoogleCalendars, outlook_calendars::OutlookCalendars,
+    },
+};
+
+/// Encrypts the stored secrets with the newest key, run it after adding a key or upgrading
+/// from plaintext storage. Older keys can be dropped once it finished.
+pub struct ReencryptSecrets;
+
+#[async_trait]
+impl Task for ReencryptSecrets {
+    fn task(&self) -> TaskInfo {
+        TaskInfo {
+            name: "reencrypt_secrets".to_string(),
+            detail:
+                "Encrypt stored tokens, passwords and API secrets with the newest encryption key"
+                    .to_string(),
+        }
+    }
+
+    async fn run(&self, ctx: &AppContext, _vars: &task::Vars) -> Result<()> {
+        encryption::install(&ctx.config)?;
+
+        let google_calendars = GoogleCalendars::reencrypt_secrets(&ctx.db).await?;
+        let outlook_calendars = OutlookCalendars::reencrypt_secrets(&ctx.db).await?;
+        let caldav_connections = CaldavConnections::reencrypt_secrets(&ctx.db).await?;
+        let admin_settings = AdminSettings::reencrypt_secrets(&ctx.db).await?;
+        tracing::info!(
+            "Re-encrypted {} Google connections, {} Outlook connections, {} CalDAV connections \
+             and {} admin settings",
+            google_calendars,
+            outlook_calendars,
+            caldav_connections,
+            admin_settings
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,23 @@
+use loco_rs::prelude::*;
+
+use crate::workers::refresh_busy_intervals::{
+    RefreshBusyIntervalsWorker, RefreshBusyIntervalsWorkerArgs,
+};
+
+/// Enqueues [`RefreshBusyIntervalsWorker`], run it on a schedule.
+pub struct RefreshBusyIntervals;
+
+#[async_trait]
+impl Task for RefreshBusyIntervals {
+    fn task(&self) -> TaskInfo {
+        TaskInfo {
+            name: "refresh_busy_intervals".to_string(),
+            detail: "Fetch the busy times of connected calendars again".to_string(),
+        }
+    }
+
+    async fn run(&self, ctx: &AppContext, _vars: &task::Vars) -> Result<()> {
+        RefreshBusyIntervalsWorker::perform_later(ctx, RefreshBusyIntervalsWorkerArgs {}).await?;
+        Ok(())
+    }
+}
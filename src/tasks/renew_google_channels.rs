@@ -0,0 +1,23 @@
+use loco_rs::prelude::*;
+
+use crate::workers::renew_google_channels::{
+    RenewGoogleChannelsWorker, RenewGoogleChannelsWorkerArgs,
+};
+
+/// Enqueues [`RenewGoogleChannelsWorker`], run it on a schedule.
+pub struct RenewGoogleChannels;
+
+#[async_trait]
+impl Task for RenewGoogleChannels {
+    fn task(&self) -> TaskInfo {
+        TaskInfo {
+            name: "renew_google_channels".to_string(),
+            detail: "Register and renew Google Calendar push notification channels".to_string(),
+        }
+    }
+
+    async fn run(&self, ctx: &AppContext, _vars: &task::Vars) -> Result<()> {
+        RenewGoogleChannelsWorker::perform_later(ctx, RenewGoogleChannelsWorkerArgs {}).await?;
+        Ok(())
+    }
+}
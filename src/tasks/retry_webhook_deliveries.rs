@@ -0,0 +1,24 @@
+use loco_rs::prelude::*;
+
+use crate::workers::retry_webhook_deliveries::{
+    RetryWebhookDeliveriesWorker, RetryWebhookDeliveriesWorkerArgs,
+};
+
+/// Enqueues [`RetryWebhookDeliveriesWorker`], run it on a schedule.
+pub struct RetryWebhookDeliveries;
+
+#[async_trait]
+impl Task for RetryWebhookDeliveries {
+    fn task(&self) -> TaskInfo {
+        TaskInfo {
+            name: "retry_webhook_deliveries".to_string(),
+            detail: "Retry the failed webhook deliveries that are due".to_string(),
+        }
+    }
+
+    async fn run(&self, ctx: &AppContext, _vars: &task::Vars) -> Result<()> {
+        RetryWebhookDeliveriesWorker::perform_later(ctx, RetryWebhookDeliveriesWorkerArgs {})
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,21 @@
+use loco_rs::prelude::*;
+
+use crate::workers::send_reminders::{SendRemindersWorker, SendRemindersWorkerArgs};
+
+/// Enqueues [`SendRemindersWorker`], run it on a schedule.
+pub struct SendReminders;
+
+#[async_trait]
+impl Task for SendReminders {
+    fn task(&self) -> TaskInfo {
+        TaskInfo {
+            name: "send_reminders".to_string(),
+            detail: "Send the appointment reminders that are due".to_string(),
+        }
+    }
+
+    async fn run(&self, ctx: &AppContext, _vars: &task::Vars) -> Result<()> {
+        SendRemindersWorker::perform_later(ctx, SendRemindersWorkerArgs {}).await?;
+        Ok(())
+    }
+}
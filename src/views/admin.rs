@@ -96,6 +96,16 @@ pub struct WeeklyAvailabilitiesCreateUpdateParams {
     pub normalized: NormalizedAvailabilityDay,
     /// Zero indexed weekday (0 = Monday, 1 = Tuesday, etc.)
     pub weekday: i32,
+    /// Schedule the window belongs to, defaults to the default schedule.
+    #[ts(optional)]
+    pub schedule_id: Option<i32>,
+}
+
+#[derive(Deserialize, Debug, ts_rs::TS)]
+#[ts(export)]
+pub struct WeeklyAvailabilitiesQueryParams {
+    /// Defaults to the default schedule.
+    pub schedule_id: Option<i32>,
 }
 
 impl GenericWindowComparison<i32> for WeeklyAvailabilitiesCreateUpdateParams {
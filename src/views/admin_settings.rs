@@ -1,5 +1,5 @@
 use crate::models::{
-    _entities::admin_settings::GoogleCalendarSettings,
+    _entities::admin_settings::{GoogleCalendarSettings, OutlookCalendarSettings},
     admin_settings::{ActiveModel, Model},
 };
 use loco_rs::prelude::*;
@@ -10,6 +10,7 @@ use serde::{Deserialize, Serialize};
 pub struct AdminSettingsParams {
     pub allow_new_registrations: Option<bool>,
     pub google_calendar_settings: Option<GoogleCalendarSettings>,
+    pub outlook_calendar_settings: Option<OutlookCalendarSettings>,
 }
 
 impl AdminSettingsParams {
@@ -21,6 +22,10 @@ impl AdminSettingsParams {
         if self.google_calendar_settings.is_some() {
             item.google_calendar_settings = Set(self.google_calendar_settings.clone());
         }
+
+        if self.outlook_calendar_settings.is_some() {
+            item.outlook_calendar_settings = Set(self.outlook_calendar_settings.clone());
+        }
     }
 }
 
@@ -32,6 +37,7 @@ pub struct AdminSettingsClientFacing {
     pub id: i32,
     pub allow_new_registrations: bool,
     pub google_calendar_settings: Option<GoogleCalendarSettings>,
+    pub outlook_calendar_settings: Option<OutlookCalendarSettings>,
 }
 
 impl From<Model> for AdminSettingsClientFacing {
@@ -47,6 +53,14 @@ impl From<Model> for AdminSettingsClientFacing {
                     google_oauth_client_id: settings.google_oauth_client_id,
                     google_oauth_secret: "*".repeat(settings.google_oauth_secret.len()),
                     google_oauth_redirect_uri_base: settings.google_oauth_redirect_uri_base,
+                    google_oauth_token_url: settings.google_oauth_token_url,
+                    google_calendar_api_url: settings.google_calendar_api_url,
+                }
+            }),
+            outlook_calendar_settings: item.outlook_calendar_settings.map(|settings| {
+                OutlookCalendarSettings {
+                    outlook_oauth_secret: "*".repeat(settings.outlook_oauth_secret.len()),
+                    ..settings
                 }
             }),
         }
@@ -1,10 +1,33 @@
 use serde::Deserialize;
 
+use crate::models::appointment_types::IntakeQuestion;
+
 #[derive(Debug, Deserialize, ts_rs::TS)]
 #[ts(export)]
 pub struct CreateAppointmentTypeParams {
     pub duration_in_minutes: i32,
     pub display_name: String,
+    /// Free time kept before each appointment, defaults to 0.
+    #[ts(optional)]
+    pub buffer_before_minutes: Option<i32>,
+    /// Free time kept after each appointment, defaults to 0.
+    #[ts(optional)]
+    pub buffer_after_minutes: Option<i32>,
+    /// How often a slot may start, defaults to the duration.
+    #[ts(optional)]
+    pub slot_interval_minutes: Option<i32>,
+    /// Schedule to take weekly windows from, defaults to the default schedule.
+    #[ts(optional)]
+    pub schedule_id: Option<i32>,
+    /// Seats per slot, defaults to 1.
+    #[ts(optional)]
+    pub max_attendees: Option<i32>,
+    /// Questions asked when booking, defaults to none.
+    #[ts(optional)]
+    pub intake_questions: Option<Vec<IntakeQuestion>>,
+    /// Bookings wait for the owner's approval, defaults to false.
+    #[ts(optional)]
+    pub requires_approval: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, ts_rs::TS)]
@@ -12,4 +35,39 @@ pub struct CreateAppointmentTypeParams {
 pub struct UpdateAppointmentTypeParams {
     pub duration_in_minutes: i32,
     pub display_name: String,
+    /// Leaves the current value untouched when missing.
+    #[ts(optional)]
+    pub buffer_before_minutes: Option<i32>,
+    /// Leaves the current value untouched when missing.
+    #[ts(optional)]
+    pub buffer_after_minutes: Option<i32>,
+    /// Leaves the current value untouched when missing, `null` goes back to the duration.
+    #[serde(default, with = "present")]
+    #[ts(optional, type = "number | null")]
+    pub slot_interval_minutes: Option<Option<i32>>,
+    /// Leaves the current value untouched when missing.
+    #[ts(optional)]
+    pub schedule_id: Option<i32>,
+    /// Leaves the current value untouched when missing.
+    #[ts(optional)]
+    pub max_attendees: Option<i32>,
+    /// Leaves the current value untouched when missing.
+    #[ts(optional)]
+    pub intake_questions: Option<Vec<IntakeQuestion>>,
+    /// Leaves the current value untouched when missing.
+    #[ts(optional)]
+    pub requires_approval: Option<bool>,
+}
+
+/// Tells a `null` field apart from a missing one, which `#[serde(default)]` leaves `None`.
+mod present {
+    use serde::{Deserialize, Deserializer};
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de>,
+    {
+        Option::deserialize(deserializer).map(Some)
+    }
 }
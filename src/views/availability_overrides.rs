@@ -0,0 +1,17 @@
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+use crate::models::availability_overrides::OverrideWindow;
+
+#[derive(Debug, Deserialize, ts_rs::TS)]
+#[ts(export)]
+pub struct AvailabilityOverrideParams {
+    /// Date in the owner's timezone.
+    pub date: NaiveDate,
+    /// Blocks the whole date when true.
+    #[ts(optional)]
+    pub unavailable: Option<bool>,
+    /// Windows in minutes from 00:00 replacing the weekly pattern for the date.
+    #[ts(optional)]
+    pub windows: Option<Vec<OverrideWindow>>,
+}
@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{common::caldav, models::caldav_connections};
+
+use super::google_calendars::CalendarSettingsResponse;
+
+#[derive(Debug, Deserialize, ts_rs::TS)]
+#[ts(export)]
+pub struct CaldavConnectionParams {
+    /// Address of the CalDAV server, e.g. `https://caldav.fastmail.com/dav/`.
+    pub server_url: String,
+    pub username: String,
+    /// Most providers want an app password here rather than the account password.
+    pub password: String,
+}
+
+#[derive(Debug, Serialize, ts_rs::TS)]
+#[ts(export)]
+pub struct CaldavCalendarEntry {
+    /// URL of the calendar, used as `calendar_id` when picking calendars.
+    pub id: String,
+    pub summary: String,
+}
+
+impl From<caldav::Calendar> for CaldavCalendarEntry {
+    fn from(calendar: caldav::Calendar) -> Self {
+        Self {
+            id: calendar.url,
+            summary: calendar.display_name,
+        }
+    }
+}
+
+impl From<caldav_connections::Model> for CalendarSettingsResponse {
+    fn from(model: caldav_connections::Model) -> Self {
+        Self {
+            calendars_for_collision_check: model.calendars_for_collision_check.0,
+            calendars_for_event_handling: model.calendars_for_event_handling.0,
+        }
+    }
+}
@@ -0,0 +1,14 @@
+use serde::Serialize;
+
+use crate::calendar_providers::CalendarProviderKind;
+
+#[derive(Debug, Serialize, ts_rs::TS)]
+#[ts(export)]
+pub struct CalendarConnection {
+    pub provider: CalendarProviderKind,
+    /// Tells apart several connections of the same provider, set for Google.
+    pub connection_id: Option<i32>,
+    /// False once the provider stopped accepting the stored credentials, the owner has to
+    /// connect it again.
+    pub connected: bool,
+}
@@ -1,11 +1,17 @@
-use crate::traits::GenericWindowComparison;
+use crate::{
+    models::{_entities::appointments::Status, appointment_types, appointments, users},
+    traits::GenericWindowComparison,
+};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Serialize, ts_rs::TS)]
 pub struct AvailabilityWindow {
     pub start: DateTime<Utc>,
     pub end: DateTime<Utc>,
+    /// Seats still open on a bookable slot, `None` for busy windows.
+    pub remaining_seats: Option<i32>,
 }
 
 impl GenericWindowComparison<chrono::DateTime<Utc>> for AvailabilityWindow {
@@ -17,6 +23,34 @@ impl GenericWindowComparison<chrono::DateTime<Utc>> for AvailabilityWindow {
     }
 }
 
+impl AvailabilityWindow {
+    /// Removes `other` from this window, returning whatever is left of it
+    /// (nothing, one piece or two pieces).
+    #[must_use]
+    pub fn subtract(self, other: &impl GenericWindowComparison<DateTime<Utc>>) -> Vec<Self> {
+        if other.end_time() <= self.start || other.start_time() >= self.end {
+            return vec![self];
+        }
+
+        let mut remaining = Vec::new();
+        if other.start_time() > self.start {
+            remaining.push(Self {
+                start: self.start,
+                end: other.start_time(),
+                remaining_seats: self.remaining_seats,
+            });
+        }
+        if other.end_time() < self.end {
+            remaining.push(Self {
+                start: other.end_time(),
+                end: self.end,
+                remaining_seats: self.remaining_seats,
+            });
+        }
+        remaining
+    }
+}
+
 #[derive(Debug, Serialize, ts_rs::TS)]
 #[ts(export)]
 pub struct BookDay {
@@ -32,4 +66,55 @@ pub struct BookingParams {
     pub booker_email: String,
     pub from: DateTime<Utc>,
     pub to: DateTime<Utc>,
+    /// Answers to the intake questions, keyed by question key.
+    #[ts(optional)]
+    pub answers: Option<BTreeMap<String, IntakeAnswerParam>>,
+}
+
+/// Checkbox questions take a boolean, every other kind a string.
+#[derive(Debug, Deserialize, ts_rs::TS)]
+#[serde(untagged)]
+#[ts(export)]
+pub enum IntakeAnswerParam {
+    Checked(bool),
+    Text(String),
+}
+
+#[derive(Debug, Deserialize, ts_rs::TS)]
+#[ts(export)]
+pub struct RescheduleParams {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+}
+
+/// What a booker gets to see about their own appointment through the manage link.
+#[derive(Debug, Serialize, ts_rs::TS)]
+#[ts(export)]
+pub struct ManagedAppointment {
+    pub user_name: String,
+    pub appointment_type_id: i32,
+    pub appointment_type_display_name: String,
+    pub booker_name: String,
+    pub start_time: DateTime<Utc>,
+    pub endtime: DateTime<Utc>,
+    pub status: Status,
+}
+
+impl ManagedAppointment {
+    #[must_use]
+    pub fn new(
+        appointment: appointments::Model,
+        appointment_type: &appointment_types::Model,
+        user: &users::Model,
+    ) -> Self {
+        Self {
+            user_name: user.name.clone(),
+            appointment_type_id: appointment_type.id,
+            appointment_type_display_name: appointment_type.display_name.clone(),
+            booker_name: appointment.booker_name,
+            start_time: appointment.start_time.to_utc(),
+            endtime: appointment.endtime.to_utc(),
+            status: appointment.status,
+        }
+    }
 }
@@ -61,3 +61,28 @@ impl From<google_calendars::Model> for CalendarSettingsResponse {
         }
     }
 }
+
+/// One connected Google account.
+#[derive(Debug, Serialize, ts_rs::TS)]
+#[ts(export)]
+pub struct GoogleCalendarConnection {
+    pub id: i32,
+    /// `None` for accounts connected before several accounts were supported.
+    pub account_email: Option<String>,
+    /// False once Google revoked access, the account has to be connected again.
+    pub connected: bool,
+    pub calendars_for_collision_check: HashSet<String>,
+    pub calendars_for_event_handling: HashSet<String>,
+}
+
+impl From<google_calendars::Model> for GoogleCalendarConnection {
+    fn from(model: google_calendars::Model) -> Self {
+        Self {
+            id: model.id,
+            account_email: model.account_email,
+            connected: model.broken_at.is_none(),
+            calendars_for_collision_check: model.calendars_for_collision_check.0,
+            calendars_for_event_handling: model.calendars_for_event_handling.0,
+        }
+    }
+}
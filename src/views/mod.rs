@@ -3,6 +3,12 @@ pub mod admin_settings;
 pub mod appointment_types;
 pub mod appointments;
 pub mod auth;
+pub mod availability_overrides;
+pub mod caldav;
+pub mod calendar_providers;
 pub mod client_facing;
 pub mod google_calendars;
+pub mod outlook_calendars;
+pub mod schedules;
 pub mod user_settings;
+pub mod webhooks;
@@ -0,0 +1,38 @@
+use serde::Serialize;
+
+use crate::{common::microsoft_graph, models::outlook_calendars};
+
+use super::google_calendars::CalendarSettingsResponse;
+
+#[derive(Debug, Serialize, ts_rs::TS)]
+#[ts(export)]
+pub struct OutlookCalendarEntry {
+    pub id: String,
+    pub summary: String,
+    pub primary: bool,
+    /// Events can only be written to calendars the user can edit.
+    pub can_edit: bool,
+    /// Address of the mailbox the calendar belongs to.
+    pub owner: Option<String>,
+}
+
+impl From<microsoft_graph::Calendar> for OutlookCalendarEntry {
+    fn from(calendar: microsoft_graph::Calendar) -> Self {
+        Self {
+            id: calendar.id,
+            summary: calendar.name,
+            primary: calendar.is_default_calendar,
+            can_edit: calendar.can_edit,
+            owner: calendar.owner.map(|owner| owner.address),
+        }
+    }
+}
+
+impl From<outlook_calendars::Model> for CalendarSettingsResponse {
+    fn from(model: outlook_calendars::Model) -> Self {
+        Self {
+            calendars_for_collision_check: model.calendars_for_collision_check.0,
+            calendars_for_event_handling: model.calendars_for_event_handling.0,
+        }
+    }
+}
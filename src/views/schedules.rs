@@ -0,0 +1,10 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, ts_rs::TS)]
+#[ts(export)]
+pub struct ScheduleParams {
+    pub name: String,
+    /// Makes this the default schedule, unsetting the previous one.
+    #[ts(optional)]
+    pub is_default: Option<bool>,
+}
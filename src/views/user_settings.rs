@@ -1,4 +1,4 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Deserialize, ts_rs::TS)]
 pub struct DaysHoursMinutes {
@@ -24,4 +24,18 @@ impl DaysHoursMinutes {
 pub struct UserSettingsProps {
     pub start_how_far_from_now: DaysHoursMinutes,
     pub end_how_far_from_now: DaysHoursMinutes,
+    /// Leaves the current value untouched when missing.
+    #[ts(optional)]
+    pub pending_expires_after: Option<DaysHoursMinutes>,
+    /// How long before an appointment reminders go out. Leaves the current value untouched
+    /// when missing.
+    #[ts(optional)]
+    pub reminder_offsets: Option<Vec<DaysHoursMinutes>>,
+}
+
+#[derive(Debug, Serialize, ts_rs::TS)]
+#[ts(export)]
+pub struct CalendarFeed {
+    /// Secret address to subscribe to from calendar apps, `null` while the feed is off.
+    pub url: Option<String>,
 }
@@ -0,0 +1,14 @@
+use serde::Deserialize;
+
+use crate::models::webhook_endpoints::WebhookEvent;
+
+#[derive(Debug, Deserialize, ts_rs::TS)]
+#[ts(export)]
+pub struct WebhookEndpointParams {
+    /// Where the signed `POST` requests are sent.
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    /// Disabled endpoints keep their secret and history but receive nothing. Defaults to true.
+    #[ts(optional)]
+    pub active: Option<bool>,
+}
@@ -0,0 +1,19 @@
+use loco_rs::prelude::*;
+
+use crate::mailers::attachments::EmailWithAttachments;
+
+/// Sends emails with attachments, the counterpart of loco's mailer worker.
+pub struct AttachmentMailerWorker {
+    pub ctx: AppContext,
+}
+
+#[async_trait]
+impl BackgroundWorker<EmailWithAttachments> for AttachmentMailerWorker {
+    fn build(ctx: &AppContext) -> Self {
+        Self { ctx: ctx.clone() }
+    }
+
+    async fn perform(&self, email: EmailWithAttachments) -> Result<()> {
+        email.send(self.ctx.config.mailer.as_ref()).await
+    }
+}
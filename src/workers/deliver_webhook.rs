@@ -0,0 +1,37 @@
+use loco_rs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::models::webhook_deliveries::WebhookDeliveries;
+
+/// Makes a single delivery attempt, see [`crate::models::webhook_deliveries::Model::deliver`].
+pub struct DeliverWebhookWorker {
+    pub ctx: AppContext,
+}
+
+#[derive(Deserialize, Debug, Serialize)]
+pub struct DeliverWebhookWorkerArgs {
+    pub delivery_id: i32,
+}
+
+#[async_trait]
+impl BackgroundWorker<DeliverWebhookWorkerArgs> for DeliverWebhookWorker {
+    fn build(ctx: &AppContext) -> Self {
+        Self { ctx: ctx.clone() }
+    }
+
+    async fn perform(&self, args: DeliverWebhookWorkerArgs) -> Result<()> {
+        let delivery = WebhookDeliveries::find_by_id(&self.ctx.db, args.delivery_id).await?;
+        let delivery = delivery.deliver(&self.ctx.db).await?;
+
+        if let Some(error) = &delivery.error {
+            tracing::warn!(
+                "Webhook delivery {} attempt {} failed: {}",
+                delivery.id,
+                delivery.attempt,
+                error
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,32 @@
+use loco_rs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::models::appointments::Appointments;
+
+/// Declines pending appointments the owner did not answer in time.
+pub struct ExpirePendingAppointmentsWorker {
+    pub ctx: AppContext,
+}
+
+#[derive(Deserialize, Debug, Serialize)]
+pub struct ExpirePendingAppointmentsWorkerArgs {}
+
+#[async_trait]
+impl BackgroundWorker<ExpirePendingAppointmentsWorkerArgs> for ExpirePendingAppointmentsWorker {
+    fn build(ctx: &AppContext) -> Self {
+        Self { ctx: ctx.clone() }
+    }
+
+    async fn perform(&self, _args: ExpirePendingAppointmentsWorkerArgs) -> Result<()> {
+        let expired = Appointments::find_expired_pending(&self.ctx.db).await?;
+
+        for appointment in expired {
+            let id = appointment.id;
+            if let Err(err) = appointment.decline(&self.ctx).await {
+                tracing::error!("Failed to expire pending appointment {}: {}", id, err);
+            }
+        }
+
+        Ok(())
+    }
+}
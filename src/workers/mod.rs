@@ -1 +1,12 @@
+pub mod attachment_mailer;
+pub mod deliver_webhook;
 pub mod downloader;
+pub mod expire_pending_appointments;
+pub mod notify_broken_calendars;
+pub mod process_outbox;
+pub mod reconcile_google_events;
+pub mod refresh_busy_intervals;
+pub mod renew_google_channels;
+pub mod retry_webhook_deliveries;
+pub mod send_reminders;
+pub mod sync_google_calendar;
@@ -0,0 +1,50 @@
+use loco_rs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    mailers::calendars::CalendarsMailer,
+    models::{google_calendars::GoogleCalendars, users::Users},
+};
+
+/// Emails owners whose Google calendar connection broke, once per connection.
+pub struct NotifyBrokenCalendarsWorker {
+    pub ctx: AppContext,
+}
+
+#[derive(Deserialize, Debug, Serialize)]
+pub struct NotifyBrokenCalendarsWorkerArgs {}
+
+#[async_trait]
+impl BackgroundWorker<NotifyBrokenCalendarsWorkerArgs> for NotifyBrokenCalendarsWorker {
+    fn build(ctx: &AppContext) -> Self {
+        Self { ctx: ctx.clone() }
+    }
+
+    async fn perform(&self, _args: NotifyBrokenCalendarsWorkerArgs) -> Result<()> {
+        let broken = GoogleCalendars::find_broken_unnotified(&self.ctx.db).await?;
+
+        for google_calendar in broken {
+            if !GoogleCalendars::claim_broken_notification(&self.ctx.db, &google_calendar).await? {
+                continue;
+            }
+
+            let user = Users::find_by_id(&self.ctx.db, google_calendar.user_id).await?;
+            // Users may connect several Google accounts, the email names the broken one.
+            let provider = google_calendar.account_email.as_ref().map_or_else(
+                || "Google Calendar".to_string(),
+                |account_email| format!("Google Calendar ({account_email})"),
+            );
+            if let Err(err) =
+                CalendarsMailer::send_disconnected_to_user(&self.ctx, &user, &provider).await
+            {
+                tracing::error!(
+                    "Failed to notify user {} of the broken Google calendar: {}",
+                    user.id,
+                    err
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,64 @@
+use loco_rs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    models::outbox_jobs::{OutboxJobs, MAX_ATTEMPTS},
+    our_chrono,
+};
+
+/// Performs the queued side effects of appointments, rescheduling failed ones with backoff.
+pub struct ProcessOutboxWorker {
+    pub ctx: AppContext,
+}
+
+#[derive(Deserialize, Debug, Serialize)]
+pub struct ProcessOutboxWorkerArgs {}
+
+impl ProcessOutboxWorker {
+    /// Asks for the outbox to be processed right away instead of on the next scheduled run.
+    /// Failing to enqueue is only logged, the jobs are safe in the database either way.
+    pub async fn kick(ctx: &AppContext) {
+        if let Err(err) = Self::perform_later(ctx, ProcessOutboxWorkerArgs {}).await {
+            tracing::warn!("Failed to enqueue outbox processing: {}", err);
+        }
+    }
+}
+
+#[async_trait]
+impl BackgroundWorker<ProcessOutboxWorkerArgs> for ProcessOutboxWorker {
+    fn build(ctx: &AppContext) -> Self {
+        Self { ctx: ctx.clone() }
+    }
+
+    async fn perform(&self, _args: ProcessOutboxWorkerArgs) -> Result<()> {
+        let db = &self.ctx.db;
+        let now = our_chrono::utc_now();
+
+        for job in OutboxJobs::find_due(db, now).await? {
+            if !OutboxJobs::claim(db, &job, now).await? {
+                continue;
+            }
+
+            match job.perform(&self.ctx).await {
+                Ok(()) => {
+                    job.into_active_model().complete(db).await?;
+                }
+                Err(err) => {
+                    let failed = job.into_active_model().fail(db, &err.to_string()).await?;
+                    if failed.attempts >= MAX_ATTEMPTS {
+                        tracing::error!(
+                            "Giving up on outbox job {} after {} attempts: {}",
+                            failed.id,
+                            failed.attempts,
+                            err
+                        );
+                    } else {
+                        tracing::warn!("Outbox job {} failed, will retry: {}", failed.id, err);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,54 @@
+use std::collections::{hash_map::Entry, HashMap};
+
+use loco_rs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    calendar_providers::google::GoogleCalendarProvider,
+    models::{appointments::Appointments, users::Users},
+};
+
+/// Checks the Google events of upcoming appointments for changes made outside the app, see
+/// [`crate::models::appointments::Model::reconcile_with_google`].
+pub struct ReconcileGoogleEventsWorker {
+    pub ctx: AppContext,
+}
+
+#[derive(Deserialize, Debug, Serialize)]
+pub struct ReconcileGoogleEventsWorkerArgs {}
+
+#[async_trait]
+impl BackgroundWorker<ReconcileGoogleEventsWorkerArgs> for ReconcileGoogleEventsWorker {
+    fn build(ctx: &AppContext) -> Self {
+        Self { ctx: ctx.clone() }
+    }
+
+    async fn perform(&self, _args: ReconcileGoogleEventsWorkerArgs) -> Result<()> {
+        let db = &self.ctx.db;
+        let appointments = Appointments::find_to_reconcile(db).await?;
+
+        let mut providers_by_user: HashMap<i32, Vec<GoogleCalendarProvider>> = HashMap::new();
+        for appointment in appointments {
+            let user_id = appointment.user_id;
+            if let Entry::Vacant(entry) = providers_by_user.entry(user_id) {
+                let user = Users::find_by_id(db, user_id).await?;
+                let providers = GoogleCalendarProvider::connected_for_user(db, &user).await?;
+                entry.insert(providers);
+            }
+
+            let appointment_id = appointment.id;
+            if let Err(err) = appointment
+                .reconcile_with_google(&self.ctx, &providers_by_user[&user_id])
+                .await
+            {
+                tracing::error!(
+                    "Failed to reconcile appointment {} with Google: {}",
+                    appointment_id,
+                    err
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+use loco_rs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{busy_intervals::BusyIntervals, users::Users};
+
+/// Fetches the busy times of every owner whose availability was asked for again, so that
+/// availability requests are answered from the table instead of waiting for the calendars.
+pub struct RefreshBusyIntervalsWorker {
+    pub ctx: AppContext,
+}
+
+#[derive(Deserialize, Debug, Serialize)]
+pub struct RefreshBusyIntervalsWorkerArgs {}
+
+#[async_trait]
+impl BackgroundWorker<RefreshBusyIntervalsWorkerArgs> for RefreshBusyIntervalsWorker {
+    fn build(ctx: &AppContext) -> Self {
+        Self { ctx: ctx.clone() }
+    }
+
+    async fn perform(&self, _args: RefreshBusyIntervalsWorkerArgs) -> Result<()> {
+        let db = &self.ctx.db;
+        for busy_intervals in BusyIntervals::find().all(db).await? {
+            let user = Users::find_by_id(db, busy_intervals.user_id).await?;
+            if let Err(err) = BusyIntervals::refresh(db, &user).await {
+                tracing::error!(
+                    "Failed to refresh the busy times of user {}: {}",
+                    user.id,
+                    err
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+use loco_rs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{
+    google_calendar_channels::GoogleCalendarChannels, google_calendars::GoogleCalendars,
+};
+
+/// Keeps the watch channels of every Google connection in line with its collision check
+/// calendars, replacing those about to expire.
+pub struct RenewGoogleChannelsWorker {
+    pub ctx: AppContext,
+}
+
+#[derive(Deserialize, Debug, Serialize)]
+pub struct RenewGoogleChannelsWorkerArgs {}
+
+#[async_trait]
+impl BackgroundWorker<RenewGoogleChannelsWorkerArgs> for RenewGoogleChannelsWorker {
+    fn build(ctx: &AppContext) -> Self {
+        Self { ctx: ctx.clone() }
+    }
+
+    async fn perform(&self, _args: RenewGoogleChannelsWorkerArgs) -> Result<()> {
+        for google_calendar in GoogleCalendars::find().all(&self.ctx.db).await? {
+            if let Err(err) = GoogleCalendarChannels::sync(&self.ctx.db, &google_calendar).await {
+                tracing::error!(
+                    "Failed to renew the Google channels of {}: {}",
+                    google_calendar.id,
+                    err
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+use loco_rs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    models::{
+        webhook_deliveries::{self, WebhookDeliveries},
+        webhook_endpoints::WebhookEndpoints,
+    },
+    our_chrono,
+};
+
+/// Queues the next attempt of failed webhook deliveries once their backoff passed.
+pub struct RetryWebhookDeliveriesWorker {
+    pub ctx: AppContext,
+}
+
+#[derive(Deserialize, Debug, Serialize)]
+pub struct RetryWebhookDeliveriesWorkerArgs {}
+
+#[async_trait]
+impl BackgroundWorker<RetryWebhookDeliveriesWorkerArgs> for RetryWebhookDeliveriesWorker {
+    fn build(ctx: &AppContext) -> Self {
+        Self { ctx: ctx.clone() }
+    }
+
+    async fn perform(&self, _args: RetryWebhookDeliveriesWorkerArgs) -> Result<()> {
+        let db = &self.ctx.db;
+        let now = our_chrono::utc_now();
+
+        for delivery in WebhookDeliveries::find_due_retries(db, now).await? {
+            if !WebhookDeliveries::claim_retry(db, &delivery, now).await? {
+                continue;
+            }
+
+            let endpoint = WebhookEndpoints::find_by_id(db, delivery.webhook_endpoint_id).await?;
+            webhook_deliveries::ActiveModel::enqueue(
+                &self.ctx,
+                &endpoint,
+                delivery.payload,
+                delivery.attempt + 1,
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+
+use chrono::Duration;
+use loco_rs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    mailers::appointments::AppointmentsMailer,
+    models::{
+        appointment_reminders::{self, ReminderRecipient},
+        appointments::Appointments,
+        user_settings::{self, MAX_REMINDER_OFFSET_IN_MINUTES},
+        users::Users,
+    },
+    our_chrono,
+};
+
+/// Sends the booker and owner reminders that are due, at the offsets each owner configured.
+pub struct SendRemindersWorker {
+    pub ctx: AppContext,
+}
+
+#[derive(Deserialize, Debug, Serialize)]
+pub struct SendRemindersWorkerArgs {}
+
+#[async_trait]
+impl BackgroundWorker<SendRemindersWorkerArgs> for SendRemindersWorker {
+    fn build(ctx: &AppContext) -> Self {
+        Self { ctx: ctx.clone() }
+    }
+
+    async fn perform(&self, _args: SendRemindersWorkerArgs) -> Result<()> {
+        let db = &self.ctx.db;
+        let now = our_chrono::utc_now();
+        let upcoming = Appointments::find_booked_starting_between(
+            db,
+            now,
+            now + Duration::minutes(MAX_REMINDER_OFFSET_IN_MINUTES.into()),
+        )
+        .await?;
+
+        let mut offsets_by_user: HashMap<i32, Vec<i32>> = HashMap::new();
+        for appointment in upcoming {
+            let offsets = if let Some(offsets) = offsets_by_user.get(&appointment.user_id) {
+                offsets.clone()
+            } else {
+                let user = Users::find_by_id(db, appointment.user_id).await?;
+                let offsets = user_settings::Model::get_or_create(db, &user)
+                    .await?
+                    .reminder_offsets_in_minutes
+                    .0;
+                offsets_by_user.insert(appointment.user_id, offsets.clone());
+                offsets
+            };
+            let Some(offset) = appointment.due_reminder_offset(&offsets, now) else {
+                continue;
+            };
+
+            for recipient in [ReminderRecipient::Booker, ReminderRecipient::Owner] {
+                if !appointment_reminders::ActiveModel::claim(db, &appointment, offset, recipient)
+                    .await?
+                {
+                    continue;
+                }
+                let sent = match recipient {
+                    ReminderRecipient::Booker => {
+                        AppointmentsMailer::send_reminder_to_booker(&self.ctx, &appointment).await
+                    }
+                    ReminderRecipient::Owner => {
+                        AppointmentsMailer::send_reminder_to_user(&self.ctx, &appointment).await
+                    }
+                };
+                if let Err(err) = sent {
+                    tracing::error!(
+                        "Failed to send reminder for appointment {}: {}",
+                        appointment.id,
+                        err
+                    );
+                    appointment_reminders::ActiveModel::release(
+                        db,
+                        &appointment,
+                        offset,
+                        recipient,
+                    )
+                    .await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
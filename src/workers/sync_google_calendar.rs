@@ -0,0 +1,60 @@
+use loco_rs::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    calendar_providers::google::GoogleCalendarProvider,
+    models::{
+        appointments::Appointments, busy_intervals::BusyIntervals,
+        google_calendars::GoogleCalendars, users::Users,
+    },
+};
+
+/// Follows a change Google notified about through a watch channel: the booked appointments of
+/// the owner are reconciled with their events and the stored busy times fetched again, right
+/// away instead of on the next scheduled run.
+pub struct SyncGoogleCalendarWorker {
+    pub ctx: AppContext,
+}
+
+#[derive(Deserialize, Debug, Serialize)]
+pub struct SyncGoogleCalendarWorkerArgs {
+    pub google_calendar_id: i32,
+}
+
+#[async_trait]
+impl BackgroundWorker<SyncGoogleCalendarWorkerArgs> for SyncGoogleCalendarWorker {
+    fn build(ctx: &AppContext) -> Self {
+        Self { ctx: ctx.clone() }
+    }
+
+    async fn perform(&self, args: SyncGoogleCalendarWorkerArgs) -> Result<()> {
+        let db = &self.ctx.db;
+        // Disconnected in the meantime.
+        let Some(google_calendar) = GoogleCalendars::find_by_id(args.google_calendar_id)
+            .one(db)
+            .await?
+        else {
+            return Ok(());
+        };
+        let user = Users::find_by_id(db, google_calendar.user_id).await?;
+
+        let providers = GoogleCalendarProvider::connected_for_user(db, &user).await?;
+        for appointment in Appointments::find_to_reconcile_by_user(db, &user).await? {
+            let appointment_id = appointment.id;
+            if let Err(err) = appointment
+                .reconcile_with_google(&self.ctx, &providers)
+                .await
+            {
+                tracing::error!(
+                    "Failed to reconcile appointment {} with Google: {}",
+                    appointment_id,
+                    err
+                );
+            }
+        }
+
+        BusyIntervals::refresh(db, &user).await?;
+
+        Ok(())
+    }
+}
@@ -1,7 +1,10 @@
 use appointments::{
     app::App,
     models::{
-        appointment_types::{self, CreateOrUpdateAppointmentType},
+        appointment_types::{
+            self, CreateOrUpdateAppointmentType, IntakeQuestion, IntakeQuestionKind,
+            IntakeQuestions,
+        },
         users::users,
     },
 };
@@ -32,6 +35,13 @@ async fn test_model() {
         CreateOrUpdateAppointmentType {
             duration_in_minutes: 60,
             display_name: "Test Appointment Type".to_string(),
+            buffer_before_minutes: 0,
+            buffer_after_minutes: 0,
+            slot_interval_minutes: None,
+            schedule_id: None,
+            max_attendees: 1,
+            intake_questions: IntakeQuestions::default(),
+            requires_approval: false,
             user: &user,
         },
     )
@@ -57,6 +67,13 @@ async fn negative_duration_error() {
         CreateOrUpdateAppointmentType {
             duration_in_minutes: -60,
             display_name: "Test Appointment Type".to_string(),
+            buffer_before_minutes: 0,
+            buffer_after_minutes: 0,
+            slot_interval_minutes: None,
+            schedule_id: None,
+            max_attendees: 1,
+            intake_questions: IntakeQuestions::default(),
+            requires_approval: false,
             user: &user,
         },
     )
@@ -81,6 +98,13 @@ async fn duplicate_duration_error() {
         CreateOrUpdateAppointmentType {
             duration_in_minutes: 60,
             display_name: "Test Appointment Type".to_string(),
+            buffer_before_minutes: 0,
+            buffer_after_minutes: 0,
+            slot_interval_minutes: None,
+            schedule_id: None,
+            max_attendees: 1,
+            intake_questions: IntakeQuestions::default(),
+            requires_approval: false,
             user: &user,
         },
     )
@@ -91,6 +115,13 @@ async fn duplicate_duration_error() {
         CreateOrUpdateAppointmentType {
             duration_in_minutes: 60,
             display_name: "Test Appointment Type".to_string(),
+            buffer_before_minutes: 0,
+            buffer_after_minutes: 0,
+            slot_interval_minutes: None,
+            schedule_id: None,
+            max_attendees: 1,
+            intake_questions: IntakeQuestions::default(),
+            requires_approval: false,
             user: &user,
         },
     )
@@ -98,3 +129,102 @@ async fn duplicate_duration_error() {
 
     assert!(appointment_type_2.is_err());
 }
+
+#[tokio::test]
+#[serial]
+async fn negative_buffer_error() {
+    configure_insta!();
+
+    let boot = boot_test::<App>().await.unwrap();
+    seed::<App>(&boot.app_context).await.unwrap();
+    let db = &boot.app_context.db;
+
+    let user = users::Entity::find_by_id(db, 2).await.unwrap();
+
+    let appointment_type = appointment_types::ActiveModel::create(
+        db,
+        CreateOrUpdateAppointmentType {
+            duration_in_minutes: 60,
+            display_name: "Test Appointment Type".to_string(),
+            buffer_before_minutes: -15,
+            buffer_after_minutes: 0,
+            slot_interval_minutes: None,
+            schedule_id: None,
+            max_attendees: 1,
+            intake_questions: IntakeQuestions::default(),
+            requires_approval: false,
+            user: &user,
+        },
+    )
+    .await;
+
+    assert!(appointment_type.is_err());
+}
+
+#[tokio::test]
+#[serial]
+async fn zero_slot_interval_error() {
+    configure_insta!();
+
+    let boot = boot_test::<App>().await.unwrap();
+    seed::<App>(&boot.app_context).await.unwrap();
+    let db = &boot.app_context.db;
+
+    let user = users::Entity::find_by_id(db, 2).await.unwrap();
+
+    let appointment_type = appointment_types::ActiveModel::create(
+        db,
+        CreateOrUpdateAppointmentType {
+            duration_in_minutes: 45,
+            display_name: "Test Appointment Type".to_string(),
+            buffer_before_minutes: 0,
+            buffer_after_minutes: 0,
+            slot_interval_minutes: Some(0),
+            schedule_id: None,
+            max_attendees: 1,
+            intake_questions: IntakeQuestions::default(),
+            requires_approval: false,
+            user: &user,
+        },
+    )
+    .await;
+
+    assert!(appointment_type.is_err());
+}
+
+#[tokio::test]
+#[serial]
+async fn select_question_without_options_error() {
+    configure_insta!();
+
+    let boot = boot_test::<App>().await.unwrap();
+    seed::<App>(&boot.app_context).await.unwrap();
+    let db = &boot.app_context.db;
+
+    let user = users::Entity::find_by_id(db, 2).await.unwrap();
+
+    let appointment_type = appointment_types::ActiveModel::create(
+        db,
+        CreateOrUpdateAppointmentType {
+            duration_in_minutes: 45,
+            display_name: "Test Appointment Type".to_string(),
+            buffer_before_minutes: 0,
+            buffer_after_minutes: 0,
+            slot_interval_minutes: None,
+            schedule_id: None,
+            max_attendees: 1,
+            intake_questions: IntakeQuestions(vec![IntakeQuestion {
+                key: "insurance".to_string(),
+                label: "Insurance provider".to_string(),
+                kind: IntakeQuestionKind::Select,
+                required: true,
+                options: vec![],
+            }]),
+            requires_approval: false,
+            user: &user,
+        },
+    )
+    .await;
+
+    assert!(appointment_type.is_err());
+}
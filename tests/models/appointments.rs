@@ -1,5 +1,10 @@
-use appointments::app::App;
+use appointments::{
+    app::App,
+    common::ics,
+    models::{_entities::appointments::Status, appointments::Appointments, users::Users},
+};
 use loco_rs::testing::prelude::*;
+use sea_orm::{ActiveModelTrait, ActiveValue, EntityTrait, IntoActiveModel};
 use serial_test::serial;
 
 macro_rules! configure_insta {
@@ -29,3 +34,53 @@ async fn test_model() {
     // snapshot the result:
     // assert_debug_snapshot!(item);
 }
+
+#[tokio::test]
+#[serial]
+async fn ics_invites_keep_their_uid_and_grow_their_sequence() {
+    let boot = boot_test::<App>().await.unwrap();
+    seed::<App>(&boot.app_context).await.unwrap();
+    let ctx = &boot.app_context;
+
+    let user = Users::find_by_id(&ctx.db, 1).await.unwrap();
+    let appointment = Appointments::find_by_id(1)
+        .one(&ctx.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let booked = appointment.ics_event(ctx, &user, "Consultation, with; notes".to_string(), None);
+
+    let mut cancelled = appointment.into_active_model();
+    cancelled.status = ActiveValue::set(Status::Cancelled);
+    let cancelled = cancelled.update(&ctx.db).await.unwrap();
+    let cancelled = cancelled.ics_event(ctx, &user, booked.summary.clone(), None);
+
+    assert_eq!(booked.uid, cancelled.uid);
+    assert!(cancelled.sequence > booked.sequence);
+    assert_eq!(booked.status, ics::EventStatus::Confirmed);
+    assert_eq!(cancelled.status, ics::EventStatus::Cancelled);
+
+    let calendar = ics::Calendar {
+        method: ics::Method::Cancel,
+        name: None,
+        events: vec![ics::Event {
+            description: Some(
+                "A description long enough to be folded over more than one line of the file."
+                    .to_string(),
+            ),
+            ..cancelled
+        }],
+    };
+    let file = calendar.to_ics();
+    assert!(file.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+    assert!(file.contains("METHOD:CANCEL\r\n"));
+    assert!(file.contains("DTSTART:20250113T180000Z\r\n"));
+    assert!(file.contains("SUMMARY:Consultation\\, with\\; notes\r\n"));
+    assert!(file.contains("STATUS:CANCELLED\r\n"));
+    assert!(file.ends_with("END:VCALENDAR\r\n"));
+    assert!(file.split("\r\n").all(|line| line.len() <= 75));
+    assert_eq!(
+        calendar.content_type(),
+        "text/calendar; charset=utf-8; method=CANCEL"
+    );
+}
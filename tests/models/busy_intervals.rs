@@ -0,0 +1,88 @@
+use appointments::{
+    app::App,
+    calendar_providers::in_memory::InMemoryCalendarProvider,
+    models::{
+        _entities::appointments::Column,
+        appointments::Appointments,
+        busy_intervals::BusyIntervals,
+        users::{self, Users},
+    },
+};
+use chrono::{DateTime, Duration, Utc};
+use loco_rs::testing::prelude::*;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseConnection, EntityTrait, IntoActiveModel,
+    QueryFilter,
+};
+use serial_test::serial;
+
+async fn busy_count(
+    db: &DatabaseConnection,
+    user: &users::Model,
+    time_min: DateTime<Utc>,
+    time_max: DateTime<Utc>,
+) -> usize {
+    BusyIntervals::busy_times(db, user, time_min, time_max)
+        .await
+        .unwrap()
+        .len()
+}
+
+#[tokio::test]
+#[serial]
+async fn busy_times_are_stored_until_refreshed_or_stale() {
+    let boot = boot_test::<App>().await.unwrap();
+    seed::<App>(&boot.app_context).await.unwrap();
+    let db = &boot.app_context.db;
+
+    let user = Users::find_by_id(db, 1).await.unwrap();
+    let calendar = InMemoryCalendarProvider::connect(&user);
+    let (time_min, time_max) = (Utc::now(), Utc::now() + Duration::days(7));
+    let start = Utc::now() + Duration::days(1);
+
+    calendar.add_busy(start, start + Duration::hours(1));
+    assert_eq!(busy_count(db, &user, time_min, time_max).await, 1);
+    let stored = BusyIntervals::find_by_user(db, &user)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(stored.covers(time_min, time_max));
+    assert_eq!(stored.intervals.0.len(), 1);
+
+    calendar.add_busy(start + Duration::hours(2), start + Duration::hours(3));
+    assert_eq!(
+        busy_count(db, &user, time_min, time_max).await,
+        1,
+        "Answered from the table."
+    );
+    BusyIntervals::refresh(db, &user).await.unwrap();
+    assert_eq!(busy_count(db, &user, time_min, time_max).await, 2);
+
+    calendar.add_busy(start + Duration::hours(4), start + Duration::hours(5));
+    let appointment = Appointments::find()
+        .filter(Column::UserId.eq(user.id))
+        .one(db)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut appointment = appointment.into_active_model();
+    appointment.booker_phone = ActiveValue::Set("555555555".to_string());
+    appointment.update(db).await.unwrap();
+    assert_eq!(
+        busy_count(db, &user, time_min, time_max).await,
+        3,
+        "Changed appointments make the stored busy times stale."
+    );
+
+    calendar.set_connected(false);
+    BusyIntervals::refresh(db, &user).await.unwrap();
+    assert!(
+        BusyIntervals::find_by_user(db, &user)
+            .await
+            .unwrap()
+            .is_none(),
+        "Partial answers are not stored."
+    );
+
+    InMemoryCalendarProvider::disconnect(&user);
+}
@@ -0,0 +1,246 @@
+use std::sync::{Arc, Mutex};
+
+use appointments::{
+    app::App,
+    calendar_providers::{
+        google::GoogleCalendarProvider, in_memory::InMemoryCalendarProvider, CalendarProvider,
+        CalendarProviders,
+    },
+    common::ics,
+    models::{
+        _entities::{
+            admin_settings::GoogleCalendarSettings,
+            appointments::{CalendarEvent, CalendarProviderKind},
+        },
+        admin_settings::AdminSettings,
+        google_calendars::{self, OAuthTokenResponseSuccess},
+        users::Users,
+    },
+};
+use axum::{extract::State, routing::patch, Json, Router};
+use chrono::{Duration, TimeZone, Utc};
+use loco_rs::{app::AppContext, testing::prelude::*};
+use sea_orm::{ActiveModelTrait, ActiveValue, IntoActiveModel};
+use serial_test::serial;
+
+const CALENDAR_ID: &str = "owner@example.com";
+
+type PatchBodies = Arc<Mutex<Vec<serde_json::Value>>>;
+
+/// Calendar API stand-in recording the bodies of event patches, every event has one guest.
+async fn start_calendar_api(patches: PatchBodies) -> String {
+    let app = Router::new()
+        .route(
+            "/calendars/{calendar_id}/events/{event_id}",
+            patch(
+                |State(patches): State<PatchBodies>, Json(body): Json<serde_json::Value>| async move {
+                    patches.lock().unwrap().push(body);
+                    Json(serde_json::json!({}))
+                },
+            )
+            .get(|| async {
+                Json(serde_json::json!({
+                    "attendees": [{ "email": "first@example.com" }],
+                }))
+            }),
+        )
+        .with_state(patches);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{address}")
+}
+
+async fn connect_google_calendar(ctx: &AppContext, api_url: &str) -> GoogleCalendarProvider {
+    let mut admin_settings = AdminSettings::load(&ctx.db)
+        .await
+        .unwrap()
+        .into_active_model();
+    admin_settings.google_calendar_settings = ActiveValue::Set(Some(GoogleCalendarSettings {
+        google_calendar_api_key: "google-api-key".to_string(),
+        google_oauth_client_id: "google-client".to_string(),
+        google_oauth_secret: "google-secret".to_string(),
+        google_oauth_redirect_uri_base: url::Url::parse("https://appointments.example.com")
+            .unwrap(),
+        google_oauth_token_url: None,
+        google_calendar_api_url: Some(url::Url::parse(api_url).unwrap()),
+    }));
+    admin_settings.update(&ctx.db).await.unwrap();
+
+    let user = Users::find_by_id(&ctx.db, 1).await.unwrap();
+    let google_calendar = google_calendars::ActiveModel::create(
+        &ctx.db,
+        OAuthTokenResponseSuccess {
+            access_token: "google-access-token".to_string(),
+            expires_in: 3600,
+            refresh_token: "google-refresh-token".to_string(),
+            refresh_token_expires_in: None,
+            scope: "https://www.googleapis.com/auth/calendar.events".to_string(),
+            token_type: "Bearer".to_string(),
+        },
+        user,
+        CALENDAR_ID.to_string(),
+    )
+    .await
+    .unwrap();
+    GoogleCalendarProvider::new(&ctx.db, google_calendar).await
+}
+
+fn google_event(event_id: &str, connection_id: Option<i32>) -> CalendarEvent {
+    CalendarEvent {
+        provider: CalendarProviderKind::Google,
+        calendar_id: CALENDAR_ID.to_string(),
+        event_id: event_id.to_string(),
+        connection_id,
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn busy_times_are_merged_and_failing_providers_skipped() {
+    let boot = boot_test::<App>().await.unwrap();
+    seed::<App>(&boot.app_context).await.unwrap();
+    let db = &boot.app_context.db;
+
+    let user = Users::find_by_id(db, 1).await.unwrap();
+    let calendar = InMemoryCalendarProvider::connect(&user);
+    let nine = Utc.with_ymd_and_hms(2030, 1, 7, 9, 0, 0).unwrap();
+    calendar.add_busy(nine + Duration::hours(1), nine + Duration::hours(2));
+    calendar.add_busy(nine, nine + Duration::minutes(90));
+    calendar.add_busy(nine + Duration::hours(4), nine + Duration::hours(5));
+
+    let providers = CalendarProviders::for_user(db, &user).await.unwrap();
+    let busy: Vec<_> = providers
+        .busy_times(nine, nine + Duration::days(1))
+        .await
+        .into_iter()
+        .map(|window| (window.start, window.end))
+        .collect();
+    assert_eq!(
+        busy,
+        vec![
+            (nine, nine + Duration::hours(2)),
+            (nine + Duration::hours(4), nine + Duration::hours(5)),
+        ]
+    );
+
+    calendar.set_connected(false);
+    assert!(providers
+        .busy_times(nine, nine + Duration::days(1))
+        .await
+        .is_empty());
+
+    InMemoryCalendarProvider::disconnect(&user);
+    let providers = CalendarProviders::for_user(db, &user).await.unwrap();
+    assert_eq!(providers.all().count(), 0);
+}
+
+#[test]
+fn calendar_objects_from_caldav_servers_are_read_and_rescheduled() {
+    let data = "BEGIN:VCALENDAR\r\n\
+VERSION:2.0\r\n\
+BEGIN:VTIMEZONE\r\n\
+TZID:America/Vancouver\r\n\
+END:VTIMEZONE\r\n\
+BEGIN:VEVENT\r\n\
+UID:busy\r\n\
+DTSTART;TZID=America/Vancouver:20300107T090000\r\n\
+DURATION:PT1H30M\r\n\
+BEGIN:VALARM\r\n\
+TRIGGER:-PT15M\r\n\
+END:VALARM\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:all-day\r\n\
+DTSTART;VALUE=DATE:20300108\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:free\r\n\
+DTSTART:20300109T170000Z\r\n\
+DTEND:20300109T180000Z\r\n\
+TRANSP:TRANSPARENT\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+    let start = Utc.with_ymd_and_hms(2030, 1, 7, 17, 0, 0).unwrap();
+    let all_day = Utc.with_ymd_and_hms(2030, 1, 8, 0, 0, 0).unwrap();
+    assert_eq!(
+        ics::busy_times(data),
+        vec![
+            (start, start + Duration::minutes(90)),
+            (all_day, all_day + Duration::days(1)),
+        ]
+    );
+
+    let next_week = start + Duration::days(7);
+    let moved = ics::reschedule(data, next_week, next_week + Duration::hours(1));
+    assert!(moved.contains("SEQUENCE:1\r\n"));
+    assert!(moved.contains("TRIGGER:-PT15M\r\n"));
+    assert_eq!(
+        ics::busy_times(&moved)[0],
+        (next_week, next_week + Duration::hours(1))
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn moving_google_events_only_patches_their_window() {
+    let boot = boot_test::<App>().await.unwrap();
+    seed::<App>(&boot.app_context).await.unwrap();
+    let ctx = &boot.app_context;
+
+    let patches = PatchBodies::default();
+    let api_url = start_calendar_api(patches.clone()).await;
+    let provider = connect_google_calendar(ctx, &api_url).await;
+
+    let start = Utc.with_ymd_and_hms(2030, 1, 7, 17, 0, 0).unwrap();
+    provider
+        .update_events(
+            &[google_event("moved", provider.connection_id())],
+            start,
+            start + Duration::hours(1),
+        )
+        .await
+        .unwrap();
+
+    let patches = patches.lock().unwrap();
+    assert_eq!(patches.len(), 1);
+    let fields: Vec<&String> = patches[0].as_object().unwrap().keys().collect();
+    assert_eq!(
+        fields,
+        vec!["end", "start"],
+        "Guest permissions of the event are left alone."
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn adding_guests_to_google_events_only_patches_their_attendees() {
+    let boot = boot_test::<App>().await.unwrap();
+    seed::<App>(&boot.app_context).await.unwrap();
+    let ctx = &boot.app_context;
+
+    let patches = PatchBodies::default();
+    let api_url = start_calendar_api(patches.clone()).await;
+    let provider = connect_google_calendar(ctx, &api_url).await;
+
+    provider
+        .add_attendee(
+            &[google_event("group", provider.connection_id())],
+            "second@example.com",
+        )
+        .await
+        .unwrap();
+
+    let patches = patches.lock().unwrap();
+    assert_eq!(patches.len(), 1);
+    let fields: Vec<&String> = patches[0].as_object().unwrap().keys().collect();
+    assert_eq!(fields, vec!["attendees"]);
+    let emails: Vec<&serde_json::Value> = patches[0]["attendees"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|attendee| &attendee["email"])
+        .collect();
+    assert_eq!(emails, vec!["first@example.com", "second@example.com"]);
+}
@@ -1,31 +1,322 @@
-use appointments::app::App;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use appointments::{
+    app::App,
+    calendar_providers::{google::GoogleCalendarProvider, CalendarProvider, CalendarProviderKind},
+    common::encryption,
+    models::{
+        _entities::{admin_settings::GoogleCalendarSettings, appointments::CalendarEvent},
+        admin_settings::AdminSettings,
+        google_calendars::{self, GoogleCalendars, OAuthTokenResponseSuccess},
+        users::Users,
+    },
+    views::google_calendars::{CalendarSettingParams, CalendarSettingType},
+};
+use axum::{extract::State, http::StatusCode, routing::post, Json, Router};
+use chrono::{Duration, Utc};
 use loco_rs::testing::prelude::*;
+use sea_orm::{ActiveModelTrait, ActiveValue, EntityTrait, IntoActiveModel};
 use serial_test::serial;
 
-macro_rules! configure_insta {
-    ($($expr:expr),*) => {
-        let mut settings = insta::Settings::clone_current();
-        settings.set_prepend_module_to_snapshot(false);
-        let _guard = settings.bind_to_scope();
-    };
+/// Token endpoint stand-in, answering refresh grants with `response` and counting them.
+async fn start_token_server(
+    status: StatusCode,
+    response: serde_json::Value,
+) -> (String, Arc<AtomicUsize>) {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let app = Router::new()
+        .route(
+            "/token",
+            post(
+                move |State(requests): State<Arc<AtomicUsize>>, body: String| {
+                    let response = response.clone();
+                    async move {
+                        assert!(body.contains("grant_type=refresh_token"));
+                        assert!(body.contains("refresh_token=google-refresh-token"));
+                        requests.fetch_add(1, Ordering::SeqCst);
+                        (status, Json(response))
+                    }
+                },
+            ),
+        )
+        .with_state(requests.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (format!("http://{address}/token"), requests)
+}
+
+async fn connect_google_calendar(
+    ctx: &loco_rs::app::AppContext,
+    token_url: &str,
+) -> google_calendars::Model {
+    let mut admin_settings = AdminSettings::load(&ctx.db)
+        .await
+        .unwrap()
+        .into_active_model();
+    admin_settings.google_calendar_settings = ActiveValue::Set(Some(GoogleCalendarSettings {
+        google_calendar_api_key: "google-api-key".to_string(),
+        google_oauth_client_id: "google-client".to_string(),
+        google_oauth_secret: "google-secret".to_string(),
+        google_oauth_redirect_uri_base: url::Url::parse("https://appointments.example.com")
+            .unwrap(),
+        google_oauth_token_url: Some(url::Url::parse(token_url).unwrap()),
+        google_calendar_api_url: None,
+    }));
+    admin_settings.update(&ctx.db).await.unwrap();
+
+    let user = Users::find_by_id(&ctx.db, 1).await.unwrap();
+    google_calendars::ActiveModel::create(
+        &ctx.db,
+        tokens("google-access-token"),
+        user,
+        "owner@example.com".to_string(),
+    )
+    .await
+    .unwrap()
+}
+
+fn tokens(access_token: &str) -> OAuthTokenResponseSuccess {
+    OAuthTokenResponseSuccess {
+        access_token: access_token.to_string(),
+        expires_in: 3600,
+        refresh_token: "google-refresh-token".to_string(),
+        refresh_token_expires_in: None,
+        scope: "https://www.googleapis.com/auth/calendar.events".to_string(),
+        token_type: "Bearer".to_string(),
+    }
+}
+
+async fn expire_access_token(
+    ctx: &loco_rs::app::AppContext,
+    google_calendar: google_calendars::Model,
+) -> google_calendars::Model {
+    let mut active_model = google_calendar.into_active_model();
+    active_model.expires_at = ActiveValue::Set(Some((Utc::now() + Duration::seconds(30)).into()));
+    active_model.update(&ctx.db).await.unwrap()
+}
+
+#[tokio::test]
+#[serial]
+async fn valid_access_tokens_are_used_without_refreshing() {
+    let boot = boot_test::<App>().await.unwrap();
+    seed::<App>(&boot.app_context).await.unwrap();
+    let ctx = &boot.app_context;
+
+    let (token_url, requests) = start_token_server(StatusCode::OK, serde_json::json!({})).await;
+    let google_calendar = connect_google_calendar(ctx, &token_url).await;
+
+    google_calendar.client(&ctx.db).await.unwrap();
+    google_calendar.client(&ctx.db).await.unwrap();
+    assert_eq!(requests.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+#[serial]
+async fn expiring_access_tokens_are_refreshed_once_and_stored() {
+    let boot = boot_test::<App>().await.unwrap();
+    seed::<App>(&boot.app_context).await.unwrap();
+    let ctx = &boot.app_context;
+
+    let (token_url, requests) = start_token_server(
+        StatusCode::OK,
+        serde_json::json!({
+            "access_token": "refreshed-access-token",
+            "expires_in": 3599,
+            "scope": "https://www.googleapis.com/auth/calendar.events",
+            "token_type": "Bearer",
+        }),
+    )
+    .await;
+    let google_calendar = connect_google_calendar(ctx, &token_url).await;
+    let google_calendar = expire_access_token(ctx, google_calendar).await;
+
+    google_calendar.client(&ctx.db).await.unwrap();
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+    let google_calendar = GoogleCalendars::find_by_id(google_calendar.id)
+        .one(&ctx.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(
+        google_calendar.access_token.starts_with("enc:v1:"),
+        "Tokens are stored encrypted."
+    );
+    assert_eq!(
+        encryption::decrypt(&google_calendar.access_token).unwrap(),
+        "refreshed-access-token"
+    );
+    assert_eq!(
+        encryption::decrypt(&google_calendar.refresh_token).unwrap(),
+        "google-refresh-token"
+    );
+    assert!(google_calendar.expires_at.unwrap() > Utc::now() + Duration::minutes(59));
+
+    google_calendar.client(&ctx.db).await.unwrap();
+    assert_eq!(
+        requests.load(Ordering::SeqCst),
+        1,
+        "The stored token is reused."
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn revoked_refresh_tokens_mark_the_connection_as_broken() {
+    let boot = boot_test::<App>().await.unwrap();
+    seed::<App>(&boot.app_context).await.unwrap();
+    let ctx = &boot.app_context;
+
+    let (token_url, requests) = start_token_server(
+        StatusCode::BAD_REQUEST,
+        serde_json::json!({
+            "error": "invalid_grant",
+            "error_description": "Token has been expired or revoked.",
+        }),
+    )
+    .await;
+    let google_calendar = connect_google_calendar(ctx, &token_url).await;
+    let google_calendar = expire_access_token(ctx, google_calendar).await;
+
+    assert!(google_calendar.client(&ctx.db).await.is_err());
+
+    let google_calendar = GoogleCalendars::find_by_id(google_calendar.id)
+        .one(&ctx.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(google_calendar.broken_at.is_some());
+    assert!(google_calendar.broken_notified_at.is_none());
+
+    assert!(google_calendar.client(&ctx.db).await.is_err());
+    assert_eq!(
+        requests.load(Ordering::SeqCst),
+        1,
+        "A broken connection is not refreshed again."
+    );
 }
 
 #[tokio::test]
 #[serial]
-async fn test_model() {
-    configure_insta!();
+async fn every_google_account_gets_its_own_connection() {
+    let boot = boot_test::<App>().await.unwrap();
+    seed::<App>(&boot.app_context).await.unwrap();
+    let ctx = &boot.app_context;
+    let user = Users::find_by_id(&ctx.db, 1).await.unwrap();
+
+    let work = google_calendars::ActiveModel::create(
+        &ctx.db,
+        tokens("work-access-token"),
+        user.clone(),
+        "work@example.com".to_string(),
+    )
+    .await
+    .unwrap();
+    let work = work
+        .add_calendar_to_settings(
+            &ctx.db,
+            CalendarSettingParams {
+                calendar_id: "work@example.com".to_string(),
+                setting_type: CalendarSettingType::EventHandling,
+            },
+        )
+        .await
+        .unwrap();
+    let mut broken = work.clone().into_active_model();
+    broken.broken_at = ActiveValue::Set(Some(Utc::now().into()));
+    broken.update(&ctx.db).await.unwrap();
+
+    let personal = google_calendars::ActiveModel::create(
+        &ctx.db,
+        tokens("personal-access-token"),
+        user.clone(),
+        "personal@example.com".to_string(),
+    )
+    .await
+    .unwrap();
+    assert_ne!(personal.id, work.id);
+
+    let reconnected = google_calendars::ActiveModel::create(
+        &ctx.db,
+        tokens("new-work-access-token"),
+        user.clone(),
+        "work@example.com".to_string(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(reconnected.id, work.id, "Reconnecting updates the account.");
+    assert_eq!(
+        encryption::decrypt(&reconnected.access_token).unwrap(),
+        "new-work-access-token"
+    );
+    assert!(reconnected.broken_at.is_none());
+    assert!(reconnected
+        .calendars_for_event_handling
+        .0
+        .contains("work@example.com"));
 
+    let connections = GoogleCalendars::find_by_user(&ctx.db, &user).await.unwrap();
+    assert_eq!(
+        connections
+            .iter()
+            .map(|connection| connection.account_email.clone())
+            .collect::<Vec<_>>(),
+        vec![
+            Some("work@example.com".to_string()),
+            Some("personal@example.com".to_string())
+        ]
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn events_belong_to_the_connection_that_created_them() {
     let boot = boot_test::<App>().await.unwrap();
     seed::<App>(&boot.app_context).await.unwrap();
+    let ctx = &boot.app_context;
+
+    let legacy = connect_google_calendar(ctx, "http://127.0.0.1:9/token").await;
+    let legacy = legacy
+        .add_calendar_to_settings(
+            &ctx.db,
+            CalendarSettingParams {
+                calendar_id: "legacy@example.com".to_string(),
+                setting_type: CalendarSettingType::EventHandling,
+            },
+        )
+        .await
+        .unwrap();
+    let user = Users::find_by_id(&ctx.db, 1).await.unwrap();
+    let other = google_calendars::ActiveModel::create(
+        &ctx.db,
+        tokens("other-access-token"),
+        user,
+        "other@example.com".to_string(),
+    )
+    .await
+    .unwrap();
 
-    // query your model, e.g.:
-    //
-    // let item = models::posts::Model::find_by_pid(
-    //     &boot.app_context.db,
-    //     "11111111-1111-1111-1111-111111111111",
-    // )
-    // .await;
+    let legacy_id = legacy.id;
+    let other_id = other.id;
+    let legacy = GoogleCalendarProvider::new(&ctx.db, legacy).await;
+    let other = GoogleCalendarProvider::new(&ctx.db, other).await;
+    let event = |calendar_id: &str, connection_id: Option<i32>| CalendarEvent {
+        provider: CalendarProviderKind::Google,
+        calendar_id: calendar_id.to_string(),
+        event_id: "event".to_string(),
+        connection_id,
+    };
 
-    // snapshot the result:
-    // assert_debug_snapshot!(item);
+    assert!(other.owns(&event("legacy@example.com", Some(other_id))));
+    assert!(!legacy.owns(&event("legacy@example.com", Some(other_id))));
+    assert!(legacy.owns(&event("legacy@example.com", Some(legacy_id))));
+    assert!(
+        legacy.owns(&event("legacy@example.com", None)),
+        "Events stored before connection ids go to the connection writing to their calendar."
+    );
+    assert!(!other.owns(&event("legacy@example.com", None)));
 }
@@ -1,7 +1,10 @@
 mod admin_settings;
 mod appointment_types;
 mod appointments;
+mod busy_intervals;
+mod calendar_providers;
 mod google_calendars;
 mod oauth_states;
 mod users;
+mod webhook_endpoints;
 mod weekly_availabilities;
@@ -0,0 +1,26 @@
+use appointments::common::public_http::validate_public_url;
+
+#[test]
+fn webhooks_only_go_to_public_https_hosts() {
+    assert!(validate_public_url("https://hooks.example.com/appointments").is_ok());
+    assert!(validate_public_url("https://93.184.215.14/hooks").is_ok());
+
+    for url in [
+        "http://hooks.example.com/appointments",
+        "https://localhost/hooks",
+        "https://api.localhost/hooks",
+        "https://127.0.0.1/hooks",
+        "https://10.0.0.5/hooks",
+        "https://172.16.0.1/hooks",
+        "https://192.168.1.1/hooks",
+        "https://169.254.169.254/latest/meta-data",
+        "https://100.64.0.1/hooks",
+        "https://0.0.0.0/hooks",
+        "https://[::1]/hooks",
+        "https://[fd00::1]/hooks",
+        "https://[fe80::1]/hooks",
+        "https://[::ffff:127.0.0.1]/hooks",
+    ] {
+        assert!(validate_public_url(url).is_err(), "{url} is rejected");
+    }
+}
@@ -1,6 +1,6 @@
 use appointments::{
     app::App,
-    models::{users, weekly_availabilities},
+    models::{schedules, users, weekly_availabilities},
 };
 use loco_rs::testing::prelude::*;
 use serial_test::serial;
@@ -43,6 +43,9 @@ async fn test_out_of_range() {
     let db = &boot.app_context.db;
 
     let user = users::Entity::find_by_id(db, 1).await.unwrap();
+    let schedule = schedules::Model::get_or_create_default(db, &user)
+        .await
+        .unwrap();
 
     let first = weekly_availabilities::ActiveModel::create(
         db,
@@ -50,6 +53,7 @@ async fn test_out_of_range() {
             from: -10,
             to: 1000,
             user: &user,
+            schedule: &schedule,
         },
     )
     .await;
@@ -64,6 +68,7 @@ async fn test_out_of_range() {
             from: 0,
             to: 10081,
             user: &user,
+            schedule: &schedule,
         },
     )
     .await;
@@ -76,6 +81,7 @@ async fn test_out_of_range() {
             from: 800,
             to: 700,
             user: &user,
+            schedule: &schedule,
         },
     )
     .await;
@@ -92,6 +98,9 @@ async fn test_clash() {
     seed::<App>(&boot.app_context).await.unwrap();
     let db = &boot.app_context.db;
     let user = users::Entity::find_by_id(db, 2).await.unwrap();
+    let schedule = schedules::Model::get_or_create_default(db, &user)
+        .await
+        .unwrap();
 
     let first = weekly_availabilities::ActiveModel::create(
         db,
@@ -99,6 +108,7 @@ async fn test_clash() {
             from: 800,
             to: 1000,
             user: &user,
+            schedule: &schedule,
         },
     )
     .await;
@@ -111,6 +121,7 @@ async fn test_clash() {
             from: 900,
             to: 1200,
             user: &user,
+            schedule: &schedule,
         },
     )
     .await;
@@ -123,6 +134,7 @@ async fn test_clash() {
             from: 600,
             to: 900,
             user: &user,
+            schedule: &schedule,
         },
     )
     .await;
@@ -135,6 +147,7 @@ async fn test_clash() {
             from: 600,
             to: 1200,
             user: &user,
+            schedule: &schedule,
         },
     )
     .await;
@@ -147,9 +160,47 @@ async fn test_clash() {
             from: 850,
             to: 900,
             user: &user,
+            schedule: &schedule,
         },
     )
     .await;
 
     assert!(inner_clash.is_err());
 }
+
+#[tokio::test]
+#[serial]
+async fn test_no_clash_across_schedules() {
+    configure_insta!();
+
+    let boot = boot_test::<App>().await.unwrap();
+    seed::<App>(&boot.app_context).await.unwrap();
+    let db = &boot.app_context.db;
+    let user = users::Entity::find_by_id(db, 1).await.unwrap();
+    let intake = schedules::ActiveModel::create(
+        db,
+        schedules::CreateOrUpdateSchedule {
+            name: "Intake".to_string(),
+            is_default: false,
+            user: &user,
+        },
+    )
+    .await
+    .unwrap();
+
+    assert!(!intake.is_default);
+
+    // Overlaps the default schedule fixtures, but lives in its own schedule.
+    let first = weekly_availabilities::ActiveModel::create(
+        db,
+        weekly_availabilities::CreateProps {
+            from: 600,
+            to: 720,
+            user: &user,
+            schedule: &intake,
+        },
+    )
+    .await;
+
+    assert!(first.is_ok());
+}
@@ -1,4 +1,4 @@
-use appointments::app::App;
+use appointments::{app::App, models::users::Users};
 use loco_rs::testing::prelude::*;
 use serial_test::serial;
 
@@ -14,3 +14,121 @@ async fn can_get_types() {
     })
     .await;
 }
+
+#[tokio::test]
+#[serial]
+async fn can_clear_slot_interval() {
+    request::<App, _, _>(|mut request, ctx| async move {
+        seed::<App>(&ctx).await.unwrap();
+
+        let user = Users::find_by_id(&ctx.db, 1).await.unwrap();
+        let settings = ctx.config.get_jwt_config().unwrap();
+        let jwt = user
+            .generate_jwt(&settings.secret, settings.expiration)
+            .unwrap();
+        request.add_header("Authorization", format!("Bearer {jwt}"));
+
+        let res = request
+            .post("/api/appointment_types")
+            .json(&serde_json::json!({
+                "duration_in_minutes": 45,
+                "display_name": "Interval",
+                "slot_interval_minutes": 15,
+            }))
+            .await;
+        assert_eq!(res.status_code(), 200);
+        let id = res.json::<serde_json::Value>()["id"].clone();
+
+        let res = request
+            .put(&format!("/api/appointment_types/{id}"))
+            .json(&serde_json::json!({
+                "duration_in_minutes": 45,
+                "display_name": "Interval",
+            }))
+            .await;
+        assert_eq!(res.status_code(), 200);
+        assert_eq!(
+            res.json::<serde_json::Value>()["slot_interval_minutes"],
+            15,
+            "A missing interval is left untouched."
+        );
+
+        let res = request
+            .put(&format!("/api/appointment_types/{id}"))
+            .json(&serde_json::json!({
+                "duration_in_minutes": 45,
+                "display_name": "Interval",
+                "slot_interval_minutes": null,
+            }))
+            .await;
+        assert_eq!(res.status_code(), 200);
+        assert!(res.json::<serde_json::Value>()["slot_interval_minutes"].is_null());
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn slots_start_on_the_interval_and_can_be_booked() {
+    request::<App, _, _>(|mut request, ctx| async move {
+        seed::<App>(&ctx).await.unwrap();
+
+        let user = Users::find_by_id(&ctx.db, 1).await.unwrap();
+        let settings = ctx.config.get_jwt_config().unwrap();
+        let jwt = user
+            .generate_jwt(&settings.secret, settings.expiration)
+            .unwrap();
+        request.add_header("Authorization", format!("Bearer {jwt}"));
+        request.add_header("timezone", "America/Vancouver");
+
+        let res = request
+            .post("/api/appointment_types")
+            .json(&serde_json::json!({
+                "duration_in_minutes": 45,
+                "display_name": "Interval",
+                "slot_interval_minutes": 15,
+            }))
+            .await;
+        assert_eq!(res.status_code(), 200);
+        let id = res.json::<serde_json::Value>()["id"].clone();
+
+        let days: Vec<serde_json::Value> = request
+            .get(&format!("/api/client-facing/availabilities/{id}"))
+            .await
+            .json();
+        // The first day may already be partly over.
+        let slots: Vec<(chrono::DateTime<chrono::Utc>, chrono::DateTime<chrono::Utc>)> = days
+            .iter()
+            .map(|day| day["availabilities"].as_array().unwrap())
+            .find(|slots| slots.len() > 2)
+            .unwrap()
+            .iter()
+            .map(|slot| {
+                (
+                    serde_json::from_value(slot["start"].clone()).unwrap(),
+                    serde_json::from_value(slot["end"].clone()).unwrap(),
+                )
+            })
+            .collect();
+        for pair in slots.windows(2) {
+            assert_eq!(pair[1].0 - pair[0].0, chrono::Duration::minutes(15));
+        }
+        for (start, end) in &slots {
+            assert_eq!(*end - *start, chrono::Duration::minutes(45));
+        }
+
+        let (start, end) = slots[1];
+        let res = request
+            .post(&format!("/api/client-facing/book/{id}"))
+            .json(&serde_json::json!({
+                "booker_name": "Interval",
+                "booker_phone": "555555555",
+                "booker_email": "interval@example.com",
+                "from": start,
+                "to": end,
+            }))
+            .await;
+        assert_eq!(res.status_code(), 200);
+    })
+    .await;
+}
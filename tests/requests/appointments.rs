@@ -1,5 +1,16 @@
-use appointments::app::App;
-use loco_rs::testing::prelude::*;
+use appointments::{
+    app::App,
+    models::{
+        _entities::appointments::{Column, Status},
+        appointment_types::AppointmentTypes,
+        appointments::Appointments,
+        users::Users,
+    },
+};
+use loco_rs::{testing::prelude::*, TestServer};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, IntoActiveModel, QueryFilter,
+};
 use serial_test::serial;
 
 #[tokio::test]
@@ -14,3 +25,261 @@ async fn can_get_appointments() {
     })
     .await;
 }
+
+#[tokio::test]
+#[serial]
+async fn can_not_reschedule_past_appointment() {
+    request::<App, _, _>(|mut request, ctx| async move {
+        seed::<App>(&ctx).await.unwrap();
+
+        let user = Users::find_by_id(&ctx.db, 1).await.unwrap();
+        let settings = ctx.config.get_jwt_config().unwrap();
+        let jwt = user
+            .generate_jwt(&settings.secret, settings.expiration)
+            .unwrap();
+        request.add_header("Authorization", format!("Bearer {jwt}"));
+
+        let res = request
+            .patch("/api/appointments/reschedule/1")
+            .json(&serde_json::json!({
+                "from": "2025-01-14T18:00:00Z",
+                "to": "2025-01-14T18:30:00Z",
+            }))
+            .await;
+        assert_ne!(
+            res.status_code(),
+            200,
+            "Past appointments can not be rescheduled."
+        );
+    })
+    .await;
+}
+
+async fn open_slots(request: &TestServer) -> Vec<serde_json::Value> {
+    let days: Vec<serde_json::Value> = request
+        .get("/api/client-facing/availabilities/1")
+        .await
+        .json();
+    days.iter()
+        .flat_map(|day| day["availabilities"].as_array().unwrap().clone())
+        .collect()
+}
+
+#[tokio::test]
+#[serial]
+async fn owners_can_reschedule_into_a_free_slot() {
+    request::<App, _, _>(|mut request, ctx| async move {
+        seed::<App>(&ctx).await.unwrap();
+        request.add_header("timezone", "America/Vancouver");
+
+        let slot = open_slots(&request).await[0].clone();
+        let res = request
+            .post("/api/client-facing/book/1")
+            .json(&serde_json::json!({
+                "booker_name": "Rescheduled",
+                "booker_phone": "555555555",
+                "booker_email": "rescheduled@example.com",
+                "from": slot["start"],
+                "to": slot["end"],
+            }))
+            .await;
+        assert_eq!(res.status_code(), 200);
+        let booked = Appointments::find()
+            .filter(Column::BookerEmail.eq("rescheduled@example.com"))
+            .one(&ctx.db)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let free_slot = open_slots(&request).await[0].clone();
+        assert_ne!(free_slot["start"], slot["start"]);
+        let from = chrono::DateTime::parse_from_rfc3339(free_slot["start"].as_str().unwrap())
+            .unwrap()
+            .to_utc();
+        let to = chrono::DateTime::parse_from_rfc3339(free_slot["end"].as_str().unwrap())
+            .unwrap()
+            .to_utc();
+
+        let user = Users::find_by_id(&ctx.db, 1).await.unwrap();
+        let settings = ctx.config.get_jwt_config().unwrap();
+        let jwt = user
+            .generate_jwt(&settings.secret, settings.expiration)
+            .unwrap();
+        request.add_header("Authorization", format!("Bearer {jwt}"));
+
+        let res = request
+            .patch(&format!("/api/appointments/reschedule/{}", booked.id))
+            .json(&serde_json::json!({ "from": from, "to": to }))
+            .await;
+        assert_eq!(res.status_code(), 200);
+        let rescheduled: serde_json::Value = res.json();
+        for (field, expected) in [("start_time", from), ("endtime", to)] {
+            let time =
+                chrono::DateTime::parse_from_rfc3339(rescheduled[field].as_str().unwrap()).unwrap();
+            assert_eq!(time, expected);
+        }
+
+        let stored = Appointments::find_by_id(booked.id)
+            .one(&ctx.db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.start_time, from);
+        assert_eq!(stored.endtime, to);
+        assert_eq!(stored.status, Status::Booked);
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn pending_bookings_hold_the_slot_until_answered() {
+    request::<App, _, _>(|mut request, ctx| async move {
+        seed::<App>(&ctx).await.unwrap();
+        request.add_header("timezone", "America/Vancouver");
+
+        let mut appointment_type = AppointmentTypes::find_by_id(&ctx.db, 1)
+            .await
+            .unwrap()
+            .into_active_model();
+        appointment_type.requires_approval = ActiveValue::set(true);
+        appointment_type.update(&ctx.db).await.unwrap();
+
+        let slots = open_slots(&request).await;
+        let first_slot = slots[0].clone();
+        let second_slot = slots[1].clone();
+        for (email, slot) in [
+            ("approve@example.com", &first_slot),
+            ("decline@example.com", &second_slot),
+        ] {
+            let res = request
+                .post("/api/client-facing/book/1")
+                .json(&serde_json::json!({
+                    "booker_name": "Pending",
+                    "booker_phone": "555555555",
+                    "booker_email": email,
+                    "from": slot["start"],
+                    "to": slot["end"],
+                }))
+                .await;
+            assert_eq!(res.status_code(), 200);
+        }
+
+        let to_approve = Appointments::find()
+            .filter(Column::BookerEmail.eq("approve@example.com"))
+            .one(&ctx.db)
+            .await
+            .unwrap()
+            .unwrap();
+        let to_decline = Appointments::find()
+            .filter(Column::BookerEmail.eq("decline@example.com"))
+            .one(&ctx.db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(to_approve.status, Status::Pending);
+        assert!(to_approve.calendar_events.is_empty());
+        assert!(to_approve.pending_expires_at.is_some());
+
+        assert_ne!(
+            open_slots(&request).await[0]["start"],
+            first_slot["start"],
+            "Pending appointments hold their slot."
+        );
+
+        let user = Users::find_by_id(&ctx.db, 1).await.unwrap();
+        let settings = ctx.config.get_jwt_config().unwrap();
+        let jwt = user
+            .generate_jwt(&settings.secret, settings.expiration)
+            .unwrap();
+        request.add_header("Authorization", format!("Bearer {jwt}"));
+
+        let res = request
+            .patch(&format!("/api/appointments/approve/{}", to_approve.id))
+            .await;
+        assert_eq!(res.status_code(), 200);
+        assert_eq!(res.json::<serde_json::Value>()["status"], "Booked");
+
+        let res = request
+            .patch(&format!("/api/appointments/decline/{}", to_decline.id))
+            .await;
+        assert_eq!(res.status_code(), 200);
+        assert_eq!(res.json::<serde_json::Value>()["status"], "Declined");
+
+        let res = request
+            .patch(&format!("/api/appointments/approve/{}", to_decline.id))
+            .await;
+        assert_ne!(
+            res.status_code(),
+            200,
+            "Declined appointments can not be approved."
+        );
+
+        assert_eq!(
+            open_slots(&request).await[0]["start"],
+            second_slot["start"],
+            "Declining frees the slot."
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn expired_requests_can_not_be_approved() {
+    request::<App, _, _>(|mut request, ctx| async move {
+        seed::<App>(&ctx).await.unwrap();
+        request.add_header("timezone", "America/Vancouver");
+
+        let mut appointment_type = AppointmentTypes::find_by_id(&ctx.db, 1)
+            .await
+            .unwrap()
+            .into_active_model();
+        appointment_type.requires_approval = ActiveValue::set(true);
+        appointment_type.update(&ctx.db).await.unwrap();
+
+        let slot = open_slots(&request).await[0].clone();
+        let res = request
+            .post("/api/client-facing/book/1")
+            .json(&serde_json::json!({
+                "booker_name": "Pending",
+                "booker_phone": "555555555",
+                "booker_email": "expired@example.com",
+                "from": slot["start"],
+                "to": slot["end"],
+            }))
+            .await;
+        assert_eq!(res.status_code(), 200);
+
+        let pending = Appointments::find()
+            .filter(Column::BookerEmail.eq("expired@example.com"))
+            .one(&ctx.db)
+            .await
+            .unwrap()
+            .unwrap();
+        let mut expired = pending.into_active_model();
+        expired.pending_expires_at = ActiveValue::set(Some(
+            (chrono::Utc::now() - chrono::Duration::days(1)).into(),
+        ));
+        let expired = expired.update(&ctx.db).await.unwrap();
+
+        let user = Users::find_by_id(&ctx.db, 1).await.unwrap();
+        let settings = ctx.config.get_jwt_config().unwrap();
+        let jwt = user
+            .generate_jwt(&settings.secret, settings.expiration)
+            .unwrap();
+        request.add_header("Authorization", format!("Bearer {jwt}"));
+
+        let res = request
+            .patch(&format!("/api/appointments/approve/{}", expired.id))
+            .await;
+        assert_ne!(res.status_code(), 200);
+        let expired = Appointments::find_by_id(expired.id)
+            .one(&ctx.db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(expired.status, Status::Pending);
+    })
+    .await;
+}
@@ -0,0 +1,154 @@
+use appointments::{app::App, models::users::Users};
+use loco_rs::testing::prelude::*;
+use serial_test::serial;
+
+#[tokio::test]
+#[serial]
+async fn can_create_availability_override() {
+    request::<App, _, _>(|mut request, ctx| async move {
+        seed::<App>(&ctx).await.unwrap();
+
+        let user = Users::find_by_id(&ctx.db, 1).await.unwrap();
+        let settings = ctx.config.get_jwt_config().unwrap();
+        let jwt = user
+            .generate_jwt(&settings.secret, settings.expiration)
+            .unwrap();
+        request.add_header("Authorization", format!("Bearer {jwt}"));
+
+        let res = request
+            .post("/api/availability_overrides")
+            .json(&serde_json::json!({
+                "date": "2030-12-25",
+                "unavailable": true,
+            }))
+            .await;
+        assert_eq!(res.status_code(), 200);
+
+        let res = request
+            .post("/api/availability_overrides")
+            .json(&serde_json::json!({
+                "date": "2030-12-25",
+                "windows": [{ "from": 600, "to": 720 }],
+            }))
+            .await;
+        assert_ne!(res.status_code(), 200, "Only one override per date.");
+
+        let res = request.get("/api/availability_overrides").await;
+        assert_eq!(res.status_code(), 200);
+        assert_eq!(res.json::<Vec<serde_json::Value>>().len(), 1);
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_not_create_availability_override_with_clashing_windows() {
+    request::<App, _, _>(|mut request, ctx| async move {
+        seed::<App>(&ctx).await.unwrap();
+
+        let user = Users::find_by_id(&ctx.db, 1).await.unwrap();
+        let settings = ctx.config.get_jwt_config().unwrap();
+        let jwt = user
+            .generate_jwt(&settings.secret, settings.expiration)
+            .unwrap();
+        request.add_header("Authorization", format!("Bearer {jwt}"));
+
+        let res = request
+            .post("/api/availability_overrides")
+            .json(&serde_json::json!({
+                "date": "2030-12-27",
+                "windows": [{ "from": 600, "to": 720 }, { "from": 700, "to": 800 }],
+            }))
+            .await;
+        assert_ne!(res.status_code(), 200);
+    })
+    .await;
+}
+
+/// Local start times of the slots of appointment type 1 on `date`.
+async fn local_starts_on(
+    request: &loco_rs::TestServer,
+    date: chrono::NaiveDate,
+) -> Vec<chrono::NaiveTime> {
+    let days: Vec<serde_json::Value> = request
+        .get("/api/client-facing/availabilities/1")
+        .await
+        .json();
+    days.iter()
+        .flat_map(|day| day["availabilities"].as_array().unwrap().clone())
+        .map(|slot| {
+            serde_json::from_value::<chrono::DateTime<chrono::Utc>>(slot["start"].clone())
+                .unwrap()
+                .with_timezone(&chrono_tz::America::Vancouver)
+        })
+        .filter(|start| start.date_naive() == date)
+        .map(|start| start.time())
+        .collect()
+}
+
+#[tokio::test]
+#[serial]
+async fn overrides_replace_the_weekly_windows_of_their_date() {
+    request::<App, _, _>(|mut request, ctx| async move {
+        seed::<App>(&ctx).await.unwrap();
+
+        let user = Users::find_by_id(&ctx.db, 1).await.unwrap();
+        let settings = ctx.config.get_jwt_config().unwrap();
+        let jwt = user
+            .generate_jwt(&settings.secret, settings.expiration)
+            .unwrap();
+        request.add_header("Authorization", format!("Bearer {jwt}"));
+        request.add_header("timezone", "America/Vancouver");
+
+        // A later day, the first one may already be partly over.
+        let days: Vec<serde_json::Value> = request
+            .get("/api/client-facing/availabilities/1")
+            .await
+            .json();
+        let date = days
+            .iter()
+            .flat_map(|day| day["availabilities"].as_array().unwrap().clone())
+            .map(|slot| {
+                serde_json::from_value::<chrono::DateTime<chrono::Utc>>(slot["start"].clone())
+                    .unwrap()
+                    .with_timezone(&chrono_tz::America::Vancouver)
+                    .date_naive()
+            })
+            .nth(10)
+            .unwrap();
+        assert!(local_starts_on(&request, date).await.len() > 2);
+
+        let res = request
+            .post("/api/availability_overrides")
+            .json(&serde_json::json!({ "date": date, "unavailable": true }))
+            .await;
+        assert_eq!(res.status_code(), 200);
+        let id = res.json::<serde_json::Value>()["id"].clone();
+        assert!(
+            local_starts_on(&request, date).await.is_empty(),
+            "A day off has no slots."
+        );
+
+        let res = request
+            .delete(&format!("/api/availability_overrides/{id}"))
+            .await;
+        assert_eq!(res.status_code(), 200);
+        let res = request
+            .post("/api/availability_overrides")
+            .json(&serde_json::json!({
+                "date": date,
+                "windows": [{ "from": 600, "to": 720 }],
+            }))
+            .await;
+        assert_eq!(res.status_code(), 200);
+        assert_eq!(
+            local_starts_on(&request, date).await,
+            vec![
+                chrono::NaiveTime::from_hms_opt(10, 0, 0).unwrap(),
+                chrono::NaiveTime::from_hms_opt(11, 0, 0).unwrap(),
+            ],
+            "The windows are in the owner's timezone."
+        );
+    })
+    .await;
+}
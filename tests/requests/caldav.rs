@@ -0,0 +1,331 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use appointments::{
+    app::App,
+    calendar_providers::CalendarProviderKind,
+    models::{_entities::appointments::Column, appointments::Appointments, users::Users},
+    workers::refresh_busy_intervals::{RefreshBusyIntervalsWorker, RefreshBusyIntervalsWorkerArgs},
+};
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    response::{IntoResponse, Response},
+    Router,
+};
+use chrono::{DateTime, Utc};
+use loco_rs::{bgworker::BackgroundWorker, testing::prelude::*};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serial_test::serial;
+
+const HOME: &str = "/calendars/owner/";
+const WORK_CALENDAR: &str = "/calendars/owner/work/";
+
+/// Calendar objects of the stand-in server by path.
+type Objects = Arc<Mutex<HashMap<String, String>>>;
+
+fn multistatus(responses: &str) -> Response {
+    (
+        StatusCode::MULTI_STATUS,
+        [("Content-Type", "application/xml; charset=utf-8")],
+        format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<d:multistatus xmlns:d="DAV:" xmlns:cal="urn:ietf:params:xml:ns:caldav">{responses}</d:multistatus>"#
+        ),
+    )
+        .into_response()
+}
+
+/// Just enough of a CalDAV server for the client: a principal, a home with an event calendar
+/// and a task list, and calendar objects kept in memory.
+async fn caldav_server(objects: Objects, request: Request<Body>) -> Response {
+    let method = request.method().as_str().to_string();
+    let path = request.uri().path().to_string();
+    let body = axum::body::to_bytes(request.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body = String::from_utf8_lossy(&body).to_string();
+
+    match (method.as_str(), path.as_str()) {
+        ("PROPFIND", "/") => multistatus(
+            r#"<d:response><d:href>/</d:href><d:propstat><d:prop>
+<d:current-user-principal><d:href>/principals/owner/</d:href></d:current-user-principal>
+</d:prop></d:propstat></d:response>"#,
+        ),
+        ("PROPFIND", "/principals/owner/") => multistatus(&format!(
+            r#"<d:response><d:href>/principals/owner/</d:href><d:propstat><d:prop>
+<cal:calendar-home-set><d:href>{HOME}</d:href></cal:calendar-home-set>
+</d:prop></d:propstat></d:response>"#
+        )),
+        ("PROPFIND", HOME) => multistatus(&format!(
+            r#"<d:response><d:href>{HOME}</d:href><d:propstat><d:prop>
+<d:resourcetype><d:collection/></d:resourcetype>
+</d:prop></d:propstat></d:response>
+<d:response><d:href>{WORK_CALENDAR}</d:href><d:propstat><d:prop>
+<d:resourcetype><d:collection/><cal:calendar/></d:resourcetype>
+<d:displayname>Work</d:displayname>
+<cal:supported-calendar-component-set><cal:comp name="VEVENT"/></cal:supported-calendar-component-set>
+</d:prop></d:propstat></d:response>
+<d:response><d:href>{HOME}tasks/</d:href><d:propstat><d:prop>
+<d:resourcetype><d:collection/><cal:calendar/></d:resourcetype>
+<d:displayname>Tasks</d:displayname>
+<cal:supported-calendar-component-set><cal:comp name="VTODO"/></cal:supported-calendar-component-set>
+</d:prop></d:propstat></d:response>"#
+        )),
+        ("REPORT", WORK_CALENDAR) => {
+            assert!(body.contains("calendar-query"));
+            let responses: String = objects
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(href, _)| href.starts_with(WORK_CALENDAR))
+                .map(|(href, data)| {
+                    format!(
+                        "<d:response><d:href>{href}</d:href><d:propstat><d:prop>\
+<cal:calendar-data><![CDATA[{data}]]></cal:calendar-data>\
+</d:prop></d:propstat></d:response>"
+                    )
+                })
+                .collect();
+            multistatus(&responses)
+        }
+        ("GET", _) => match objects.lock().unwrap().get(&path) {
+            Some(data) => (StatusCode::OK, [("ETag", "\"1\"")], data.clone()).into_response(),
+            None => StatusCode::NOT_FOUND.into_response(),
+        },
+        ("PUT", _) => {
+            objects.lock().unwrap().insert(path, body);
+            StatusCode::CREATED.into_response()
+        }
+        ("DELETE", _) => match objects.lock().unwrap().remove(&path) {
+            Some(_) => StatusCode::NO_CONTENT.into_response(),
+            None => StatusCode::NOT_FOUND.into_response(),
+        },
+        _ => StatusCode::METHOD_NOT_ALLOWED.into_response(),
+    }
+}
+
+/// Serves the stand-in on a free port and returns its URL.
+async fn start_caldav_server(objects: Objects) -> String {
+    let app = Router::new().fallback(move |request: Request<Body>| {
+        let objects = objects.clone();
+        async move { caldav_server(objects, request).await }
+    });
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{address}/")
+}
+
+fn busy_event(start: DateTime<Utc>, end: DateTime<Utc>) -> String {
+    format!(
+        "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nBEGIN:VEVENT\r\nUID:dentist\r\nDTSTART:{}\r\n\
+DTEND:{}\r\nSUMMARY:Dentist\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n",
+        start.format("%Y%m%dT%H%M%SZ"),
+        end.format("%Y%m%dT%H%M%SZ"),
+    )
+}
+
+#[tokio::test]
+#[serial]
+async fn can_not_connect_to_a_server_that_is_not_caldav() {
+    request::<App, _, _>(|mut request, ctx| async move {
+        seed::<App>(&ctx).await.unwrap();
+
+        let user = Users::find_by_id(&ctx.db, 1).await.unwrap();
+        let settings = ctx.config.get_jwt_config().unwrap();
+        let jwt = user
+            .generate_jwt(&settings.secret, settings.expiration)
+            .unwrap();
+        request.add_header("Authorization", format!("Bearer {jwt}"));
+
+        let res = request
+            .post("/api/caldav")
+            .json(&serde_json::json!({
+                "server_url": "http://127.0.0.1:9/",
+                "username": "owner",
+                "password": "app-password",
+            }))
+            .await;
+        assert_eq!(res.status_code(), 400);
+        assert_eq!(
+            res.json::<serde_json::Value>()["error"],
+            "invalid_caldav_connection"
+        );
+
+        let res = request.get("/api/caldav/get_settings").await;
+        assert_eq!(res.status_code(), 404);
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn credentials_only_go_to_the_configured_server() {
+    request::<App, _, _>(|mut request, ctx| async move {
+        seed::<App>(&ctx).await.unwrap();
+
+        let contacted = Arc::new(AtomicBool::new(false));
+        let other = Router::new().fallback({
+            let contacted = contacted.clone();
+            move || async move {
+                contacted.store(true, Ordering::SeqCst);
+                StatusCode::NOT_FOUND
+            }
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let other_address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, other).await.unwrap() });
+
+        // Points the client to the principal on the other server.
+        let server = Router::new().fallback(move || async move {
+            multistatus(&format!(
+                r#"<d:response><d:href>/</d:href><d:propstat><d:prop>
+<d:current-user-principal><d:href>http://{other_address}/principals/owner/</d:href></d:current-user-principal>
+</d:prop></d:propstat></d:response>"#
+            ))
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, server).await.unwrap() });
+
+        let user = Users::find_by_id(&ctx.db, 1).await.unwrap();
+        let settings = ctx.config.get_jwt_config().unwrap();
+        let jwt = user
+            .generate_jwt(&settings.secret, settings.expiration)
+            .unwrap();
+        request.add_header("Authorization", format!("Bearer {jwt}"));
+
+        let res = request
+            .post("/api/caldav")
+            .json(&serde_json::json!({
+                "server_url": format!("http://{address}/"),
+                "username": "owner",
+                "password": "app-password",
+            }))
+            .await;
+        assert_eq!(res.status_code(), 400);
+        assert!(!contacted.load(Ordering::SeqCst));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn caldav_calendars_block_slots_and_receive_the_bookings() {
+    request::<App, _, _>(|mut request, ctx| async move {
+        seed::<App>(&ctx).await.unwrap();
+        request.add_header("timezone", "America/Vancouver");
+
+        let objects = Objects::default();
+        let server_url = start_caldav_server(objects.clone()).await;
+
+        let user = Users::find_by_id(&ctx.db, 1).await.unwrap();
+        let settings = ctx.config.get_jwt_config().unwrap();
+        let jwt = user
+            .generate_jwt(&settings.secret, settings.expiration)
+            .unwrap();
+        request.add_header("Authorization", format!("Bearer {jwt}"));
+
+        let calendars: serde_json::Value = request
+            .post("/api/caldav")
+            .json(&serde_json::json!({
+                "server_url": server_url,
+                "username": "owner",
+                "password": "app-password",
+            }))
+            .await
+            .json();
+        let work_calendar = format!("{}{}", server_url.trim_end_matches('/'), WORK_CALENDAR);
+        assert_eq!(
+            calendars,
+            serde_json::json!([{ "id": work_calendar, "summary": "Work" }]),
+            "Only calendars that hold events are listed."
+        );
+
+        for setting_type in ["CollisionCheck", "EventHandling"] {
+            let res = request
+                .post("/api/caldav/add_calendar")
+                .json(&serde_json::json!({
+                    "calendar_id": work_calendar,
+                    "setting_type": setting_type,
+                }))
+                .await;
+            assert_eq!(res.status_code(), 200);
+        }
+
+        let days: serde_json::Value = request
+            .get("/api/client-facing/availabilities/1")
+            .await
+            .json();
+        let busy_slot = days[0]["availabilities"][0].clone();
+        objects.lock().unwrap().insert(
+            format!("{WORK_CALENDAR}dentist.ics"),
+            busy_event(
+                serde_json::from_value(busy_slot["start"].clone()).unwrap(),
+                serde_json::from_value(busy_slot["end"].clone()).unwrap(),
+            ),
+        );
+
+        RefreshBusyIntervalsWorker::perform_later(&ctx, RefreshBusyIntervalsWorkerArgs {})
+            .await
+            .unwrap();
+
+        let days: serde_json::Value = request
+            .get("/api/client-facing/availabilities/1")
+            .await
+            .json();
+        let slot = days[0]["availabilities"][0].clone();
+        assert_ne!(slot["start"], busy_slot["start"]);
+
+        let booked = request
+            .post("/api/client-facing/book/1")
+            .json(&serde_json::json!({
+                "booker_name": "Calendar",
+                "booker_phone": "555555555",
+                "booker_email": "calendar@example.com",
+                "from": slot["start"],
+                "to": slot["end"],
+            }))
+            .await;
+        assert_eq!(booked.status_code(), 200);
+
+        let appointment = Appointments::find()
+            .filter(Column::BookerEmail.eq("calendar@example.com"))
+            .one(&ctx.db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(appointment.calendar_events.len(), 1);
+        let event = &appointment.calendar_events[0];
+        assert_eq!(event.provider, CalendarProviderKind::Caldav);
+        assert_eq!(event.calendar_id, work_calendar);
+
+        let event_path = url::Url::parse(&event.event_id).unwrap().path().to_string();
+        let data = objects.lock().unwrap().get(&event_path).cloned().unwrap();
+        // Long lines are folded, unfold them before looking for a value.
+        let data = data.replace("\r\n ", "");
+        assert!(data.contains("mailto:calendar@example.com"));
+        assert!(!data.contains("METHOD:"));
+
+        let cancelled = request
+            .post(&format!(
+                "/api/client-facing/manage/{}/cancel",
+                appointment.manage_token.unwrap()
+            ))
+            .await;
+        assert_eq!(cancelled.status_code(), 200);
+        assert!(!objects.lock().unwrap().contains_key(&event_path));
+
+        let res = request.delete("/api/caldav").await;
+        assert_eq!(res.status_code(), 200);
+        let res = request.get("/api/caldav/get_settings").await;
+        assert_eq!(res.status_code(), 404);
+    })
+    .await;
+}
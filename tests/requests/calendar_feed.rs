@@ -0,0 +1,92 @@
+use appointments::{
+    app::App,
+    models::{_entities::appointments::Column, appointments::Appointments, users::Users},
+};
+use loco_rs::testing::prelude::*;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serial_test::serial;
+
+#[tokio::test]
+#[serial]
+async fn calendar_feed_lists_bookings_and_stops_working_once_rotated() {
+    request::<App, _, _>(|mut request, ctx| async move {
+        seed::<App>(&ctx).await.unwrap();
+        request.add_header("timezone", "America/Vancouver");
+
+        let user = Users::find_by_id(&ctx.db, 1).await.unwrap();
+        let settings = ctx.config.get_jwt_config().unwrap();
+        let jwt = user
+            .generate_jwt(&settings.secret, settings.expiration)
+            .unwrap();
+        request.add_header("Authorization", format!("Bearer {jwt}"));
+
+        let feed: serde_json::Value = request.get("/api/user_settings/calendar-feed").await.json();
+        assert!(feed["url"].is_null(), "The feed is off until turned on.");
+
+        let days: serde_json::Value = request
+            .get("/api/client-facing/availabilities/1")
+            .await
+            .json();
+        let slot = days[0]["availabilities"][0].clone();
+        let booked = request
+            .post("/api/client-facing/book/1")
+            .json(&serde_json::json!({
+                "booker_name": "Subscriber",
+                "booker_phone": "555555555",
+                "booker_email": "subscriber@example.com",
+                "from": slot["start"],
+                "to": slot["end"],
+            }))
+            .await;
+        assert_eq!(booked.status_code(), 200);
+
+        let feed: serde_json::Value = request
+            .post("/api/user_settings/calendar-feed")
+            .await
+            .json();
+        let url = feed["url"].as_str().unwrap().to_string();
+        let path = &url[url.find("/api/").unwrap()..];
+
+        let res = request.get(path).await;
+        assert_eq!(res.status_code(), 200);
+        assert!(res
+            .header("content-type")
+            .to_str()
+            .unwrap()
+            .starts_with("text/calendar"));
+        let calendar = res.text();
+        assert!(calendar.contains("METHOD:PUBLISH\r\n"));
+        assert!(calendar.contains("with Subscriber\r\n"));
+        assert!(calendar.contains("STATUS:CONFIRMED\r\n"));
+
+        let appointment = Appointments::find()
+            .filter(Column::BookerEmail.eq("subscriber@example.com"))
+            .one(&ctx.db)
+            .await
+            .unwrap()
+            .unwrap();
+        let cancelled = request
+            .patch(&format!("/api/appointments/cancel/{}", appointment.id))
+            .await;
+        assert_eq!(cancelled.status_code(), 200);
+        let calendar = request.get(path).await.text();
+        assert!(
+            calendar.contains("STATUS:CANCELLED\r\n"),
+            "Cancelled appointments stay in the feed for a while."
+        );
+
+        let rotated: serde_json::Value = request
+            .post("/api/user_settings/calendar-feed")
+            .await
+            .json();
+        assert_ne!(rotated["url"], feed["url"]);
+        assert_eq!(request.get(path).await.status_code(), 404);
+
+        let disabled: serde_json::Value = request
+            .delete("/api/user_settings/calendar-feed")
+            .await
+            .json();
+        assert!(disabled["url"].is_null());
+    })
+    .await;
+}
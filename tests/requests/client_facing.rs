@@ -0,0 +1,700 @@
+use std::future::IntoFuture;
+
+use appointments::{
+    app::App,
+    calendar_providers::{in_memory::InMemoryCalendarProvider, CalendarProviderKind},
+    models::{
+        _entities::appointments::{Column, IntakeAnswers},
+        appointment_types::{
+            AppointmentTypes, IntakeQuestion, IntakeQuestionKind, IntakeQuestions,
+        },
+        appointments::{ActiveModel, Appointments, CreateAppointmentProps},
+        outbox_jobs::{OutboxJobKind, OutboxJobs},
+        users::Users,
+    },
+    workers::refresh_busy_intervals::{RefreshBusyIntervalsWorker, RefreshBusyIntervalsWorkerArgs},
+};
+use loco_rs::{app::AppContext, bgworker::BackgroundWorker, testing::prelude::*, TestServer};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, IntoActiveModel, PaginatorTrait,
+    QueryFilter,
+};
+use serial_test::serial;
+
+#[tokio::test]
+#[serial]
+async fn can_get_appointment_by_manage_token() {
+    request::<App, _, _>(|request, ctx| async move {
+        seed::<App>(&ctx).await.unwrap();
+
+        let res = request
+            .get("/api/client-facing/manage/fixture-manage-token-1")
+            .await;
+        assert_eq!(res.status_code(), 200);
+        let body: serde_json::Value = res.json();
+        assert_eq!(body["booker_name"], "Daniel");
+        assert_eq!(body["status"], "Booked");
+
+        let res = request
+            .get("/api/client-facing/manage/not-a-real-token")
+            .await;
+        assert_eq!(res.status_code(), 404);
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_not_cancel_past_appointment_by_manage_token() {
+    request::<App, _, _>(|request, ctx| async move {
+        seed::<App>(&ctx).await.unwrap();
+
+        let res = request
+            .post("/api/client-facing/manage/fixture-manage-token-1/cancel")
+            .await;
+        assert_ne!(
+            res.status_code(),
+            200,
+            "Past appointments can not be cancelled."
+        );
+    })
+    .await;
+}
+
+/// Books the slot and returns the manage token of the appointment.
+async fn book(
+    request: &TestServer,
+    ctx: &AppContext,
+    slot: &serde_json::Value,
+    booker_email: &str,
+) -> String {
+    let booked = request
+        .post("/api/client-facing/book/1")
+        .json(&serde_json::json!({
+            "booker_name": "Manage",
+            "booker_phone": "555555555",
+            "booker_email": booker_email,
+            "from": slot["start"],
+            "to": slot["end"],
+        }))
+        .await;
+    assert_eq!(booked.status_code(), 200);
+
+    Appointments::find()
+        .filter(Column::BookerEmail.eq(booker_email))
+        .one(&ctx.db)
+        .await
+        .unwrap()
+        .unwrap()
+        .manage_token
+        .unwrap()
+}
+
+#[tokio::test]
+#[serial]
+async fn booker_can_cancel_through_the_manage_link() {
+    request::<App, _, _>(|mut request, ctx| async move {
+        seed::<App>(&ctx).await.unwrap();
+        request.add_header("timezone", "America/Vancouver");
+
+        let days: serde_json::Value = request
+            .get("/api/client-facing/availabilities/1")
+            .await
+            .json();
+        let slot = days[0]["availabilities"][0].clone();
+        let token = book(&request, &ctx, &slot, "cancel@example.com").await;
+
+        let res = request
+            .post(&format!("/api/client-facing/manage/{token}/cancel"))
+            .await;
+        assert_eq!(res.status_code(), 200);
+        assert_eq!(res.json::<serde_json::Value>()["status"], "Cancelled");
+
+        let days: serde_json::Value = request
+            .get("/api/client-facing/availabilities/1")
+            .await
+            .json();
+        assert_eq!(
+            days[0]["availabilities"][0]["start"], slot["start"],
+            "The slot is free again."
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn booker_can_reschedule_through_the_manage_link() {
+    request::<App, _, _>(|mut request, ctx| async move {
+        seed::<App>(&ctx).await.unwrap();
+        request.add_header("timezone", "America/Vancouver");
+
+        let days: serde_json::Value = request
+            .get("/api/client-facing/availabilities/1")
+            .await
+            .json();
+        let slot = days[0]["availabilities"][0].clone();
+        let token = book(&request, &ctx, &slot, "reschedule@example.com").await;
+
+        let days: Vec<serde_json::Value> = request
+            .get(&format!("/api/client-facing/manage/{token}/availabilities"))
+            .await
+            .json();
+        let slots: Vec<serde_json::Value> = days
+            .iter()
+            .flat_map(|day| day["availabilities"].as_array().unwrap().clone())
+            .collect();
+        assert_eq!(
+            slots[0]["start"], slot["start"],
+            "The booker's own slot stays on offer to them."
+        );
+        let new_slot = slots[1].clone();
+
+        let res = request
+            .post(&format!("/api/client-facing/manage/{token}/reschedule"))
+            .json(&serde_json::json!({ "from": new_slot["start"], "to": new_slot["end"] }))
+            .await;
+        assert_eq!(res.status_code(), 200);
+        let body: serde_json::Value = res.json();
+        assert_eq!(body["status"], "Booked");
+        assert_eq!(body["start_time"], new_slot["start"]);
+
+        let days: Vec<serde_json::Value> = request
+            .get("/api/client-facing/availabilities/1")
+            .await
+            .json();
+        let starts: Vec<serde_json::Value> = days
+            .iter()
+            .flat_map(|day| day["availabilities"].as_array().unwrap().clone())
+            .map(|window| window["start"].clone())
+            .collect();
+        assert!(
+            starts.contains(&slot["start"]),
+            "The old slot is free again."
+        );
+        assert!(!starts.contains(&new_slot["start"]));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn concurrent_bookings_for_the_same_slot_only_book_once() {
+    request::<App, _, _>(|mut request, ctx| async move {
+        seed::<App>(&ctx).await.unwrap();
+        request.add_header("timezone", "America/Vancouver");
+
+        let days: serde_json::Value = request
+            .get("/api/client-facing/availabilities/1")
+            .await
+            .json();
+        let slot = &days[0]["availabilities"][0];
+        let booking = |name: &str| {
+            serde_json::json!({
+                "booker_name": name,
+                "booker_phone": "555555555",
+                "booker_email": format!("{name}@example.com"),
+                "from": slot["start"],
+                "to": slot["end"],
+            })
+        };
+
+        let first_booking = booking("first");
+        let second_booking = booking("second");
+        let (first, second) = futures::future::join(
+            request
+                .post("/api/client-facing/book/1")
+                .json(&first_booking)
+                .into_future(),
+            request
+                .post("/api/client-facing/book/1")
+                .json(&second_booking)
+                .into_future(),
+        )
+        .await;
+
+        let mut statuses = vec![first.status_code().as_u16(), second.status_code().as_u16()];
+        statuses.sort_unstable();
+        assert_eq!(statuses, vec![200, 409], "Exactly one booking should win.");
+
+        let booked = Appointments::find()
+            .filter(
+                Column::StartTime.eq(slot["start"]
+                    .as_str()
+                    .unwrap()
+                    .parse::<chrono::DateTime<chrono::Utc>>()
+                    .unwrap()),
+            )
+            .count(&ctx.db)
+            .await
+            .unwrap();
+        assert_eq!(booked, 1);
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn slots_never_offered_are_rejected_as_invalid() {
+    request::<App, _, _>(|mut request, ctx| async move {
+        seed::<App>(&ctx).await.unwrap();
+        request.add_header("timezone", "America/Vancouver");
+
+        let days: serde_json::Value = request
+            .get("/api/client-facing/availabilities/1")
+            .await
+            .json();
+        let slot = days[0]["availabilities"][0].clone();
+        let start: chrono::DateTime<chrono::Utc> =
+            serde_json::from_value(slot["start"].clone()).unwrap();
+        // 03:00 in the owner's timezone, outside of every weekly window.
+        let night = start
+            .with_timezone(&chrono_tz::America::Vancouver)
+            .date_naive()
+            .and_hms_opt(3, 0, 0)
+            .unwrap()
+            .and_local_timezone(chrono_tz::America::Vancouver)
+            .unwrap()
+            .to_utc();
+        let booking = |from: chrono::DateTime<chrono::Utc>| {
+            serde_json::json!({
+                "booker_name": "Night",
+                "booker_phone": "555555555",
+                "booker_email": "night@example.com",
+                "from": from,
+                "to": from + chrono::Duration::hours(1),
+            })
+        };
+
+        let res = request
+            .post("/api/client-facing/book/1")
+            .json(&booking(night))
+            .await;
+        assert_eq!(res.status_code(), 400);
+
+        let res = request
+            .post("/api/client-facing/book/1")
+            .json(&booking(start))
+            .await;
+        assert_eq!(res.status_code(), 200);
+        let res = request
+            .post("/api/client-facing/book/1")
+            .json(&booking(start))
+            .await;
+        assert_eq!(res.status_code(), 409, "Taken, but it was on offer.");
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn buffers_keep_the_neighbouring_slots_free() {
+    request::<App, _, _>(|mut request, ctx| async move {
+        seed::<App>(&ctx).await.unwrap();
+        request.add_header("timezone", "America/Vancouver");
+
+        let mut appointment_type = AppointmentTypes::find_by_id(&ctx.db, 1)
+            .await
+            .unwrap()
+            .into_active_model();
+        appointment_type.buffer_after_minutes = ActiveValue::set(30);
+        let appointment_type = appointment_type.update(&ctx.db).await.unwrap();
+
+        let days: serde_json::Value = request
+            .get("/api/client-facing/availabilities/1")
+            .await
+            .json();
+        let slot = days[0]["availabilities"][0].clone();
+        let start: chrono::DateTime<chrono::Utc> =
+            serde_json::from_value(slot["start"].clone()).unwrap();
+        let next_start = start + chrono::Duration::hours(1);
+        let booking = |from: chrono::DateTime<chrono::Utc>| {
+            serde_json::json!({
+                "booker_name": "Buffer",
+                "booker_phone": "555555555",
+                "booker_email": "buffer@example.com",
+                "from": from,
+                "to": from + chrono::Duration::hours(1),
+            })
+        };
+        let res = request
+            .post("/api/client-facing/book/1")
+            .json(&booking(start))
+            .await;
+        assert_eq!(res.status_code(), 200);
+
+        let days: Vec<serde_json::Value> = request
+            .get("/api/client-facing/availabilities/1")
+            .await
+            .json();
+        let starts: Vec<chrono::DateTime<chrono::Utc>> = days
+            .iter()
+            .flat_map(|day| day["availabilities"].as_array().unwrap().clone())
+            .map(|window| serde_json::from_value(window["start"].clone()).unwrap())
+            .collect();
+        assert!(
+            !starts.contains(&next_start),
+            "The next slot starts inside the buffer."
+        );
+
+        let res = request
+            .post("/api/client-facing/book/1")
+            .json(&booking(next_start))
+            .await;
+        assert_eq!(res.status_code(), 409);
+
+        // Bookings that got past validation before the first one committed.
+        let user = Users::find_by_id(&ctx.db, 1).await.unwrap();
+        let raced = ActiveModel::create_if_available(
+            &ctx.db,
+            CreateAppointmentProps {
+                booker_phone: "555555555".to_string(),
+                booker_name: "Race".to_string(),
+                booker_timezone: chrono_tz::America::Vancouver,
+                booker_email: "race@example.com".to_string(),
+                start_time: next_start,
+                endtime: next_start + chrono::Duration::hours(1),
+                intake_answers: IntakeAnswers(vec![]),
+                pending_expires_at: None,
+                user: &user,
+                appointment_type: &appointment_type,
+            },
+        )
+        .await;
+        assert!(raced.is_err(), "The buffer is checked when writing too.");
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn group_slots_stay_bookable_until_full() {
+    request::<App, _, _>(|mut request, ctx| async move {
+        seed::<App>(&ctx).await.unwrap();
+        request.add_header("timezone", "America/Vancouver");
+
+        let mut appointment_type = AppointmentTypes::find_by_id(&ctx.db, 1)
+            .await
+            .unwrap()
+            .into_active_model();
+        appointment_type.max_attendees = ActiveValue::set(2);
+        appointment_type.update(&ctx.db).await.unwrap();
+
+        let days: serde_json::Value = request
+            .get("/api/client-facing/availabilities/1")
+            .await
+            .json();
+        let slot = days[0]["availabilities"][0].clone();
+        assert_eq!(slot["remaining_seats"], 2);
+        let booking = |name: &str| {
+            serde_json::json!({
+                "booker_name": name,
+                "booker_phone": "555555555",
+                "booker_email": format!("{name}@example.com"),
+                "from": slot["start"],
+                "to": slot["end"],
+            })
+        };
+
+        let first = request
+            .post("/api/client-facing/book/1")
+            .json(&booking("first"))
+            .await;
+        assert_eq!(first.status_code(), 200);
+
+        let days: serde_json::Value = request
+            .get("/api/client-facing/availabilities/1")
+            .await
+            .json();
+        assert_eq!(days[0]["availabilities"][0]["start"], slot["start"]);
+        assert_eq!(days[0]["availabilities"][0]["remaining_seats"], 1);
+
+        let second = request
+            .post("/api/client-facing/book/1")
+            .json(&booking("second"))
+            .await;
+        assert_eq!(second.status_code(), 200);
+
+        let third = request
+            .post("/api/client-facing/book/1")
+            .json(&booking("third"))
+            .await;
+        assert_eq!(third.status_code(), 409, "The slot is full.");
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn booking_validates_and_stores_intake_answers() {
+    request::<App, _, _>(|mut request, ctx| async move {
+        seed::<App>(&ctx).await.unwrap();
+        request.add_header("timezone", "America/Vancouver");
+
+        let mut appointment_type = AppointmentTypes::find_by_id(&ctx.db, 1)
+            .await
+            .unwrap()
+            .into_active_model();
+        appointment_type.intake_questions = ActiveValue::set(IntakeQuestions(vec![
+            IntakeQuestion {
+                key: "reason".to_string(),
+                label: "Reason for visit".to_string(),
+                kind: IntakeQuestionKind::LongText,
+                required: true,
+                options: vec![],
+            },
+            IntakeQuestion {
+                key: "insurance".to_string(),
+                label: "Insurance provider".to_string(),
+                kind: IntakeQuestionKind::Select,
+                required: false,
+                options: vec!["Acme".to_string(), "Globex".to_string()],
+            },
+        ]));
+        appointment_type.update(&ctx.db).await.unwrap();
+
+        let days: serde_json::Value = request
+            .get("/api/client-facing/availabilities/1")
+            .await
+            .json();
+        let slot = days[0]["availabilities"][0].clone();
+        let booking = |answers: serde_json::Value| {
+            serde_json::json!({
+                "booker_name": "Intake",
+                "booker_phone": "555555555",
+                "booker_email": "intake@example.com",
+                "from": slot["start"],
+                "to": slot["end"],
+                "answers": answers,
+            })
+        };
+
+        let missing = request
+            .post("/api/client-facing/book/1")
+            .json(&booking(serde_json::json!({ "insurance": "Acme" })))
+            .await;
+        assert_eq!(missing.status_code(), 400, "The reason is required.");
+
+        let unknown_option = request
+            .post("/api/client-facing/book/1")
+            .json(&booking(serde_json::json!({
+                "reason": "Checkup",
+                "insurance": "Initech",
+            })))
+            .await;
+        assert_eq!(unknown_option.status_code(), 400);
+
+        let booked = request
+            .post("/api/client-facing/book/1")
+            .json(&booking(serde_json::json!({
+                "reason": " Checkup ",
+                "insurance": "Acme",
+            })))
+            .await;
+        assert_eq!(booked.status_code(), 200);
+
+        let appointment = Appointments::find()
+            .filter(Column::BookerEmail.eq("intake@example.com"))
+            .one(&ctx.db)
+            .await
+            .unwrap()
+            .unwrap();
+        let answers: Vec<(&str, &str)> = appointment
+            .intake_answers
+            .0
+            .iter()
+            .map(|answer| (answer.question.as_str(), answer.answer.as_str()))
+            .collect();
+        assert_eq!(
+            answers,
+            vec![
+                ("Reason for visit", "Checkup"),
+                ("Insurance provider", "Acme")
+            ]
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn booking_queues_and_performs_its_side_effects() {
+    request::<App, _, _>(|mut request, ctx| async move {
+        seed::<App>(&ctx).await.unwrap();
+        request.add_header("timezone", "America/Vancouver");
+
+        let days: serde_json::Value = request
+            .get("/api/client-facing/availabilities/1")
+            .await
+            .json();
+        let slot = days[0]["availabilities"][0].clone();
+        let booked = request
+            .post("/api/client-facing/book/1")
+            .json(&serde_json::json!({
+                "booker_name": "Outbox",
+                "booker_phone": "555555555",
+                "booker_email": "outbox@example.com",
+                "from": slot["start"],
+                "to": slot["end"],
+            }))
+            .await;
+        assert_eq!(booked.status_code(), 200);
+
+        let appointment = Appointments::find()
+            .filter(Column::BookerEmail.eq("outbox@example.com"))
+            .one(&ctx.db)
+            .await
+            .unwrap()
+            .unwrap();
+        let jobs = OutboxJobs::find_by_appointment(&ctx.db, &appointment)
+            .await
+            .unwrap();
+        assert_eq!(
+            jobs.iter().map(|job| job.kind).collect::<Vec<_>>(),
+            vec![
+                OutboxJobKind::AddToCalendars,
+                OutboxJobKind::NotifyBooker,
+                OutboxJobKind::NotifyOwner,
+                OutboxJobKind::NotifyWebhooks
+            ]
+        );
+        assert!(jobs.iter().all(|job| job.completed_at.is_some()));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn connected_calendars_block_slots_and_receive_the_bookings() {
+    request::<App, _, _>(|mut request, ctx| async move {
+        seed::<App>(&ctx).await.unwrap();
+        request.add_header("timezone", "America/Vancouver");
+        let user = Users::find_by_id(&ctx.db, 1).await.unwrap();
+        let calendar = InMemoryCalendarProvider::connect(&user);
+
+        let days: serde_json::Value = request
+            .get("/api/client-facing/availabilities/1")
+            .await
+            .json();
+        let busy_slot = days[0]["availabilities"][0].clone();
+        calendar.add_busy(
+            serde_json::from_value(busy_slot["start"].clone()).unwrap(),
+            serde_json::from_value(busy_slot["end"].clone()).unwrap(),
+        );
+
+        // Availability comes from the stored busy times until they are refreshed, booking
+        // asks the calendar itself.
+        let days: serde_json::Value = request
+            .get("/api/client-facing/availabilities/1")
+            .await
+            .json();
+        assert_eq!(days[0]["availabilities"][0]["start"], busy_slot["start"]);
+        let rejected = request
+            .post("/api/client-facing/book/1")
+            .json(&serde_json::json!({
+                "booker_name": "Calendar",
+                "booker_phone": "555555555",
+                "booker_email": "calendar@example.com",
+                "from": busy_slot["start"],
+                "to": busy_slot["end"],
+            }))
+            .await;
+        assert_eq!(rejected.status_code(), 409);
+
+        RefreshBusyIntervalsWorker::perform_later(&ctx, RefreshBusyIntervalsWorkerArgs {})
+            .await
+            .unwrap();
+        let days: serde_json::Value = request
+            .get("/api/client-facing/availabilities/1")
+            .await
+            .json();
+        let slot = days[0]["availabilities"][0].clone();
+        assert_ne!(slot["start"], busy_slot["start"]);
+
+        let booked = request
+            .post("/api/client-facing/book/1")
+            .json(&serde_json::json!({
+                "booker_name": "Calendar",
+                "booker_phone": "555555555",
+                "booker_email": "calendar@example.com",
+                "from": slot["start"],
+                "to": slot["end"],
+            }))
+            .await;
+        assert_eq!(booked.status_code(), 200);
+
+        let appointment = Appointments::find()
+            .filter(Column::BookerEmail.eq("calendar@example.com"))
+            .one(&ctx.db)
+            .await
+            .unwrap()
+            .unwrap();
+        let events = calendar.events();
+        assert_eq!(events.len(), 1);
+        assert!(events[0]
+            .attendees
+            .contains(&"calendar@example.com".to_string()));
+        assert_eq!(appointment.calendar_events.len(), 1);
+        assert_eq!(
+            appointment.calendar_events[0].provider,
+            CalendarProviderKind::InMemory
+        );
+
+        let cancelled = request
+            .post(&format!(
+                "/api/client-facing/manage/{}/cancel",
+                appointment.manage_token.unwrap()
+            ))
+            .await;
+        assert_eq!(cancelled.status_code(), 200);
+        assert!(calendar.events().is_empty());
+
+        InMemoryCalendarProvider::disconnect(&user);
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn booking_waits_for_calendars_that_do_not_answer() {
+    request::<App, _, _>(|mut request, ctx| async move {
+        seed::<App>(&ctx).await.unwrap();
+        request.add_header("timezone", "America/Vancouver");
+        let user = Users::find_by_id(&ctx.db, 1).await.unwrap();
+        let calendar = InMemoryCalendarProvider::connect(&user);
+
+        let days: serde_json::Value = request
+            .get("/api/client-facing/availabilities/1")
+            .await
+            .json();
+        let slot = days[0]["availabilities"][0].clone();
+        let booking = serde_json::json!({
+            "booker_name": "Calendar",
+            "booker_phone": "555555555",
+            "booker_email": "calendar@example.com",
+            "from": slot["start"],
+            "to": slot["end"],
+        });
+
+        calendar.set_connected(false);
+        let res = request
+            .post("/api/client-facing/book/1")
+            .json(&booking)
+            .await;
+        assert_eq!(res.status_code(), 503);
+        assert_eq!(
+            res.json::<serde_json::Value>()["error"],
+            "calendar_unavailable"
+        );
+
+        calendar.set_connected(true);
+        let res = request
+            .post("/api/client-facing/book/1")
+            .json(&booking)
+            .await;
+        assert_eq!(res.status_code(), 200);
+
+        InMemoryCalendarProvider::disconnect(&user);
+    })
+    .await;
+}
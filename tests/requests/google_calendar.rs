@@ -1,16 +1,275 @@
-use appointments::app::App;
-use loco_rs::testing::prelude::*;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use appointments::{
+    app::App,
+    models::{
+        _entities::{admin_settings::GoogleCalendarSettings, google_calendar_channels},
+        admin_settings::AdminSettings,
+        google_calendars::{self, GoogleCalendars, OAuthTokenResponseSuccess},
+        oauth_states,
+        users::{self, Users},
+    },
+};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::{Duration, Utc};
+use loco_rs::{app::AppContext, testing::prelude::*};
+use sea_orm::{ActiveModelTrait, ActiveValue, IntoActiveModel};
 use serial_test::serial;
 
+async fn connect_account(
+    ctx: &AppContext,
+    user: users::Model,
+    account_email: &str,
+) -> google_calendars::Model {
+    google_calendars::ActiveModel::create(
+        &ctx.db,
+        OAuthTokenResponseSuccess {
+            access_token: format!("{account_email}-access-token"),
+            expires_in: 3600,
+            refresh_token: format!("{account_email}-refresh-token"),
+            refresh_token_expires_in: None,
+            scope: "https://www.googleapis.com/auth/calendar.events".to_string(),
+            token_type: "Bearer".to_string(),
+        },
+        user,
+        account_email.to_string(),
+    )
+    .await
+    .unwrap()
+}
+
 #[tokio::test]
 #[serial]
-async fn can_get_google_calendars() {
-    request::<App, _, _>(|request, _ctx| async move {
-        let res = request.get("/api/google_calendars/").await;
+async fn lists_every_connected_google_account() {
+    request::<App, _, _>(|mut request, ctx| async move {
+        seed::<App>(&ctx).await.unwrap();
+
+        let user = Users::find_by_id(&ctx.db, 1).await.unwrap();
+        let work = connect_account(&ctx, user.clone(), "work@example.com").await;
+        let personal = connect_account(&ctx, user.clone(), "personal@example.com").await;
+
+        let settings = ctx.config.get_jwt_config().unwrap();
+        let jwt = user
+            .generate_jwt(&settings.secret, settings.expiration)
+            .unwrap();
+        request.add_header("Authorization", format!("Bearer {jwt}"));
+
+        let res = request.get("/api/google_calendar").await;
         assert_eq!(res.status_code(), 200);
+        let connections: serde_json::Value = res.json();
+        let connections = connections.as_array().unwrap();
+        assert_eq!(connections.len(), 2);
+        assert_eq!(connections[0]["id"], work.id);
+        assert_eq!(connections[0]["account_email"], "work@example.com");
+        assert_eq!(connections[0]["connected"], true);
+        assert_eq!(connections[1]["id"], personal.id);
+
+        let res = request
+            .post(&format!(
+                "/api/google_calendar/{}/add_calendar",
+                personal.id
+            ))
+            .json(&serde_json::json!({
+                "calendar_id": "personal@example.com",
+                "setting_type": "CollisionCheck",
+            }))
+            .await;
+        assert_eq!(res.status_code(), 200);
+
+        let res = request
+            .get(&format!("/api/google_calendar/{}/get_settings", work.id))
+            .await;
+        assert_eq!(res.status_code(), 200);
+        let work_settings: serde_json::Value = res.json();
+        assert_eq!(
+            work_settings["calendars_for_collision_check"],
+            serde_json::json!([]),
+            "Each account keeps its own calendars."
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn can_not_use_the_google_account_of_another_user() {
+    request::<App, _, _>(|mut request, ctx| async move {
+        seed::<App>(&ctx).await.unwrap();
+
+        let owner = Users::find_by_id(&ctx.db, 1).await.unwrap();
+        let connection = connect_account(&ctx, owner, "owner@example.com").await;
+
+        let other = Users::find_by_id(&ctx.db, 2).await.unwrap();
+        let settings = ctx.config.get_jwt_config().unwrap();
+        let jwt = other
+            .generate_jwt(&settings.secret, settings.expiration)
+            .unwrap();
+        request.add_header("Authorization", format!("Bearer {jwt}"));
+
+        let res = request
+            .get(&format!(
+                "/api/google_calendar/{}/get_settings",
+                connection.id
+            ))
+            .await;
+        assert_eq!(res.status_code(), 404);
+
+        let res = request
+            .delete(&format!("/api/google_calendar/{}", connection.id))
+            .await;
+        assert_eq!(res.status_code(), 404);
+
+        let res = request.get("/api/google_calendar").await;
+        assert_eq!(res.json::<serde_json::Value>(), serde_json::json!([]));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn only_accepts_notifications_of_known_channels() {
+    request::<App, _, _>(|request, ctx| async move {
+        seed::<App>(&ctx).await.unwrap();
+
+        let user = Users::find_by_id(&ctx.db, 1).await.unwrap();
+        let google_calendar = connect_account(&ctx, user, "work@example.com").await;
+        google_calendar_channels::ActiveModel {
+            google_calendar_id: ActiveValue::Set(google_calendar.id),
+            calendar_id: ActiveValue::Set("work@example.com".to_string()),
+            channel_id: ActiveValue::Set("channel-1".to_string()),
+            resource_id: ActiveValue::Set("work-events".to_string()),
+            token: ActiveValue::Set("channel-token".to_string()),
+            expires_at: ActiveValue::Set((Utc::now() + Duration::days(7)).into()),
+            ..Default::default()
+        }
+        .insert(&ctx.db)
+        .await
+        .unwrap();
+
+        let notify = |token: &'static str, state: &'static str| {
+            request
+                .post("/api/google_calendar/notifications")
+                .add_header("x-goog-channel-id", "channel-1")
+                .add_header("x-goog-channel-token", token)
+                .add_header("x-goog-resource-id", "work-events")
+                .add_header("x-goog-resource-state", state)
+        };
+
+        assert_eq!(notify("guessed-token", "exists").await.status_code(), 404);
+        assert_eq!(notify("channel-token", "sync").await.status_code(), 200);
+        assert_eq!(notify("channel-token", "exists").await.status_code(), 200);
+    })
+    .await;
+}
+
+/// Google stand-in for the OAuth callback: grants tokens for any code and answers who the
+/// account is while `account_readable` is set.
+async fn start_google(account_readable: Arc<AtomicBool>) -> String {
+    let app = Router::new()
+        .route(
+            "/token",
+            post(|| async {
+                Json(serde_json::json!({
+                    "access_token": "google-access-token",
+                    "expires_in": 3600,
+                    "refresh_token": "google-refresh-token",
+                    "scope": "https://www.googleapis.com/auth/calendar.events",
+                    "token_type": "Bearer",
+                }))
+            }),
+        )
+        .route(
+            "/calendars/primary",
+            get(
+                |State(account_readable): State<Arc<AtomicBool>>| async move {
+                    if account_readable.load(Ordering::SeqCst) {
+                        (
+                            StatusCode::OK,
+                            Json(serde_json::json!({ "id": "work@example.com" })),
+                        )
+                    } else {
+                        (StatusCode::NOT_FOUND, Json(serde_json::json!({})))
+                    }
+                },
+            ),
+        )
+        .with_state(account_readable);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{address}")
+}
+
+#[tokio::test]
+#[serial]
+async fn oauth_callback_connects_each_google_account_once() {
+    request::<App, _, _>(|request, ctx| async move {
+        seed::<App>(&ctx).await.unwrap();
+
+        let account_readable = Arc::new(AtomicBool::new(false));
+        let google = start_google(account_readable.clone()).await;
+        let mut admin_settings = AdminSettings::load(&ctx.db)
+            .await
+            .unwrap()
+            .into_active_model();
+        admin_settings.google_calendar_settings = ActiveValue::Set(Some(GoogleCalendarSettings {
+            google_calendar_api_key: "google-api-key".to_string(),
+            google_oauth_client_id: "google-client".to_string(),
+            google_oauth_secret: "google-secret".to_string(),
+            google_oauth_redirect_uri_base: url::Url::parse("https://appointments.example.com")
+                .unwrap(),
+            google_oauth_token_url: Some(url::Url::parse(&format!("{google}/token")).unwrap()),
+            google_calendar_api_url: Some(url::Url::parse(&google).unwrap()),
+        }));
+        admin_settings.update(&ctx.db).await.unwrap();
+
+        let user = Users::find_by_id(&ctx.db, 1).await.unwrap();
+        let callback = |state: oauth_states::Model| {
+            format!(
+                "/api/google_calendar/oauth_callback?code=google-code&state={}&scope=calendar",
+                state.id
+            )
+        };
+
+        let state = oauth_states::ActiveModel::create(&ctx.db, &user)
+            .await
+            .unwrap();
+        let res = request.get(&callback(state)).await;
+        assert!(res
+            .header("location")
+            .to_str()
+            .unwrap()
+            .contains("error=account_unknown"));
+        assert!(GoogleCalendars::find_by_user(&ctx.db, &user)
+            .await
+            .unwrap()
+            .is_empty());
 
-        // you can assert content like this:
-        // assert_eq!(res.text(), "content");
+        account_readable.store(true, Ordering::SeqCst);
+        for _ in 0..2 {
+            let state = oauth_states::ActiveModel::create(&ctx.db, &user)
+                .await
+                .unwrap();
+            let res = request.get(&callback(state)).await;
+            assert_eq!(
+                res.header("location").to_str().unwrap(),
+                "/dashboard/integrations?success"
+            );
+        }
+        let connections = GoogleCalendars::find_by_user(&ctx.db, &user).await.unwrap();
+        assert_eq!(connections.len(), 1);
+        assert_eq!(
+            connections[0].account_email.as_deref(),
+            Some("work@example.com")
+        );
     })
     .await;
 }
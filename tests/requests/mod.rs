@@ -4,7 +4,13 @@ mod prepare_data;
 pub mod admin_settings;
 pub mod appointment_types;
 pub mod appointments;
+pub mod availability_overrides;
+pub mod caldav;
+pub mod calendar_feed;
+pub mod client_facing;
 pub mod google_calendar;
+pub mod outlook_calendar;
+pub mod webhooks;
 pub mod weekly_availabilities;
 
 pub mod user_settings;
@@ -0,0 +1,333 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use appointments::{
+    app::App,
+    calendar_providers::CalendarProviderKind,
+    models::{
+        _entities::appointments::Column, appointments::Appointments,
+        google_calendars::OAuthTokenResponseSuccess, outlook_calendars, users::Users,
+    },
+    workers::refresh_busy_intervals::{RefreshBusyIntervalsWorker, RefreshBusyIntervalsWorkerArgs},
+};
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
+use loco_rs::{bgworker::BackgroundWorker, testing::prelude::*};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serial_test::serial;
+
+const ACCESS_TOKEN: &str = "outlook-access-token";
+const MAILBOX: &str = "owner@contoso.example";
+
+#[derive(Default)]
+struct MockGraph {
+    busy: Vec<(String, String)>,
+    events: HashMap<String, serde_json::Value>,
+    deleted: Vec<String>,
+}
+
+type SharedGraph = Arc<Mutex<MockGraph>>;
+
+fn authorize(headers: &HeaderMap) -> Result<(), StatusCode> {
+    let expected = format!("Bearer {ACCESS_TOKEN}");
+    if headers
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        == Some(expected.as_str())
+    {
+        Ok(())
+    } else {
+        Err(StatusCode::UNAUTHORIZED)
+    }
+}
+
+async fn calendars(headers: HeaderMap) -> Result<Json<serde_json::Value>, StatusCode> {
+    authorize(&headers)?;
+    Ok(Json(serde_json::json!({
+        "value": [
+            {
+                "id": "calendar-1",
+                "name": "Calendar",
+                "canEdit": true,
+                "isDefaultCalendar": true,
+                "owner": { "name": "Owner", "address": MAILBOX },
+            },
+            {
+                "id": "holidays",
+                "name": "Holidays",
+                "canEdit": false,
+                "isDefaultCalendar": false,
+                "owner": { "name": "Holidays", "address": "holidays@contoso.example" },
+            },
+        ],
+    })))
+}
+
+async fn get_schedule(
+    State(graph): State<SharedGraph>,
+    headers: HeaderMap,
+    Json(body): Json<serde_json::Value>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    authorize(&headers)?;
+    assert_eq!(body["schedules"], serde_json::json!([MAILBOX]));
+
+    let items: Vec<_> = graph
+        .lock()
+        .unwrap()
+        .busy
+        .iter()
+        .map(|(start, end)| {
+            serde_json::json!({
+                "status": "busy",
+                "start": { "dateTime": start, "timeZone": "UTC" },
+                "end": { "dateTime": end, "timeZone": "UTC" },
+            })
+        })
+        .collect();
+    Ok(Json(serde_json::json!({
+        "value": [{ "scheduleId": MAILBOX, "scheduleItems": items }],
+    })))
+}
+
+async fn create_event(
+    State(graph): State<SharedGraph>,
+    Path(calendar_id): Path<String>,
+    headers: HeaderMap,
+    Json(mut event): Json<serde_json::Value>,
+) -> Result<(StatusCode, Json<serde_json::Value>), StatusCode> {
+    authorize(&headers)?;
+    assert_eq!(calendar_id, "calendar-1");
+
+    let mut graph = graph.lock().unwrap();
+    event["id"] = format!("event-{}", graph.events.len() + 1).into();
+    graph
+        .events
+        .insert(event["id"].as_str().unwrap().to_string(), event.clone());
+    Ok((StatusCode::CREATED, Json(event)))
+}
+
+async fn delete_event(
+    State(graph): State<SharedGraph>,
+    Path(event_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<StatusCode, StatusCode> {
+    authorize(&headers)?;
+
+    let mut graph = graph.lock().unwrap();
+    graph.deleted.push(event_id.clone());
+    match graph.events.remove(&event_id) {
+        Some(_) => Ok(StatusCode::NO_CONTENT),
+        None => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+/// Serves a stand-in for the Graph calendar endpoints and returns its `v1.0` root.
+async fn start_graph_server(graph: SharedGraph) -> String {
+    let app = Router::new()
+        .route("/v1.0/me/calendars", get(calendars))
+        .route("/v1.0/me/calendar/getSchedule", post(get_schedule))
+        .route(
+            "/v1.0/me/calendars/{calendar_id}/events",
+            post(create_event),
+        )
+        .route(
+            "/v1.0/me/events/{event_id}",
+            axum::routing::delete(delete_event),
+        )
+        .with_state(graph);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{address}/v1.0/")
+}
+
+#[tokio::test]
+#[serial]
+async fn oauth_url_points_to_microsoft_with_the_configured_client() {
+    request::<App, _, _>(|mut request, ctx| async move {
+        seed::<App>(&ctx).await.unwrap();
+
+        let user = Users::find_by_id(&ctx.db, 1).await.unwrap();
+        let settings = ctx.config.get_jwt_config().unwrap();
+        let jwt = user
+            .generate_jwt(&settings.secret, settings.expiration)
+            .unwrap();
+        request.add_header("Authorization", format!("Bearer {jwt}"));
+
+        let res = request
+            .put("/api/admin_settings")
+            .json(&serde_json::json!({
+                "outlook_calendar_settings": {
+                    "outlook_oauth_client_id": "outlook-client",
+                    "outlook_oauth_secret": "outlook-secret",
+                    "outlook_oauth_redirect_uri_base": "https://appointments.example.com",
+                },
+            }))
+            .await;
+        assert_eq!(res.status_code(), 200);
+        let admin_settings: serde_json::Value = res.json();
+        assert_eq!(
+            admin_settings["outlook_calendar_settings"]["outlook_oauth_secret"], "**************",
+            "The secret is never sent back."
+        );
+
+        let oauth_url: String = request.get("/api/outlook_calendar/oauth_url").await.json();
+        let oauth_url = url::Url::parse(&oauth_url).unwrap();
+        assert_eq!(oauth_url.host_str(), Some("login.microsoftonline.com"));
+        let query: HashMap<_, _> = oauth_url.query_pairs().into_owned().collect();
+        assert_eq!(query["client_id"], "outlook-client");
+        assert_eq!(
+            query["redirect_uri"],
+            "https://appointments.example.com/api/outlook_calendar/oauth_callback"
+        );
+        assert!(query["scope"].contains("Calendars.ReadWrite"));
+        assert!(query["scope"].contains("offline_access"));
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn outlook_busy_times_block_slots_and_bookings_get_teams_events() {
+    request::<App, _, _>(|mut request, ctx| async move {
+        seed::<App>(&ctx).await.unwrap();
+        request.add_header("timezone", "America/Vancouver");
+
+        let graph = SharedGraph::default();
+        let graph_base_url = start_graph_server(graph.clone()).await;
+
+        let user = Users::find_by_id(&ctx.db, 1).await.unwrap();
+        let settings = ctx.config.get_jwt_config().unwrap();
+        let jwt = user
+            .generate_jwt(&settings.secret, settings.expiration)
+            .unwrap();
+        request.add_header("Authorization", format!("Bearer {jwt}"));
+
+        let res = request
+            .put("/api/admin_settings")
+            .json(&serde_json::json!({
+                "outlook_calendar_settings": {
+                    "outlook_oauth_client_id": "outlook-client",
+                    "outlook_oauth_secret": "outlook-secret",
+                    "outlook_oauth_redirect_uri_base": "https://appointments.example.com",
+                    "graph_base_url": graph_base_url,
+                },
+            }))
+            .await;
+        assert_eq!(res.status_code(), 200);
+
+        outlook_calendars::ActiveModel::create(
+            &ctx.db,
+            OAuthTokenResponseSuccess {
+                access_token: ACCESS_TOKEN.to_string(),
+                expires_in: 3600,
+                refresh_token: "outlook-refresh-token".to_string(),
+                refresh_token_expires_in: None,
+                scope: "Calendars.ReadWrite".to_string(),
+                token_type: "Bearer".to_string(),
+            },
+            user.clone(),
+        )
+        .await
+        .unwrap();
+
+        let calendars: serde_json::Value = request
+            .get("/api/outlook_calendar/get_calendars")
+            .await
+            .json();
+        assert_eq!(calendars[0]["id"], "calendar-1");
+        assert_eq!(calendars[0]["primary"], true);
+        assert_eq!(calendars[1]["can_edit"], false);
+
+        for setting_type in ["CollisionCheck", "EventHandling"] {
+            let res = request
+                .post("/api/outlook_calendar/add_calendar")
+                .json(&serde_json::json!({
+                    "calendar_id": "calendar-1",
+                    "setting_type": setting_type,
+                }))
+                .await;
+            assert_eq!(res.status_code(), 200);
+        }
+
+        let days: serde_json::Value = request
+            .get("/api/client-facing/availabilities/1")
+            .await
+            .json();
+        let busy_slot = days[0]["availabilities"][0].clone();
+        let as_graph_time = |value: &serde_json::Value| {
+            serde_json::from_value::<chrono::DateTime<chrono::Utc>>(value.clone())
+                .unwrap()
+                .format("%Y-%m-%dT%H:%M:%S.0000000")
+                .to_string()
+        };
+        graph.lock().unwrap().busy.push((
+            as_graph_time(&busy_slot["start"]),
+            as_graph_time(&busy_slot["end"]),
+        ));
+
+        RefreshBusyIntervalsWorker::perform_later(&ctx, RefreshBusyIntervalsWorkerArgs {})
+            .await
+            .unwrap();
+
+        let days: serde_json::Value = request
+            .get("/api/client-facing/availabilities/1")
+            .await
+            .json();
+        let slot = days[0]["availabilities"][0].clone();
+        assert_ne!(slot["start"], busy_slot["start"]);
+
+        let booked = request
+            .post("/api/client-facing/book/1")
+            .json(&serde_json::json!({
+                "booker_name": "Outlook",
+                "booker_phone": "555555555",
+                "booker_email": "outlook@example.com",
+                "from": slot["start"],
+                "to": slot["end"],
+            }))
+            .await;
+        assert_eq!(booked.status_code(), 200);
+
+        let appointment = Appointments::find()
+            .filter(Column::BookerEmail.eq("outlook@example.com"))
+            .one(&ctx.db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(appointment.calendar_events.len(), 1);
+        let event = &appointment.calendar_events[0];
+        assert_eq!(event.provider, CalendarProviderKind::Outlook);
+        assert_eq!(event.calendar_id, "calendar-1");
+
+        let created = graph.lock().unwrap().events[&event.event_id].clone();
+        assert_eq!(created["isOnlineMeeting"], true);
+        assert_eq!(created["onlineMeetingProvider"], "teamsForBusiness");
+        assert_eq!(
+            created["attendees"][0]["emailAddress"]["address"],
+            "outlook@example.com"
+        );
+        assert_eq!(created["start"]["timeZone"], "UTC");
+
+        let cancelled = request
+            .post(&format!(
+                "/api/client-facing/manage/{}/cancel",
+                appointment.manage_token.unwrap()
+            ))
+            .await;
+        assert_eq!(cancelled.status_code(), 200);
+        assert_eq!(graph.lock().unwrap().deleted, vec![event.event_id.clone()]);
+
+        let res = request.delete("/api/outlook_calendar").await;
+        assert_eq!(res.status_code(), 200);
+        let res = request.get("/api/outlook_calendar/get_settings").await;
+        assert_eq!(res.status_code(), 404);
+    })
+    .await;
+}
@@ -0,0 +1,108 @@
+use appointments::{app::App, models::users::Users};
+use loco_rs::testing::prelude::*;
+use serial_test::serial;
+
+/// Nothing listens on the discard port, so deliveries fail right away.
+const UNREACHABLE_URL: &str = "http://127.0.0.1:9/hooks";
+
+#[tokio::test]
+#[serial]
+async fn can_not_create_webhook_endpoint_without_events_or_valid_url() {
+    request::<App, _, _>(|mut request, ctx| async move {
+        seed::<App>(&ctx).await.unwrap();
+
+        let user = Users::find_by_id(&ctx.db, 1).await.unwrap();
+        let settings = ctx.config.get_jwt_config().unwrap();
+        let jwt = user
+            .generate_jwt(&settings.secret, settings.expiration)
+            .unwrap();
+        request.add_header("Authorization", format!("Bearer {jwt}"));
+
+        let res = request
+            .post("/api/webhooks")
+            .json(&serde_json::json!({ "url": UNREACHABLE_URL, "events": [] }))
+            .await;
+        assert_ne!(res.status_code(), 200, "At least one event is required.");
+
+        let res = request
+            .post("/api/webhooks")
+            .json(&serde_json::json!({
+                "url": "ftp://example.com/hooks",
+                "events": ["appointment.booked"],
+            }))
+            .await;
+        assert_ne!(res.status_code(), 200, "Only http and https are allowed.");
+    })
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn bookings_are_delivered_to_subscribed_endpoints_and_can_be_replayed() {
+    request::<App, _, _>(|mut request, ctx| async move {
+        seed::<App>(&ctx).await.unwrap();
+        request.add_header("timezone", "America/Vancouver");
+
+        let user = Users::find_by_id(&ctx.db, 1).await.unwrap();
+        let settings = ctx.config.get_jwt_config().unwrap();
+        let jwt = user
+            .generate_jwt(&settings.secret, settings.expiration)
+            .unwrap();
+        request.add_header("Authorization", format!("Bearer {jwt}"));
+
+        let endpoint: serde_json::Value = request
+            .post("/api/webhooks")
+            .json(&serde_json::json!({
+                "url": UNREACHABLE_URL,
+                "events": ["appointment.booked", "appointment.cancelled"],
+            }))
+            .await
+            .json();
+        assert!(endpoint["secret"].as_str().unwrap().starts_with("whsec_"));
+
+        let days: serde_json::Value = request
+            .get("/api/client-facing/availabilities/1")
+            .await
+            .json();
+        let slot = days[0]["availabilities"][0].clone();
+        let booked = request
+            .post("/api/client-facing/book/1")
+            .json(&serde_json::json!({
+                "booker_name": "Hooked",
+                "booker_phone": "555555555",
+                "booker_email": "hooked@example.com",
+                "from": slot["start"],
+                "to": slot["end"],
+            }))
+            .await;
+        assert_eq!(booked.status_code(), 200);
+
+        let deliveries_url = format!("/api/webhooks/{}/deliveries", endpoint["id"]);
+        let deliveries: Vec<serde_json::Value> = request.get(&deliveries_url).await.json();
+        assert_eq!(deliveries.len(), 1);
+        let delivery = &deliveries[0];
+        assert_eq!(delivery["event"], "appointment.booked");
+        assert_eq!(
+            delivery["payload"]["appointment"]["booker_email"],
+            "hooked@example.com"
+        );
+        assert_eq!(delivery["attempt"], 1);
+        assert!(delivery["error"].is_string());
+        assert!(
+            delivery["retry_at"].is_string(),
+            "Failed deliveries are retried."
+        );
+
+        let replayed = request
+            .post(&format!(
+                "/api/webhooks/deliveries/{}/replay",
+                delivery["id"]
+            ))
+            .await;
+        assert_eq!(replayed.status_code(), 200);
+        let deliveries: Vec<serde_json::Value> = request.get(&deliveries_url).await.json();
+        assert_eq!(deliveries.len(), 2);
+        assert_eq!(deliveries[0]["payload"], delivery["payload"]);
+    })
+    .await;
+}
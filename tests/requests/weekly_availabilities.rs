@@ -1,5 +1,9 @@
-use appointments::app::App;
+use appointments::{
+    app::App,
+    models::{users::Users, weekly_availabilities::WeeklyAvailabilities},
+};
 use loco_rs::testing::prelude::*;
+use sea_orm::EntityTrait;
 use serial_test::serial;
 
 #[tokio::test]
@@ -14,3 +18,38 @@ async fn can_get_weekly_availabilities() {
     })
     .await;
 }
+
+#[tokio::test]
+#[serial]
+async fn can_not_change_weekly_availabilities_of_other_users() {
+    request::<App, _, _>(|mut request, ctx| async move {
+        seed::<App>(&ctx).await.unwrap();
+
+        let user = Users::find_by_id(&ctx.db, 2).await.unwrap();
+        let settings = ctx.config.get_jwt_config().unwrap();
+        let jwt = user
+            .generate_jwt(&settings.secret, settings.expiration)
+            .unwrap();
+        request.add_header("Authorization", format!("Bearer {jwt}"));
+
+        let res = request
+            .put("/api/weekly_availabilities/1")
+            .json(&serde_json::json!({
+                "normalized": { "from": 0, "to": 60 },
+                "weekday": 0,
+            }))
+            .await;
+        assert_eq!(res.status_code(), 401);
+
+        let res = request.delete("/api/weekly_availabilities/1").await;
+        assert_eq!(res.status_code(), 401);
+
+        let window = WeeklyAvailabilities::find_by_id(1)
+            .one(&ctx.db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(window.user_id, 1);
+    })
+    .await;
+}
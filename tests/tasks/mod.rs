@@ -1 +1 @@
-
+mod reencrypt_secrets;
@@ -0,0 +1,188 @@
+use appointments::{
+    app::App,
+    common::encryption::{self, Keyring},
+    models::{
+        _entities::{
+            admin_settings::{self, GoogleCalendarSettings},
+            caldav_connections::Column as CaldavConnectionColumn,
+            google_calendars::Column,
+        },
+        admin_settings::AdminSettings,
+        caldav_connections::{self, CaldavConnections},
+        google_calendars::{self, GoogleCalendars, OAuthTokenResponseSuccess},
+        users::Users,
+    },
+    tasks::reencrypt_secrets::ReencryptSecrets,
+};
+use loco_rs::{
+    task::{self, Task},
+    testing::prelude::*,
+};
+use sea_orm::{
+    sea_query::Expr, ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, QueryFilter,
+};
+use serial_test::serial;
+
+#[tokio::test]
+#[serial]
+async fn plaintext_secrets_are_encrypted_and_stay_readable() {
+    let boot = boot_test::<App>().await.unwrap();
+    seed::<App>(&boot.app_context).await.unwrap();
+    let ctx = &boot.app_context;
+
+    let user = Users::find_by_id(&ctx.db, 1).await.unwrap();
+    let google_calendar = google_calendars::ActiveModel::create(
+        &ctx.db,
+        OAuthTokenResponseSuccess {
+            access_token: "google-access-token".to_string(),
+            expires_in: 3600,
+            refresh_token: "google-refresh-token".to_string(),
+            refresh_token_expires_in: None,
+            scope: "https://www.googleapis.com/auth/calendar.events".to_string(),
+            token_type: "Bearer".to_string(),
+        },
+        user,
+        "owner@example.com".to_string(),
+    )
+    .await
+    .unwrap();
+    assert!(encryption::is_current(&google_calendar.access_token));
+
+    // Rows written before encryption, the update skips the model hooks.
+    GoogleCalendars::update_many()
+        .col_expr(Column::AccessToken, Expr::value("google-access-token"))
+        .col_expr(Column::RefreshToken, Expr::value("google-refresh-token"))
+        .filter(Column::Id.eq(google_calendar.id))
+        .exec(&ctx.db)
+        .await
+        .unwrap();
+    AdminSettings::update_many()
+        .col_expr(
+            admin_settings::Column::GoogleCalendarSettings,
+            Expr::value(
+                serde_json::to_value(GoogleCalendarSettings {
+                    google_calendar_api_key: "google-api-key".to_string(),
+                    google_oauth_client_id: "google-client".to_string(),
+                    google_oauth_secret: "google-secret".to_string(),
+                    google_oauth_redirect_uri_base: url::Url::parse(
+                        "https://appointments.example.com",
+                    )
+                    .unwrap(),
+                    google_oauth_token_url: None,
+                    google_calendar_api_url: None,
+                })
+                .unwrap(),
+            ),
+        )
+        .exec(&ctx.db)
+        .await
+        .unwrap();
+    assert_eq!(
+        AdminSettings::get_google_calendar_settings(&ctx.db)
+            .await
+            .unwrap()
+            .google_oauth_secret,
+        "google-secret",
+        "Plaintext is read as it is."
+    );
+
+    ReencryptSecrets
+        .run(ctx, &task::Vars::from_cli_args(vec![]))
+        .await
+        .unwrap();
+
+    let google_calendar = GoogleCalendars::find_by_id(google_calendar.id)
+        .one(&ctx.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(encryption::is_current(&google_calendar.access_token));
+    assert!(encryption::is_current(&google_calendar.refresh_token));
+    assert_eq!(
+        encryption::decrypt(&google_calendar.refresh_token).unwrap(),
+        "google-refresh-token"
+    );
+
+    let stored = AdminSettings::load(&ctx.db)
+        .await
+        .unwrap()
+        .google_calendar_settings
+        .unwrap();
+    assert!(encryption::is_current(&stored.google_oauth_secret));
+    assert!(encryption::is_current(&stored.google_calendar_api_key));
+    assert_eq!(stored.google_oauth_client_id, "google-client");
+    let settings = AdminSettings::get_google_calendar_settings(&ctx.db)
+        .await
+        .unwrap();
+    assert_eq!(settings.google_oauth_secret, "google-secret");
+    assert_eq!(settings.google_calendar_api_key, "google-api-key");
+}
+
+#[tokio::test]
+#[serial]
+async fn plaintext_caldav_passwords_are_encrypted_and_stay_usable() {
+    let boot = boot_test::<App>().await.unwrap();
+    seed::<App>(&boot.app_context).await.unwrap();
+    let ctx = &boot.app_context;
+
+    let connection = caldav_connections::ActiveModel {
+        user_id: ActiveValue::Set(1),
+        server_url: ActiveValue::Set("https://caldav.example.com/".to_string()),
+        username: ActiveValue::Set("owner".to_string()),
+        password: ActiveValue::Set("app-password".to_string()),
+        ..Default::default()
+    }
+    .insert(&ctx.db)
+    .await
+    .unwrap();
+    assert!(encryption::is_current(&connection.password));
+    assert!(connection.client().is_ok());
+
+    // Rows written before encryption, the update skips the model hooks.
+    CaldavConnections::update_many()
+        .col_expr(
+            CaldavConnectionColumn::Password,
+            Expr::value("app-password"),
+        )
+        .exec(&ctx.db)
+        .await
+        .unwrap();
+
+    ReencryptSecrets
+        .run(ctx, &task::Vars::from_cli_args(vec![]))
+        .await
+        .unwrap();
+
+    let connection = CaldavConnections::find_by_id(connection.id)
+        .one(&ctx.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(encryption::is_current(&connection.password));
+    assert_eq!(
+        encryption::decrypt(&connection.password).unwrap(),
+        "app-password"
+    );
+}
+
+#[test]
+fn values_of_an_older_key_move_to_the_newest() {
+    let old_key = "1:0T4znSlNbpayeIL0ztOuocKxjq5nO/dvhN+FV3HqArU=";
+    let new_key = "2:xmdqoSwd/NtIf/GFFWpMsFQh3f62bpdL0k3KUD991sw=";
+    let old = Keyring::parse(old_key).unwrap();
+    let rotated = Keyring::parse(&format!("{old_key},{new_key}")).unwrap();
+    let new = Keyring::parse(new_key).unwrap();
+
+    let sealed = old.encrypt("refresh-token").unwrap();
+    assert!(sealed.starts_with("enc:v1:"));
+    assert!(!rotated.is_current(&sealed));
+
+    let resealed = rotated.reencrypt(&sealed).unwrap();
+    assert!(resealed.starts_with("enc:v2:"));
+    assert_eq!(rotated.reencrypt(&resealed).unwrap(), resealed);
+    assert_eq!(new.decrypt(&resealed).unwrap(), "refresh-token");
+    assert!(
+        new.decrypt(&sealed).is_err(),
+        "The old key is needed until the rows moved."
+    );
+}
@@ -0,0 +1,68 @@
+use appointments::{
+    app::App,
+    models::{
+        _entities::appointments::{IntakeAnswers, Status},
+        appointment_types::AppointmentTypes,
+        appointments::{ActiveModel, Appointments, CreateAppointmentProps},
+        users::Users,
+    },
+    workers::expire_pending_appointments::{
+        ExpirePendingAppointmentsWorker, ExpirePendingAppointmentsWorkerArgs,
+    },
+};
+use chrono::{Duration, Utc};
+use loco_rs::{bgworker::BackgroundWorker, testing::prelude::*};
+use sea_orm::EntityTrait;
+use serial_test::serial;
+
+#[tokio::test]
+#[serial]
+async fn declines_pending_appointments_past_their_expiry() {
+    let boot = boot_test::<App>().await.unwrap();
+    seed::<App>(&boot.app_context).await.unwrap();
+    let db = &boot.app_context.db;
+
+    let user = Users::find_by_id(db, 1).await.unwrap();
+    let appointment_type = AppointmentTypes::find_by_id(db, 1).await.unwrap();
+    let start_time = Utc::now() + Duration::days(2);
+    let pending = |expires_in: Duration, email: &str| CreateAppointmentProps {
+        booker_phone: "555555555".to_string(),
+        booker_name: "Pending".to_string(),
+        booker_timezone: chrono_tz::Tz::America__Vancouver,
+        booker_email: email.to_string(),
+        start_time,
+        endtime: start_time + Duration::hours(1),
+        intake_answers: IntakeAnswers(vec![]),
+        pending_expires_at: Some(Utc::now() + expires_in),
+        user: &user,
+        appointment_type: &appointment_type,
+    };
+    let expired = ActiveModel::create(db, pending(-Duration::hours(1), "expired@example.com"))
+        .await
+        .unwrap();
+    let waiting = ActiveModel::create(db, pending(Duration::hours(1), "waiting@example.com"))
+        .await
+        .unwrap();
+
+    ExpirePendingAppointmentsWorker::perform_later(
+        &boot.app_context,
+        ExpirePendingAppointmentsWorkerArgs {},
+    )
+    .await
+    .unwrap();
+
+    let expired = Appointments::find_by_id(expired.id)
+        .one(db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(expired.status, Status::Declined);
+    assert_eq!(expired.pending_expires_at, None);
+
+    let waiting = Appointments::find_by_id(waiting.id)
+        .one(db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(waiting.status, Status::Pending);
+}
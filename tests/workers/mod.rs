@@ -1 +1,7 @@
-
+mod expire_pending_appointments;
+mod notify_broken_calendars;
+mod process_outbox;
+mod reconcile_google_events;
+mod renew_google_channels;
+mod retry_webhook_deliveries;
+mod send_reminders;
@@ -0,0 +1,61 @@
+use appointments::{
+    app::App,
+    models::{
+        google_calendars::{self, GoogleCalendars, OAuthTokenResponseSuccess},
+        users::Users,
+    },
+    workers::notify_broken_calendars::{
+        NotifyBrokenCalendarsWorker, NotifyBrokenCalendarsWorkerArgs,
+    },
+};
+use chrono::Utc;
+use loco_rs::{bgworker::BackgroundWorker, testing::prelude::*};
+use sea_orm::{ActiveModelTrait, ActiveValue, EntityTrait, IntoActiveModel};
+use serial_test::serial;
+
+#[tokio::test]
+#[serial]
+async fn emails_the_owner_of_a_broken_connection_once() {
+    let boot = boot_test::<App>().await.unwrap();
+    seed::<App>(&boot.app_context).await.unwrap();
+    let db = &boot.app_context.db;
+
+    let user = Users::find_by_id(db, 1).await.unwrap();
+    let google_calendar = google_calendars::ActiveModel::create(
+        db,
+        OAuthTokenResponseSuccess {
+            access_token: "google-access-token".to_string(),
+            expires_in: 3600,
+            refresh_token: "google-refresh-token".to_string(),
+            refresh_token_expires_in: None,
+            scope: "https://www.googleapis.com/auth/calendar.events".to_string(),
+            token_type: "Bearer".to_string(),
+        },
+        user,
+        "owner@example.com".to_string(),
+    )
+    .await
+    .unwrap();
+    let mut active_model = google_calendar.into_active_model();
+    active_model.broken_at = ActiveValue::Set(Some(Utc::now().into()));
+    let google_calendar = active_model.update(db).await.unwrap();
+
+    for _ in 0..2 {
+        NotifyBrokenCalendarsWorker::perform_later(
+            &boot.app_context,
+            NotifyBrokenCalendarsWorkerArgs {},
+        )
+        .await
+        .unwrap();
+    }
+
+    let deliveries = boot.app_context.mailer.as_ref().unwrap().deliveries();
+    assert_eq!(deliveries.count, 1, "The owner is emailed once.");
+
+    let google_calendar = GoogleCalendars::find_by_id(google_calendar.id)
+        .one(db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(google_calendar.broken_notified_at.is_some());
+}
@@ -0,0 +1,58 @@
+use appointments::{
+    app::App,
+    models::{
+        appointments::Appointments,
+        outbox_jobs::{self, OutboxJobKind, OutboxJobs},
+    },
+    workers::process_outbox::{ProcessOutboxWorker, ProcessOutboxWorkerArgs},
+};
+use chrono::Utc;
+use loco_rs::{bgworker::BackgroundWorker, testing::prelude::*};
+use sea_orm::{EntityTrait, IntoActiveModel};
+use serial_test::serial;
+
+#[tokio::test]
+#[serial]
+async fn performs_due_jobs_and_leaves_failed_ones_for_later() {
+    let boot = boot_test::<App>().await.unwrap();
+    seed::<App>(&boot.app_context).await.unwrap();
+    let db = &boot.app_context.db;
+
+    let booked = Appointments::find_by_id(1).one(db).await.unwrap().unwrap();
+    let cancelled = Appointments::find_by_id(2).one(db).await.unwrap().unwrap();
+    outbox_jobs::ActiveModel::enqueue(
+        db,
+        &booked,
+        &[OutboxJobKind::NotifyBooker, OutboxJobKind::NotifyOwner],
+    )
+    .await
+    .unwrap();
+    outbox_jobs::ActiveModel::enqueue(db, &cancelled, &[OutboxJobKind::NotifyBooker])
+        .await
+        .unwrap();
+
+    // The owner mail failed once already, so it waits for its backoff.
+    let owner_job = OutboxJobs::find_by_appointment(db, &booked).await.unwrap()[1].clone();
+    let failed = owner_job
+        .into_active_model()
+        .fail(db, "smtp unavailable")
+        .await
+        .unwrap();
+    assert!(failed.run_at > Utc::now());
+
+    ProcessOutboxWorker::perform_later(&boot.app_context, ProcessOutboxWorkerArgs {})
+        .await
+        .unwrap();
+
+    let jobs = OutboxJobs::find_by_appointment(db, &booked).await.unwrap();
+    assert!(jobs[0].completed_at.is_some());
+    assert!(jobs[1].completed_at.is_none());
+    assert_eq!(jobs[1].attempts, 1);
+    assert_eq!(jobs[1].last_error.as_deref(), Some("smtp unavailable"));
+
+    // Jobs of appointments that no longer hold their slot complete without doing anything.
+    let jobs = OutboxJobs::find_by_appointment(db, &cancelled)
+        .await
+        .unwrap();
+    assert!(jobs[0].completed_at.is_some());
+}
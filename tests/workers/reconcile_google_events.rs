@@ -0,0 +1,324 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use appointments::{
+    app::App,
+    models::{
+        _entities::{
+            admin_settings::GoogleCalendarSettings,
+            appointments::{
+                CalendarEvent, CalendarProviderKind, CalendarReview, IntakeAnswers, Status,
+            },
+        },
+        admin_settings::AdminSettings,
+        appointment_types::AppointmentTypes,
+        appointments::{ActiveModel, Appointments, CreateAppointmentProps, Model as Appointment},
+        google_calendars::{self, OAuthTokenResponseSuccess},
+        users::{self, Users},
+    },
+    views::google_calendars::{CalendarSettingParams, CalendarSettingType},
+    workers::reconcile_google_events::{
+        ReconcileGoogleEventsWorker, ReconcileGoogleEventsWorkerArgs,
+    },
+};
+use axum::{
+    extract::{Path, State},
+    http::{header::RETRY_AFTER, StatusCode},
+    response::IntoResponse,
+    routing::get,
+    Json, Router,
+};
+use chrono::{DateTime, Duration, Utc};
+use loco_rs::{app::AppContext, bgworker::BackgroundWorker, testing::prelude::*};
+use sea_orm::{ActiveModelTrait, ActiveValue, EntityTrait, IntoActiveModel};
+use serial_test::serial;
+
+const CALENDAR_ID: &str = "owner@example.com";
+
+type GoogleEvents = Arc<HashMap<String, (StatusCode, serde_json::Value)>>;
+
+/// Calendar API stand-in, answering reads of the events in `events` and accepting deletes.
+async fn start_calendar_api(events: GoogleEvents) -> String {
+    let app = Router::new()
+        .route(
+            "/calendars/{calendar_id}/events/{event_id}",
+            get(
+                |State(events): State<GoogleEvents>,
+                 Path((_calendar_id, event_id)): Path<(String, String)>| async move {
+                    let (status, event) = events[&event_id].clone();
+                    (status, Json(event))
+                },
+            )
+            .delete(|| async { StatusCode::NO_CONTENT }),
+        )
+        .with_state(events);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{address}")
+}
+
+fn google_event(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    booker_email: &str,
+    booker_response: &str,
+) -> serde_json::Value {
+    serde_json::json!({
+        "status": "confirmed",
+        "start": { "dateTime": start.to_rfc3339() },
+        "end": { "dateTime": end.to_rfc3339() },
+        "attendees": [
+            { "email": CALENDAR_ID, "responseStatus": "accepted", "organizer": true },
+            { "email": booker_email, "responseStatus": booker_response },
+        ],
+    })
+}
+
+async fn connect_google_calendar(
+    ctx: &AppContext,
+    user: &users::Model,
+    api_url: &str,
+) -> google_calendars::Model {
+    let mut admin_settings = AdminSettings::load(&ctx.db)
+        .await
+        .unwrap()
+        .into_active_model();
+    admin_settings.google_calendar_settings = ActiveValue::Set(Some(GoogleCalendarSettings {
+        google_calendar_api_key: "google-api-key".to_string(),
+        google_oauth_client_id: "google-client".to_string(),
+        google_oauth_secret: "google-secret".to_string(),
+        google_oauth_redirect_uri_base: url::Url::parse("https://appointments.example.com")
+            .unwrap(),
+        google_oauth_token_url: None,
+        google_calendar_api_url: Some(url::Url::parse(api_url).unwrap()),
+    }));
+    admin_settings.update(&ctx.db).await.unwrap();
+
+    google_calendars::ActiveModel::create(
+        &ctx.db,
+        OAuthTokenResponseSuccess {
+            access_token: "google-access-token".to_string(),
+            expires_in: 3600,
+            refresh_token: "google-refresh-token".to_string(),
+            refresh_token_expires_in: None,
+            scope: "https://www.googleapis.com/auth/calendar.events".to_string(),
+            token_type: "Bearer".to_string(),
+        },
+        user.clone(),
+        CALENDAR_ID.to_string(),
+    )
+    .await
+    .unwrap()
+    .add_calendar_to_settings(
+        &ctx.db,
+        CalendarSettingParams {
+            calendar_id: CALENDAR_ID.to_string(),
+            setting_type: CalendarSettingType::EventHandling,
+        },
+    )
+    .await
+    .unwrap()
+}
+
+async fn book_with_google_event(
+    ctx: &AppContext,
+    user: &users::Model,
+    google_calendar: &google_calendars::Model,
+    event_id: &str,
+    start_time: DateTime<Utc>,
+) -> Appointment {
+    let appointment_type = AppointmentTypes::find_by_id(&ctx.db, 1).await.unwrap();
+    let appointment = ActiveModel::create(
+        &ctx.db,
+        CreateAppointmentProps {
+            booker_phone: "555555555".to_string(),
+            booker_name: event_id.to_string(),
+            booker_timezone: chrono_tz::Tz::America__Vancouver,
+            booker_email: format!("{event_id}@example.com"),
+            start_time,
+            endtime: start_time + Duration::hours(1),
+            intake_answers: IntakeAnswers(vec![]),
+            pending_expires_at: None,
+            user,
+            appointment_type: &appointment_type,
+        },
+    )
+    .await
+    .unwrap();
+
+    appointment
+        .into_active_model()
+        .attach_calendar_events(
+            &ctx.db,
+            vec![CalendarEvent {
+                provider: CalendarProviderKind::Google,
+                calendar_id: CALENDAR_ID.to_string(),
+                event_id: event_id.to_string(),
+                connection_id: Some(google_calendar.id),
+            }],
+        )
+        .await
+        .unwrap()
+}
+
+async fn reload(ctx: &AppContext, appointment: &Appointment) -> Appointment {
+    Appointments::find_by_id(appointment.id)
+        .one(&ctx.db)
+        .await
+        .unwrap()
+        .unwrap()
+}
+
+#[tokio::test]
+#[serial]
+async fn follows_events_changed_in_google() {
+    let boot = boot_test::<App>().await.unwrap();
+    seed::<App>(&boot.app_context).await.unwrap();
+    let ctx = &boot.app_context;
+
+    let start_time = Utc::now() + Duration::days(3);
+    let end_time = start_time + Duration::hours(1);
+    let moved_to = start_time + Duration::days(1);
+    let events: GoogleEvents = Arc::new(HashMap::from([
+        (
+            "unchanged".to_string(),
+            (
+                StatusCode::OK,
+                google_event(start_time, end_time, "unchanged@example.com", "accepted"),
+            ),
+        ),
+        (
+            "moved".to_string(),
+            (
+                StatusCode::OK,
+                google_event(
+                    moved_to,
+                    moved_to + Duration::hours(1),
+                    "moved@example.com",
+                    "accepted",
+                ),
+            ),
+        ),
+        (
+            "deleted".to_string(),
+            (
+                StatusCode::NOT_FOUND,
+                serde_json::json!({ "error": { "code": 404, "message": "Not Found" } }),
+            ),
+        ),
+        (
+            "declined".to_string(),
+            (
+                StatusCode::OK,
+                google_event(start_time, end_time, "declined@example.com", "declined"),
+            ),
+        ),
+    ]));
+    let api_url = start_calendar_api(events).await;
+
+    let user = Users::find_by_id(&ctx.db, 1).await.unwrap();
+    let google_calendar = connect_google_calendar(ctx, &user, &api_url).await;
+    let unchanged =
+        book_with_google_event(ctx, &user, &google_calendar, "unchanged", start_time).await;
+    let moved = book_with_google_event(ctx, &user, &google_calendar, "moved", start_time).await;
+    let deleted = book_with_google_event(ctx, &user, &google_calendar, "deleted", start_time).await;
+    let declined =
+        book_with_google_event(ctx, &user, &google_calendar, "declined", start_time).await;
+
+    for _ in 0..2 {
+        ReconcileGoogleEventsWorker::perform_later(ctx, ReconcileGoogleEventsWorkerArgs {})
+            .await
+            .unwrap();
+    }
+
+    let unchanged = reload(ctx, &unchanged).await;
+    assert_eq!(unchanged.status, Status::Booked);
+    assert_eq!(unchanged.calendar_review, None);
+
+    let moved = reload(ctx, &moved).await;
+    assert_eq!(moved.status, Status::Booked);
+    assert_eq!(moved.calendar_review, Some(CalendarReview::EventMoved));
+    assert_eq!(
+        moved.start_time.timestamp(),
+        start_time.timestamp(),
+        "The owner decides about the new time."
+    );
+
+    let deleted = reload(ctx, &deleted).await;
+    assert_eq!(deleted.status, Status::Booked);
+    assert_eq!(deleted.calendar_review, Some(CalendarReview::EventDeleted));
+    assert!(deleted.calendar_events.is_empty());
+
+    let declined = reload(ctx, &declined).await;
+    assert_eq!(declined.status, Status::Cancelled);
+
+    let deliveries = ctx.mailer.as_ref().unwrap().deliveries();
+    assert_eq!(
+        deliveries.count, 3,
+        "The owner is told once about each review and about the decline."
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn retries_reads_google_rate_limited() {
+    let boot = boot_test::<App>().await.unwrap();
+    seed::<App>(&boot.app_context).await.unwrap();
+    let ctx = &boot.app_context;
+
+    let start_time = Utc::now() + Duration::days(3);
+    let moved_to = start_time + Duration::days(1);
+    let event = google_event(
+        moved_to,
+        moved_to + Duration::hours(1),
+        "moved@example.com",
+        "accepted",
+    );
+    let reads = Arc::new(AtomicUsize::new(0));
+    let app = Router::new()
+        .route(
+            "/calendars/{calendar_id}/events/{event_id}",
+            get(move |State(reads): State<Arc<AtomicUsize>>| {
+                let event = event.clone();
+                async move {
+                    // Google answers some rate limits with a 403 and the reason in the body.
+                    if reads.fetch_add(1, Ordering::SeqCst) == 0 {
+                        return (
+                            StatusCode::FORBIDDEN,
+                            [(RETRY_AFTER, "0")],
+                            Json(serde_json::json!({
+                                "error": {
+                                    "code": 403,
+                                    "errors": [{ "reason": "rateLimitExceeded" }],
+                                },
+                            })),
+                        )
+                            .into_response();
+                    }
+                    Json(event).into_response()
+                }
+            }),
+        )
+        .with_state(reads.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let user = Users::find_by_id(&ctx.db, 1).await.unwrap();
+    let google_calendar = connect_google_calendar(ctx, &user, &format!("http://{address}")).await;
+    let moved = book_with_google_event(ctx, &user, &google_calendar, "moved", start_time).await;
+
+    ReconcileGoogleEventsWorker::perform_later(ctx, ReconcileGoogleEventsWorkerArgs {})
+        .await
+        .unwrap();
+
+    assert_eq!(reads.load(Ordering::SeqCst), 2);
+    let moved = reload(ctx, &moved).await;
+    assert_eq!(moved.calendar_review, Some(CalendarReview::EventMoved));
+}
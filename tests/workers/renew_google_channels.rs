@@ -0,0 +1,165 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use appointments::{
+    app::App,
+    models::{
+        _entities::admin_settings::GoogleCalendarSettings,
+        admin_settings::AdminSettings,
+        google_calendar_channels::GoogleCalendarChannels,
+        google_calendars::{self, OAuthTokenResponseSuccess},
+        users::Users,
+    },
+    views::google_calendars::{CalendarSettingParams, CalendarSettingType},
+    workers::renew_google_channels::{RenewGoogleChannelsWorker, RenewGoogleChannelsWorkerArgs},
+};
+use axum::{extract::State, routing::post, Json, Router};
+use chrono::{Duration, Utc};
+use loco_rs::{app::AppContext, bgworker::BackgroundWorker, testing::prelude::*};
+use sea_orm::{ActiveModelTrait, ActiveValue, IntoActiveModel};
+use serial_test::serial;
+
+const CALENDAR_ID: &str = "owner@example.com";
+
+#[derive(Clone, Default)]
+struct Calls {
+    watches: Arc<AtomicUsize>,
+    stops: Arc<AtomicUsize>,
+}
+
+/// Calendar API stand-in, counting the channels registered and stopped.
+async fn start_calendar_api(calls: Calls) -> String {
+    let app = Router::new()
+        .route(
+            "/calendars/{calendar_id}/events/watch",
+            post(
+                |State(calls): State<Calls>, Json(channel): Json<serde_json::Value>| async move {
+                    assert_eq!(
+                        channel["address"],
+                        "https://appointments.example.com/api/google_calendar/notifications"
+                    );
+                    calls.watches.fetch_add(1, Ordering::SeqCst);
+                    Json(serde_json::json!({
+                        "kind": "api#channel",
+                        "id": channel["id"],
+                        "resourceId": "owner-events",
+                        // Google encodes 64 bit numbers as strings.
+                        "expiration": channel["expiration"].to_string(),
+                    }))
+                },
+            ),
+        )
+        .route(
+            "/channels/stop",
+            post(|State(calls): State<Calls>| async move {
+                calls.stops.fetch_add(1, Ordering::SeqCst);
+            }),
+        )
+        .with_state(calls);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{address}")
+}
+
+async fn connect_google_calendar(ctx: &AppContext, api_url: &str) -> google_calendars::Model {
+    let mut admin_settings = AdminSettings::load(&ctx.db)
+        .await
+        .unwrap()
+        .into_active_model();
+    admin_settings.google_calendar_settings = ActiveValue::Set(Some(GoogleCalendarSettings {
+        google_calendar_api_key: "google-api-key".to_string(),
+        google_oauth_client_id: "google-client".to_string(),
+        google_oauth_secret: "google-secret".to_string(),
+        google_oauth_redirect_uri_base: url::Url::parse("https://appointments.example.com")
+            .unwrap(),
+        google_oauth_token_url: None,
+        google_calendar_api_url: Some(url::Url::parse(api_url).unwrap()),
+    }));
+    admin_settings.update(&ctx.db).await.unwrap();
+
+    let user = Users::find_by_id(&ctx.db, 1).await.unwrap();
+    google_calendars::ActiveModel::create(
+        &ctx.db,
+        OAuthTokenResponseSuccess {
+            access_token: "google-access-token".to_string(),
+            expires_in: 3600,
+            refresh_token: "google-refresh-token".to_string(),
+            refresh_token_expires_in: None,
+            scope: "https://www.googleapis.com/auth/calendar.events".to_string(),
+            token_type: "Bearer".to_string(),
+        },
+        user,
+        CALENDAR_ID.to_string(),
+    )
+    .await
+    .unwrap()
+}
+
+async fn renew(ctx: &AppContext) {
+    RenewGoogleChannelsWorker::perform_later(ctx, RenewGoogleChannelsWorkerArgs {})
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn keeps_one_live_channel_per_collision_check_calendar() {
+    let boot = boot_test::<App>().await.unwrap();
+    seed::<App>(&boot.app_context).await.unwrap();
+    let ctx = &boot.app_context;
+
+    let calls = Calls::default();
+    let api_url = start_calendar_api(calls.clone()).await;
+    let google_calendar = connect_google_calendar(ctx, &api_url).await;
+    let setting = || CalendarSettingParams {
+        calendar_id: CALENDAR_ID.to_string(),
+        setting_type: CalendarSettingType::CollisionCheck,
+    };
+    let google_calendar = google_calendar
+        .add_calendar_to_settings(&ctx.db, setting())
+        .await
+        .unwrap();
+
+    renew(ctx).await;
+    renew(ctx).await;
+    let channels = GoogleCalendarChannels::find_by_google_calendar(&ctx.db, &google_calendar)
+        .await
+        .unwrap();
+    assert_eq!(channels.len(), 1);
+    assert_eq!(channels[0].calendar_id, CALENDAR_ID);
+    assert_eq!(channels[0].resource_id, "owner-events");
+    assert!(channels[0].expires_at.to_utc() > Utc::now() + Duration::days(6));
+    assert_eq!(
+        calls.watches.load(Ordering::SeqCst),
+        1,
+        "Live channels stay."
+    );
+
+    let mut expiring = channels[0].clone().into_active_model();
+    expiring.expires_at = ActiveValue::Set((Utc::now() + Duration::hours(1)).into());
+    let expiring = expiring.update(&ctx.db).await.unwrap();
+    renew(ctx).await;
+    let channels = GoogleCalendarChannels::find_by_google_calendar(&ctx.db, &google_calendar)
+        .await
+        .unwrap();
+    assert_eq!(channels.len(), 1);
+    assert_ne!(channels[0].channel_id, expiring.channel_id);
+    assert_eq!(calls.watches.load(Ordering::SeqCst), 2);
+    assert_eq!(calls.stops.load(Ordering::SeqCst), 1);
+
+    let google_calendar = google_calendar
+        .remove_calendar_from_settings(&ctx.db, setting())
+        .await
+        .unwrap();
+    renew(ctx).await;
+    assert!(
+        GoogleCalendarChannels::find_by_google_calendar(&ctx.db, &google_calendar)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    assert_eq!(calls.stops.load(Ordering::SeqCst), 2);
+}
@@ -0,0 +1,206 @@
+use appointments::{
+    app::App,
+    models::{
+        appointments::Appointments,
+        users::Users,
+        webhook_deliveries::{self, WebhookDeliveries, WebhookPayload},
+        webhook_endpoints::{self, CreateOrUpdateWebhookEndpoint, WebhookEvent},
+    },
+    workers::retry_webhook_deliveries::{
+        RetryWebhookDeliveriesWorker, RetryWebhookDeliveriesWorkerArgs,
+    },
+};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use axum::{
+    http::{header, StatusCode},
+    routing::post,
+    Router,
+};
+use chrono::{Duration, Utc};
+use hmac::{Hmac, Mac};
+use loco_rs::{bgworker::BackgroundWorker, testing::prelude::*};
+use sea_orm::{ActiveModelTrait, ActiveValue, EntityTrait, IntoActiveModel};
+use serial_test::serial;
+use sha2::Sha256;
+
+#[tokio::test]
+#[serial]
+async fn retries_failed_deliveries_once_their_backoff_passed() {
+    let boot = boot_test::<App>().await.unwrap();
+    seed::<App>(&boot.app_context).await.unwrap();
+    let ctx = &boot.app_context;
+
+    let user = Users::find_by_id(&ctx.db, 1).await.unwrap();
+    let endpoint = webhook_endpoints::ActiveModel::create(
+        &ctx.db,
+        CreateOrUpdateWebhookEndpoint {
+            url: "http://127.0.0.1:9/hooks".to_string(),
+            events: vec![WebhookEvent::AppointmentBooked],
+            active: true,
+            user: &user,
+        },
+    )
+    .await
+    .unwrap();
+    let appointment = Appointments::find_by_id(1)
+        .one(&ctx.db)
+        .await
+        .unwrap()
+        .unwrap();
+    let payload = WebhookPayload {
+        event: WebhookEvent::AppointmentBooked,
+        occurred_at: Utc::now().into(),
+        appointment,
+    };
+
+    let failed = webhook_deliveries::ActiveModel::enqueue(ctx, &endpoint, payload, 1)
+        .await
+        .unwrap();
+    let failed = WebhookDeliveries::find_by_id(&ctx.db, failed.id)
+        .await
+        .unwrap();
+    assert!(failed.error.is_some());
+    assert!(failed.retry_at.is_some());
+
+    // Not due yet, nothing happens.
+    RetryWebhookDeliveriesWorker::perform_later(ctx, RetryWebhookDeliveriesWorkerArgs {})
+        .await
+        .unwrap();
+    assert_eq!(
+        WebhookDeliveries::find_by_endpoint(&ctx.db, &endpoint)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+
+    let mut due = failed.into_active_model();
+    due.retry_at = ActiveValue::set(Some((Utc::now() - Duration::minutes(1)).into()));
+    due.update(&ctx.db).await.unwrap();
+    RetryWebhookDeliveriesWorker::perform_later(ctx, RetryWebhookDeliveriesWorkerArgs {})
+        .await
+        .unwrap();
+
+    let deliveries = WebhookDeliveries::find_by_endpoint(&ctx.db, &endpoint)
+        .await
+        .unwrap();
+    assert_eq!(
+        deliveries
+            .iter()
+            .map(|delivery| (delivery.attempt, delivery.retry_at.is_some()))
+            .collect::<Vec<_>>(),
+        vec![(2, true), (1, false)],
+        "The retry is a new attempt, the old one is not retried again."
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn signs_timestamp_and_body_with_the_endpoint_secret() {
+    let boot = boot_test::<App>().await.unwrap();
+    seed::<App>(&boot.app_context).await.unwrap();
+    let db = &boot.app_context.db;
+
+    let user = Users::find_by_id(db, 1).await.unwrap();
+    let endpoint = webhook_endpoints::ActiveModel::create(
+        db,
+        CreateOrUpdateWebhookEndpoint {
+            url: "https://example.com/hooks".to_string(),
+            events: vec![WebhookEvent::AppointmentCancelled],
+            active: true,
+            user: &user,
+        },
+    )
+    .await
+    .unwrap();
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(endpoint.secret.as_bytes()).unwrap();
+    mac.update(b"1700000000.{\"ok\":true}");
+    assert_eq!(
+        endpoint.sign(1_700_000_000, b"{\"ok\":true}"),
+        hex::encode(mac.finalize().into_bytes())
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn keeps_neither_redirects_nor_response_bodies_of_failed_deliveries() {
+    let boot = boot_test::<App>().await.unwrap();
+    seed::<App>(&boot.app_context).await.unwrap();
+    let ctx = &boot.app_context;
+
+    let followed = Arc::new(AtomicBool::new(false));
+    let app = Router::new()
+        .route(
+            "/redirect",
+            post(|| async {
+                (
+                    StatusCode::TEMPORARY_REDIRECT,
+                    [(header::LOCATION, "/internal")],
+                )
+            }),
+        )
+        .route(
+            "/internal",
+            post({
+                let followed = followed.clone();
+                move || async move {
+                    followed.store(true, Ordering::SeqCst);
+                    "metadata"
+                }
+            }),
+        )
+        .route(
+            "/failing",
+            post(|| async { (StatusCode::INTERNAL_SERVER_ERROR, "internal details") }),
+        );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let user = Users::find_by_id(&ctx.db, 1).await.unwrap();
+    let appointment = Appointments::find_by_id(1)
+        .one(&ctx.db)
+        .await
+        .unwrap()
+        .unwrap();
+    for (path, status) in [("redirect", 307), ("failing", 500)] {
+        let endpoint = webhook_endpoints::ActiveModel::create(
+            &ctx.db,
+            CreateOrUpdateWebhookEndpoint {
+                url: format!("http://{address}/{path}"),
+                events: vec![WebhookEvent::AppointmentBooked],
+                active: true,
+                user: &user,
+            },
+        )
+        .await
+        .unwrap();
+        let payload = WebhookPayload {
+            event: WebhookEvent::AppointmentBooked,
+            occurred_at: Utc::now().into(),
+            appointment: appointment.clone(),
+        };
+
+        let delivery = webhook_deliveries::ActiveModel::enqueue(ctx, &endpoint, payload, 1)
+            .await
+            .unwrap();
+        let delivery = WebhookDeliveries::find_by_id(&ctx.db, delivery.id)
+            .await
+            .unwrap();
+        assert_eq!(delivery.response_status, Some(status));
+        assert_eq!(
+            delivery.error,
+            Some(format!("Responded with {status}.")),
+            "Only the status is kept, never the body."
+        );
+    }
+    assert!(
+        !followed.load(Ordering::SeqCst),
+        "Redirects are not followed."
+    );
+}
@@ -0,0 +1,140 @@
+use appointments::{
+    app::App,
+    models::{
+        _entities::appointments::IntakeAnswers,
+        appointment_reminders::{self, AppointmentReminders, ReminderRecipient},
+        appointment_types::{self, AppointmentTypes},
+        appointments::{ActiveModel, CreateAppointmentProps, Model as Appointment},
+        users::{self, Users},
+    },
+    workers::send_reminders::{SendRemindersWorker, SendRemindersWorkerArgs},
+};
+use chrono::{DateTime, Duration, Utc};
+use loco_rs::{bgworker::BackgroundWorker, testing::prelude::*};
+use sea_orm::{ActiveModelTrait, ActiveValue, DatabaseConnection, IntoActiveModel};
+use serial_test::serial;
+
+async fn book(
+    db: &DatabaseConnection,
+    user: &users::Model,
+    appointment_type: &appointment_types::Model,
+    start_time: DateTime<Utc>,
+) -> Appointment {
+    ActiveModel::create(
+        db,
+        CreateAppointmentProps {
+            booker_phone: "555555555".to_string(),
+            booker_name: "Reminded".to_string(),
+            booker_timezone: chrono_tz::Tz::America__Vancouver,
+            booker_email: "reminded@example.com".to_string(),
+            start_time,
+            endtime: start_time + Duration::hours(1),
+            intake_answers: IntakeAnswers(vec![]),
+            pending_expires_at: None,
+            user,
+            appointment_type,
+        },
+    )
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+#[serial]
+async fn sends_the_closest_due_reminder_once() {
+    let boot = boot_test::<App>().await.unwrap();
+    seed::<App>(&boot.app_context).await.unwrap();
+    let db = &boot.app_context.db;
+
+    let user = Users::find_by_id(db, 1).await.unwrap();
+    let appointment_type = AppointmentTypes::find_by_id(db, 1).await.unwrap();
+    let appointment = book(
+        db,
+        &user,
+        &appointment_type,
+        Utc::now() + Duration::minutes(50),
+    )
+    .await;
+    // Booked two days ago, so both the 24h and the 1h reminders have passed by now.
+    let mut booked_earlier = appointment.into_active_model();
+    booked_earlier.created_at = ActiveValue::set((Utc::now() - Duration::days(2)).into());
+    let appointment = booked_earlier.update(db).await.unwrap();
+
+    for _ in 0..2 {
+        SendRemindersWorker::perform_later(&boot.app_context, SendRemindersWorkerArgs {})
+            .await
+            .unwrap();
+    }
+
+    let reminders = AppointmentReminders::find_by_appointment(db, &appointment)
+        .await
+        .unwrap();
+    let mut sent: Vec<(i32, ReminderRecipient)> = reminders
+        .iter()
+        .map(|reminder| (reminder.offset_in_minutes, reminder.recipient))
+        .collect();
+    sent.sort_by_key(|(_, recipient)| *recipient == ReminderRecipient::Owner);
+    assert_eq!(
+        sent,
+        vec![
+            (60, ReminderRecipient::Booker),
+            (60, ReminderRecipient::Owner)
+        ]
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn released_claims_can_be_claimed_again() {
+    let boot = boot_test::<App>().await.unwrap();
+    seed::<App>(&boot.app_context).await.unwrap();
+    let db = &boot.app_context.db;
+
+    let user = Users::find_by_id(db, 1).await.unwrap();
+    let appointment_type = AppointmentTypes::find_by_id(db, 1).await.unwrap();
+    let appointment = book(
+        db,
+        &user,
+        &appointment_type,
+        Utc::now() + Duration::minutes(50),
+    )
+    .await;
+
+    let claim = || {
+        appointment_reminders::ActiveModel::claim(db, &appointment, 60, ReminderRecipient::Booker)
+    };
+    assert!(claim().await.unwrap());
+    assert!(!claim().await.unwrap());
+    appointment_reminders::ActiveModel::release(db, &appointment, 60, ReminderRecipient::Booker)
+        .await
+        .unwrap();
+    assert!(claim().await.unwrap(), "A failed send is tried again.");
+}
+
+#[tokio::test]
+#[serial]
+async fn rescheduling_forgets_the_sent_reminders() {
+    let boot = boot_test::<App>().await.unwrap();
+    seed::<App>(&boot.app_context).await.unwrap();
+    let db = &boot.app_context.db;
+
+    let user = Users::find_by_id(db, 1).await.unwrap();
+    let appointment_type = AppointmentTypes::find_by_id(db, 1).await.unwrap();
+    let start_time = Utc::now() + Duration::minutes(50);
+    let appointment = book(db, &user, &appointment_type, start_time).await;
+    appointment_reminders::ActiveModel::claim(db, &appointment, 60, ReminderRecipient::Owner)
+        .await
+        .unwrap();
+
+    let later = start_time + Duration::days(1);
+    let appointment = appointment
+        .into_active_model()
+        .reschedule_appointment(db, &later, &(later + Duration::hours(1)))
+        .await
+        .unwrap();
+
+    assert!(AppointmentReminders::find_by_appointment(db, &appointment)
+        .await
+        .unwrap()
+        .is_empty());
+}
@@ -792,8 +792,9 @@ impl Events {
      *   Whether to send notifications about the event update (for example, description changes, etc.). Note that some emails might still be sent even if you set the value to false. The default is false.
      * * `send_updates: crate::types::SendUpdates` -- Whether to send notifications about the creation of the new event. Note that some emails might still be sent. The default is false.
      * * `supports_attachments: bool` -- Whether this calendar list entry has been deleted from the calendar list. Read-only. Optional. The default is False.
+     * * `body` -- The fields to change. Every field a whole `Event` serializes is written, flags left at their default included, so pass only the changed fields, e.g. as a `serde_json::Value`.
      */
-    pub async fn patch(
+    pub async fn patch<B: serde::Serialize + ?Sized>(
         &self,
         calendar_id: &str,
         event_id: &str,
@@ -802,7 +803,7 @@ impl Events {
         send_notifications: bool,
         send_updates: crate::types::SendUpdates,
         supports_attachments: bool,
-        body: &crate::types::Event,
+        body: &B,
     ) -> ClientResult<crate::Response<crate::types::Event>> {
         let mut query_args: Vec<(String, String)> = Default::default();
         if !conference_data_version.to_string().is_empty() {
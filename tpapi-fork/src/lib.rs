@@ -121,12 +121,14 @@ pub mod channels;
 pub mod colors;
 pub mod events;
 pub mod freebusy;
+pub mod retry;
 pub mod settings;
 pub mod types;
 #[doc(hidden)]
 pub mod utils;
 
 pub use reqwest::{header::HeaderMap, StatusCode};
+pub use retry::RetryPolicy;
 
 #[derive(Debug)]
 pub struct Response<T> {
@@ -147,6 +149,13 @@ impl<T> Response<T> {
 
 type ClientResult<T> = Result<T, ClientError>;
 
+/// Status, headers and body of a response that was read in full.
+type RawResponse = (
+    reqwest::StatusCode,
+    reqwest::header::HeaderMap,
+    bytes::Bytes,
+);
+
 use thiserror::Error;
 
 /// Errors returned by the client
@@ -184,6 +193,58 @@ pub enum ClientError {
     },
 }
 
+/// What kind of failure a [`ClientError`] is, for callers that react to each differently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// The access token was rejected or does not grant access: 401, a 403 that is not a rate
+    /// limit, or no refresh token to get a new one with.
+    Auth,
+    /// The calendar or event does not exist, or no longer does: 404 and 410.
+    NotFound,
+    /// Over a quota: 429, or a 403 with a rate limit reason. Only returned once the retries
+    /// of the [`RetryPolicy`] ran out, or when Google asked to wait longer than it allows.
+    RateLimited,
+    /// Google failed or could not be reached: 5xx, timeouts and refused connections. Only
+    /// returned once the retries of the [`RetryPolicy`] ran out.
+    Server,
+    /// Anything else, like a request Google rejected or a response that could not be read.
+    Other,
+}
+
+impl ClientError {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ClientError::HttpError { status, error, .. } => {
+                if retry::is_rate_limit(*status, error) {
+                    ErrorKind::RateLimited
+                } else {
+                    match status.as_u16() {
+                        401 | 403 => ErrorKind::Auth,
+                        404 | 410 => ErrorKind::NotFound,
+                        500..=599 => ErrorKind::Server,
+                        _ => ErrorKind::Other,
+                    }
+                }
+            }
+            ClientError::EmptyRefreshToken => ErrorKind::Auth,
+            ClientError::ReqwestError(err) => transport_error_kind(err),
+            #[cfg(feature = "middleware")]
+            ClientError::ReqwestMiddleWareError(reqwest_middleware::Error::Reqwest(err)) => {
+                transport_error_kind(err)
+            }
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+fn transport_error_kind(err: &reqwest::Error) -> ErrorKind {
+    if err.is_timeout() || err.is_connect() {
+        ErrorKind::Server
+    } else {
+        ErrorKind::Other
+    }
+}
+
 pub const FALLBACK_HOST: &str = "https://www.googleapis.com/calendar/v3";
 
 mod progenitor_support {
@@ -219,6 +280,13 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
+async fn read_response(response: reqwest::Response) -> ClientResult<RawResponse> {
+    let status = response.status();
+    let headers = response.headers().clone();
+    let body = response.bytes().await?;
+    Ok((status, headers, body))
+}
+
 const TOKEN_ENDPOINT: &str = "https://oauth2.googleapis.com/token";
 const USER_CONSENT_ENDPOINT: &str = "https://accounts.google.com/o/oauth2/v2/auth";
 
@@ -240,6 +308,7 @@ pub struct Client {
     client_id: String,
     client_secret: String,
     redirect_uri: String,
+    retry_policy: RetryPolicy,
 
     auto_refresh: bool,
     #[cfg(feature = "middleware")]
@@ -318,9 +387,6 @@ impl Client {
         T: ToString,
         Q: ToString,
     {
-        // Retry up to 3 times with increasing intervals between attempts.
-        let retry_policy =
-            reqwest_retry::policies::ExponentialBackoff::builder().build_with_max_retries(3);
         let client = reqwest::Client::builder()
             .redirect(reqwest::redirect::Policy::none())
             .build();
@@ -331,11 +397,6 @@ impl Client {
                     reqwest_middleware::ClientBuilder::new(c)
                         // Trace HTTP requests. See the tracing crate to make use of these traces.
                         .with(reqwest_tracing::TracingMiddleware::default())
-                        // Retry failed requests.
-                        .with(reqwest_conditional_middleware::ConditionalMiddleware::new(
-                            reqwest_retry::RetryTransientMiddleware::new_with_policy(retry_policy),
-                            |req: &reqwest::Request| req.try_clone().is_some(),
-                        ))
                         .build()
                 };
                 #[cfg(not(feature = "middleware"))]
@@ -349,6 +410,7 @@ impl Client {
                     client_id: client_id.to_string(),
                     client_secret: client_secret.to_string(),
                     redirect_uri: redirect_uri.to_string(),
+                    retry_policy: RetryPolicy::default(),
                     token: Arc::new(RwLock::new(InnerToken {
                         access_token: token.to_string(),
                         refresh_token: refresh_token.to_string(),
@@ -432,6 +494,16 @@ impl Client {
         self
     }
 
+    /// Sets how rate limited and failed requests are retried, see [`RetryPolicy`].
+    pub fn with_retry_policy(&mut self, retry_policy: RetryPolicy) -> &mut Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn get_retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
     /// Disables the global host override for the client.
     pub fn remove_host_override(&mut self) -> &mut Self {
         self.host_override = None;
@@ -477,9 +549,6 @@ impl Client {
         let client = reqwest::Client::builder()
             .redirect(reqwest::redirect::Policy::none())
             .build();
-        let retry_policy =
-            reqwest_retry::policies::ExponentialBackoff::builder().build_with_max_retries(3);
-
         match client {
             Ok(c) => {
                 #[cfg(feature = "middleware")]
@@ -487,11 +556,6 @@ impl Client {
                     reqwest_middleware::ClientBuilder::new(c)
                         // Trace HTTP requests. See the tracing crate to make use of these traces.
                         .with(reqwest_tracing::TracingMiddleware::default())
-                        // Retry failed requests.
-                        .with(reqwest_conditional_middleware::ConditionalMiddleware::new(
-                            reqwest_retry::RetryTransientMiddleware::new_with_policy(retry_policy),
-                            |req: &reqwest::Request| req.try_clone().is_some(),
-                        ))
                         .build()
                 };
                 #[cfg(not(feature = "middleware"))]
@@ -504,6 +568,7 @@ impl Client {
                     client_id: secret.client_id.to_string(),
                     client_secret: secret.client_secret.to_string(),
                     redirect_uri: secret.redirect_uris[0].to_string(),
+                    retry_policy: RetryPolicy::default(),
                     token: Arc::new(RwLock::new(InnerToken {
                         access_token: token.to_string(),
                         refresh_token: refresh_token.to_string(),
@@ -668,12 +733,83 @@ impl Client {
         Ok(req.build()?)
     }
 
+    /// Sends the request, again as long as it fails for a reason the [`RetryPolicy`] retries,
+    /// and reads the final response. Requests that are not idempotent, like a `POST` creating
+    /// an event, are only sent again when Google certainly did not act on them: it rate limited
+    /// them or the connection was never made.
     async fn request_raw(
         &self,
         method: reqwest::Method,
         uri: &str,
         message: Message,
-    ) -> ClientResult<reqwest::Response> {
+    ) -> ClientResult<RawResponse> {
+        let req = self.prepare_request(method, uri, message).await?;
+        let idempotent = req.method().is_idempotent();
+
+        let mut retry = 0;
+        let mut waited = std::time::Duration::ZERO;
+        loop {
+            // Requests with a streamed body can not be sent twice, they get a single attempt.
+            let attempt = match req.try_clone() {
+                Some(attempt) if retry < self.retry_policy.max_retries => attempt,
+                _ => return read_response(self.client.execute(req).await?).await,
+            };
+
+            let (outcome, delay) = match self.client.execute(attempt).await {
+                Ok(resp) => {
+                    let (status, headers, body) = read_response(resp).await?;
+                    let retryable = if idempotent {
+                        retry::is_retryable(status, &body)
+                    } else {
+                        retry::is_rate_limit(status, &String::from_utf8_lossy(&body))
+                    };
+                    if !retryable {
+                        return Ok((status, headers, body));
+                    }
+                    let delay = self.retry_policy.delay(retry, retry::retry_after(&headers));
+                    (Ok((status, headers, body)), delay)
+                }
+                Err(err) => {
+                    let connect = err.is_connect();
+                    let err = ClientError::from(err);
+                    let retryable = if idempotent {
+                        err.kind() == ErrorKind::Server
+                    } else {
+                        connect
+                    };
+                    if !retryable {
+                        return Err(err);
+                    }
+                    (Err(err), self.retry_policy.delay(retry, None))
+                }
+            };
+
+            // Google asked for a longer wait than allowed, or the wait would go over the time
+            // the retries may take in total.
+            let delay = match delay {
+                Some(delay) if waited + delay <= self.retry_policy.max_elapsed => delay,
+                _ => return outcome,
+            };
+            log::debug!(
+                "Retrying {} {} in {:?}, retry {} of {}.",
+                req.method(),
+                req.url(),
+                delay,
+                retry + 1,
+                self.retry_policy.max_retries
+            );
+            tokio::time::sleep(delay).await;
+            waited += delay;
+            retry += 1;
+        }
+    }
+
+    async fn prepare_request(
+        &self,
+        method: reqwest::Method,
+        uri: &str,
+        message: Message,
+    ) -> ClientResult<reqwest::Request> {
         if self.auto_refresh {
             let expired = self.is_expired().await;
 
@@ -701,10 +837,7 @@ impl Client {
             }
         }
 
-        let req = self.make_request(&method, uri, message).await?;
-        let resp = self.client.execute(req).await?;
-
-        Ok(resp)
+        self.make_request(&method, uri, message).await
     }
 
     async fn request<Out>(
@@ -716,12 +849,7 @@ impl Client {
     where
         Out: serde::de::DeserializeOwned + 'static + Send,
     {
-        let response = self.request_raw(method, uri, message).await?;
-
-        let status = response.status();
-        let headers = response.headers().clone();
-
-        let response_body = response.bytes().await?;
+        let (status, headers, response_body) = self.request_raw(method, uri, message).await?;
 
         if status.is_success() {
             log::debug!("Received successful response. Read payload.");
@@ -761,20 +889,14 @@ impl Client {
     where
         Out: serde::de::DeserializeOwned + 'static + Send,
     {
-        let response = self.request_raw(method, uri, message).await?;
-
-        let status = response.status();
-        let headers = response.headers().clone();
-        let link = response
-            .headers()
+        let (status, headers, response_body) = self.request_raw(method, uri, message).await?;
+        let link = headers
             .get(http::header::LINK)
             .and_then(|l| l.to_str().ok())
             .and_then(|l| parse_link_header::parse(l).ok())
             .as_ref()
             .and_then(crate::utils::next_link);
 
-        let response_body = response.bytes().await?;
-
         if status.is_success() {
             log::debug!("Received successful response. Read payload.");
 
@@ -0,0 +1,183 @@
+//! Retries of requests that failed for reasons that go away on their own: rate limits, server
+//! errors and connections that could not be made.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, SystemTime};
+
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+
+/// Reasons Google gives for a 403 that is a rate limit rather than missing access.
+const RATE_LIMIT_REASONS: [&str; 2] = ["rateLimitExceeded", "userRateLimitExceeded"];
+
+/// How often a failed request is sent again and how long to wait in between.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt, `0` sends every request once.
+    pub max_retries: u32,
+    /// Wait before the first retry, doubled for every one after it.
+    pub base_delay: Duration,
+    /// Longest wait between two attempts. When Google asks to wait longer with `Retry-After`
+    /// the request is not retried, coming back earlier would only be turned down again.
+    pub max_delay: Duration,
+    /// Longest time spent waiting across all retries of a request. A retry that would go over
+    /// it is not made, so callers someone waits on give up in time.
+    pub max_elapsed: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            max_elapsed: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Sends every request once.
+    pub fn never() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+
+    /// Wait before retry number `retry`, counted from 0, or `None` when Google asked to wait
+    /// longer than `max_delay`. The `Retry-After` Google asked for wins, otherwise the backoff
+    /// doubles with every retry and half of it is random, so clients that failed together do
+    /// not all come back at the same moment.
+    pub fn delay(&self, retry: u32, retry_after: Option<Duration>) -> Option<Duration> {
+        if let Some(retry_after) = retry_after {
+            return (retry_after <= self.max_delay).then_some(retry_after);
+        }
+
+        let backoff = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_delay);
+        let half = backoff / 2;
+        Some(half + half.mul_f64(jitter()))
+    }
+}
+
+/// A number between 0 and 1. Every `RandomState` is seeded differently, which is all the
+/// randomness jitter needs.
+fn jitter() -> f64 {
+    let random = RandomState::new().build_hasher().finish();
+    random as f64 / u64::MAX as f64
+}
+
+/// Whether Google turned the request down for going over a quota. Besides 429 it answers some
+/// of those with a 403, told apart from missing access by the reason in the body.
+pub(crate) fn is_rate_limit(status: StatusCode, body: &str) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS
+        || (status == StatusCode::FORBIDDEN
+            && RATE_LIMIT_REASONS
+                .iter()
+                .any(|reason| body.contains(&format!("\"{reason}\""))))
+}
+
+/// Whether a response is worth sending the request again for.
+pub(crate) fn is_retryable(status: StatusCode, body: &[u8]) -> bool {
+    is_rate_limit(status, &String::from_utf8_lossy(body))
+        || (status.is_server_error() && status != StatusCode::NOT_IMPLEMENTED)
+}
+
+/// `Retry-After` of a response, given either in seconds or as an HTTP date.
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let at = SystemTime::UNIX_EPOCH + Duration::from_secs(u64::try_from(at.timestamp()).ok()?);
+    Some(at.duration_since(SystemTime::now()).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_retries: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(1),
+            max_elapsed: Duration::from_secs(5),
+        }
+    }
+
+    fn retry_after_header(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn backoff_doubles_with_half_of_it_random_up_to_max_delay() {
+        let policy = policy();
+        for _ in 0..100 {
+            for (retry, backoff) in [(0, 100), (1, 200), (2, 400), (3, 800), (10, 1000)] {
+                let delay = policy.delay(retry, None).unwrap();
+                let backoff = Duration::from_millis(backoff);
+                assert!(
+                    delay >= backoff / 2 && delay <= backoff,
+                    "retry {retry} waits {delay:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn waits_as_long_as_google_asks_but_not_longer_than_max_delay() {
+        let policy = policy();
+        assert_eq!(
+            policy.delay(3, Some(Duration::from_millis(700))),
+            Some(Duration::from_millis(700))
+        );
+        assert_eq!(
+            policy.delay(0, Some(Duration::from_secs(1))),
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(policy.delay(0, Some(Duration::from_secs(60))), None);
+    }
+
+    #[test]
+    fn reads_retry_after_in_seconds_or_as_http_date() {
+        assert_eq!(
+            retry_after(&retry_after_header("120")),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            retry_after(&retry_after_header("Wed, 21 Oct 2015 07:28:00 GMT")),
+            Some(Duration::ZERO),
+            "A date that passed means retrying right away."
+        );
+        let far = retry_after(&retry_after_header("Fri, 01 Jan 2100 00:00:00 GMT")).unwrap();
+        assert!(far > Duration::from_secs(60 * 60 * 24 * 365 * 50));
+        assert_eq!(retry_after(&retry_after_header("soon")), None);
+        assert_eq!(retry_after(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn retries_rate_limits_and_server_errors_only() {
+        let rate_limited = br#"{"error":{"errors":[{"reason":"rateLimitExceeded"}]}}"#;
+        let forbidden = br#"{"error":{"errors":[{"reason":"forbidden"}]}}"#;
+
+        assert!(is_retryable(StatusCode::TOO_MANY_REQUESTS, b""));
+        assert!(is_retryable(StatusCode::FORBIDDEN, rate_limited));
+        assert!(is_retryable(StatusCode::INTERNAL_SERVER_ERROR, b""));
+        assert!(is_retryable(StatusCode::SERVICE_UNAVAILABLE, b""));
+
+        assert!(!is_retryable(StatusCode::FORBIDDEN, forbidden));
+        assert!(!is_retryable(StatusCode::FORBIDDEN, b""));
+        assert!(!is_retryable(StatusCode::NOT_IMPLEMENTED, b""));
+        assert!(!is_retryable(StatusCode::NOT_FOUND, b""));
+        assert!(!is_retryable(StatusCode::BAD_REQUEST, b""));
+    }
+}
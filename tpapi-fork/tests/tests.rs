@@ -1 +1,146 @@
+use std::time::Duration;
 
+use google_calendar::{
+    types::{Event, SendUpdates},
+    Client, ErrorKind, RetryPolicy,
+};
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+const EVENTS: &str = "/calendars/primary/events";
+const EVENT: &str = "/calendars/primary/events/event";
+
+fn client(server: &MockServer, retry_policy: RetryPolicy) -> Client {
+    let mut client = Client::new("id", "secret", "http://localhost", "token", "refresh");
+    client
+        .with_host_override(server.uri())
+        .with_retry_policy(retry_policy);
+    client
+}
+
+fn quick_retries() -> RetryPolicy {
+    RetryPolicy {
+        max_retries: 2,
+        base_delay: Duration::from_millis(1),
+        max_delay: Duration::from_secs(1),
+        max_elapsed: Duration::from_secs(5),
+    }
+}
+
+async fn get_event(client: &Client) -> ErrorKind {
+    client
+        .events()
+        .get("primary", "event", 0, "")
+        .await
+        .unwrap_err()
+        .kind()
+}
+
+#[tokio::test]
+async fn reads_answered_with_a_server_error_are_retried() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(EVENT))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+
+    assert_eq!(
+        get_event(&client(&server, quick_retries())).await,
+        ErrorKind::Server
+    );
+    assert_eq!(server.received_requests().await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn creations_answered_with_a_server_error_are_sent_once() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(EVENTS))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+
+    let err = client(&server, quick_retries())
+        .events()
+        .insert(
+            "primary",
+            0,
+            0,
+            false,
+            SendUpdates::None,
+            false,
+            &Event::default(),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Server);
+    assert_eq!(
+        server.received_requests().await.unwrap().len(),
+        1,
+        "Google may have created the event, sending it again could create it twice."
+    );
+}
+
+#[tokio::test]
+async fn missing_access_is_not_retried() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(EVENT))
+        .respond_with(
+            ResponseTemplate::new(403)
+                .set_body_string(r#"{"error":{"code":403,"errors":[{"reason":"forbidden"}]}}"#),
+        )
+        .mount(&server)
+        .await;
+
+    assert_eq!(
+        get_event(&client(&server, quick_retries())).await,
+        ErrorKind::Auth
+    );
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn retry_after_longer_than_max_delay_is_not_retried_early() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(EVENT))
+        .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "60"))
+        .mount(&server)
+        .await;
+
+    assert_eq!(
+        get_event(&client(&server, quick_retries())).await,
+        ErrorKind::RateLimited
+    );
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn retries_stop_before_they_take_longer_than_max_elapsed() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(EVENT))
+        .respond_with(ResponseTemplate::new(503).insert_header("Retry-After", "1"))
+        .mount(&server)
+        .await;
+
+    let retry_policy = RetryPolicy {
+        max_retries: 5,
+        max_delay: Duration::from_secs(2),
+        max_elapsed: Duration::from_millis(1500),
+        ..quick_retries()
+    };
+    assert_eq!(
+        get_event(&client(&server, retry_policy)).await,
+        ErrorKind::Server
+    );
+    assert_eq!(
+        server.received_requests().await.unwrap().len(),
+        2,
+        "A second wait of a second would go over the limit."
+    );
+}